@@ -1,52 +1,91 @@
+//! Computes the rewards paid to validators for their attestations in an epoch.
+//!
+//! The rewards for an epoch are derived from the state at the end of the following epoch. On a
+//! node which has not reconstructed its historic states that state may not be stored, in which
+//! case it is computed by replaying blocks on top of a restore point in the freezer database.
+//!
+//! Rather than the rewards themselves, the participation and effective balance of each validator
+//! are kept, which suffice to compute the rewards of any set of validators in a fraction of the
+//! space. These are cached in memory, and persisted for every epoch passed through by a replay so
+//! that each epoch only needs to be replayed once.
+//!
+//! Replays proceed forwards from the lower limit of the historic states, and the newest replayed
+//! state is kept so that the next replay resumes from it. Requests for a single epoch may replay
+//! at most `MAX_ATTESTATION_REWARDS_REPLAY_SLOTS` slots, whilst requests for a range of epochs may
+//! replay any number, so that a range request can advance through the whole gap in the historic
+//! states step by step.
 use crate::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use eth2::lighthouse::attestation_rewards::{IdealAttestationRewards, TotalAttestationRewards};
 use eth2::lighthouse::StandardAttestationRewards;
+use lru::LruCache;
+use parking_lot::Mutex;
 use participation_cache::ParticipationCache;
 use safe_arith::SafeArith;
 use slog::{debug, Logger};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
 use state_processing::{
     common::altair::BaseRewardPerIncrement,
     per_epoch_processing::altair::{participation_cache, rewards_and_penalties::get_flag_weight},
 };
-use std::collections::HashMap;
+use std::cmp::min;
+use std::convert::TryFrom;
+use std::sync::Arc;
 use store::consts::altair::{
     PARTICIPATION_FLAG_WEIGHTS, TIMELY_HEAD_FLAG_INDEX, TIMELY_SOURCE_FLAG_INDEX,
     TIMELY_TARGET_FLAG_INDEX,
 };
+use store::{
+    get_key_for_col, DBColumn, Error as StoreError, KeyValueStore, KeyValueStoreOp, StoreItem,
+};
 use types::consts::altair::WEIGHT_DENOMINATOR;
 
-use types::{Epoch, EthSpec};
+use types::{BeaconState, BeaconStateError, ChainSpec, Epoch, EthSpec, Hash256, Slot};
 
 use eth2::types::ValidatorId;
 
-impl<T: BeaconChainTypes> BeaconChain<T> {
-    pub fn compute_attestation_rewards(
-        &self,
-        epoch: Epoch,
-        validators: Vec<ValidatorId>,
-        log: Logger,
-    ) -> Result<StandardAttestationRewards, BeaconChainError> {
-        debug!(log, "computing attestation rewards"; "epoch" => epoch, "validator_count" => validators.len());
-
-        // Get state
-        let spec = &self.spec;
+/// The number of epochs for which attestation outcomes are cached in memory.
+const ATTESTATION_REWARDS_CACHE_SIZE: usize = 8;
+/// The maximum number of slots replayed to compute the attestation rewards of a single epoch.
+pub const MAX_ATTESTATION_REWARDS_REPLAY_SLOTS: u64 = 2048;
+/// The largest effective balance for which ideal rewards are computed, in increments.
+const MAX_EFFECTIVE_BALANCE_INCREMENTS: u64 = 32;
+/// Set in the participation of a validator which is eligible for rewards and penalties.
+const ELIGIBLE_FLAG: u8 = 1 << 7;
 
-        let state_slot = (epoch + 1).end_slot(T::EthSpec::slots_per_epoch());
-
-        let state_root = self
-            .state_root_at_slot(state_slot)?
-            .ok_or(BeaconChainError::NoStateForSlot(state_slot))?;
-
-        let mut state = self
-            .get_state(&state_root, Some(state_slot))?
-            .ok_or(BeaconChainError::MissingBeaconState(state_root))?;
-
-        // Calculate ideal_rewards
-        let participation_cache = ParticipationCache::new(&state, spec)?;
+/// The outcome of the attestations of every validator in an epoch.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct EpochAttestationOutcomes {
+    epoch: Epoch,
+    /// The reward for each participation flag and effective balance, indexed by
+    /// `flag_index * (MAX_EFFECTIVE_BALANCE_INCREMENTS + 1) + effective_balance_increments`.
+    ideal_rewards: Vec<u64>,
+    /// The penalty for missing each participation flag, indexed as for `ideal_rewards`.
+    penalties: Vec<u64>,
+    /// The participation flags of each validator in the epoch, along with `ELIGIBLE_FLAG`.
+    participation: Vec<u8>,
+    /// The effective balance of each validator, in increments.
+    effective_balances: Vec<u8>,
+}
 
+impl EpochAttestationOutcomes {
+    /// Computes the outcomes for the previous epoch of `state`, which should be the state at the
+    /// end of the epoch following the one of interest.
+    pub fn from_state<E: EthSpec>(
+        state: &BeaconState<E>,
+        spec: &ChainSpec,
+    ) -> Result<Self, BeaconChainError> {
+        let participation_cache = ParticipationCache::new(state, spec)?;
         let previous_epoch = state.previous_epoch();
 
-        let mut ideal_rewards_hashmap = HashMap::new();
+        let total_active_balance = participation_cache.current_epoch_total_active_balance();
+        let active_increments = total_active_balance.safe_div(spec.effective_balance_increment)?;
+        let base_reward_per_increment = BaseRewardPerIncrement::new(total_active_balance, spec)?;
+        let is_in_inactivity_leak = state.is_in_inactivity_leak(previous_epoch, spec);
+
+        let mut participating_indices = vec![];
+        let mut ideal_rewards = vec![];
+        let mut penalties = vec![];
 
         for flag_index in 0..PARTICIPATION_FLAG_WEIGHTS.len() {
             let weight = get_flag_weight(flag_index)
@@ -63,21 +102,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             let unslashed_participating_increments =
                 unslashed_participating_balance.safe_div(spec.effective_balance_increment)?;
 
-            let total_active_balance = participation_cache.current_epoch_total_active_balance();
-
-            let active_increments =
-                total_active_balance.safe_div(spec.effective_balance_increment)?;
-
-            let base_reward_per_increment =
-                BaseRewardPerIncrement::new(total_active_balance, spec)?;
-
-            for effective_balance_eth in 0..=32 {
-                let effective_balance =
-                    effective_balance_eth.safe_mul(spec.effective_balance_increment)?;
+            for effective_balance_eth in 0..=MAX_EFFECTIVE_BALANCE_INCREMENTS {
                 let base_reward =
                     effective_balance_eth.safe_mul(base_reward_per_increment.as_u64())?;
 
-                let penalty = -(base_reward.safe_mul(weight)?.safe_div(WEIGHT_DENOMINATOR)? as i64);
+                let penalty = base_reward.safe_mul(weight)?.safe_div(WEIGHT_DENOMINATOR)?;
 
                 let reward_numerator = base_reward
                     .safe_mul(weight)?
@@ -86,106 +115,139 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 let ideal_reward = reward_numerator
                     .safe_div(active_increments)?
                     .safe_div(WEIGHT_DENOMINATOR)?;
-                if !state.is_in_inactivity_leak(previous_epoch, spec) {
-                    ideal_rewards_hashmap
-                        .insert((flag_index, effective_balance), (ideal_reward, penalty));
+
+                if is_in_inactivity_leak {
+                    ideal_rewards.push(0);
                 } else {
-                    ideal_rewards_hashmap.insert((flag_index, effective_balance), (0, penalty));
+                    ideal_rewards.push(ideal_reward);
                 }
+                penalties.push(penalty);
             }
+
+            participating_indices.push(unslashed_participating_indices);
         }
 
-        // Calculate total_rewards
-        let mut total_rewards: Vec<TotalAttestationRewards> = Vec::new();
+        let mut participation = Vec::with_capacity(state.validators().len());
+        let mut effective_balances = Vec::with_capacity(state.validators().len());
+
+        for (validator_index, validator) in state.validators().iter().enumerate() {
+            let mut flags = 0;
+            if state.is_eligible_validator(previous_epoch, validator_index)? {
+                flags |= ELIGIBLE_FLAG;
+            }
+            for (flag_index, indices) in participating_indices.iter().enumerate() {
+                if indices
+                    .contains(validator_index)
+                    .map_err(|_| BeaconChainError::AttestationRewardsError)?
+                {
+                    flags |= 1 << flag_index;
+                }
+            }
+            participation.push(flags);
+
+            let increments = validator
+                .effective_balance
+                .safe_div(spec.effective_balance_increment)?;
+            effective_balances.push(
+                u8::try_from(increments).map_err(|_| BeaconChainError::AttestationRewardsError)?,
+            );
+        }
+
+        Ok(Self {
+            epoch: previous_epoch,
+            ideal_rewards,
+            penalties,
+            participation,
+            effective_balances,
+        })
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
 
+    /// Returns the rewards of `validators`, or of all eligible validators if it is empty.
+    pub fn rewards(
+        &self,
+        validators: &[usize],
+        spec: &ChainSpec,
+    ) -> Result<StandardAttestationRewards, BeaconChainError> {
+        let table_index = |flag_index: usize, increments: u64| {
+            flag_index * (MAX_EFFECTIVE_BALANCE_INCREMENTS as usize + 1) + increments as usize
+        };
+
+        let eligible_validators;
         let validators = if validators.is_empty() {
-            participation_cache.eligible_validator_indices().to_vec()
+            eligible_validators = self
+                .participation
+                .iter()
+                .enumerate()
+                .filter(|(_, flags)| *flags & ELIGIBLE_FLAG != 0)
+                .map(|(validator_index, _)| validator_index)
+                .collect::<Vec<_>>();
+            &eligible_validators
         } else {
             validators
-                .into_iter()
-                .map(|validator| match validator {
-                    ValidatorId::Index(i) => Ok(i as usize),
-                    ValidatorId::PublicKey(pubkey) => state
-                        .get_validator_index(&pubkey)?
-                        .ok_or(BeaconChainError::ValidatorPubkeyUnknown(pubkey)),
-                })
-                .collect::<Result<Vec<_>, _>>()?
         };
 
-        for validator_index in &validators {
-            let eligible = state.is_eligible_validator(previous_epoch, *validator_index)?;
+        let mut total_rewards: Vec<TotalAttestationRewards> = Vec::new();
+
+        for &validator_index in validators {
+            let flags = *self
+                .participation
+                .get(validator_index)
+                .ok_or(BeaconStateError::UnknownValidator(validator_index))?;
             let mut head_reward = 0u64;
             let mut target_reward = 0i64;
             let mut source_reward = 0i64;
 
-            if eligible {
-                let effective_balance = state.get_effective_balance(*validator_index)?;
+            if flags & ELIGIBLE_FLAG != 0 {
+                let increments = u64::from(self.effective_balances[validator_index]);
+                if increments > MAX_EFFECTIVE_BALANCE_INCREMENTS {
+                    return Err(BeaconChainError::AttestationRewardsError);
+                }
 
                 for flag_index in 0..PARTICIPATION_FLAG_WEIGHTS.len() {
-                    let (ideal_reward, penalty) = ideal_rewards_hashmap
-                        .get(&(flag_index, effective_balance))
-                        .ok_or(BeaconChainError::AttestationRewardsError)?;
-                    let voted_correctly = participation_cache
-                        .get_unslashed_participating_indices(flag_index, previous_epoch)
-                        .map_err(|_| BeaconChainError::AttestationRewardsError)?
-                        .contains(*validator_index)
-                        .map_err(|_| BeaconChainError::AttestationRewardsError)?;
+                    let i = table_index(flag_index, increments);
+                    let ideal_reward = self.ideal_rewards[i];
+                    let penalty = -(self.penalties[i] as i64);
+                    let voted_correctly = flags & (1 << flag_index) != 0;
                     if voted_correctly {
                         if flag_index == TIMELY_HEAD_FLAG_INDEX {
                             head_reward += ideal_reward;
                         } else if flag_index == TIMELY_TARGET_FLAG_INDEX {
-                            target_reward += *ideal_reward as i64;
+                            target_reward += ideal_reward as i64;
                         } else if flag_index == TIMELY_SOURCE_FLAG_INDEX {
-                            source_reward += *ideal_reward as i64;
+                            source_reward += ideal_reward as i64;
                         }
                     } else if flag_index == TIMELY_HEAD_FLAG_INDEX {
                         head_reward = 0;
                     } else if flag_index == TIMELY_TARGET_FLAG_INDEX {
-                        target_reward = *penalty;
+                        target_reward = penalty;
                     } else if flag_index == TIMELY_SOURCE_FLAG_INDEX {
-                        source_reward = *penalty;
+                        source_reward = penalty;
                     }
                 }
             }
             total_rewards.push(TotalAttestationRewards {
-                validator_index: *validator_index as u64,
+                validator_index: validator_index as u64,
                 head: head_reward,
                 target: target_reward,
                 source: source_reward,
             });
         }
 
-        // Convert hashmap to vector
-        let mut ideal_rewards: Vec<IdealAttestationRewards> = ideal_rewards_hashmap
-            .iter()
-            .map(
-                |((flag_index, effective_balance), (ideal_reward, _penalty))| {
-                    (flag_index, effective_balance, ideal_reward)
-                },
-            )
-            .fold(
-                HashMap::new(),
-                |mut acc, (flag_index, &effective_balance, ideal_reward)| {
-                    let entry = acc
-                        .entry(effective_balance)
-                        .or_insert(IdealAttestationRewards {
-                            effective_balance,
-                            head: 0,
-                            target: 0,
-                            source: 0,
-                        });
-                    match *flag_index {
-                        TIMELY_SOURCE_FLAG_INDEX => entry.source += ideal_reward,
-                        TIMELY_TARGET_FLAG_INDEX => entry.target += ideal_reward,
-                        TIMELY_HEAD_FLAG_INDEX => entry.head += ideal_reward,
-                        _ => {}
-                    }
-                    acc
-                },
-            )
-            .into_values()
-            .collect::<Vec<IdealAttestationRewards>>();
-        ideal_rewards.sort_by(|a, b| a.effective_balance.cmp(&b.effective_balance));
+        let ideal_rewards = (0..=MAX_EFFECTIVE_BALANCE_INCREMENTS)
+            .map(|increments| {
+                let reward = |flag_index| self.ideal_rewards[table_index(flag_index, increments)];
+                Ok(IdealAttestationRewards {
+                    effective_balance: increments.safe_mul(spec.effective_balance_increment)?,
+                    head: reward(TIMELY_HEAD_FLAG_INDEX),
+                    target: reward(TIMELY_TARGET_FLAG_INDEX),
+                    source: reward(TIMELY_SOURCE_FLAG_INDEX),
+                })
+            })
+            .collect::<Result<Vec<_>, BeaconChainError>>()?;
 
         Ok(StandardAttestationRewards {
             ideal_rewards,
@@ -193,3 +255,335 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 }
+
+impl StoreItem for EpochAttestationOutcomes {
+    fn db_column() -> DBColumn {
+        DBColumn::AttestationRewards
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// Cache of the attestation outcomes of recently requested finalized epochs.
+pub struct AttestationRewardsCache<E: EthSpec> {
+    cache: Mutex<LruCache<Epoch, Arc<EpochAttestationOutcomes>>>,
+    /// The newest state computed by a replay, from which the next replay resumes.
+    replay_state: Mutex<Option<BeaconState<E>>>,
+}
+
+impl<E: EthSpec> Default for AttestationRewardsCache<E> {
+    fn default() -> Self {
+        Self {
+            cache: Mutex::new(LruCache::new(ATTESTATION_REWARDS_CACHE_SIZE)),
+            replay_state: Mutex::new(None),
+        }
+    }
+}
+
+impl<T: BeaconChainTypes> BeaconChain<T> {
+    pub fn compute_attestation_rewards(
+        &self,
+        epoch: Epoch,
+        validators: Vec<ValidatorId>,
+        log: Logger,
+    ) -> Result<StandardAttestationRewards, BeaconChainError> {
+        debug!(log, "computing attestation rewards"; "epoch" => epoch, "validator_count" => validators.len());
+
+        let outcomes = match self.get_stored_attestation_outcomes(epoch)? {
+            Some(outcomes) => outcomes,
+            None => {
+                let mut outcomes = None;
+                self.replay_attestation_outcomes(
+                    epoch,
+                    epoch,
+                    Some(MAX_ATTESTATION_REWARDS_REPLAY_SLOTS),
+                    |epoch_outcomes| {
+                        outcomes = Some(epoch_outcomes);
+                        Ok(true)
+                    },
+                )?;
+                outcomes.ok_or_else(|| {
+                    BeaconChainError::NoStateForSlot(Self::attestation_rewards_state_slot(epoch))
+                })?
+            }
+        };
+
+        let validators = self.attestation_rewards_validator_indices(&outcomes, validators)?;
+        outcomes.rewards(&validators, &self.spec)
+    }
+
+    /// Computes the attestation rewards of `validators` for each epoch from `start_epoch` to
+    /// `end_epoch` (inclusive), calling `f` with the rewards of each epoch in turn.
+    ///
+    /// Epochs which must be replayed are replayed together, so that a range of epochs costs
+    /// little more than its last epoch. Unlike `compute_attestation_rewards` the replay isn't
+    /// limited in length. Stops early if `f` returns `false`.
+    pub fn compute_attestation_rewards_range(
+        &self,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+        validators: Vec<ValidatorId>,
+        mut f: impl FnMut(Epoch, StandardAttestationRewards) -> bool,
+    ) -> Result<(), BeaconChainError> {
+        let mut epoch = start_epoch;
+
+        while epoch <= end_epoch {
+            if let Some(outcomes) = self.get_stored_attestation_outcomes(epoch)? {
+                let validators =
+                    self.attestation_rewards_validator_indices(&outcomes, validators.clone())?;
+                if !f(epoch, outcomes.rewards(&validators, &self.spec)?) {
+                    return Ok(());
+                }
+                epoch += 1;
+                continue;
+            }
+
+            // Replay the run of epochs whose states are unavailable.
+            let mut last_epoch = epoch;
+            while last_epoch < end_epoch
+                && self.attestation_rewards_require_replay(Self::attestation_rewards_state_slot(
+                    last_epoch + 1,
+                ))
+            {
+                last_epoch += 1;
+            }
+
+            let mut stopped = false;
+            self.replay_attestation_outcomes(epoch, last_epoch, None, |outcomes| {
+                let validators =
+                    self.attestation_rewards_validator_indices(&outcomes, validators.clone())?;
+                stopped = !f(outcomes.epoch(), outcomes.rewards(&validators, &self.spec)?);
+                Ok(!stopped)
+            })?;
+            if stopped {
+                return Ok(());
+            }
+            epoch = last_epoch + 1;
+        }
+
+        Ok(())
+    }
+
+    /// The slot of the state from which the attestation rewards for `epoch` are computed.
+    fn attestation_rewards_state_slot(epoch: Epoch) -> Slot {
+        (epoch + 1).end_slot(T::EthSpec::slots_per_epoch())
+    }
+
+    /// Returns `true` if the state at `state_slot` lies between the historic state limits, and so
+    /// must be computed by replay.
+    fn attestation_rewards_require_replay(&self, state_slot: Slot) -> bool {
+        let (lower_limit, upper_limit) = self.store.get_historic_state_limits();
+        state_slot > lower_limit && state_slot < upper_limit
+    }
+
+    /// Computes the attestation outcomes of the epochs from `start_epoch` to `end_epoch` by
+    /// replay, calling `f` with the outcomes of each epoch in turn until it returns `false`.
+    ///
+    /// The replay resumes from the newest replayed state if it precedes `start_epoch`, and
+    /// otherwise starts from the lower limit of the historic states. The outcomes of every epoch
+    /// passed through are persisted, including those before `start_epoch`, and the newest state is
+    /// kept for the next replay. If `max_replay_slots` is set, an error is returned rather than
+    /// replaying more slots than that.
+    fn replay_attestation_outcomes(
+        &self,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+        max_replay_slots: Option<u64>,
+        mut f: impl FnMut(Arc<EpochAttestationOutcomes>) -> Result<bool, BeaconChainError>,
+    ) -> Result<(), BeaconChainError> {
+        let start_slot = Self::attestation_rewards_state_slot(start_epoch);
+        let end_slot = Self::attestation_rewards_state_slot(end_epoch);
+
+        let resume_state = {
+            let mut replay_state = self.attestation_rewards_cache.replay_state.lock();
+            if replay_state
+                .as_ref()
+                .map_or(false, |state| state.slot() < start_slot)
+            {
+                replay_state.take()
+            } else {
+                None
+            }
+        };
+
+        let (lower_limit, _) = self.store.get_historic_state_limits();
+        let base_slot = resume_state
+            .as_ref()
+            .map_or(lower_limit, |state| state.slot());
+        if let Some(max_replay_slots) = max_replay_slots {
+            if start_slot > base_slot + max_replay_slots {
+                // Keep the resume state for a later replay.
+                if let Some(state) = resume_state {
+                    self.keep_attestation_rewards_replay_state(state);
+                }
+                return Err(BeaconChainError::AttestationRewardsReplayTooLong {
+                    state_slot: start_slot,
+                    base_slot,
+                });
+            }
+        }
+
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        let on_state = |state: &BeaconState<T::EthSpec>, _| {
+            // Outcomes are computed from the state at the end of each epoch, and only exist once
+            // participation is recorded from Altair onwards.
+            if state.slot() != state.current_epoch().end_slot(slots_per_epoch)
+                || matches!(state, BeaconState::Base(_))
+            {
+                return Ok(true);
+            }
+            let outcomes = self.cache_attestation_outcomes(state)?;
+            if outcomes.epoch() < start_epoch {
+                return Ok(true);
+            }
+            f(outcomes)
+        };
+
+        // Start one slot after the base so that every epoch passed through is persisted.
+        let state = match resume_state {
+            Some(state) => {
+                let first_slot = state.slot() + 1;
+                self.store
+                    .replay_historic_states_from(state, first_slot, end_slot, on_state)?
+            }
+            None => self.store.replay_historic_states(
+                min(lower_limit + 1, start_slot),
+                end_slot,
+                on_state,
+            )?,
+        };
+        self.keep_attestation_rewards_replay_state(state);
+
+        Ok(())
+    }
+
+    /// Keeps `state` for the next replay, if it is newer than the state already kept.
+    fn keep_attestation_rewards_replay_state(&self, state: BeaconState<T::EthSpec>) {
+        let mut replay_state = self.attestation_rewards_cache.replay_state.lock();
+        if replay_state
+            .as_ref()
+            .map_or(true, |kept| kept.slot() < state.slot())
+        {
+            *replay_state = Some(state);
+        }
+    }
+
+    /// Returns the attestation outcomes for `epoch` from the cache, the database or a stored
+    /// state, or `None` if the state must be computed by replay.
+    fn get_stored_attestation_outcomes(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Option<Arc<EpochAttestationOutcomes>>, BeaconChainError> {
+        if let Some(outcomes) = self.attestation_rewards_cache.cache.lock().get(&epoch) {
+            return Ok(Some(outcomes.clone()));
+        }
+
+        let state_slot = Self::attestation_rewards_state_slot(epoch);
+        let is_finalized = state_slot < self.store.get_split_slot();
+        if is_finalized {
+            if let Some(outcomes) = self
+                .store
+                .get_item::<EpochAttestationOutcomes>(&Hash256::from_low_u64_be(epoch.as_u64()))?
+            {
+                let outcomes = Arc::new(outcomes);
+                self.attestation_rewards_cache
+                    .cache
+                    .lock()
+                    .put(epoch, outcomes.clone());
+                return Ok(Some(outcomes));
+            }
+
+            if self.attestation_rewards_require_replay(state_slot) {
+                return Ok(None);
+            }
+        }
+
+        let state_root = self
+            .state_root_at_slot(state_slot)?
+            .ok_or(BeaconChainError::NoStateForSlot(state_slot))?;
+
+        let state = self
+            .get_state(&state_root, Some(state_slot))?
+            .ok_or(BeaconChainError::MissingBeaconState(state_root))?;
+
+        let outcomes = Arc::new(EpochAttestationOutcomes::from_state(&state, &self.spec)?);
+        // The outcomes of unfinalized epochs may change, so are not cached. Those of finalized
+        // epochs are cheap to recompute from the stored state, so are not persisted.
+        if is_finalized {
+            self.attestation_rewards_cache
+                .cache
+                .lock()
+                .put(epoch, outcomes.clone());
+        }
+        Ok(Some(outcomes))
+    }
+
+    /// Computes the attestation outcomes from the replayed finalized `state`, caching and
+    /// persisting them.
+    fn cache_attestation_outcomes(
+        &self,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<Arc<EpochAttestationOutcomes>, BeaconChainError> {
+        let outcomes = EpochAttestationOutcomes::from_state(state, &self.spec)?;
+        let epoch = outcomes.epoch();
+        self.store
+            .put_item(&Hash256::from_low_u64_be(epoch.as_u64()), &outcomes)?;
+
+        let outcomes = Arc::new(outcomes);
+        self.attestation_rewards_cache
+            .cache
+            .lock()
+            .put(epoch, outcomes.clone());
+        Ok(outcomes)
+    }
+
+    /// Deletes the persisted attestation outcomes of epochs which no longer need to be replayed,
+    /// as their states have since been reconstructed or pruned.
+    pub fn prune_attestation_outcomes(&self) -> Result<(), BeaconChainError> {
+        let column = EpochAttestationOutcomes::db_column();
+
+        let mut batch = vec![];
+        for result in self.store.hot_db.iter_column_keys(column) {
+            let key = result?;
+            let epoch = Epoch::new(key.to_low_u64_be());
+            if !self.attestation_rewards_require_replay(Self::attestation_rewards_state_slot(epoch))
+            {
+                batch.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    column.into(),
+                    key.as_bytes(),
+                )));
+            }
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.store.hot_db.do_atomically(batch)?;
+
+        Ok(())
+    }
+
+    fn attestation_rewards_validator_indices(
+        &self,
+        outcomes: &EpochAttestationOutcomes,
+        validators: Vec<ValidatorId>,
+    ) -> Result<Vec<usize>, BeaconChainError> {
+        validators
+            .into_iter()
+            .map(|validator| match validator {
+                ValidatorId::Index(i) => Ok(i as usize),
+                ValidatorId::PublicKey(pubkey) => self
+                    .validator_index(&pubkey)?
+                    // Validators which joined after the epoch are unknown.
+                    .filter(|i| *i < outcomes.participation.len())
+                    .ok_or(BeaconChainError::ValidatorPubkeyUnknown(pubkey)),
+            })
+            .collect()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::reconstructed_payload_cache::ReconstructedPayloadCache;
 use crate::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use execution_layer::{ExecutionLayer, ExecutionPayloadBodyV1};
 use slog::{crit, debug, Logger};
@@ -30,6 +31,9 @@ pub enum Error {
     BlocksByRangeFailure(Box<execution_layer::Error>),
     RequestNotFound,
     BlockResultNotFound,
+    /// The result of a single block request was unexpectedly still shared, so its error could
+    /// only be copied in its debug form.
+    SharedBlockResult(String),
 }
 
 const BLOCKS_PER_RANGE_REQUEST: u64 = 32;
@@ -126,6 +130,7 @@ fn reconstruct_default_header_block<E: EthSpec>(
 fn reconstruct_blocks<E: EthSpec>(
     block_map: &mut HashMap<Hash256, Arc<BlockResult<E>>>,
     block_parts_with_bodies: HashMap<Hash256, BlockParts<E>>,
+    payload_cache: Option<&ReconstructedPayloadCache<E>>,
     log: &Logger,
 ) {
     for (root, block_parts) in block_parts_with_bodies {
@@ -134,6 +139,9 @@ fn reconstruct_blocks<E: EthSpec>(
                 Ok(payload) => {
                     let header_from_payload = ExecutionPayloadHeader::from(payload.to_ref());
                     if header_from_payload == *block_parts.header {
+                        if let Some(payload_cache) = payload_cache {
+                            payload_cache.insert(payload.clone());
+                        }
                         block_map.insert(
                             root,
                             Arc::new(
@@ -229,7 +237,12 @@ impl<E: EthSpec> BodiesByRange<E> {
         }
     }
 
-    async fn execute(&mut self, execution_layer: &ExecutionLayer<E>, log: &Logger) {
+    async fn execute(
+        &mut self,
+        execution_layer: &ExecutionLayer<E>,
+        payload_cache: Option<&ReconstructedPayloadCache<E>>,
+        log: &Logger,
+    ) {
         if let RequestState::UnSent(blocks_parts_ref) = &mut self.state {
             let block_parts_vec = std::mem::take(blocks_parts_ref);
 
@@ -258,7 +271,7 @@ impl<E: EthSpec> BodiesByRange<E> {
                             });
                     }
 
-                    reconstruct_blocks(&mut block_map, with_bodies, log);
+                    reconstruct_blocks(&mut block_map, with_bodies, payload_cache, log);
                 }
                 Err(e) => {
                     let block_result =
@@ -277,9 +290,10 @@ impl<E: EthSpec> BodiesByRange<E> {
         &mut self,
         root: &Hash256,
         execution_layer: &ExecutionLayer<E>,
+        payload_cache: Option<&ReconstructedPayloadCache<E>>,
         log: &Logger,
     ) -> Option<Arc<BlockResult<E>>> {
-        self.execute(execution_layer, log).await;
+        self.execute(execution_layer, payload_cache, log).await;
         if let RequestState::Sent(map) = &self.state {
             return map.get(root).cloned();
         }
@@ -358,6 +372,7 @@ impl<E: EthSpec> EngineRequest<E> {
         &self,
         root: &Hash256,
         execution_layer: &ExecutionLayer<E>,
+        payload_cache: Option<&ReconstructedPayloadCache<E>>,
         log: &Logger,
     ) -> Arc<BlockResult<E>> {
         match self {
@@ -365,7 +380,7 @@ impl<E: EthSpec> EngineRequest<E> {
                 by_range
                     .write()
                     .await
-                    .get_block_result(root, execution_layer, log)
+                    .get_block_result(root, execution_layer, payload_cache, log)
                     .await
             }
             Self::NoRequest(map) => map.read().await.get(root).cloned(),
@@ -449,8 +464,9 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
     /// Pre-process the loaded blocks into execution engine requests.
     ///
     /// The purpose of this function is to separate the blocks into 2 categories:
-    /// 1) no_request - when we already have the full block or there's an error
-    /// 2) blocks_by_range - used for blinded blocks
+    /// 1) no_request - when we already have the full block, its payload is cached, or there's an
+    ///    error
+    /// 2) blocks_by_range - used for other blinded blocks
     ///
     /// The function returns a vector of block roots in the same order as requested
     /// along with the engine request that each root corresponds to.
@@ -488,6 +504,16 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
                                     header,
                                     &self.beacon_chain.spec,
                                 )
+                            } else if let Some(payload) = self
+                                .beacon_chain
+                                .reconstructed_payload_cache
+                                .get(&header.block_hash())
+                            {
+                                blinded_block
+                                    .try_into_full_block(Some(payload))
+                                    .ok_or(BeaconChainError::AddPayloadLogicError)
+                                    .map(Arc::new)
+                                    .map(Some)
                             } else {
                                 // Add the block to the set requiring a by-range request.
                                 let block_parts = BlockParts::new(blinded_block, header);
@@ -552,13 +578,14 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
             self.beacon_chain.log,
             "Using slower fallback method of eth_getBlockByHash()"
         );
+        let cache_payload = block_roots.len() == 1;
         for root in block_roots {
             let cached_block = self.check_early_attester_cache(root);
             let block_result = if cached_block.is_some() {
                 Ok(cached_block)
             } else {
                 self.beacon_chain
-                    .get_block(&root)
+                    .get_block_and_cache_payload(&root, cache_payload)
                     .await
                     .map(|opt_block| opt_block.map(Arc::new))
             };
@@ -579,6 +606,11 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
         let mut n_sent = 0usize;
         let mut engine_requests = 0usize;
 
+        // Only cache the payloads of single block requests, so that batches (e.g. a peer syncing
+        // a range) don't evict the payloads of blocks which are requested repeatedly.
+        let payload_cache =
+            (n_roots == 1).then_some(&self.beacon_chain.reconstructed_payload_cache);
+
         let payloads = self.load_payloads(block_roots);
         let requests = self.get_requests(payloads).await;
 
@@ -588,7 +620,12 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
             }
 
             let result = request
-                .get_block_result(&root, &self.execution_layer, &self.beacon_chain.log)
+                .get_block_result(
+                    &root,
+                    &self.execution_layer,
+                    payload_cache,
+                    &self.beacon_chain.log,
+                )
                 .await;
 
             let successful = result
@@ -639,11 +676,36 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
                 }
             }
             Err(e) => {
-                send_errors(block_roots, sender, e).await;
+                // Blocks with payloads can still be served while the engine is unavailable.
+                debug!(
+                    self.beacon_chain.log,
+                    "Failed to get engine capabilities";
+                    "error" => ?e,
+                );
+                self.stream_blocks_fallback(block_roots, sender).await;
             }
         }
     }
 
+    /// Load a single block, reconstructing its payload if necessary.
+    ///
+    /// Unlike `launch_stream`, the block is loaded on the current task.
+    pub async fn get_block(self, block_root: Hash256) -> BlockResult<T::EthSpec> {
+        let (block_tx, mut block_rx) = mpsc::unbounded_channel();
+        self.stream(vec![block_root], block_tx).await;
+
+        let result = match block_rx.recv().await {
+            Some((_, result)) => result,
+            None => return Err(Error::BlockResultNotFound.into()),
+        };
+        // The streamer has finished and dropped its requests, so this is the only reference to
+        // the result and its error can be returned as is.
+        Arc::try_unwrap(result).unwrap_or_else(|result| match result.as_ref() {
+            Ok(block) => Ok(block.clone()),
+            Err(e) => Err(Error::SharedBlockResult(format!("{:?}", e)).into()),
+        })
+    }
+
     pub fn launch_stream(
         self,
         block_roots: Vec<Hash256>,
@@ -660,19 +722,6 @@ impl<T: BeaconChainTypes> BeaconBlockStreamer<T> {
     }
 }
 
-async fn send_errors<E: EthSpec>(
-    block_roots: Vec<Hash256>,
-    sender: UnboundedSender<(Hash256, Arc<BlockResult<E>>)>,
-    beacon_chain_error: BeaconChainError,
-) {
-    let result = Arc::new(Err(beacon_chain_error));
-    for root in block_roots {
-        if sender.send((root, result.clone())).is_err() {
-            break;
-        }
-    }
-}
-
 impl From<Error> for BeaconChainError {
     fn from(value: Error) -> Self {
         BeaconChainError::BlockStreamerError(value)
@@ -687,8 +736,12 @@ mod tests {
     use execution_layer::EngineCapabilities;
     use lazy_static::lazy_static;
     use std::time::Duration;
+    use store::DatabaseBlock;
     use tokio::sync::mpsc;
-    use types::{ChainSpec, Epoch, EthSpec, Hash256, Keypair, MinimalEthSpec, Slot};
+    use types::{
+        ChainSpec, Epoch, EthSpec, ExecPayload, ExecutionBlockHash, Hash256, Keypair,
+        MinimalEthSpec, Slot,
+    };
 
     const VALIDATOR_COUNT: usize = 48;
     lazy_static! {
@@ -970,4 +1023,106 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn only_single_block_payloads_are_cached() {
+        let slots_per_epoch = MinimalEthSpec::slots_per_epoch() as usize;
+        let num_epochs = 8;
+        let bellatrix_fork_epoch = 2usize;
+
+        let mut spec = test_spec::<MinimalEthSpec>();
+        spec.altair_fork_epoch = Some(Epoch::new(0));
+        spec.bellatrix_fork_epoch = Some(Epoch::new(bellatrix_fork_epoch as u64));
+
+        let harness = get_harness(VALIDATOR_COUNT, spec);
+        // go to bellatrix fork
+        harness
+            .extend_slots(bellatrix_fork_epoch * slots_per_epoch)
+            .await;
+        // trigger merge
+        harness
+            .execution_block_generator()
+            .move_to_terminal_block()
+            .expect("should move to terminal block");
+        let timestamp = harness.get_timestamp_at_slot() + harness.spec.seconds_per_slot;
+        harness
+            .execution_block_generator()
+            .modify_last_block(|block| {
+                if let Block::PoW(terminal_block) = block {
+                    terminal_block.timestamp = timestamp;
+                }
+            });
+        // finish rest of epochs
+        harness
+            .extend_slots((num_epochs - bellatrix_fork_epoch) * slots_per_epoch)
+            .await;
+
+        let block_roots: Vec<Hash256> = harness
+            .chain
+            .forwards_iter_block_roots(Slot::new(0))
+            .expect("should get iter")
+            .map(Result::unwrap)
+            .map(|(root, _)| root)
+            .collect();
+
+        // the payloads of finalized post-merge blocks have been pruned
+        let mut pruned = vec![];
+        for root in &block_roots {
+            if let Some(DatabaseBlock::Blinded(block)) = harness
+                .chain
+                .store
+                .try_get_full_block(root)
+                .expect("should load block")
+            {
+                let block_hash = block
+                    .message()
+                    .execution_payload()
+                    .map(|payload| payload.to_execution_payload_header().block_hash())
+                    .unwrap_or_else(|_| ExecutionBlockHash::zero());
+                if block_hash != ExecutionBlockHash::zero() {
+                    pruned.push((*root, block_hash));
+                }
+            }
+        }
+        assert!(!pruned.is_empty(), "some payloads should be pruned");
+
+        let cache = &harness.chain.reconstructed_payload_cache;
+        for (_, block_hash) in &pruned {
+            assert!(!cache.contains(block_hash), "payload should not be cached");
+        }
+
+        let streamer = BeaconBlockStreamer::new(&harness.chain, CheckEarlyAttesterCache::No)
+            .expect("should create streamer");
+        let (block_tx, mut block_rx) = mpsc::unbounded_channel();
+        streamer.stream(block_roots.clone(), block_tx).await;
+        while let Some((root, result)) = block_rx.recv().await {
+            match result.as_ref() {
+                Ok(maybe_block) => assert!(maybe_block.is_some(), "should have block {}", root),
+                Err(e) => panic!("Error retrieving block {}: {:?}", root, e),
+            }
+        }
+
+        // the payloads of single blocks are cached, but not those reconstructed in batches
+        for (root, block_hash) in &pruned {
+            assert!(
+                !cache.contains(block_hash),
+                "batch payload should not be cached"
+            );
+
+            let block = harness
+                .chain
+                .get_block_via_streamer(*root)
+                .await
+                .expect("should get block")
+                .expect("block should exist");
+            let payload_block_hash = block
+                .message()
+                .execution_payload()
+                .expect("block should have payload")
+                .block_hash();
+            assert_eq!(payload_block_hash, *block_hash);
+            assert_eq!(block.canonical_root(), *root);
+            assert!(cache.contains(block_hash), "payload should be cached");
+        }
+    }
 }
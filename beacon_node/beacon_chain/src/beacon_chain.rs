@@ -1,3 +1,4 @@
+use crate::attestation_rewards::AttestationRewardsCache;
 use crate::attestation_verification::{
     batch_verify_aggregated_attestations, batch_verify_unaggregated_attestations,
     Error as AttestationError, VerifiedAggregatedAttestation, VerifiedAttestation,
@@ -7,7 +8,7 @@ use crate::attester_cache::{AttesterCache, AttesterCacheKey};
 use crate::beacon_block_streamer::{BeaconBlockStreamer, CheckEarlyAttesterCache};
 use crate::beacon_proposer_cache::compute_proposer_duties_from_head;
 use crate::beacon_proposer_cache::BeaconProposerCache;
-use crate::block_times_cache::BlockTimesCache;
+use crate::block_times_cache::{BlockTimesCache, PersistedBlockTimes};
 use crate::block_verification::{
     check_block_is_finalized_checkpoint_or_descendant, check_block_relevancy, get_block_root,
     signature_verify_chain_segment, BlockError, ExecutionPendingBlock, GossipVerifiedBlock,
@@ -15,6 +16,7 @@ use crate::block_verification::{
 };
 pub use crate::canonical_head::{CanonicalHead, CanonicalHeadRwLock};
 use crate::chain_config::ChainConfig;
+use crate::duties_cache::{AttesterDuties, DutiesCache, SyncCommitteeDuties};
 use crate::early_attester_cache::EarlyAttesterCache;
 use crate::errors::{BeaconChainError as Error, BlockProductionError};
 use crate::eth1_chain::{Eth1Chain, Eth1ChainBackend};
@@ -30,6 +32,9 @@ use crate::light_client_finality_update_verification::{
 use crate::light_client_optimistic_update_verification::{
     Error as LightClientOptimisticUpdateError, VerifiedLightClientOptimisticUpdate,
 };
+use crate::light_client_server_cache::{
+    LightClientProducedUpdates, LightClientProducerEvent, LightClientServerCache,
+};
 use crate::migrate::BackgroundMigrator;
 use crate::naive_aggregation_pool::{
     AggregatedAttestationMap, Error as NaiveAggregationError, NaiveAggregationPool,
@@ -46,6 +51,7 @@ use crate::observed_operations::{ObservationOutcome, ObservedOperations};
 use crate::persisted_beacon_chain::{PersistedBeaconChain, DUMMY_CANONICAL_HEAD_BLOCK_ROOT};
 use crate::persisted_fork_choice::PersistedForkChoice;
 use crate::pre_finalization_cache::PreFinalizationBlockCache;
+use crate::reconstructed_payload_cache::ReconstructedPayloadCache;
 use crate::shuffling_cache::{BlockShufflingIds, ShufflingCache};
 use crate::snapshot_cache::{BlockProductionPreState, SnapshotCache};
 use crate::sync_committee_verification::{
@@ -53,15 +59,16 @@ use crate::sync_committee_verification::{
 };
 use crate::timeout_rw_lock::TimeoutRwLock;
 use crate::validator_monitor::{
-    get_slot_delay_ms, timestamp_now, ValidatorMonitor,
+    get_slot_delay_ms, timestamp_now, PersistedEpochSummary, ValidatorMonitor,
     HISTORIC_EPOCHS as VALIDATOR_MONITOR_HISTORIC_EPOCHS,
 };
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::{metrics, BeaconChainError, BeaconForkChoiceStore, BeaconSnapshot, CachedHead};
+use eth2::lighthouse::StandardBlockReward;
 use eth2::types::{EventKind, SseBlock, SseExtendedPayloadAttributes, SyncDuty};
 use execution_layer::{
     BlockProposalContents, BuilderParams, ChainHealth, ExecutionLayer, FailedCondition,
-    PayloadAttributes, PayloadStatus,
+    PayloadAttributes, PayloadSource, PayloadStatus,
 };
 pub use fork_choice::CountUnrealized;
 use fork_choice::{
@@ -71,7 +78,10 @@ use fork_choice::{
 use futures::channel::mpsc::Sender;
 use itertools::process_results;
 use itertools::Itertools;
-use operation_pool::{AttestationRef, OperationPool, PersistedOperationPool, ReceivedPreCapella};
+use operation_pool::{
+    AttestationPackingStats, AttestationRef, OperationPool, PersistedOperationPool,
+    ReceivedPreCapella,
+};
 use parking_lot::{Mutex, RwLock};
 use proto_array::{DoNotReOrg, ProposerHeadError};
 use safe_arith::SafeArith;
@@ -100,9 +110,11 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use store::iter::{BlockRootsIterator, ParentRootBlockIterator, StateRootsIterator};
 use store::{
-    DatabaseBlock, Error as DBError, HotColdDB, KeyValueStore, KeyValueStoreOp, StoreItem, StoreOp,
+    get_key_for_col, DatabaseBlock, Error as DBError, HotColdDB, KeyValueStore, KeyValueStoreOp,
+    StoreItem, StoreOp,
 };
 use task_executor::{ShutdownReason, TaskExecutor};
+use tokio::sync::mpsc;
 use tokio_stream::Stream;
 use tree_hash::TreeHash;
 use types::beacon_state::CloneConfig;
@@ -284,6 +296,21 @@ struct PartialBeaconBlock<E: EthSpec, Payload: AbstractExecPayload<E>> {
     sync_aggregate: Option<SyncAggregate<E>>,
     prepare_payload_handle: Option<PreparePayloadHandle<E, Payload>>,
     bls_to_execution_changes: Vec<SignedBlsToExecutionChange>,
+    attestation_packing: AttestationPackingStats,
+}
+
+/// The result of a block production dry-run, see `BeaconChain::simulate_block`.
+pub struct BlockSimulation<E: EthSpec, Payload: AbstractExecPayload<E>> {
+    /// The unsigned block, with an empty RANDAO reveal.
+    pub block: BeaconBlock<E, Payload>,
+    /// Statistics from packing the block's attestations.
+    pub attestation_packing: AttestationPackingStats,
+    /// Where the execution payload came from, `None` prior to the merge.
+    pub payload_source: Option<PayloadSource>,
+    /// The value of the execution payload to the proposer in Wei, `None` prior to the merge.
+    pub payload_value: Option<Uint256>,
+    /// The consensus rewards the proposer would receive for the block.
+    pub consensus_reward: StandardBlockReward,
 }
 
 pub type BeaconForkChoice<T> = ForkChoice<
@@ -367,6 +394,12 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub latest_seen_finality_update: Mutex<Option<LightClientFinalityUpdate<T::EthSpec>>>,
     /// The most recently validated light client optimistic update received on gossip.
     pub latest_seen_optimistic_update: Mutex<Option<LightClientOptimisticUpdate<T::EthSpec>>>,
+    /// Caches the light client updates produced from imported blocks.
+    pub light_client_server_cache: LightClientServerCache<T>,
+    /// Sender used to request light client updates signed by newly imported blocks.
+    pub light_client_server_tx: Option<mpsc::Sender<LightClientProducerEvent<T::EthSpec>>>,
+    /// Caches execution payloads recently fetched to reconstruct blocks with pruned payloads.
+    pub reconstructed_payload_cache: ReconstructedPayloadCache<T::EthSpec>,
     /// Provides information from the Ethereum 1 (PoW) chain.
     pub eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec>>,
     /// Interfaces with the execution client.
@@ -399,6 +432,8 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub eth1_finalization_cache: TimeoutRwLock<Eth1FinalizationCache>,
     /// Caches the beacon block proposer shuffling for a given epoch and shuffling key root.
     pub beacon_proposer_cache: Mutex<BeaconProposerCache>,
+    /// Caches pre-computed attester, proposer and sync committee duties for recent epochs.
+    pub duties_cache: Mutex<DutiesCache>,
     /// Caches a map of `validator_index -> validator_pubkey`.
     pub(crate) validator_pubkey_cache: TimeoutRwLock<ValidatorPubkeyCache<T>>,
     /// A cache used when producing attestations.
@@ -409,6 +444,8 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub block_times_cache: Arc<RwLock<BlockTimesCache>>,
     /// A cache used to track pre-finalization block roots for quick rejection.
     pub pre_finalization_block_cache: PreFinalizationBlockCache,
+    /// A cache of the attestation outcomes of recently requested finalized epochs.
+    pub attestation_rewards_cache: AttestationRewardsCache<T::EthSpec>,
     /// Sender given to tasks, so that if they encounter a state in which execution cannot
     /// continue they can request that everything shuts down.
     pub shutdown_sender: Sender<ShutdownReason>,
@@ -562,6 +599,183 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(())
     }
 
+    /// Persists block times which have been pruned from `self.block_times_cache` to disk.
+    pub fn persist_block_times(
+        &self,
+        block_times: Vec<(Hash256, PersistedBlockTimes)>,
+    ) -> Result<(), Error> {
+        if block_times.is_empty() || self.config.block_times_history_epochs == 0 {
+            return Ok(());
+        }
+
+        let batch = block_times
+            .iter()
+            .map(|(block_root, times)| times.as_kv_store_op(*block_root))
+            .collect();
+        self.store.hot_db.do_atomically(batch)?;
+
+        Ok(())
+    }
+
+    /// Deletes persisted block times which are more than `block_times_history_epochs` older than
+    /// `finalized_slot`.
+    ///
+    /// Every entry is visited, so the times of orphaned blocks which were persisted after their
+    /// fork was pruned are deleted once they fall outside of the window too.
+    pub fn prune_block_times(&self, finalized_slot: Slot) -> Result<(), Error> {
+        let history_slots = self
+            .config
+            .block_times_history_epochs
+            .saturating_mul(T::EthSpec::slots_per_epoch());
+        let column = PersistedBlockTimes::db_column();
+
+        let mut batch = vec![];
+        for result in self.store.hot_db.iter_column(column) {
+            let (block_root, bytes) = result?;
+            let block_times = PersistedBlockTimes::from_store_bytes(&bytes)?;
+            if block_times.slot.saturating_add(history_slots) < finalized_slot {
+                batch.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    column.into(),
+                    block_root.as_bytes(),
+                )));
+            }
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+        debug!(
+            self.log,
+            "Pruning block times";
+            "count" => batch.len(),
+            "finalized_slot" => finalized_slot,
+        );
+        self.store.hot_db.do_atomically(batch)?;
+
+        Ok(())
+    }
+
+    /// Writes the events journaled since the last call to disk, if the journal is enabled.
+    pub fn persist_event_journal(&self) -> Result<(), Error> {
+        if let Some(event_handler) = self.event_handler.as_ref() {
+            event_handler.persist_journal(&self.store)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the times at which the block with `block_root` was observed, imported and set as
+    /// head, from either `self.block_times_cache` or disk.
+    pub fn get_block_times(
+        &self,
+        block_root: Hash256,
+    ) -> Result<Option<PersistedBlockTimes>, Error> {
+        if let Some(block_times) = self.block_times_cache.read().get_persisted(block_root) {
+            return Ok(Some(block_times));
+        }
+        Ok(self.store.get_item(&block_root)?)
+    }
+
+    /// Persists validator monitor summaries to disk, deleting the summaries of the monitored
+    /// validators which fall outside of `validator_monitor_history_epochs` as of `epoch`.
+    ///
+    /// Summaries left behind by validators which are no longer monitored, or by epochs missed
+    /// while the node was offline, are deleted at startup by `prune_persisted_summaries`.
+    pub fn persist_validator_monitor_summaries(
+        &self,
+        summaries: Vec<PersistedEpochSummary>,
+        epoch: Epoch,
+    ) -> Result<(), Error> {
+        let history_epochs = self.config.validator_monitor_history_epochs;
+
+        let mut batch = summaries
+            .iter()
+            .filter(|summary| summary.epoch + history_epochs > epoch)
+            .map(|summary| {
+                summary.as_kv_store_op(PersistedEpochSummary::db_key(
+                    summary.validator_index,
+                    summary.epoch,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(expired_epoch) = epoch.as_u64().checked_sub(history_epochs) {
+            let validator_indices = self
+                .validator_monitor
+                .read()
+                .get_monitored_validator_indices();
+            let column = PersistedEpochSummary::db_column();
+            batch.extend(validator_indices.into_iter().map(|validator_index| {
+                let key = PersistedEpochSummary::db_key(validator_index, Epoch::new(expired_epoch));
+                KeyValueStoreOp::DeleteKey(get_key_for_col(column.into(), key.as_bytes()))
+            }));
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.store.hot_db.do_atomically(batch)?;
+
+        Ok(())
+    }
+
+    /// Returns the validator monitor summaries of the validator with `validator_index` for each
+    /// epoch in `start_epoch..=end_epoch` that a summary is available for, from either
+    /// `self.validator_monitor` or disk.
+    pub fn get_validator_monitor_summaries(
+        &self,
+        validator_index: u64,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+    ) -> Result<Vec<PersistedEpochSummary>, Error> {
+        let epochs = (start_epoch.as_u64()..=end_epoch.as_u64()).map(Epoch::new);
+
+        // Copy the summaries held in memory so that the lock isn't held while reading the disk.
+        let mut in_memory = {
+            let validator_monitor = self.validator_monitor.read();
+            match validator_monitor.get_monitored_validator(validator_index) {
+                Some(validator) => epochs
+                    .clone()
+                    .filter_map(|epoch| {
+                        validator
+                            .get_persisted_summary(epoch)
+                            .map(|summary| (epoch, summary))
+                    })
+                    .collect::<HashMap<_, _>>(),
+                None => HashMap::new(),
+            }
+        };
+
+        let mut summaries = vec![];
+        for epoch in epochs {
+            let summary = match in_memory.remove(&epoch) {
+                Some(summary) => Some(summary),
+                None => self
+                    .store
+                    .get_item(&PersistedEpochSummary::db_key(validator_index, epoch))?,
+            };
+            summaries.extend(summary);
+        }
+        Ok(summaries)
+    }
+
+    /// Checks the canonical head against the validator monitor alert rules, then publishes all
+    /// alerts raised since the previous call as server-sent events.
+    pub fn process_validator_monitor_alerts(&self) {
+        let head = self.head_snapshot();
+
+        let alerts = {
+            let mut validator_monitor = self.validator_monitor.write();
+            validator_monitor.process_head_state(&head.beacon_state, &self.spec);
+            validator_monitor.drain_alerts()
+        };
+
+        if let Some(event_handler) = self.event_handler.as_ref() {
+            for alert in alerts {
+                event_handler.register(EventKind::ValidatorMonitorAlert(alert));
+            }
+        }
+    }
+
     /// Returns the slot _right now_ according to `self.slot_clock`. Returns `Err` if the slot is
     /// unavailable.
     ///
@@ -1012,6 +1226,32 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         )
     }
 
+    /// Returns the block at the given root, if any, using the `BeaconBlockStreamer`.
+    ///
+    /// This is the same path used to serve blocks to peers, so pruned payloads are reconstructed
+    /// using the most efficient method supported by the execution engine.
+    ///
+    /// ## Errors
+    ///
+    /// May return a database or execution layer error.
+    pub async fn get_block_via_streamer(
+        self: &Arc<Self>,
+        block_root: Hash256,
+    ) -> Result<Option<Arc<SignedBeaconBlock<T::EthSpec>>>, Error> {
+        // Without an execution layer only full blocks can be served, which doesn't require the
+        // streamer.
+        if self.execution_layer.is_none() {
+            return self
+                .get_block(&block_root)
+                .await
+                .map(|block| block.map(Arc::new));
+        }
+
+        BeaconBlockStreamer::<T>::new(self, CheckEarlyAttesterCache::No)?
+            .get_block(block_root)
+            .await
+    }
+
     /// Returns the block at the given root, if any.
     ///
     /// ## Errors
@@ -1020,6 +1260,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     pub async fn get_block(
         &self,
         block_root: &Hash256,
+    ) -> Result<Option<SignedBeaconBlock<T::EthSpec>>, Error> {
+        self.get_block_and_cache_payload(block_root, true).await
+    }
+
+    /// Returns the block at the given root, if any, only adding a payload loaded from the
+    /// execution layer to `self.reconstructed_payload_cache` if `cache_payload` is `true`.
+    pub(crate) async fn get_block_and_cache_payload(
+        &self,
+        block_root: &Hash256,
+        cache_payload: bool,
     ) -> Result<Option<SignedBeaconBlock<T::EthSpec>>, Error> {
         // Load block from database, returning immediately if we have the full block w payload
         // stored.
@@ -1039,6 +1289,14 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         let exec_block_hash = execution_payload_header.block_hash();
 
+        // Cached payloads have already been verified against a header with the same block hash.
+        if let Some(execution_payload) = self.reconstructed_payload_cache.get(&exec_block_hash) {
+            return blinded_block
+                .try_into_full_block(Some(execution_payload))
+                .ok_or(Error::AddPayloadLogicError)
+                .map(Some);
+        }
+
         let execution_payload = self
             .execution_layer
             .as_ref()
@@ -1069,6 +1327,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             });
         }
 
+        if cache_payload {
+            self.reconstructed_payload_cache
+                .insert(execution_payload.clone());
+        }
+
         // Add the payload to the block to form a full block.
         blinded_block
             .try_into_full_block(Some(execution_payload))
@@ -1085,6 +1348,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
     /// Returns the state at the given root, if any.
     ///
+    /// States are served from the snapshot cache where possible. States which had to be replayed
+    /// when loaded from the database are added to the cache, so that repeated requests for the
+    /// same state do not replay it again.
+    ///
     /// ## Errors
     ///
     /// May return a database error.
@@ -1093,7 +1360,44 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         state_root: &Hash256,
         slot: Option<Slot>,
     ) -> Result<Option<BeaconState<T::EthSpec>>, Error> {
-        Ok(self.store.get_state(state_root, slot)?)
+        let cached_state = self
+            .snapshot_cache
+            .try_write_for(BLOCK_PROCESSING_CACHE_LOCK_TIMEOUT)
+            .and_then(|mut snapshot_cache| snapshot_cache.get_state_by_root(*state_root));
+        if let Some(state) = cached_state {
+            metrics::inc_counter(&metrics::STATE_CACHE_HITS);
+            return Ok(Some(Arc::try_unwrap(state).unwrap_or_else(|state| {
+                state.clone_with(CloneConfig::committee_caches_only())
+            })));
+        }
+        metrics::inc_counter(&metrics::STATE_CACHE_MISSES);
+
+        let state = self.store.get_state(state_root, slot)?;
+
+        if let Some(state) = &state {
+            if self.state_requires_replay(state.slot()) {
+                let hot = state.slot() >= self.store.get_split_slot();
+                let cached_state = state.clone_with(CloneConfig::committee_caches_only());
+                if let Some(mut snapshot_cache) = self
+                    .snapshot_cache
+                    .try_write_for(BLOCK_PROCESSING_CACHE_LOCK_TIMEOUT)
+                {
+                    snapshot_cache.insert_state(*state_root, cached_state, hot);
+                }
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Returns `true` if the state at `slot` is not stored in full by the database, and so must be
+    /// replayed when it is loaded.
+    fn state_requires_replay(&self, slot: Slot) -> bool {
+        if slot >= self.store.get_split_slot() {
+            slot % T::EthSpec::slots_per_epoch() != 0
+        } else {
+            slot % self.store.get_config().slots_per_restore_point != 0
+        }
     }
 
     /// Return the sync committee at `slot + 1` from the canonical chain.
@@ -1396,7 +1700,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 
-    /// Returns the attestation duties for the given validator indices using the shuffling cache.
+    /// Returns the attestation duties for the given validator indices using the duties cache.
     ///
     /// An error may be returned if `head_block_root` is a finalized block, this function is only
     /// designed for operations at the head of the chain.
@@ -1406,37 +1710,57 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ///
     /// ## Notes
     ///
-    /// This function will try to use the shuffling cache to return the value. If the value is not
-    /// in the shuffling cache, it will be added. Care should be taken not to wash out the
-    /// shuffling cache with historical/useless values.
+    /// This function will try to use the duties cache to return the value. If the value is not
+    /// in the duties cache, it will be computed from the shuffling cache and added to both. Care
+    /// should be taken not to wash out either cache with historical/useless values.
     pub fn validator_attestation_duties(
         &self,
         validator_indices: &[u64],
         epoch: Epoch,
         head_block_root: Hash256,
     ) -> Result<(Vec<Option<AttestationDuty>>, Hash256, ExecutionStatus), Error> {
-        let execution_status = self
-            .canonical_head
-            .fork_choice_read_lock()
-            .get_block_execution_status(&head_block_root)
-            .ok_or(Error::AttestationHeadNotInForkChoice(head_block_root))?;
-
-        let (duties, dependent_root) = self.with_committee_cache(
-            head_block_root,
-            epoch,
-            |committee_cache, dependent_root| {
-                let duties = validator_indices
-                    .iter()
-                    .map(|validator_index| {
-                        let validator_index = *validator_index as usize;
-                        committee_cache.get_attestation_duties(validator_index)
-                    })
-                    .collect();
+        let (execution_status, shuffling_id) = {
+            let fork_choice = self.canonical_head.fork_choice_read_lock();
+            let head_block = fork_choice
+                .get_block(&head_block_root)
+                .ok_or(Error::AttestationHeadNotInForkChoice(head_block_root))?;
+            let shuffling_id = BlockShufflingIds {
+                current: head_block.current_epoch_shuffling_id.clone(),
+                next: head_block.next_epoch_shuffling_id.clone(),
+                block_root: head_block.root,
+            }
+            .id_for_epoch(epoch)
+            .ok_or_else(|| Error::InvalidShufflingId {
+                shuffling_epoch: epoch,
+                head_block_epoch: head_block.slot.epoch(T::EthSpec::slots_per_epoch()),
+            })?;
+            (head_block.execution_status, shuffling_id)
+        };
 
-                Ok((duties, dependent_root))
-            },
-        )?;
-        Ok((duties, dependent_root, execution_status))
+        let cached_duties = self.duties_cache.lock().get_attester(&shuffling_id);
+        let attester_duties = if let Some(attester_duties) = cached_duties {
+            attester_duties
+        } else {
+            let attester_duties =
+                self.with_committee_cache(head_block_root, epoch, |committee_cache, _| {
+                    Ok(Arc::new(AttesterDuties::new(committee_cache)?))
+                })?;
+            self.duties_cache
+                .lock()
+                .insert_attester(shuffling_id.clone(), attester_duties.clone());
+            attester_duties
+        };
+
+        let duties = validator_indices
+            .iter()
+            .map(|validator_index| attester_duties.get(*validator_index as usize))
+            .collect();
+
+        Ok((
+            duties,
+            shuffling_id.shuffling_decision_block,
+            execution_status,
+        ))
     }
 
     /// Returns an aggregated `Attestation`, if any, that has a matching `attestation.data`.
@@ -2257,6 +2581,70 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Check whether the block with `header` conflicts with a block previously observed from the
+    /// same proposer at the same slot. If it does, form a `ProposerSlashing` from the two headers,
+    /// verify it and queue it for inclusion in a block.
+    ///
+    /// Returns the slashing if it was not previously known, so that it may be published.
+    ///
+    /// This is intended to be called for blocks rejected with `BlockError::RepeatProposal`, which
+    /// may not have had their signature checked. The signature of `header` is checked before any
+    /// state is loaded, so that invalid blocks are cheap to discard.
+    pub fn import_proposer_equivocation(
+        &self,
+        header: &SignedBeaconBlockHeader,
+    ) -> Result<Option<ProposerSlashing>, Error> {
+        let proposer_slashing = match self
+            .observed_block_producers
+            .read()
+            .proposer_slashing(header)?
+        {
+            Some(proposer_slashing) => proposer_slashing,
+            None => return Ok(None),
+        };
+
+        let signature_is_valid = {
+            let pubkey_cache = self
+                .validator_pubkey_cache
+                .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+                .ok_or(Error::ValidatorPubkeyCacheLockTimeout)?;
+            let fork = self
+                .spec
+                .fork_at_epoch(header.message.slot.epoch(T::EthSpec::slots_per_epoch()));
+            pubkey_cache
+                .get(header.message.proposer_index as usize)
+                .map_or(false, |pubkey| {
+                    header.verify_signature::<T::EthSpec>(
+                        pubkey,
+                        &fork,
+                        self.genesis_validators_root,
+                        &self.spec,
+                    )
+                })
+        };
+        if !signature_is_valid {
+            return Ok(None);
+        }
+
+        match self.verify_proposer_slashing_for_gossip(proposer_slashing)? {
+            ObservationOutcome::New(verified_slashing) => {
+                let proposer_slashing = verified_slashing.as_inner().clone();
+                warn!(
+                    self.log,
+                    "Proposer equivocation detected";
+                    "proposer_index" => header.message.proposer_index,
+                    "slot" => header.message.slot,
+                    "block_root_1" => ?proposer_slashing.signed_header_1.message.canonical_root(),
+                    "block_root_2" => ?proposer_slashing.signed_header_2.message.canonical_root(),
+                );
+                metrics::inc_counter(&metrics::PROPOSER_EQUIVOCATIONS_DETECTED);
+                self.import_proposer_slashing(verified_slashing);
+                Ok(Some(proposer_slashing))
+            }
+            ObservationOutcome::AlreadyKnown => Ok(None),
+        }
+    }
+
     /// Verify an attester slashing before allowing it to propagate on the gossip network.
     pub fn verify_attester_slashing_for_gossip(
         &self,
@@ -2368,11 +2756,52 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         epoch: Epoch,
         validator_indices: &[u64],
     ) -> Result<Vec<Option<SyncDuty>>, Error> {
-        self.with_head(move |head| {
-            head.beacon_state
-                .get_sync_committee_duties(epoch, validator_indices, &self.spec)
-                .map_err(Error::SyncDutiesError)
-        })
+        let sync_committee_period = epoch.sync_committee_period(&self.spec)?;
+        let (sync_committee, validator_count) = self.with_head(move |head| {
+            let state = &head.beacon_state;
+            let sync_committee = state
+                .get_built_sync_committee(epoch, &self.spec)
+                .map_err(Error::SyncDutiesError)?
+                .clone();
+            Ok::<_, Error>((sync_committee, state.validators().len()))
+        })?;
+
+        let cached_duties = self
+            .duties_cache
+            .lock()
+            .get_sync_committee(sync_committee_period, sync_committee.aggregate_pubkey);
+        let sync_committee_duties = if let Some(sync_committee_duties) = cached_duties {
+            sync_committee_duties
+        } else {
+            let pubkey_cache = self
+                .validator_pubkey_cache
+                .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+                .ok_or(Error::ValidatorPubkeyCacheLockTimeout)?;
+            let sync_committee_duties =
+                Arc::new(SyncCommitteeDuties::new(&sync_committee, |pubkey| {
+                    pubkey_cache.get_index(pubkey)
+                }));
+            drop(pubkey_cache);
+
+            self.duties_cache.lock().insert_sync_committee(
+                sync_committee_period,
+                sync_committee.aggregate_pubkey,
+                sync_committee_duties.clone(),
+            );
+            sync_committee_duties
+        };
+
+        validator_indices
+            .iter()
+            .map(|&validator_index| {
+                if validator_index as usize >= validator_count {
+                    return Err(Error::SyncDutiesError(BeaconStateError::UnknownValidator(
+                        validator_index as usize,
+                    )));
+                }
+                Ok(sync_committee_duties.get(validator_index))
+            })
+            .collect()
     }
 
     /// A convenience method for spawning a blocking task. It maps an `Option` and
@@ -2982,6 +3411,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             parent_block.slot(),
         );
         self.import_block_update_slasher(block, &state, &mut consensus_context);
+        self.import_block_update_light_client_server(block, block_root, &mut state);
 
         let db_write_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_DB_WRITE);
 
@@ -3358,6 +3788,129 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Cache the data required to produce light client updates signed by descendants of `block`,
+    /// and request the updates signed by the sync aggregate of `block` itself.
+    fn import_block_update_light_client_server(
+        &self,
+        block: BeaconBlockRef<T::EthSpec>,
+        block_root: Hash256,
+        state: &mut BeaconState<T::EthSpec>,
+    ) {
+        if !self.config.enable_light_client_server {
+            return;
+        }
+
+        if let Err(e) = self
+            .light_client_server_cache
+            .cache_state_data(&self.spec, block, block_root, state)
+        {
+            error!(
+                self.log,
+                "Failed to cache light client data";
+                "error" => ?e,
+                "block_root" => ?block_root,
+            );
+        }
+
+        // Blocks prior to Altair have no sync aggregate.
+        if let (Some(light_client_server_tx), Ok(sync_aggregate)) = (
+            self.light_client_server_tx.as_ref(),
+            block.body().sync_aggregate(),
+        ) {
+            let event = LightClientProducerEvent {
+                attested_block_root: block.parent_root(),
+                signature_slot: block.slot(),
+                sync_aggregate: sync_aggregate.clone(),
+            };
+            if let Err(e) = light_client_server_tx.try_send(event) {
+                debug!(
+                    self.log,
+                    "Failed to request light client updates";
+                    "error" => %e,
+                    "block_root" => ?block_root,
+                );
+            }
+        }
+    }
+
+    /// Produce the light client updates signed by the sync aggregate in `event`.
+    ///
+    /// Returns the finality and optimistic updates which supersede those previously produced, so
+    /// that they may be published.
+    pub fn recompute_and_cache_light_client_updates(
+        &self,
+        event: LightClientProducerEvent<T::EthSpec>,
+    ) -> Result<LightClientProducedUpdates<T::EthSpec>, Error> {
+        let produced = self.light_client_server_cache.recompute_and_cache_updates(
+            &self.store,
+            &event,
+            &self.spec,
+        )?;
+
+        if let Some(event_handler) = self.event_handler.as_ref() {
+            if let Some(finality_update) = produced.finality_update.as_ref() {
+                if event_handler.has_light_client_finality_update_subscribers() {
+                    event_handler.register(EventKind::LightClientFinalityUpdate(Box::new(
+                        finality_update.clone(),
+                    )));
+                }
+            }
+            if let Some(optimistic_update) = produced.optimistic_update.as_ref() {
+                if event_handler.has_light_client_optimistic_update_subscribers() {
+                    event_handler.register(EventKind::LightClientOptimisticUpdate(Box::new(
+                        optimistic_update.clone(),
+                    )));
+                }
+            }
+        }
+
+        Ok(produced)
+    }
+
+    /// Produce a `LightClientBootstrap` for the block with the given root, along with the fork at
+    /// that block.
+    ///
+    /// Returns `None` if the block is unknown or prior to Altair.
+    pub fn get_light_client_bootstrap(
+        &self,
+        block_root: &Hash256,
+    ) -> Result<Option<(LightClientBootstrap<T::EthSpec>, ForkName)>, Error> {
+        let block = match self.get_blinded_block(block_root)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let fork_name = self.spec.fork_name_at_slot::<T::EthSpec>(block.slot());
+        if fork_name == ForkName::Base {
+            return Ok(None);
+        }
+
+        let mut state = self
+            .get_state(&block.state_root(), Some(block.slot()))?
+            .ok_or_else(|| Error::MissingBeaconState(block.state_root()))?;
+        let bootstrap = LightClientBootstrap::from_beacon_state(&mut state)?;
+
+        Ok(Some((bootstrap, fork_name)))
+    }
+
+    /// Return the best `LightClientUpdate` persisted for each of the `count` sync committee
+    /// periods starting at `start_period`.
+    ///
+    /// The result stops at the first period for which no update is known.
+    pub fn get_light_client_updates(
+        &self,
+        start_period: u64,
+        count: u64,
+    ) -> Result<Vec<LightClientUpdate<T::EthSpec>>, Error> {
+        let mut updates = vec![];
+        for period in start_period..start_period.saturating_add(count) {
+            match self.store.get_light_client_update(period)? {
+                Some(update) => updates.push(update),
+                None => break,
+            }
+        }
+        Ok(updates)
+    }
+
     fn import_block_update_metrics_and_events(
         &self,
         block: BeaconBlockRef<T::EthSpec>,
@@ -4267,6 +4820,124 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .map_err(BlockProductionError::TokioJoin)?
     }
 
+    /// Produce a block at `slot` upon the head without a RANDAO reveal, returning it alongside
+    /// details of its contents and expected rewards.
+    ///
+    /// This allows operators to check that their node is ready to propose ahead of their slot.
+    /// The block has an empty RANDAO reveal and is neither signed, imported nor published.
+    pub async fn simulate_block<Payload: AbstractExecPayload<T::EthSpec> + 'static>(
+        self: &Arc<Self>,
+        slot: Slot,
+        validator_graffiti: Option<Graffiti>,
+    ) -> Result<BlockSimulation<T::EthSpec, Payload>, BlockProductionError> {
+        let randao_reveal = Signature::empty();
+
+        let chain = self.clone();
+        let mut partial_beacon_block = self
+            .task_executor
+            .spawn_blocking_handle(
+                move || {
+                    let (state, state_root_opt) = chain.load_state_for_block_simulation(slot)?;
+                    chain.produce_partial_beacon_block(
+                        state,
+                        state_root_opt,
+                        slot,
+                        randao_reveal,
+                        validator_graffiti,
+                    )
+                },
+                "simulate_partial_beacon_block",
+            )
+            .ok_or(BlockProductionError::ShuttingDown)?
+            .await
+            .map_err(BlockProductionError::TokioJoin)??;
+
+        let prepare_payload_handle = partial_beacon_block.prepare_payload_handle.take();
+        let block_contents = if let Some(prepare_payload_handle) = prepare_payload_handle {
+            Some(
+                prepare_payload_handle
+                    .await
+                    .map_err(BlockProductionError::TokioJoin)?
+                    .ok_or(BlockProductionError::ShuttingDown)??,
+            )
+        } else {
+            None
+        };
+        let payload_source = block_contents.as_ref().map(BlockProposalContents::source);
+        let payload_value = block_contents
+            .as_ref()
+            .map(|contents| *contents.block_value());
+
+        let chain = self.clone();
+        self.task_executor
+            .spawn_blocking_handle(
+                move || {
+                    let attestation_packing = partial_beacon_block.attestation_packing;
+                    let mut pre_state = partial_beacon_block
+                        .state
+                        .clone_with(CloneConfig::committee_caches_only());
+
+                    let (block, _) = chain.complete_partial_beacon_block(
+                        partial_beacon_block,
+                        block_contents,
+                        ProduceBlockVerification::NoVerification,
+                    )?;
+
+                    let consensus_reward = chain
+                        .compute_beacon_block_reward(
+                            block.to_ref(),
+                            block.canonical_root(),
+                            &mut pre_state,
+                        )
+                        .map_err(BlockProductionError::BeaconChain)?;
+
+                    Ok(BlockSimulation {
+                        block,
+                        attestation_packing,
+                        payload_source,
+                        payload_value,
+                        consensus_reward,
+                    })
+                },
+                "simulate_complete_beacon_block",
+            )
+            .ok_or(BlockProductionError::ShuttingDown)?
+            .await
+            .map_err(BlockProductionError::TokioJoin)?
+    }
+
+    /// Load a state for `simulate_block`.
+    ///
+    /// Unlike `load_state_for_block_production` this doesn't wait for fork choice or consider
+    /// re-orgs, since the simulated slot may be some distance in the future.
+    fn load_state_for_block_simulation(
+        self: &Arc<Self>,
+        slot: Slot,
+    ) -> Result<(BeaconState<T::EthSpec>, Option<Hash256>), BlockProductionError> {
+        let (head_slot, head_block_root) = {
+            let head = self.canonical_head.cached_head();
+            (head.head_slot(), head.head_block_root())
+        };
+
+        if head_slot < slot {
+            if let Some(pre_state) = self
+                .snapshot_cache
+                .try_read_for(BLOCK_PROCESSING_CACHE_LOCK_TIMEOUT)
+                .and_then(|snapshot_cache| {
+                    snapshot_cache.get_state_for_block_production(head_block_root)
+                })
+            {
+                return Ok((pre_state.pre_state, pre_state.state_root));
+            }
+        }
+
+        let state = self
+            .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
+            .map_err(|_| BlockProductionError::UnableToProduceAtSlot(slot))?;
+
+        Ok((state, None))
+    }
+
     fn produce_partial_beacon_block<Payload: AbstractExecPayload<T::EthSpec> + 'static>(
         self: &Arc<Self>,
         mut state: BeaconState<T::EthSpec>,
@@ -4383,9 +5054,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             self.filter_op_pool_attestation(&mut curr_filter_cache, att, &state)
         };
 
-        let mut attestations = self
+        let (mut attestations, attestation_packing) = self
             .op_pool
-            .get_attestations(
+            .get_attestations_with_stats(
                 &state,
                 prev_attestation_filter,
                 curr_attestation_filter,
@@ -4503,6 +5174,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             sync_aggregate,
             prepare_payload_handle,
             bls_to_execution_changes,
+            attestation_packing,
         })
     }
 
@@ -4531,6 +5203,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             // produce said `execution_payload`.
             prepare_payload_handle: _,
             bls_to_execution_changes,
+            attestation_packing: _,
         } = partial_beacon_block;
 
         let inner_block = match &state {
@@ -5392,7 +6065,40 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             // Always run the light-weight pruning tasks (these structures should be empty during
             // sync anyway).
             self.naive_aggregation_pool.write().prune(slot);
-            self.block_times_cache.write().prune(slot);
+            let pruned_block_times = self.block_times_cache.write().prune(slot);
+            if let Err(e) = self.persist_block_times(pruned_block_times) {
+                warn!(
+                    self.log,
+                    "Failed to persist block times";
+                    "error" => ?e
+                );
+            }
+            if let Err(e) = self.persist_event_journal() {
+                warn!(
+                    self.log,
+                    "Failed to persist event journal";
+                    "error" => ?e
+                );
+            }
+
+            // Persist the validator monitor summaries from two epochs ago at the start of each
+            // epoch, by which time their on-chain attestation outcomes are known.
+            let epoch = slot.epoch(T::EthSpec::slots_per_epoch());
+            if slot == epoch.start_slot(T::EthSpec::slots_per_epoch()) {
+                if let Some(summary_epoch) = epoch.as_u64().checked_sub(2).map(Epoch::new) {
+                    let summaries = self
+                        .validator_monitor
+                        .read()
+                        .get_persisted_summaries_at_epoch(summary_epoch);
+                    if let Err(e) = self.persist_validator_monitor_summaries(summaries, epoch) {
+                        warn!(
+                            self.log,
+                            "Failed to persist validator monitor summaries";
+                            "error" => ?e
+                        );
+                    }
+                }
+            }
 
             // Don't run heavy-weight tasks during sync.
             if self.best_slot() + MAX_PER_SLOT_FORK_CHOICE_DISTANCE < slot {
@@ -5423,6 +6129,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 },
                 "per_slot_task_fc_signal_tx",
             );
+
+            // Check the new head against the validator monitor alert rules and publish any alerts
+            // raised since the previous slot.
+            if self.validator_monitor.read().alert_rules().is_enabled() {
+                let chain = self.clone();
+                self.task_executor.clone().spawn_blocking(
+                    move || chain.process_validator_monitor_alerts(),
+                    "validator_monitor_alerts",
+                );
+            }
         }
     }
 
@@ -5912,7 +6628,17 @@ impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
         let drop = || -> Result<(), Error> {
             self.persist_head_and_fork_choice()?;
             self.persist_op_pool()?;
-            self.persist_eth1_cache()
+            self.persist_eth1_cache()?;
+            // Block times which haven't yet been pruned from the cache would otherwise be lost.
+            let block_times = self.block_times_cache.write().prune(Slot::new(u64::MAX));
+            self.persist_block_times(block_times)?;
+            self.persist_event_journal()?;
+            // As for block times, summaries still held by the validator monitor would be lost.
+            if let Ok(epoch) = self.epoch() {
+                let summaries = self.validator_monitor.read().get_all_persisted_summaries();
+                self.persist_validator_monitor_summaries(summaries, epoch)?;
+            }
+            Ok(())
         };
 
         if let Err(e) = drop() {
@@ -8,10 +8,16 @@
 //! - The block was observed late.
 //! - We were too slow to import it.
 //! - We were too slow to set it as head.
+//!
+//! Entries are pruned from the cache after two epochs, at which point they are persisted to the
+//! database as `PersistedBlockTimes` for historical analysis.
 
 use eth2::types::{Hash256, Slot};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
 use std::collections::HashMap;
 use std::time::Duration;
+use store::{DBColumn, Error as StoreError, StoreItem};
 
 type BlockRoot = Hash256;
 
@@ -31,7 +37,7 @@ pub struct BlockDelays {
 }
 
 impl BlockDelays {
-    fn new(times: Timestamps, slot_start_time: Duration) -> BlockDelays {
+    pub fn new(times: Timestamps, slot_start_time: Duration) -> BlockDelays {
         let observed = times
             .observed
             .and_then(|observed_time| observed_time.checked_sub(slot_start_time));
@@ -73,6 +79,69 @@ impl BlockTimesCacheValue {
     }
 }
 
+/// The contents of a `BlockTimesCacheValue` as stored in the database, keyed by block root.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct PersistedBlockTimes {
+    pub slot: Slot,
+    /// Timestamps in milliseconds since the UNIX epoch.
+    pub observed: Option<u64>,
+    pub imported: Option<u64>,
+    pub set_as_head: Option<u64>,
+    /// UTF-8 encoded ID and client of the peer which first sent us the block over gossip.
+    pub peer_id: Option<Vec<u8>>,
+    pub peer_client: Option<Vec<u8>>,
+}
+
+impl PersistedBlockTimes {
+    pub fn timestamps(&self) -> Timestamps {
+        Timestamps {
+            observed: self.observed.map(Duration::from_millis),
+            imported: self.imported.map(Duration::from_millis),
+            set_as_head: self.set_as_head.map(Duration::from_millis),
+        }
+    }
+
+    pub fn peer_info(&self) -> BlockPeerInfo {
+        let decode = |bytes: &Option<Vec<u8>>| {
+            bytes
+                .as_ref()
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+        };
+        BlockPeerInfo {
+            id: decode(&self.peer_id),
+            client: decode(&self.peer_client),
+        }
+    }
+}
+
+impl From<&BlockTimesCacheValue> for PersistedBlockTimes {
+    fn from(value: &BlockTimesCacheValue) -> Self {
+        let millis = |time: Option<Duration>| time.map(|time| time.as_millis() as u64);
+        PersistedBlockTimes {
+            slot: value.slot,
+            observed: millis(value.timestamps.observed),
+            imported: millis(value.timestamps.imported),
+            set_as_head: millis(value.timestamps.set_as_head),
+            peer_id: value.peer_info.id.clone().map(String::into_bytes),
+            peer_client: value.peer_info.client.clone().map(String::into_bytes),
+        }
+    }
+}
+
+impl StoreItem for PersistedBlockTimes {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconBlockTimes
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
 #[derive(Default)]
 pub struct BlockTimesCache {
     pub cache: HashMap<BlockRoot, BlockTimesCacheValue>,
@@ -135,9 +204,67 @@ impl BlockTimesCache {
         }
     }
 
-    // Prune the cache to only store the most recent 2 epochs.
-    pub fn prune(&mut self, current_slot: Slot) {
-        self.cache
-            .retain(|_, cache| cache.slot > current_slot.saturating_sub(64_u64));
+    pub fn get_persisted(&self, block_root: BlockRoot) -> Option<PersistedBlockTimes> {
+        self.cache.get(&block_root).map(Into::into)
+    }
+
+    // Prune the cache to only store the most recent 2 epochs, returning the pruned entries so
+    // that they can be persisted.
+    pub fn prune(&mut self, current_slot: Slot) -> Vec<(BlockRoot, PersistedBlockTimes)> {
+        let prune_slot = current_slot.saturating_sub(64_u64);
+        let pruned = self
+            .cache
+            .iter()
+            .filter(|(_, cache)| cache.slot <= prune_slot)
+            .map(|(block_root, cache)| (*block_root, cache.into()))
+            .collect();
+        self.cache.retain(|_, cache| cache.slot > prune_slot);
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_returns_persisted_entries() {
+        let mut cache = BlockTimesCache::default();
+        let old_root = Hash256::repeat_byte(1);
+        let new_root = Hash256::repeat_byte(2);
+
+        cache.set_time_observed(
+            old_root,
+            Slot::new(1),
+            Duration::from_millis(12_500),
+            Some("peer".into()),
+            Some("client".into()),
+        );
+        cache.set_time_imported(old_root, Slot::new(1), Duration::from_millis(13_000));
+        cache.set_time_observed(
+            new_root,
+            Slot::new(100),
+            Duration::from_millis(1_200_000),
+            None,
+            None,
+        );
+
+        let pruned = cache.prune(Slot::new(100));
+        assert_eq!(pruned.len(), 1);
+        assert!(cache.cache.contains_key(&new_root));
+
+        let (block_root, persisted) = &pruned[0];
+        assert_eq!(*block_root, old_root);
+        assert_eq!(persisted.slot, Slot::new(1));
+
+        let decoded = PersistedBlockTimes::from_store_bytes(&persisted.as_store_bytes()).unwrap();
+        assert_eq!(&decoded, persisted);
+
+        let delays = BlockDelays::new(decoded.timestamps(), Duration::from_secs(12));
+        assert_eq!(delays.observed, Some(Duration::from_millis(500)));
+        assert_eq!(delays.imported, Some(Duration::from_millis(500)));
+        assert_eq!(delays.set_as_head, None);
+        assert_eq!(decoded.peer_info().id.as_deref(), Some("peer"));
+        assert_eq!(decoded.peer_info().client.as_deref(), Some("client"));
     }
 }
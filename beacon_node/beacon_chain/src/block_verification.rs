@@ -189,7 +189,14 @@ pub enum BlockError<T: EthSpec> {
     ///
     /// The `proposer` has already proposed a block at this slot. The existing block may or may not
     /// be equal to the given block.
-    RepeatProposal { proposer: u64, slot: Slot },
+    ///
+    /// The `header` of the given block is included so that a proposer slashing may be formed if it
+    /// conflicts with the existing block, without hashing the block again.
+    RepeatProposal {
+        proposer: u64,
+        slot: Slot,
+        header: Box<SignedBeaconBlockHeader>,
+    },
     /// The block slot exceeds the MAXIMUM_BLOCK_SLOT_NUMBER.
     ///
     /// ## Peer scoring
@@ -407,6 +414,9 @@ impl<T: EthSpec> std::fmt::Display for BlockError<T> {
             BlockError::ParentUnknown(block) => {
                 write!(f, "ParentUnknown(parent_root:{})", block.parent_root())
             }
+            BlockError::RepeatProposal { proposer, slot, .. } => {
+                write!(f, "RepeatProposal(proposer:{}, slot:{})", proposer, slot)
+            }
             other => write!(f, "{:?}", other),
         }
     }
@@ -679,14 +689,18 @@ impl<T: BeaconChainTypes> GossipVerifiedBlock<T> {
         // it to the slasher if an error occurs, because that's the end of this block's journey,
         // and it could be a repeat proposal (a likely cause for slashing!).
         let header = block.signed_block_header();
-        Self::new_without_slasher_checks(block, chain).map_err(|e| {
+        Self::new_without_slasher_checks(block, &header, chain).map_err(|e| {
             process_block_slash_info(chain, BlockSlashInfo::from_early_error(header, e))
         })
     }
 
     /// As for new, but doesn't pass the block to the slasher.
+    ///
+    /// The `signed_block_header` of `block` is provided by the caller, so that the block body is
+    /// only hashed once.
     fn new_without_slasher_checks(
         block: Arc<SignedBeaconBlock<T::EthSpec>>,
+        signed_block_header: &SignedBeaconBlockHeader,
         chain: &BeaconChain<T>,
     ) -> Result<Self, BlockError<T::EthSpec>> {
         // Ensure the block is the correct structure for the fork at `block.slot()`.
@@ -706,7 +720,8 @@ impl<T: BeaconChainTypes> GossipVerifiedBlock<T> {
             });
         }
 
-        let block_root = get_block_root(&block);
+        // The block root is computed from the header, so that the body isn't hashed again.
+        let block_root = signed_block_header.message.canonical_root();
 
         // Disallow blocks that conflict with the anchor (weak subjectivity checkpoint), if any.
         check_block_against_anchor_slot(block.message(), chain)?;
@@ -739,6 +754,7 @@ impl<T: BeaconChainTypes> GossipVerifiedBlock<T> {
             return Err(BlockError::RepeatProposal {
                 proposer: block.message().proposer_index(),
                 slot: block.slot(),
+                header: Box::new(signed_block_header.clone()),
             });
         }
 
@@ -863,12 +879,13 @@ impl<T: BeaconChainTypes> GossipVerifiedBlock<T> {
         if chain
             .observed_block_producers
             .write()
-            .observe_proposer(block.message())
+            .observe_proposer(signed_block_header)
             .map_err(|e| BlockError::BeaconChainError(e.into()))?
         {
             return Err(BlockError::RepeatProposal {
                 proposer: block.message().proposer_index(),
                 slot: block.slot(),
+                header: Box::new(signed_block_header.clone()),
             });
         }
 
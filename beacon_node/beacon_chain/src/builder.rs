@@ -1,15 +1,17 @@
 use crate::beacon_chain::{CanonicalHead, BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, OP_POOL_DB_KEY};
 use crate::eth1_chain::{CachingEth1Backend, SszEth1};
 use crate::eth1_finalization_cache::Eth1FinalizationCache;
+use crate::event_journal::EventJournal;
 use crate::fork_choice_signal::ForkChoiceSignalTx;
 use crate::fork_revert::{reset_fork_choice_to_finalization, revert_to_fork_boundary};
 use crate::head_tracker::HeadTracker;
+use crate::light_client_server_cache::LightClientProducerEvent;
 use crate::migrate::{BackgroundMigrator, MigratorConfig};
 use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::shuffling_cache::ShufflingCache;
-use crate::snapshot_cache::{SnapshotCache, DEFAULT_SNAPSHOT_CACHE_SIZE};
+use crate::snapshot_cache::SnapshotCache;
 use crate::timeout_rw_lock::TimeoutRwLock;
-use crate::validator_monitor::ValidatorMonitor;
+use crate::validator_monitor::{prune_persisted_summaries, AlertRules, ValidatorMonitor};
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::ChainConfig;
 use crate::{
@@ -31,6 +33,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use store::{Error as StoreError, HotColdDB, ItemStore, KeyValueStoreOp};
 use task_executor::{ShutdownReason, TaskExecutor};
+use tokio::sync::mpsc;
 use types::{
     BeaconBlock, BeaconState, ChainSpec, Checkpoint, Epoch, EthSpec, Graffiti, Hash256,
     PublicKeyBytes, Signature, SignedBeaconBlock, Slot,
@@ -91,6 +94,8 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
     graffiti: Graffiti,
     slasher: Option<Arc<Slasher<T::EthSpec>>>,
     validator_monitor: Option<ValidatorMonitor<T::EthSpec>>,
+    validator_monitor_alert_rules: AlertRules,
+    light_client_server_tx: Option<mpsc::Sender<LightClientProducerEvent<T::EthSpec>>>,
     // Pending I/O batch that is constructed during building and should be executed atomically
     // alongside `PersistedBeaconChain` storage when `BeaconChainBuilder::build` is called.
     pending_io_batch: Vec<KeyValueStoreOp>,
@@ -132,6 +137,8 @@ where
             graffiti: Graffiti::default(),
             slasher: None,
             validator_monitor: None,
+            validator_monitor_alert_rules: <_>::default(),
+            light_client_server_tx: None,
             pending_io_batch: vec![],
             task_executor: None,
         }
@@ -198,6 +205,15 @@ where
         self
     }
 
+    /// Sets a `Sender` to request light client updates from imported blocks.
+    pub fn light_client_server_tx(
+        mut self,
+        sender: mpsc::Sender<LightClientProducerEvent<TEthSpec>>,
+    ) -> Self {
+        self.light_client_server_tx = Some(sender);
+        self
+    }
+
     /// Sets the slasher.
     pub fn slasher(mut self, slasher: Arc<Slasher<TEthSpec>>) -> Self {
         self.slasher = Some(slasher);
@@ -597,6 +613,12 @@ where
         self
     }
 
+    /// Sets the rules according to which the validator monitor raises alerts.
+    pub fn validator_monitor_alert_rules(mut self, rules: AlertRules) -> Self {
+        self.validator_monitor_alert_rules = rules;
+        self
+    }
+
     /// Consumes `self`, returning a `BeaconChain` if all required parameters have been supplied.
     ///
     /// An error will be returned at runtime if all required parameters have not been configured.
@@ -627,6 +649,7 @@ where
         let mut validator_monitor = self
             .validator_monitor
             .ok_or("Cannot build without a validator monitor")?;
+        validator_monitor.set_alert_rules(self.validator_monitor_alert_rules);
         let head_tracker = Arc::new(self.head_tracker.unwrap_or_default());
 
         let current_slot = if slot_clock
@@ -736,6 +759,16 @@ where
             );
         }
 
+        // If enabled, load the journal of replayable server-sent events.
+        let mut event_handler = self.event_handler;
+        if let Some(event_handler) = event_handler.as_mut() {
+            if self.chain_config.event_journal_size > 0 {
+                let journal = EventJournal::load(&store, self.chain_config.event_journal_size)
+                    .map_err(|e| format!("Unable to load event journal: {:?}", e))?;
+                event_handler.set_journal(journal);
+            }
+        }
+
         // If enabled, set up the fork choice signaller.
         let (fork_choice_signal_tx, fork_choice_signal_rx) =
             if self.chain_config.fork_choice_before_proposal_timeout_ms != 0 {
@@ -771,6 +804,10 @@ where
         let head_for_snapshot_cache = head_snapshot.clone();
         let canonical_head = CanonicalHead::new(fork_choice, Arc::new(head_snapshot));
         let shuffling_cache_size = self.chain_config.shuffling_cache_size;
+        let state_cache_memory_bytes = self
+            .chain_config
+            .state_cache_memory_mb
+            .saturating_mul(1_048_576);
 
         let beacon_chain = BeaconChain {
             spec: self.spec,
@@ -809,6 +846,9 @@ where
             observed_bls_to_execution_changes: <_>::default(),
             latest_seen_finality_update: <_>::default(),
             latest_seen_optimistic_update: <_>::default(),
+            light_client_server_cache: <_>::default(),
+            light_client_server_tx: self.light_client_server_tx,
+            reconstructed_payload_cache: <_>::default(),
             eth1_chain: self.eth1_chain,
             execution_layer: self.execution_layer,
             genesis_validators_root,
@@ -818,17 +858,19 @@ where
             genesis_state_root,
             fork_choice_signal_tx,
             fork_choice_signal_rx,
-            event_handler: self.event_handler,
+            event_handler,
             head_tracker,
             snapshot_cache: TimeoutRwLock::new(SnapshotCache::new(
-                DEFAULT_SNAPSHOT_CACHE_SIZE,
+                state_cache_memory_bytes,
                 head_for_snapshot_cache,
             )),
             shuffling_cache: TimeoutRwLock::new(ShufflingCache::new(shuffling_cache_size)),
             eth1_finalization_cache: TimeoutRwLock::new(Eth1FinalizationCache::new(log.clone())),
             beacon_proposer_cache: <_>::default(),
+            duties_cache: <_>::default(),
             block_times_cache: <_>::default(),
             pre_finalization_block_cache: <_>::default(),
+            attestation_rewards_cache: <_>::default(),
             validator_pubkey_cache: TimeoutRwLock::new(validator_pubkey_cache),
             attester_cache: <_>::default(),
             early_attester_cache: <_>::default(),
@@ -901,6 +943,22 @@ where
             );
         }
 
+        // Prune validator monitor summaries which have expired while the node was offline, or
+        // which belong to validators that are no longer monitored (in the background).
+        if let Ok(epoch) = beacon_chain.epoch() {
+            let store = beacon_chain.store.clone();
+            let history_epochs = beacon_chain.config.validator_monitor_history_epochs;
+            let log = log.clone();
+            beacon_chain.task_executor.spawn_blocking(
+                move || {
+                    if let Err(e) = prune_persisted_summaries(&store, epoch, history_epochs) {
+                        error!(log, "Error pruning validator monitor summaries"; "error" => ?e);
+                    }
+                },
+                "prune_validator_monitor_summaries",
+            );
+        }
+
         Ok(beacon_chain)
     }
 }
@@ -1018,6 +1018,30 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // Take a write-lock on the canonical head and signal for it to prune.
         self.canonical_head.fork_choice_write_lock().prune()?;
 
+        // Prune block times which have fallen outside of the retention window off the fork choice
+        // path, since every persisted entry must be read. Attestation outcomes which no longer
+        // need to be replayed are pruned at the same time.
+        let chain = self.clone();
+        self.task_executor.spawn_blocking(
+            move || {
+                if let Err(e) = chain.prune_block_times(new_finalized_slot) {
+                    warn!(
+                        chain.log,
+                        "Failed to prune block times";
+                        "error" => ?e
+                    );
+                }
+                if let Err(e) = chain.prune_attestation_outcomes() {
+                    warn!(
+                        chain.log,
+                        "Failed to prune attestation outcomes";
+                        "error" => ?e
+                    );
+                }
+            },
+            "prune_block_times",
+        );
+
         Ok(())
     }
 
@@ -15,6 +15,12 @@ pub const DEFAULT_PREPARE_PAYLOAD_LOOKAHEAD_FACTOR: u32 = 3;
 /// Fraction of a slot lookahead for fork choice in the state advance timer (500ms on mainnet).
 pub const FORK_CHOICE_LOOKAHEAD_FACTOR: u32 = 24;
 
+/// Default number of epochs of validator monitor summaries to keep on disk (~1 week on mainnet).
+pub const DEFAULT_VALIDATOR_MONITOR_HISTORY_EPOCHS: u64 = 1575;
+
+/// Default number of epochs of block times to keep on disk (~1 week on mainnet).
+pub const DEFAULT_BLOCK_TIMES_HISTORY_EPOCHS: u64 = 1575;
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub struct ChainConfig {
     /// Maximum number of slots to skip when importing a consensus message (e.g., block,
@@ -73,12 +79,34 @@ pub struct ChainConfig {
     pub optimistic_finalized_sync: bool,
     /// The size of the shuffling cache,
     pub shuffling_cache_size: usize,
+    /// The memory budget of the cache of snapshots, advanced states and replayed states, in
+    /// megabytes.
+    pub state_cache_memory_mb: usize,
     /// Whether to send payload attributes every slot, regardless of connected proposers.
     ///
     /// This is useful for block builders and testing.
     pub always_prepare_payload: bool,
     /// Whether backfill sync processing should be rate-limited.
     pub enable_backfill_rate_limiting: bool,
+    /// Whether to publish proposer slashings formed from conflicting blocks seen on gossip.
+    ///
+    /// Such slashings are always added to the op pool, regardless of this setting.
+    pub publish_proposer_equivocations: bool,
+    /// Whether to produce light client updates from imported blocks.
+    pub enable_light_client_server: bool,
+    /// Number of epochs of per-validator summaries the validator monitor should keep on disk.
+    ///
+    /// A value of 0 disables persistence of the summaries.
+    pub validator_monitor_history_epochs: u64,
+    /// Number of epochs of block observation, import and head times to keep on disk.
+    ///
+    /// A value of 0 disables persistence of block times.
+    pub block_times_history_epochs: u64,
+    /// Number of `head`, `block`, `finalized_checkpoint` and `chain_reorg` events to retain for
+    /// replay to server-sent event clients.
+    ///
+    /// A value of 0 disables the journal.
+    pub event_journal_size: usize,
 }
 
 impl Default for ChainConfig {
@@ -106,8 +134,14 @@ impl Default for ChainConfig {
             // This value isn't actually read except in tests.
             optimistic_finalized_sync: true,
             shuffling_cache_size: crate::shuffling_cache::DEFAULT_CACHE_SIZE,
+            state_cache_memory_mb: crate::snapshot_cache::DEFAULT_STATE_CACHE_MEMORY_MB,
             always_prepare_payload: false,
             enable_backfill_rate_limiting: true,
+            publish_proposer_equivocations: false,
+            enable_light_client_server: false,
+            validator_monitor_history_epochs: DEFAULT_VALIDATOR_MONITOR_HISTORY_EPOCHS,
+            block_times_history_epochs: DEFAULT_BLOCK_TIMES_HISTORY_EPOCHS,
+            event_journal_size: crate::event_journal::DEFAULT_EVENT_JOURNAL_SIZE,
         }
     }
 }
@@ -0,0 +1,408 @@
+//! The `DutiesCache` stores pre-computed attester, proposer and sync committee duties so that
+//! requests for the duties of a large set of validators can be answered in `O(k)` time, where `k`
+//! is the number of requested validators.
+//!
+//! Each kind of duty is keyed by the block which decided it:
+//!
+//! - Attester duties are keyed by `AttestationShufflingId`, like the `ShufflingCache`.
+//! - Proposer duties are keyed by `(epoch, block_root)` where `block_root` is the block root at
+//!   `end_slot(epoch - 1)`, like the `BeaconProposerCache`.
+//! - Sync committee duties are keyed by the sync committee period and the aggregate public key of
+//!   the committee.
+//!
+//! The cache is primed by the `state_advance_timer` towards the end of each slot, so the duties
+//! for the current and next epochs are typically computed once per epoch rather than once per
+//! request.
+use crate::{metrics, BeaconChain, BeaconChainError, BeaconChainTypes};
+use lru::LruCache;
+use std::collections::HashMap;
+use std::sync::Arc;
+use types::{
+    beacon_state::CommitteeCache, AttestationDuty, AttestationShufflingId, BeaconState,
+    BeaconStateError, CommitteeIndex, Epoch, EthSpec, Hash256, PublicKeyBytes, RelativeEpoch, Slot,
+    SyncCommittee, SyncDuty,
+};
+
+/// The number of attester shufflings to store. This is sufficient for the current and next epochs
+/// across a couple of competing forks.
+const ATTESTER_CACHE_SIZE: usize = 4;
+
+/// The number of sets of proposers to store.
+const PROPOSER_CACHE_SIZE: usize = 4;
+
+/// The number of sync committees to store. This is sufficient for the current and next sync
+/// committees across a couple of competing forks.
+const SYNC_COMMITTEE_CACHE_SIZE: usize = 4;
+
+/// The position of a validator within the committees of an epoch.
+///
+/// Validators which are not in any committee have a `committee` value that is out-of-bounds.
+#[derive(Clone, Copy)]
+struct CommitteePosition {
+    committee: u32,
+    position: u32,
+}
+
+impl CommitteePosition {
+    const NONE: Self = Self {
+        committee: u32::MAX,
+        position: u32::MAX,
+    };
+}
+
+/// The attestation duties of every active validator for a single epoch.
+pub struct AttesterDuties {
+    committees_per_slot: u64,
+    /// The slot, index and length of each committee in the epoch.
+    committees: Vec<(Slot, CommitteeIndex, usize)>,
+    /// The committee (as an index into `committees`) and position of each validator, indexed by
+    /// validator index.
+    positions: Vec<CommitteePosition>,
+}
+
+impl AttesterDuties {
+    pub fn new(committee_cache: &CommitteeCache) -> Result<Self, BeaconStateError> {
+        let beacon_committees = committee_cache.get_all_beacon_committees()?;
+
+        let num_validators = committee_cache
+            .active_validator_indices()
+            .iter()
+            .max()
+            .map_or(0, |max_index| max_index + 1);
+        let mut positions = vec![CommitteePosition::NONE; num_validators];
+
+        for (i, beacon_committee) in beacon_committees.iter().enumerate() {
+            for (position, &validator_index) in beacon_committee.committee.iter().enumerate() {
+                if let Some(entry) = positions.get_mut(validator_index) {
+                    *entry = CommitteePosition {
+                        committee: i as u32,
+                        position: position as u32,
+                    };
+                }
+            }
+        }
+
+        let committees = beacon_committees
+            .iter()
+            .map(|c| (c.slot, c.index, c.committee.len()))
+            .collect();
+
+        Ok(Self {
+            committees_per_slot: committee_cache.committees_per_slot(),
+            committees,
+            positions,
+        })
+    }
+
+    /// Returns the attestation duty for `validator_index`, if any.
+    pub fn get(&self, validator_index: usize) -> Option<AttestationDuty> {
+        let position = self.positions.get(validator_index)?;
+        let (slot, index, committee_len) = *self.committees.get(position.committee as usize)?;
+
+        Some(AttestationDuty {
+            slot,
+            index,
+            committee_position: position.position as usize,
+            committee_len,
+            committees_at_slot: self.committees_per_slot,
+        })
+    }
+}
+
+/// The block proposers for a single epoch.
+pub struct ProposerDuties {
+    /// The proposer for each slot of the epoch.
+    pub proposers: Vec<usize>,
+}
+
+impl ProposerDuties {
+    pub fn new(proposers: Vec<usize>) -> Self {
+        Self { proposers }
+    }
+}
+
+/// The duties of every member of a single sync committee.
+pub struct SyncCommitteeDuties {
+    duties: HashMap<u64, SyncDuty>,
+}
+
+impl SyncCommitteeDuties {
+    /// Compute the duties for `sync_committee`, using `validator_index` to map public keys to
+    /// validator indices.
+    ///
+    /// Public keys which are unknown to `validator_index` are ignored.
+    pub fn new<E: EthSpec>(
+        sync_committee: &SyncCommittee<E>,
+        validator_index: impl Fn(&PublicKeyBytes) -> Option<usize>,
+    ) -> Self {
+        let mut duties: HashMap<u64, SyncDuty> = HashMap::new();
+
+        for (position, pubkey) in sync_committee.pubkeys.iter().enumerate() {
+            if let Some(validator_index) = validator_index(pubkey) {
+                duties
+                    .entry(validator_index as u64)
+                    .or_insert_with(|| SyncDuty {
+                        pubkey: *pubkey,
+                        validator_index: validator_index as u64,
+                        validator_sync_committee_indices: vec![],
+                    })
+                    .validator_sync_committee_indices
+                    .push(position as u64);
+            }
+        }
+
+        Self { duties }
+    }
+
+    /// Returns the sync committee duty for `validator_index`, if any.
+    pub fn get(&self, validator_index: u64) -> Option<SyncDuty> {
+        self.duties.get(&validator_index).cloned()
+    }
+}
+
+/// Caches the attester, proposer and sync committee duties for recent epochs.
+///
+/// See the module-level documentation for more information.
+pub struct DutiesCache {
+    attester: LruCache<AttestationShufflingId, Arc<AttesterDuties>>,
+    proposer: LruCache<(Epoch, Hash256), Arc<ProposerDuties>>,
+    sync_committee: LruCache<(u64, PublicKeyBytes), Arc<SyncCommitteeDuties>>,
+}
+
+impl Default for DutiesCache {
+    fn default() -> Self {
+        Self {
+            attester: LruCache::new(ATTESTER_CACHE_SIZE),
+            proposer: LruCache::new(PROPOSER_CACHE_SIZE),
+            sync_committee: LruCache::new(SYNC_COMMITTEE_CACHE_SIZE),
+        }
+    }
+}
+
+impl DutiesCache {
+    pub fn get_attester(
+        &mut self,
+        shuffling_id: &AttestationShufflingId,
+    ) -> Option<Arc<AttesterDuties>> {
+        observe_lookup(self.attester.get(shuffling_id).cloned())
+    }
+
+    pub fn insert_attester(
+        &mut self,
+        shuffling_id: AttestationShufflingId,
+        duties: Arc<AttesterDuties>,
+    ) {
+        self.attester.put(shuffling_id, duties);
+    }
+
+    /// Returns the proposers for `epoch`, where `shuffling_decision_block` is the block root at
+    /// `end_slot(epoch - 1)`.
+    pub fn get_proposer(
+        &mut self,
+        epoch: Epoch,
+        shuffling_decision_block: Hash256,
+    ) -> Option<Arc<ProposerDuties>> {
+        observe_lookup(
+            self.proposer
+                .get(&(epoch, shuffling_decision_block))
+                .cloned(),
+        )
+    }
+
+    pub fn insert_proposer(
+        &mut self,
+        epoch: Epoch,
+        shuffling_decision_block: Hash256,
+        duties: Arc<ProposerDuties>,
+    ) {
+        self.proposer.put((epoch, shuffling_decision_block), duties);
+    }
+
+    /// Returns the duties of the sync committee for `sync_committee_period` which has the given
+    /// `aggregate_pubkey`.
+    pub fn get_sync_committee(
+        &mut self,
+        sync_committee_period: u64,
+        aggregate_pubkey: PublicKeyBytes,
+    ) -> Option<Arc<SyncCommitteeDuties>> {
+        observe_lookup(
+            self.sync_committee
+                .get(&(sync_committee_period, aggregate_pubkey))
+                .cloned(),
+        )
+    }
+
+    pub fn insert_sync_committee(
+        &mut self,
+        sync_committee_period: u64,
+        aggregate_pubkey: PublicKeyBytes,
+        duties: Arc<SyncCommitteeDuties>,
+    ) {
+        self.sync_committee
+            .put((sync_committee_period, aggregate_pubkey), duties);
+    }
+}
+
+fn observe_lookup<T>(result: Option<T>) -> Option<T> {
+    if result.is_some() {
+        metrics::inc_counter(&metrics::DUTIES_CACHE_HITS);
+    } else {
+        metrics::inc_counter(&metrics::DUTIES_CACHE_MISSES);
+    }
+    result
+}
+
+impl<T: BeaconChainTypes> BeaconChain<T> {
+    /// Compute and store any duties which can be determined from `state` and are not yet present
+    /// in the `duties_cache`.
+    ///
+    /// The committee caches for the current and next epochs of `state` must already be built.
+    /// `block_root` must be the root of the latest block applied to `state`.
+    pub fn prime_duties_cache(
+        &self,
+        state: &BeaconState<T::EthSpec>,
+        block_root: Hash256,
+    ) -> Result<(), BeaconChainError> {
+        let _timer = metrics::start_timer(&metrics::DUTIES_CACHE_PRIME_TIMES);
+
+        for relative_epoch in [RelativeEpoch::Current, RelativeEpoch::Next] {
+            let shuffling_id = AttestationShufflingId::new(block_root, state, relative_epoch)?;
+            if self.duties_cache.lock().attester.contains(&shuffling_id) {
+                continue;
+            }
+
+            let duties = AttesterDuties::new(state.committee_cache(relative_epoch)?)?;
+            self.duties_cache
+                .lock()
+                .insert_attester(shuffling_id, Arc::new(duties));
+        }
+
+        let epoch = state.current_epoch();
+        let proposer_decision_root = state.proposer_shuffling_decision_root(block_root)?;
+        if !self
+            .duties_cache
+            .lock()
+            .proposer
+            .contains(&(epoch, proposer_decision_root))
+        {
+            let proposers = state.get_beacon_proposer_indices(&self.spec)?;
+            let duties = ProposerDuties::new(proposers);
+            self.duties_cache.lock().insert_proposer(
+                epoch,
+                proposer_decision_root,
+                Arc::new(duties),
+            );
+        }
+
+        if let (Ok(current), Ok(next)) =
+            (state.current_sync_committee(), state.next_sync_committee())
+        {
+            let current_period = epoch.sync_committee_period(&self.spec)?;
+            for (period, sync_committee) in [(current_period, current), (current_period + 1, next)]
+            {
+                let key = (period, sync_committee.aggregate_pubkey);
+                if self.duties_cache.lock().sync_committee.contains(&key) {
+                    continue;
+                }
+
+                let pubkey_cache = self
+                    .validator_pubkey_cache
+                    .try_read_for(crate::beacon_chain::VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+                    .ok_or(BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
+                let duties = SyncCommitteeDuties::new(sync_committee, |pubkey| {
+                    pubkey_cache.get_index(pubkey)
+                });
+                drop(pubkey_cache);
+
+                self.duties_cache
+                    .lock()
+                    .insert_sync_committee(key.0, key.1, Arc::new(duties));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{
+        test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, Eth1Data,
+        MinimalEthSpec, Validator,
+    };
+
+    type E = MinimalEthSpec;
+
+    fn state_with_validators(spec: &ChainSpec, count: usize) -> BeaconState<E> {
+        let mut state = BeaconState::new(0, Eth1Data::default(), spec);
+        for keypair in generate_deterministic_keypairs(count) {
+            state
+                .validators_mut()
+                .push(Validator {
+                    pubkey: keypair.pk.compress(),
+                    effective_balance: spec.max_effective_balance,
+                    activation_epoch: Epoch::new(0),
+                    exit_epoch: spec.far_future_epoch,
+                    withdrawable_epoch: spec.far_future_epoch,
+                    ..Validator::default()
+                })
+                .unwrap();
+            state
+                .balances_mut()
+                .push(spec.max_effective_balance)
+                .unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn attester_duties_match_committee_cache() {
+        let spec = E::default_spec();
+        let validator_count = 64;
+        let mut state = state_with_validators(&spec, validator_count);
+        state
+            .build_committee_cache(RelativeEpoch::Current, &spec)
+            .unwrap();
+        let committee_cache = state.committee_cache(RelativeEpoch::Current).unwrap();
+
+        let duties = AttesterDuties::new(committee_cache).unwrap();
+
+        for validator_index in 0..validator_count + 1 {
+            assert_eq!(
+                duties.get(validator_index),
+                committee_cache.get_attestation_duties(validator_index),
+                "validator {}",
+                validator_index
+            );
+        }
+    }
+
+    #[test]
+    fn sync_committee_duties_match_from_sync_committee() {
+        let keypairs = generate_deterministic_keypairs(4);
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.pk.compress())
+            .collect::<Vec<_>>();
+
+        // The last keypair is unknown to the pubkey cache and has no validator index.
+        let mut sync_committee = SyncCommittee::<E>::temporary().unwrap();
+        for (i, pubkey) in sync_committee.pubkeys.iter_mut().enumerate() {
+            *pubkey = pubkeys[i % pubkeys.len()];
+        }
+        let validator_index =
+            |pubkey: &PublicKeyBytes| pubkeys[..3].iter().position(|known| known == pubkey);
+
+        let duties = SyncCommitteeDuties::new(&sync_committee, validator_index);
+
+        for (i, pubkey) in pubkeys.iter().enumerate() {
+            let expected = if i < 3 {
+                SyncDuty::from_sync_committee(i as u64, *pubkey, &sync_committee)
+            } else {
+                None
+            };
+            assert_eq!(duties.get(i as u64), expected);
+        }
+    }
+}
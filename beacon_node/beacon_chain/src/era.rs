@@ -0,0 +1,644 @@
+//! Export and import of the finalized chain as era files.
+//!
+//! Each file holds one era of `SLOTS_PER_HISTORICAL_ROOT` slots as a sequence of e2store records:
+//! a version record, the era's blocks in slot order, the state at the first slot of the next era,
+//! a slot index of the blocks and a slot index of the state. Blocks and states are stored as
+//! snappy-framed SSZ. Era `n` holds the blocks of slots
+//! `(n - 1) * SLOTS_PER_HISTORICAL_ROOT..n * SLOTS_PER_HISTORICAL_ROOT`, so era 0 holds only the
+//! genesis state and has no block index.
+//!
+//! Files are named `<config name>-<era number>-<short historical root>.era`, where the short
+//! historical root is the first 4 bytes of the era's historical root, or of the genesis validators
+//! root for era 0.
+use crate::validator_pubkey_cache::ValidatorPubkeyCache;
+use crate::{BeaconChainTypes, BeaconStore};
+use slog::{info, Logger};
+use snap::read::FrameDecoder;
+use snap::write::FrameEncoder;
+use ssz::Encode;
+use state_processing::{
+    per_block_processing::ParallelSignatureSets,
+    signature_sets::block_proposal_signature_set_from_parts,
+};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use store::chunked_vector::BlockRoots;
+use store::hot_cold_store::HotColdDBError;
+use store::{errors::Error, AnchorInfo, ChunkWriter, HotColdDB, ItemStore};
+use tree_hash::TreeHash;
+use types::{
+    BeaconState, ChainSpec, EthSpec, ExecPayload, ExecutionBlockHash, ExecutionPayload,
+    ExecutionPayloadMerge, ForkName, Hash256, HistoricalBatch, HistoricalSummary,
+    SignedBeaconBlock, SignedBlindedBeaconBlock, Slot,
+};
+
+/// The e2store record type of the version record which starts every file.
+const VERSION: [u8; 2] = [0x65, 0x32];
+/// The e2store record type of a snappy-framed SSZ `SignedBeaconBlock`.
+const COMPRESSED_SIGNED_BEACON_BLOCK: [u8; 2] = [0x01, 0x00];
+/// The e2store record type of a snappy-framed SSZ `BeaconState`.
+const COMPRESSED_BEACON_STATE: [u8; 2] = [0x02, 0x00];
+/// The e2store record type of a slot index.
+const SLOT_INDEX: [u8; 2] = [0x69, 0x32];
+/// The length of an e2store record header: a 2 byte type, 4 byte length and 2 reserved bytes.
+const HEADER_LEN: usize = 8;
+
+fn era_error(message: String) -> Error {
+    Error::DBError { message }
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> Error {
+    era_error(format!("unable to {} {}: {}", action, path.display(), e))
+}
+
+/// Return the name of the file holding `era`, whose boundary state is `state`.
+fn era_file_name<E: EthSpec>(spec: &ChainSpec, era: u64, state: &BeaconState<E>) -> String {
+    let root = if era == 0 {
+        state.genesis_validators_root()
+    } else {
+        era_root(state, era, state)
+    };
+    format!(
+        "{}-{:05}-{}.era",
+        spec.config_name.as_deref().unwrap_or("custom"),
+        era,
+        hex::encode(&root.as_bytes()[..4])
+    )
+}
+
+/// Return the era of a file named by `era_file_name`, or `None` if it isn't an era file.
+fn era_of_file_name(file_name: &str) -> Option<u64> {
+    let mut parts = file_name.strip_suffix(".era")?.rsplitn(3, '-');
+    let short_root = parts.next()?;
+    if short_root.len() != 8 || hex::decode(short_root).is_err() {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+/// Write a record, returning the number of bytes written.
+fn write_record<W: Write>(writer: &mut W, record_type: [u8; 2], data: &[u8]) -> Result<u64, Error> {
+    let len = u32::try_from(data.len())
+        .map_err(|_| era_error(format!("record of {} bytes is too large", data.len())))?;
+    let mut header = [0; HEADER_LEN];
+    header[..2].copy_from_slice(&record_type);
+    header[2..6].copy_from_slice(&len.to_le_bytes());
+    writer
+        .write_all(&header)
+        .and_then(|()| writer.write_all(data))
+        .map_err(|e| era_error(format!("unable to write era record: {}", e)))?;
+    Ok((HEADER_LEN + data.len()) as u64)
+}
+
+/// Return the data of a slot index record starting at `index_position` for the records at
+/// `positions`, one per slot from `starting_slot`.
+///
+/// Offsets are relative to the start of the index record, with 0 marking an empty slot.
+fn slot_index(starting_slot: Slot, positions: &[Option<u64>], index_position: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(8 * (positions.len() + 2));
+    data.extend_from_slice(&(starting_slot.as_u64() as i64).to_le_bytes());
+    for position in positions {
+        let offset = position.map_or(0, |position| position as i64 - index_position as i64);
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(&(positions.len() as i64).to_le_bytes());
+    data
+}
+
+/// Read the next record, returning `None` at the end of the file.
+fn read_record<R: Read>(reader: &mut R) -> Result<Option<([u8; 2], Vec<u8>)>, Error> {
+    let mut header = [0; HEADER_LEN];
+    match reader.read_exact(&mut header) {
+        Ok(()) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(era_error(format!("unable to read era record: {}", e))),
+    }
+    let mut len_bytes = [0; 4];
+    len_bytes.copy_from_slice(&header[2..6]);
+    let mut data = vec![0; u32::from_le_bytes(len_bytes) as usize];
+    reader
+        .read_exact(&mut data)
+        .map_err(|e| era_error(format!("unable to read era record: {}", e)))?;
+    Ok(Some(([header[0], header[1]], data)))
+}
+
+fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = FrameEncoder::new(vec![]);
+    encoder
+        .write_all(bytes)
+        .map_err(|e| era_error(format!("unable to compress era record: {}", e)))?;
+    encoder
+        .into_inner()
+        .map_err(|e| era_error(format!("unable to compress era record: {}", e)))
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut decompressed = vec![];
+    FrameDecoder::new(bytes)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| era_error(format!("unable to decompress era record: {}", e)))?;
+    Ok(decompressed)
+}
+
+/// The contents of an era file.
+pub struct Era<E: EthSpec> {
+    pub blocks: Vec<SignedBeaconBlock<E>>,
+    pub state: BeaconState<E>,
+}
+
+impl<E: EthSpec> Era<E> {
+    pub fn read(path: &Path, spec: &ChainSpec) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|e| io_error("open", path, e))?;
+        let mut reader = BufReader::new(file);
+
+        match read_record(&mut reader)? {
+            Some((VERSION, _)) => (),
+            _ => return Err(era_error(format!("{} is not an era file", path.display()))),
+        }
+
+        let mut blocks = vec![];
+        let mut state = None;
+        while let Some((record_type, data)) = read_record(&mut reader)? {
+            match record_type {
+                COMPRESSED_SIGNED_BEACON_BLOCK => blocks.push(SignedBeaconBlock::from_ssz_bytes(
+                    &decompress(&data)?,
+                    spec,
+                )?),
+                COMPRESSED_BEACON_STATE => {
+                    state = Some(BeaconState::from_ssz_bytes(&decompress(&data)?, spec)?)
+                }
+                // Skip records we don't use, such as slot indices.
+                _ => (),
+            }
+        }
+
+        let state =
+            state.ok_or_else(|| era_error(format!("{} contains no state", path.display())))?;
+        Ok(Self { blocks, state })
+    }
+
+    /// Write the era to `path`, via a temporary file so that partial files are never left behind.
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        let temp_path = path.with_extension("era.tmp");
+        let file = File::create(&temp_path).map_err(|e| io_error("create", &temp_path, e))?;
+        let mut writer = BufWriter::new(file);
+
+        let slots_per_era = E::slots_per_historical_root() as u64;
+        let state_slot = self.state.slot();
+        let start_slot = state_slot.saturating_sub(slots_per_era);
+        let mut block_positions = vec![None; slots_per_era as usize];
+
+        let mut position = write_record(&mut writer, VERSION, &[])?;
+        for block in &self.blocks {
+            let i = block
+                .slot()
+                .as_u64()
+                .checked_sub(start_slot.as_u64())
+                .filter(|i| *i < slots_per_era && block.slot() < state_slot)
+                .ok_or_else(|| {
+                    era_error(format!(
+                        "the block at slot {} is not in the era ending at slot {}",
+                        block.slot(),
+                        state_slot
+                    ))
+                })?;
+            block_positions[i as usize] = Some(position);
+            position += write_record(
+                &mut writer,
+                COMPRESSED_SIGNED_BEACON_BLOCK,
+                &compress(&block.as_ssz_bytes())?,
+            )?;
+        }
+        let state_position = position;
+        position += write_record(
+            &mut writer,
+            COMPRESSED_BEACON_STATE,
+            &compress(&self.state.as_ssz_bytes())?,
+        )?;
+
+        // Era 0 has no blocks, and so no block index.
+        if state_slot > 0 {
+            let index = slot_index(start_slot, &block_positions, position);
+            position += write_record(&mut writer, SLOT_INDEX, &index)?;
+        }
+        let index = slot_index(state_slot, &[Some(state_position)], position);
+        write_record(&mut writer, SLOT_INDEX, &index)?;
+
+        writer
+            .flush()
+            .map_err(|e| io_error("write", &temp_path, e))?;
+        fs::rename(&temp_path, path).map_err(|e| io_error("move", &temp_path, e))
+    }
+}
+
+/// Load the full block with `block_root`, filling in the default payload of blocks prior to the
+/// merge if their payload was pruned.
+fn load_full_block<E, Hot, Cold>(
+    db: &HotColdDB<E, Hot, Cold>,
+    block_root: &Hash256,
+) -> Result<SignedBeaconBlock<E>, Error>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    let blinded_block = db
+        .get_blinded_block(block_root)?
+        .ok_or(Error::BlockNotFound(*block_root))?;
+    let slot = blinded_block.slot();
+    let fork_name = blinded_block.fork_name(db.get_chain_spec())?;
+    let payload = match blinded_block.message().execution_payload() {
+        Ok(header) => match db.get_execution_payload(block_root, fork_name)? {
+            Some(payload) => Some(payload),
+            None if header.block_hash() == ExecutionBlockHash::zero()
+                && fork_name == ForkName::Merge =>
+            {
+                Some(ExecutionPayload::Merge(ExecutionPayloadMerge::default()))
+            }
+            None => {
+                return Err(era_error(format!(
+                    "the execution payload of the block at slot {} has been pruned, \
+                     start the beacon node with --prune-payloads false to keep payloads",
+                    slot
+                )))
+            }
+        },
+        Err(_) => None,
+    };
+    blinded_block
+        .try_into_full_block(payload)
+        .ok_or(Error::AddPayloadLogicError)
+}
+
+/// Write the eras from `start_era` to `end_era` inclusive to files in `output_dir`.
+///
+/// If `end_era` is `None`, every era whose state is in the freezer database is exported.
+pub fn export_eras<E, Hot, Cold>(
+    db: &HotColdDB<E, Hot, Cold>,
+    output_dir: &Path,
+    start_era: u64,
+    end_era: Option<u64>,
+    log: &Logger,
+) -> Result<(), Error>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    let spec = db.get_chain_spec();
+    let slots_per_era = E::slots_per_historical_root() as u64;
+    // The boundary state of the last era must be in the freezer database.
+    let last_era = db.get_split_slot().as_u64().saturating_sub(1) / slots_per_era;
+    let end_era = end_era.unwrap_or(last_era);
+    if end_era > last_era {
+        return Err(era_error(format!(
+            "era {} is not finalized, the last finalized era is {}",
+            end_era, last_era
+        )));
+    }
+
+    fs::create_dir_all(output_dir).map_err(|e| io_error("create", output_dir, e))?;
+
+    for era in start_era..=end_era {
+        let boundary_slot = Slot::new(era * slots_per_era);
+        let mut state = db.load_cold_state_by_slot(boundary_slot)?.ok_or_else(|| {
+            era_error(format!(
+                "the state at slot {} is not available, it can be reconstructed with \
+                 --reconstruct-historic-states",
+                boundary_slot
+            ))
+        })?;
+        state.update_tree_hash_cache()?;
+
+        let mut blocks = vec![];
+        if era > 0 {
+            let start_slot = boundary_slot - slots_per_era;
+            if start_slot < db.get_oldest_block_slot() {
+                return Err(era_error(format!(
+                    "the blocks of era {} are not available, the oldest block is at slot {}",
+                    era,
+                    db.get_oldest_block_slot()
+                )));
+            }
+
+            // The boundary state's block roots cover exactly the slots of the era.
+            let mut prev_block_root = None;
+            for (i, block_root) in state.block_roots().iter().enumerate() {
+                if prev_block_root == Some(*block_root) {
+                    continue;
+                }
+                prev_block_root = Some(*block_root);
+
+                let block = load_full_block(db, block_root)?;
+                if block.slot() == start_slot + i as u64 {
+                    blocks.push(block);
+                }
+            }
+        }
+
+        let path = output_dir.join(era_file_name(spec, era, &state));
+        let num_blocks = blocks.len();
+        Era { blocks, state }.write(&path)?;
+
+        info!(
+            log,
+            "Exported era";
+            "era" => era,
+            "blocks" => num_blocks,
+            "path" => ?path,
+        );
+    }
+
+    Ok(())
+}
+
+/// Return the root of `era` recorded by `state` in its historical roots or summaries.
+fn trusted_era_root<E: EthSpec>(state: &BeaconState<E>, era: u64) -> Result<Hash256, Error> {
+    let index = era
+        .checked_sub(1)
+        .ok_or_else(|| era_error("era 0 has no historical root".into()))? as usize;
+    let historical_roots = state.historical_roots();
+    if let Some(root) = historical_roots.get(index) {
+        return Ok(*root);
+    }
+    state
+        .historical_summaries()
+        .ok()
+        .and_then(|summaries| summaries.get(index - historical_roots.len()))
+        .map(|summary| summary.tree_hash_root())
+        .ok_or_else(|| {
+            era_error(format!(
+                "era {} is not finalized by the state at slot {}",
+                era,
+                state.slot()
+            ))
+        })
+}
+
+/// Return the root of the era ending at `state`, as it would be recorded in the historical roots
+/// or summaries of later states.
+fn era_root<E: EthSpec>(
+    state: &BeaconState<E>,
+    era: u64,
+    trusted_state: &BeaconState<E>,
+) -> Hash256 {
+    if ((era - 1) as usize) < trusted_state.historical_roots().len() {
+        HistoricalBatch::<E> {
+            block_roots: state.block_roots().clone(),
+            state_roots: state.state_roots().clone(),
+        }
+        .tree_hash_root()
+    } else {
+        HistoricalSummary::new(state).tree_hash_root()
+    }
+}
+
+/// Import the blocks from the era files in `input_dir` which precede the oldest block in the
+/// database, as backfill sync would.
+///
+/// Each era's state is verified against the historical roots or summaries of the finalized state,
+/// each block against the block roots of its era's state, and each block's proposer signature
+/// against the validator pubkey cache.
+///
+/// The database must already hold a finalized state, so a new node must be checkpoint synced
+/// before importing era files.
+pub fn import_eras<T: BeaconChainTypes>(
+    db: BeaconStore<T>,
+    input_dir: &Path,
+    log: &Logger,
+) -> Result<(), Error> {
+    let spec = db.get_chain_spec();
+    let slots_per_era = T::EthSpec::slots_per_historical_root() as u64;
+
+    let mut era_files = BTreeMap::new();
+    for entry in fs::read_dir(input_dir).map_err(|e| io_error("read", input_dir, e))? {
+        let path = entry.map_err(|e| io_error("read", input_dir, e))?.path();
+        if let Some(era) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(era_of_file_name)
+        {
+            era_files.insert(era, path);
+        }
+    }
+
+    let split = db.get_split_info();
+    if split.state_root.is_zero() {
+        return Err(era_error(
+            "the database has no finalized state, checkpoint sync the beacon node before \
+             importing era files"
+                .into(),
+        ));
+    }
+    let trusted_state = db.get_state(&split.state_root, Some(split.slot))?.ok_or(
+        HotColdDBError::MissingSplitState(split.state_root, split.slot),
+    )?;
+
+    let anchor_info = match db.get_anchor_info() {
+        Some(anchor_info) if !anchor_info.block_backfill_complete() => anchor_info,
+        _ => {
+            info!(log, "All blocks are already in the database");
+            return Ok(());
+        }
+    };
+
+    // Start from the era holding the parent of the oldest block.
+    let mut era = anchor_info.oldest_block_slot.as_u64().saturating_sub(1) / slots_per_era + 1;
+    let era_end_slot = Slot::new(era * slots_per_era);
+    if era_end_slot > split.slot {
+        return Err(era_error(format!(
+            "era {} ends at slot {}, which is not yet finalized, run the beacon node until it \
+             finalizes slot {}",
+            era, era_end_slot, era_end_slot
+        )));
+    }
+
+    let pubkey_cache = ValidatorPubkeyCache::<T>::load_from_store(db.clone())
+        .map_err(|e| era_error(format!("unable to load the validator pubkeys: {:?}", e)))?;
+    while let Some(anchor_info) = db
+        .get_anchor_info()
+        .filter(|anchor_info| !anchor_info.block_backfill_complete())
+    {
+        let path: &PathBuf = match era_files.get(&era) {
+            Some(path) => path,
+            None => {
+                info!(
+                    log,
+                    "Era file not found, stopping import";
+                    "era" => era,
+                    "oldest_block_slot" => anchor_info.oldest_block_slot,
+                );
+                return Ok(());
+            }
+        };
+
+        let Era { blocks, mut state } = Era::<T::EthSpec>::read(path, spec)?;
+        let boundary_slot = Slot::new(era * slots_per_era);
+        if state.slot() != boundary_slot {
+            return Err(era_error(format!(
+                "{} has a state at slot {}, expected {}",
+                path.display(),
+                state.slot(),
+                boundary_slot
+            )));
+        }
+        state.update_tree_hash_cache()?;
+        let expected_era_root = trusted_era_root(&trusted_state, era)?;
+        if era_root(&state, era, &trusted_state) != expected_era_root {
+            return Err(era_error(format!(
+                "the state in {} does not match the historical root of era {}",
+                path.display(),
+                era
+            )));
+        }
+
+        let num_blocks = import_era_blocks(
+            &db,
+            anchor_info,
+            &state,
+            blocks,
+            &pubkey_cache,
+            trusted_state.genesis_validators_root(),
+        )?;
+
+        info!(
+            log,
+            "Imported era";
+            "era" => era,
+            "blocks" => num_blocks,
+            "oldest_block_slot" => db.get_oldest_block_slot(),
+        );
+        era -= 1;
+    }
+
+    info!(log, "Imported all retained blocks");
+    Ok(())
+}
+
+/// Import the `blocks` of the era ending at `state` which precede the oldest block in the
+/// database, and update the anchor to match.
+fn import_era_blocks<T: BeaconChainTypes>(
+    db: &BeaconStore<T>,
+    anchor_info: AnchorInfo,
+    state: &BeaconState<T::EthSpec>,
+    blocks: Vec<SignedBeaconBlock<T::EthSpec>>,
+    pubkey_cache: &ValidatorPubkeyCache<T>,
+    genesis_validators_root: Hash256,
+) -> Result<usize, Error> {
+    let spec = db.get_chain_spec();
+    let slots_per_era = T::EthSpec::slots_per_historical_root() as u64;
+    let start_slot = state.slot() - slots_per_era;
+    let history_retention_slot = db.get_history_retention_slot();
+    let mut expected_block_root = anchor_info.oldest_block_parent;
+    let mut prev_block_slot = anchor_info.oldest_block_slot;
+
+    // Check the chain of blocks back from the oldest block before verifying any signatures.
+    let mut blocks_to_import = vec![];
+    for block in blocks
+        .into_iter()
+        .rev()
+        .filter(|block| block.slot() < anchor_info.oldest_block_slot)
+    {
+        let block_root = block.canonical_root();
+        let slot = block.slot();
+        let state_block_root = slot
+            .as_u64()
+            .checked_sub(start_slot.as_u64())
+            .and_then(|i| state.block_roots().get(i as usize));
+        if state_block_root != Some(&block_root) {
+            return Err(era_error(format!(
+                "the block at slot {} does not match the state of its era",
+                slot
+            )));
+        }
+        if block_root != expected_block_root {
+            return Err(era_error(format!(
+                "the block at slot {} has root {:?}, expected {:?}",
+                slot, block_root, expected_block_root
+            )));
+        }
+
+        prev_block_slot = slot;
+        expected_block_root = block.parent_root();
+        blocks_to_import.push((block_root, block));
+
+        // Reaching the start of the retained history or the genesis block completes the
+        // backfill.
+        if history_retention_slot.map_or(false, |retention_slot| slot <= retention_slot)
+            || slot == spec.genesis_slot
+        {
+            expected_block_root = Hash256::zero();
+            break;
+        }
+    }
+
+    // Skipped slots at the start of the era refer to the parent of its oldest block.
+    if expected_block_root != Hash256::zero()
+        && prev_block_slot > start_slot
+        && state.block_roots().first() != Some(&expected_block_root)
+    {
+        return Err(era_error(format!(
+            "the era ending at slot {} is missing blocks prior to slot {}",
+            state.slot(),
+            prev_block_slot
+        )));
+    }
+
+    // The genesis block is unsigned.
+    let signature_sets = blocks_to_import
+        .iter()
+        .filter(|(_, block)| block.slot() != spec.genesis_slot)
+        .map(|(block_root, block)| {
+            block_proposal_signature_set_from_parts(
+                block,
+                Some(*block_root),
+                block.message().proposer_index(),
+                &spec.fork_at_epoch(block.message().epoch()),
+                genesis_validators_root,
+                |validator_index| pubkey_cache.get(validator_index).cloned().map(Cow::Owned),
+                spec,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| era_error(format!("unable to verify proposer signatures: {:?}", e)))?;
+    if !ParallelSignatureSets::from(signature_sets).verify() {
+        return Err(era_error(format!(
+            "the era ending at slot {} has an invalid proposer signature",
+            state.slot()
+        )));
+    }
+
+    let mut chunk_writer = ChunkWriter::<BlockRoots, _, _>::new(
+        &db.cold_db,
+        anchor_info.oldest_block_slot.as_usize(),
+    )?;
+    let mut hot_batch = vec![];
+    let mut cold_batch = vec![];
+    let mut next_block_slot = anchor_info.oldest_block_slot;
+    let num_blocks = blocks_to_import.len();
+    for (block_root, block) in blocks_to_import {
+        let blinded_block = SignedBlindedBeaconBlock::from(block);
+        db.blinded_block_as_kv_store_ops(&block_root, &blinded_block, &mut hot_batch);
+        for slot in (blinded_block.slot().as_usize()..next_block_slot.as_usize()).rev() {
+            chunk_writer.set(slot, block_root, &mut cold_batch)?;
+        }
+        next_block_slot = blinded_block.slot();
+    }
+    chunk_writer.write(&mut cold_batch)?;
+
+    // Write the blocks before the block roots and anchor which refer to them.
+    db.hot_db.do_atomically(hot_batch)?;
+    db.cold_db.do_atomically(cold_batch)?;
+
+    let new_anchor = AnchorInfo {
+        oldest_block_slot: prev_block_slot,
+        oldest_block_parent: expected_block_root,
+        ..anchor_info.clone()
+    };
+    db.compare_and_set_anchor_info_with_write(Some(anchor_info), Some(new_anchor))?;
+
+    Ok(num_blocks)
+}
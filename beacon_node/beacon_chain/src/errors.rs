@@ -29,6 +29,7 @@ use state_processing::{
 use std::time::Duration;
 use task_executor::ShutdownReason;
 use tokio::task::JoinError;
+use types::light_client_update::Error as LightClientUpdateError;
 use types::*;
 
 macro_rules! easy_from_to {
@@ -165,6 +166,10 @@ pub enum BeaconChainError {
     BlockRewardSyncError,
     SyncCommitteeRewardsSyncError,
     AttestationRewardsError,
+    AttestationRewardsReplayTooLong {
+        state_slot: Slot,
+        base_slot: Slot,
+    },
     HeadMissingFromForkChoice(Hash256),
     FinalizedBlockMissingFromForkChoice(Hash256),
     HeadBlockMissingFromForkChoice(Hash256),
@@ -213,6 +218,7 @@ pub enum BeaconChainError {
     BlsToExecutionConflictsWithPool,
     InconsistentFork(InconsistentFork),
     ProposerHeadForkChoiceError(fork_choice::Error<proto_array::Error>),
+    LightClientUpdateError(LightClientUpdateError),
 }
 
 easy_from_to!(SlotProcessingError, BeaconChainError);
@@ -237,6 +243,7 @@ easy_from_to!(HistoricalBlockError, BeaconChainError);
 easy_from_to!(StateAdvanceError, BeaconChainError);
 easy_from_to!(BlockReplayError, BeaconChainError);
 easy_from_to!(InconsistentFork, BeaconChainError);
+easy_from_to!(LightClientUpdateError, BeaconChainError);
 
 #[derive(Debug)]
 pub enum BlockProductionError {
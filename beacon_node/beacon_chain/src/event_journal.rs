@@ -0,0 +1,482 @@
+//! A bounded journal of the server-sent events which may be replayed to clients which reconnect.
+//!
+//! Only the `head`, `block`, `finalized_checkpoint` and `chain_reorg` topics are journaled. Each
+//! entry is assigned a sequence number, which is sent to clients as the SSE event id, and the slot
+//! of the chain at the time the event was emitted. Clients may then resume from the id of the last
+//! event they received (via the `Last-Event-ID` header) or from a slot (via `since_slot`).
+//!
+//! The journal is held in memory and periodically written to the hot database so that it
+//! survives a restart. Once the journal is full the oldest entries are dropped.
+//!
+//! Entries issued since the last write are lost if the node crashes. So that their sequence
+//! numbers are never reissued, each run of the node issues sequence numbers from a new block of
+//! `SEQUENCES_PER_RUN`, which is recorded in the database as soon as the journal is loaded.
+use eth2::types::{EventKind, EventTopic};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::cmp;
+use std::collections::VecDeque;
+use std::str::from_utf8;
+use store::{
+    get_key_for_col, DBColumn, Error as StoreError, HotColdDB, ItemStore, KeyValueStore,
+    KeyValueStoreOp, StoreItem,
+};
+use types::{EthSpec, Hash256, Slot};
+
+/// The default number of events retained in the journal. The journal is disabled by default.
+pub const DEFAULT_EVENT_JOURNAL_SIZE: usize = 0;
+/// The number of sequence numbers reserved for each run of the node.
+const SEQUENCES_PER_RUN: u64 = 1 << 32;
+/// The key of the journal bounds in `DBColumn::EventJournal`. Entries use the keys from 1.
+const JOURNAL_METADATA_KEY: Hash256 = Hash256::zero();
+
+/// An event in the journal.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct JournalEntry {
+    pub sequence: u64,
+    pub slot: Slot,
+    topic: Vec<u8>,
+    /// The event data, as JSON.
+    data: Vec<u8>,
+}
+
+impl JournalEntry {
+    pub fn topic(&self) -> &str {
+        from_utf8(&self.topic).unwrap_or_default()
+    }
+
+    pub fn data(&self) -> &str {
+        from_utf8(&self.data).unwrap_or_default()
+    }
+}
+
+impl StoreItem for JournalEntry {
+    fn db_column() -> DBColumn {
+        DBColumn::EventJournal
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// The range of sequence numbers of the entries stored in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+struct JournalBounds {
+    /// The sequence number of the oldest entry.
+    first_sequence: u64,
+    /// The sequence number which will be assigned to the next entry.
+    next_sequence: u64,
+}
+
+impl Default for JournalBounds {
+    fn default() -> Self {
+        Self {
+            first_sequence: 1,
+            next_sequence: 1,
+        }
+    }
+}
+
+impl StoreItem for JournalBounds {
+    fn db_column() -> DBColumn {
+        DBColumn::EventJournal
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// Returns `true` if events of `topic` are recorded in the journal.
+pub fn is_journaled_topic(topic: EventTopic) -> bool {
+    matches!(
+        topic,
+        EventTopic::Head
+            | EventTopic::Block
+            | EventTopic::FinalizedCheckpoint
+            | EventTopic::ChainReorg
+    )
+}
+
+/// The point from which a client resumes the stream of journaled events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayStart {
+    /// Only new events.
+    None,
+    /// Events after the one with the given sequence number, as sent in `Last-Event-ID`.
+    AfterSequence(u64),
+    /// Events emitted at or after the given slot.
+    Slot(Slot),
+}
+
+fn entry_key(sequence: u64) -> Hash256 {
+    Hash256::from_low_u64_be(sequence)
+}
+
+pub struct EventJournal {
+    entries: VecDeque<JournalEntry>,
+    capacity: usize,
+    next_sequence: u64,
+    /// The highest slot of any journaled event, used as the slot of events without one.
+    latest_slot: Slot,
+    /// The entries which have been written to the database.
+    persisted: JournalBounds,
+    /// The sequence numbers of the entries in the database, oldest first.
+    persisted_sequences: VecDeque<u64>,
+}
+
+impl EventJournal {
+    /// Returns an empty journal which retains at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            next_sequence: JournalBounds::default().next_sequence,
+            latest_slot: Slot::new(0),
+            persisted: JournalBounds::default(),
+            persisted_sequences: VecDeque::new(),
+        }
+    }
+
+    /// Loads the journal from `store`, retaining at most the newest `capacity` entries.
+    ///
+    /// Older entries are deleted, and the sequence numbers of this run are reserved.
+    pub fn load<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+        store: &HotColdDB<E, Hot, Cold>,
+        capacity: usize,
+    ) -> Result<Self, StoreError> {
+        let mut journal = Self::new(capacity);
+        let bounds = match store.get_item::<JournalBounds>(&JOURNAL_METADATA_KEY)? {
+            Some(bounds) => bounds,
+            None => return Ok(journal),
+        };
+
+        let mut sequences = vec![];
+        for result in store.hot_db.iter_column_keys(DBColumn::EventJournal) {
+            let key = result?;
+            if key != JOURNAL_METADATA_KEY {
+                sequences.push(key.to_low_u64_be());
+            }
+        }
+        sequences.sort_unstable();
+
+        let mut ops = vec![];
+        let num_dropped = sequences.len().saturating_sub(capacity);
+        for (i, sequence) in sequences.into_iter().enumerate() {
+            if i < num_dropped {
+                ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::EventJournal.into(),
+                    entry_key(sequence).as_bytes(),
+                )));
+            } else if let Some(entry) = store.get_item::<JournalEntry>(&entry_key(sequence))? {
+                journal.latest_slot = cmp::max(journal.latest_slot, entry.slot);
+                journal.entries.push_back(entry);
+                journal.persisted_sequences.push_back(sequence);
+            }
+        }
+
+        // Skip any sequence numbers which were issued since the journal was last written.
+        journal.next_sequence = (bounds.next_sequence / SEQUENCES_PER_RUN + 1) * SEQUENCES_PER_RUN;
+        let bounds = journal.bounds();
+        ops.push(bounds.as_kv_store_op(JOURNAL_METADATA_KEY));
+        store.hot_db.do_atomically(ops)?;
+        journal.persisted = bounds;
+
+        Ok(journal)
+    }
+
+    /// Appends `event` to the journal, dropping the oldest entry if the journal is full.
+    ///
+    /// Returns `None` if events of this kind are not journaled.
+    pub fn append<E: EthSpec>(&mut self, event: &EventKind<E>) -> Option<JournalEntry> {
+        let slot = match event {
+            EventKind::Head(head) => head.slot,
+            EventKind::Block(block) => block.slot,
+            EventKind::ChainReorg(reorg) => reorg.slot,
+            EventKind::FinalizedCheckpoint(_) => self.latest_slot,
+            _ => return None,
+        };
+        // Serializing these events cannot fail: they contain no maps or non-string keys.
+        let data = serde_json::to_vec(event).ok()?;
+
+        let entry = JournalEntry {
+            sequence: self.next_sequence,
+            slot,
+            topic: event.topic_name().as_bytes().to_vec(),
+            data,
+        };
+        self.next_sequence += 1;
+        self.latest_slot = cmp::max(self.latest_slot, slot);
+
+        if self.capacity == 0 {
+            return Some(entry);
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry.clone());
+        Some(entry)
+    }
+
+    /// The sequence number of the most recent entry, or 0 if no events have been journaled.
+    pub fn last_sequence(&self) -> u64 {
+        self.next_sequence - 1
+    }
+
+    /// The sequence number of the oldest retained entry, if any.
+    pub fn first_sequence(&self) -> Option<u64> {
+        self.entries.front().map(|entry| entry.sequence)
+    }
+
+    /// Returns the retained entries with a sequence number greater than `sequence`.
+    pub fn entries_after(&self, sequence: u64) -> Vec<JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.sequence > sequence)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the retained entries emitted at or after `slot`.
+    pub fn entries_since_slot(&self, slot: Slot) -> Vec<JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.slot >= slot)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the retained entries selected by `start`.
+    pub fn replay(&self, start: ReplayStart) -> Vec<JournalEntry> {
+        match start {
+            ReplayStart::None => vec![],
+            ReplayStart::AfterSequence(sequence) => self.entries_after(sequence),
+            ReplayStart::Slot(slot) => self.entries_since_slot(slot),
+        }
+    }
+
+    /// Writes the entries appended since the last call to `store`, and deletes those which have
+    /// since been dropped from the journal.
+    pub fn persist<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+        &mut self,
+        store: &HotColdDB<E, Hot, Cold>,
+    ) -> Result<(), StoreError> {
+        let bounds = self.bounds();
+        if bounds == self.persisted {
+            return Ok(());
+        }
+
+        let num_dropped = self
+            .persisted_sequences
+            .iter()
+            .take_while(|sequence| **sequence < bounds.first_sequence)
+            .count();
+        let mut ops = self
+            .persisted_sequences
+            .iter()
+            .take(num_dropped)
+            .map(|sequence| {
+                KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::EventJournal.into(),
+                    entry_key(*sequence).as_bytes(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let new_entries = self
+            .entries
+            .iter()
+            .filter(|entry| entry.sequence >= self.persisted.next_sequence)
+            .collect::<Vec<_>>();
+        ops.extend(
+            new_entries
+                .iter()
+                .map(|entry| entry.as_kv_store_op(entry_key(entry.sequence))),
+        );
+        ops.push(bounds.as_kv_store_op(JOURNAL_METADATA_KEY));
+
+        store.hot_db.do_atomically(ops)?;
+        let new_sequences = new_entries
+            .into_iter()
+            .map(|entry| entry.sequence)
+            .collect::<Vec<_>>();
+        self.persisted_sequences.drain(..num_dropped);
+        self.persisted_sequences.extend(new_sequences);
+        self.persisted = bounds;
+        Ok(())
+    }
+
+    /// The bounds of the entries currently in the journal.
+    fn bounds(&self) -> JournalBounds {
+        JournalBounds {
+            first_sequence: self
+                .entries
+                .front()
+                .map_or(self.next_sequence, |entry| entry.sequence),
+            next_sequence: self.next_sequence,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth2::types::{SseFinalizedCheckpoint, SseHead};
+    use logging::test_logger;
+    use store::{MemoryStore, StoreConfig};
+    use types::{Epoch, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+    type Store = HotColdDB<E, MemoryStore<E>, MemoryStore<E>>;
+
+    fn store() -> Store {
+        HotColdDB::open_ephemeral(StoreConfig::default(), E::default_spec(), test_logger()).unwrap()
+    }
+
+    fn head(slot: u64) -> EventKind<E> {
+        EventKind::Head(SseHead {
+            slot: Slot::new(slot),
+            block: Hash256::zero(),
+            state: Hash256::zero(),
+            current_duty_dependent_root: Hash256::zero(),
+            previous_duty_dependent_root: Hash256::zero(),
+            epoch_transition: false,
+            execution_optimistic: false,
+        })
+    }
+
+    fn finalized(epoch: u64) -> EventKind<E> {
+        EventKind::FinalizedCheckpoint(SseFinalizedCheckpoint {
+            block: Hash256::zero(),
+            state: Hash256::zero(),
+            epoch: Epoch::new(epoch),
+            execution_optimistic: false,
+        })
+    }
+
+    fn sequences(entries: &[JournalEntry]) -> Vec<u64> {
+        entries.iter().map(|entry| entry.sequence).collect()
+    }
+
+    #[test]
+    fn entries_are_bounded_and_ordered() {
+        let mut journal = EventJournal::new(3);
+        for slot in 1..=5 {
+            journal.append(&head(slot)).unwrap();
+        }
+        assert_eq!(journal.last_sequence(), 5);
+        assert_eq!(sequences(&journal.entries_after(0)), vec![3, 4, 5]);
+        assert_eq!(sequences(&journal.entries_after(4)), vec![5]);
+        assert_eq!(
+            sequences(&journal.entries_since_slot(Slot::new(4))),
+            vec![4, 5]
+        );
+
+        let entry = &journal.entries_after(4)[0];
+        assert_eq!(entry.topic(), "head");
+        assert_eq!(
+            serde_json::from_str::<SseHead>(entry.data()).unwrap().slot,
+            Slot::new(5)
+        );
+    }
+
+    #[test]
+    fn finalized_checkpoint_uses_latest_slot() {
+        let mut journal = EventJournal::new(8);
+        journal.append(&head(17)).unwrap();
+        let entry = journal.append(&finalized(1)).unwrap();
+        assert_eq!(entry.slot, Slot::new(17));
+        assert_eq!(entry.topic(), "finalized_checkpoint");
+    }
+
+    #[test]
+    fn journal_survives_reload() {
+        let store = store();
+        let mut journal = EventJournal::new(4);
+        for slot in 1..=3 {
+            journal.append(&head(slot)).unwrap();
+        }
+        journal.persist(&store).unwrap();
+        for slot in 4..=6 {
+            journal.append(&head(slot)).unwrap();
+        }
+        journal.persist(&store).unwrap();
+
+        let reloaded = EventJournal::load(&store, 4).unwrap();
+        assert_eq!(reloaded.last_sequence(), SEQUENCES_PER_RUN - 1);
+        assert_eq!(
+            reloaded.entries_after(0),
+            journal.entries_after(0),
+            "reloaded journal should match"
+        );
+        // Dropped entries are deleted from the database.
+        assert!(store
+            .get_item::<JournalEntry>(&entry_key(2))
+            .unwrap()
+            .is_none());
+
+        // A smaller journal only loads the newest entries.
+        let smaller = EventJournal::load(&store, 2).unwrap();
+        assert_eq!(sequences(&smaller.entries_after(0)), vec![5, 6]);
+        assert_eq!(smaller.latest_slot, Slot::new(6));
+        // The entries which didn't fit are deleted.
+        assert!(store
+            .get_item::<JournalEntry>(&entry_key(4))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn sequences_are_not_reissued_after_crash() {
+        let store = store();
+        let mut journal = EventJournal::new(4);
+        journal.append(&head(1)).unwrap();
+        journal.persist(&store).unwrap();
+
+        // Events issued since the journal was last written are lost in a crash.
+        let lost = journal.append(&head(2)).unwrap();
+
+        let mut reloaded = EventJournal::load(&store, 4).unwrap();
+        let entry = reloaded.append(&head(3)).unwrap();
+        assert!(entry.sequence > lost.sequence);
+        reloaded.persist(&store).unwrap();
+
+        // The same holds after a crash in a later run.
+        let lost = reloaded.append(&head(4)).unwrap();
+        let mut reloaded = EventJournal::load(&store, 4).unwrap();
+        assert!(reloaded.append(&head(5)).unwrap().sequence > lost.sequence);
+        assert_eq!(
+            sequences(&reloaded.entries_after(0)),
+            vec![1, entry.sequence, 2 * SEQUENCES_PER_RUN]
+        );
+
+        // Entries from previous runs are dropped once the journal is full.
+        for slot in 6..=8 {
+            reloaded.append(&head(slot)).unwrap();
+        }
+        reloaded.persist(&store).unwrap();
+        assert!(store
+            .get_item::<JournalEntry>(&entry_key(1))
+            .unwrap()
+            .is_none());
+        assert!(store
+            .get_item::<JournalEntry>(&entry_key(entry.sequence))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn empty_journal_keeps_no_entries() {
+        let mut journal = EventJournal::new(0);
+        assert_eq!(journal.append(&head(1)).unwrap().sequence, 1);
+        assert!(journal.entries_after(0).is_empty());
+    }
+}
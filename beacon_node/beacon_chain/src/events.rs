@@ -1,5 +1,8 @@
+use crate::event_journal::{EventJournal, JournalEntry, ReplayStart};
 pub use eth2::types::{EventKind, SseBlock, SseFinalizedCheckpoint, SseHead};
+use parking_lot::Mutex;
 use slog::{trace, Logger};
+use store::{Error as StoreError, HotColdDB, ItemStore};
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::{error::SendError, Receiver, Sender};
 use types::EthSpec;
@@ -17,6 +20,12 @@ pub struct ServerSentEventHandler<T: EthSpec> {
     payload_attributes_tx: Sender<EventKind<T>>,
     late_head: Sender<EventKind<T>>,
     block_reward_tx: Sender<EventKind<T>>,
+    light_client_finality_update_tx: Sender<EventKind<T>>,
+    light_client_optimistic_update_tx: Sender<EventKind<T>>,
+    validator_monitor_alert_tx: Sender<EventKind<T>>,
+    /// The journal of replayable events, if enabled.
+    journal: Option<Mutex<EventJournal>>,
+    journal_tx: Sender<JournalEntry>,
     log: Logger,
 }
 
@@ -36,6 +45,10 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
         let (payload_attributes_tx, _) = broadcast::channel(capacity);
         let (late_head, _) = broadcast::channel(capacity);
         let (block_reward_tx, _) = broadcast::channel(capacity);
+        let (light_client_finality_update_tx, _) = broadcast::channel(capacity);
+        let (light_client_optimistic_update_tx, _) = broadcast::channel(capacity);
+        let (validator_monitor_alert_tx, _) = broadcast::channel(capacity);
+        let (journal_tx, _) = broadcast::channel(capacity);
 
         Self {
             attestation_tx,
@@ -48,11 +61,63 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
             payload_attributes_tx,
             late_head,
             block_reward_tx,
+            light_client_finality_update_tx,
+            light_client_optimistic_update_tx,
+            validator_monitor_alert_tx,
+            journal: None,
+            journal_tx,
             log,
         }
     }
 
+    /// Record `head`, `block`, `finalized_checkpoint` and `chain_reorg` events in `journal` so
+    /// that they may be replayed to clients.
+    pub fn set_journal(&mut self, journal: EventJournal) {
+        self.journal = Some(Mutex::new(journal));
+    }
+
+    pub fn journal_enabled(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Returns the journaled events selected by `start` along with a receiver for those which are
+    /// journaled afterwards, or `None` if the journal is disabled.
+    ///
+    /// The receiver is created while the journal is locked, so that no event is either missed or
+    /// received twice.
+    pub fn subscribe_journal(
+        &self,
+        start: ReplayStart,
+    ) -> Option<(Vec<JournalEntry>, Receiver<JournalEntry>)> {
+        let journal = self.journal.as_ref()?.lock();
+        Some((journal.replay(start), self.journal_tx.subscribe()))
+    }
+
+    /// The sequence number of the oldest entry retained by the journal, if any.
+    pub fn journal_first_sequence(&self) -> Option<u64> {
+        self.journal.as_ref()?.lock().first_sequence()
+    }
+
+    /// Writes any new journal entries to `store`.
+    pub fn persist_journal<Hot: ItemStore<T>, Cold: ItemStore<T>>(
+        &self,
+        store: &HotColdDB<T, Hot, Cold>,
+    ) -> Result<(), StoreError> {
+        match &self.journal {
+            Some(journal) => journal.lock().persist(store),
+            None => Ok(()),
+        }
+    }
+
     pub fn register(&self, kind: EventKind<T>) {
+        if let Some(journal) = &self.journal {
+            let mut journal = journal.lock();
+            if let Some(entry) = journal.append(&kind) {
+                // Sent while the journal is locked so that entries are received in order.
+                let _ = self.journal_tx.send(entry);
+            }
+        }
+
         let log_count = |name, count| {
             trace!(
                 self.log,
@@ -102,6 +167,18 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
                 .block_reward_tx
                 .send(kind)
                 .map(|count| log_count("block reward", count)),
+            EventKind::LightClientFinalityUpdate(_) => self
+                .light_client_finality_update_tx
+                .send(kind)
+                .map(|count| log_count("light client finality update", count)),
+            EventKind::LightClientOptimisticUpdate(_) => self
+                .light_client_optimistic_update_tx
+                .send(kind)
+                .map(|count| log_count("light client optimistic update", count)),
+            EventKind::ValidatorMonitorAlert(_) => self
+                .validator_monitor_alert_tx
+                .send(kind)
+                .map(|count| log_count("validator monitor alert", count)),
         };
         if let Err(SendError(event)) = result {
             trace!(self.log, "No receivers registered to listen for event"; "event" => ?event);
@@ -148,20 +225,32 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
         self.block_reward_tx.subscribe()
     }
 
+    pub fn subscribe_light_client_finality_update(&self) -> Receiver<EventKind<T>> {
+        self.light_client_finality_update_tx.subscribe()
+    }
+
+    pub fn subscribe_light_client_optimistic_update(&self) -> Receiver<EventKind<T>> {
+        self.light_client_optimistic_update_tx.subscribe()
+    }
+
+    pub fn subscribe_validator_monitor_alert(&self) -> Receiver<EventKind<T>> {
+        self.validator_monitor_alert_tx.subscribe()
+    }
+
     pub fn has_attestation_subscribers(&self) -> bool {
         self.attestation_tx.receiver_count() > 0
     }
 
     pub fn has_block_subscribers(&self) -> bool {
-        self.block_tx.receiver_count() > 0
+        self.block_tx.receiver_count() > 0 || self.journal.is_some()
     }
 
     pub fn has_finalized_subscribers(&self) -> bool {
-        self.finalized_tx.receiver_count() > 0
+        self.finalized_tx.receiver_count() > 0 || self.journal.is_some()
     }
 
     pub fn has_head_subscribers(&self) -> bool {
-        self.head_tx.receiver_count() > 0
+        self.head_tx.receiver_count() > 0 || self.journal.is_some()
     }
 
     pub fn has_exit_subscribers(&self) -> bool {
@@ -169,7 +258,7 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
     }
 
     pub fn has_reorg_subscribers(&self) -> bool {
-        self.chain_reorg_tx.receiver_count() > 0
+        self.chain_reorg_tx.receiver_count() > 0 || self.journal.is_some()
     }
 
     pub fn has_contribution_subscribers(&self) -> bool {
@@ -187,4 +276,16 @@ impl<T: EthSpec> ServerSentEventHandler<T> {
     pub fn has_block_reward_subscribers(&self) -> bool {
         self.block_reward_tx.receiver_count() > 0
     }
+
+    pub fn has_light_client_finality_update_subscribers(&self) -> bool {
+        self.light_client_finality_update_tx.receiver_count() > 0
+    }
+
+    pub fn has_light_client_optimistic_update_subscribers(&self) -> bool {
+        self.light_client_optimistic_update_tx.receiver_count() > 0
+    }
+
+    pub fn has_validator_monitor_alert_subscribers(&self) -> bool {
+        self.validator_monitor_alert_tx.receiver_count() > 0
+    }
 }
@@ -92,6 +92,11 @@ impl SszHeadTracker {
         let (roots, slots) = map.iter().map(|(hash, slot)| (*hash, *slot)).unzip();
         SszHeadTracker { roots, slots }
     }
+
+    /// Iterate through the `(block_root, slot)` pairs of the tracked heads.
+    pub fn heads(&self) -> impl Iterator<Item = (Hash256, Slot)> + '_ {
+        self.roots.iter().copied().zip(self.slots.iter().copied())
+    }
 }
 
 #[cfg(test)]
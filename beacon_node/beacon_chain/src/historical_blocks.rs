@@ -66,6 +66,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .get_anchor_info()
             .ok_or(HistoricalBlockError::NoAnchorInfo)?;
 
+        if anchor_info.block_backfill_complete() {
+            return Ok(0);
+        }
+
         // Take all blocks with slots less than the oldest block slot.
         let num_relevant =
             blocks.partition_point(|block| block.slot() < anchor_info.oldest_block_slot);
@@ -87,6 +91,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             return Ok(0);
         }
 
+        let history_retention_slot = self.store.get_history_retention_slot();
         let mut expected_block_root = anchor_info.oldest_block_parent;
         let mut prev_block_slot = anchor_info.oldest_block_slot;
         let mut chunk_writer =
@@ -119,6 +124,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             prev_block_slot = block.slot();
             expected_block_root = block.message().parent_root();
 
+            // If we've reached the start of the retained history, stop and mark the backfill
+            // as complete.
+            if history_retention_slot.map_or(false, |slot| prev_block_slot <= slot) {
+                expected_block_root = Hash256::zero();
+                break;
+            }
+
             // If we've reached genesis, add the genesis block root to the batch and set the
             // anchor slot to 0 to indicate completion.
             if expected_block_root == self.genesis_block_root {
@@ -8,16 +8,19 @@ mod beacon_fork_choice_store;
 pub mod beacon_proposer_cache;
 mod beacon_snapshot;
 pub mod block_reward;
-mod block_times_cache;
+pub mod block_times_cache;
 mod block_verification;
 pub mod builder;
 pub mod canonical_head;
 pub mod capella_readiness;
 pub mod chain_config;
+pub mod duties_cache;
 mod early_attester_cache;
+pub mod era;
 mod errors;
 pub mod eth1_chain;
 mod eth1_finalization_cache;
+pub mod event_journal;
 pub mod events;
 pub mod execution_payload;
 pub mod fork_choice_signal;
@@ -26,6 +29,7 @@ mod head_tracker;
 pub mod historical_blocks;
 pub mod light_client_finality_update_verification;
 pub mod light_client_optimistic_update_verification;
+pub mod light_client_server_cache;
 pub mod merge_readiness;
 pub mod metrics;
 pub mod migrate;
@@ -39,6 +43,7 @@ mod persisted_beacon_chain;
 mod persisted_fork_choice;
 mod pre_finalization_cache;
 pub mod proposer_prep_service;
+pub mod reconstructed_payload_cache;
 pub mod schema_change;
 pub mod shuffling_cache;
 mod snapshot_cache;
@@ -51,9 +56,10 @@ pub mod validator_monitor;
 pub mod validator_pubkey_cache;
 
 pub use self::beacon_chain::{
-    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, BeaconStore, ChainSegmentResult,
-    CountUnrealized, ForkChoiceError, OverrideForkchoiceUpdate, ProduceBlockVerification,
-    StateSkipConfig, WhenSlotSkipped, INVALID_FINALIZED_MERGE_TRANSITION_BLOCK_SHUTDOWN_REASON,
+    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, BeaconStore, BlockSimulation,
+    ChainSegmentResult, CountUnrealized, ForkChoiceError, OverrideForkchoiceUpdate,
+    ProduceBlockVerification, StateSkipConfig, WhenSlotSkipped, BEACON_CHAIN_DB_KEY,
+    INVALID_FINALIZED_MERGE_TRANSITION_BLOCK_SHUTDOWN_REASON,
     INVALID_JUSTIFIED_PAYLOAD_SHUTDOWN_REASON, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 pub use self::beacon_snapshot::BeaconSnapshot;
@@ -73,6 +79,8 @@ pub use execution_payload::NotifyExecutionLayer;
 pub use fork_choice::{ExecutionStatus, ForkchoiceUpdateParameters};
 pub use metrics::scrape_for_metrics;
 pub use parking_lot;
+pub use persisted_beacon_chain::PersistedBeaconChain;
+pub use persisted_fork_choice::PersistedForkChoice;
 pub use slot_clock;
 pub use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, DepositValidationError,
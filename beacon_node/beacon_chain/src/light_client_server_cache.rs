@@ -0,0 +1,305 @@
+//! Produces light client updates from the blocks imported by this node, so that it may serve
+//! light clients.
+//!
+//! An update signed by the sync aggregate of some block attests to the block's parent, and so
+//! requires data from the parent's post-state. This data is cached as each block is imported to
+//! avoid loading states from the database in the common case.
+//!
+//! Only the roots of the state's fields are cached during block import, which are cheap to read
+//! from the state's tree hash cache. The Merkle proofs are computed from these roots when the
+//! updates are produced, off the block import path.
+
+use crate::{BeaconChainError, BeaconChainTypes, BeaconStore};
+use lru::LruCache;
+use parking_lot::{Mutex, RwLock};
+use std::cmp::Ordering;
+use std::sync::Arc;
+use types::light_client_update::{
+    FinalizedRootProofLen, NextSyncCommitteeProofLen, FINALIZED_ROOT_INDEX,
+    NEXT_SYNC_COMMITTEE_INDEX,
+};
+use types::{
+    BeaconBlockHeader, BeaconBlockRef, BeaconState, ChainSpec, Checkpoint, EthSpec, FixedVector,
+    Hash256, LightClientFinalityUpdate, LightClientOptimisticUpdate, LightClientUpdate, Slot,
+    SyncAggregate, SyncCommittee,
+};
+
+/// The number of recently imported blocks for which post-state data is cached.
+///
+/// Blocks are usually attested to by their immediate child, so this only needs to be large enough
+/// to cover blocks which are imported out of order.
+const PREV_BLOCK_CACHE_SIZE: usize = 32;
+
+/// Sent by block import to request the light client updates signed by the imported block.
+pub struct LightClientProducerEvent<T: EthSpec> {
+    /// The parent of the imported block, which is attested to by its sync aggregate.
+    pub attested_block_root: Hash256,
+    /// The slot of the imported block.
+    pub signature_slot: Slot,
+    pub sync_aggregate: SyncAggregate<T>,
+}
+
+/// The updates produced from a single sync aggregate which supersede those produced before it,
+/// and so should be published.
+pub struct LightClientProducedUpdates<T: EthSpec> {
+    pub finality_update: Option<LightClientFinalityUpdate<T>>,
+    pub optimistic_update: Option<LightClientOptimisticUpdate<T>>,
+}
+
+/// Data from the post-state of an attested block which is required to produce updates.
+#[derive(Clone)]
+struct LightClientCachedData<T: EthSpec> {
+    attested_header: BeaconBlockHeader,
+    finalized_checkpoint: Checkpoint,
+    next_sync_committee: Arc<SyncCommittee<T>>,
+    /// The roots of the fields of the attested state, from which the branches are computed.
+    state_leaves: Vec<Hash256>,
+}
+
+impl<T: EthSpec> LightClientCachedData<T> {
+    /// Read the data for the block with `state_root` from its post-`state`.
+    fn from_state(
+        state: &mut BeaconState<T>,
+        state_root: Hash256,
+    ) -> Result<Self, BeaconChainError> {
+        let mut attested_header = state.latest_block_header().clone();
+        attested_header.state_root = state_root;
+
+        Ok(Self {
+            attested_header,
+            finalized_checkpoint: state.finalized_checkpoint(),
+            next_sync_committee: state.next_sync_committee()?.clone(),
+            state_leaves: state.tree_hash_leaves()?,
+        })
+    }
+
+    fn finality_branch(
+        &self,
+    ) -> Result<FixedVector<Hash256, FinalizedRootProofLen>, BeaconChainError> {
+        let branch = BeaconState::<T>::compute_merkle_proof_from_leaves(
+            &self.state_leaves,
+            FINALIZED_ROOT_INDEX,
+            self.finalized_checkpoint.epoch,
+        )?;
+        Ok(FixedVector::new(branch)?)
+    }
+
+    fn next_sync_committee_branch(
+        &self,
+    ) -> Result<FixedVector<Hash256, NextSyncCommitteeProofLen>, BeaconChainError> {
+        let branch = BeaconState::<T>::compute_merkle_proof_from_leaves(
+            &self.state_leaves,
+            NEXT_SYNC_COMMITTEE_INDEX,
+            self.finalized_checkpoint.epoch,
+        )?;
+        Ok(FixedVector::new(branch)?)
+    }
+}
+
+/// Caches the latest light client updates produced by this node.
+///
+/// The best `LightClientUpdate` for each sync committee period is persisted to the database,
+/// rather than cached here.
+pub struct LightClientServerCache<T: BeaconChainTypes> {
+    latest_finality_update: RwLock<Option<LightClientFinalityUpdate<T::EthSpec>>>,
+    latest_optimistic_update: RwLock<Option<LightClientOptimisticUpdate<T::EthSpec>>>,
+    prev_block_cache: Mutex<LruCache<Hash256, LightClientCachedData<T::EthSpec>>>,
+}
+
+impl<T: BeaconChainTypes> Default for LightClientServerCache<T> {
+    fn default() -> Self {
+        Self {
+            latest_finality_update: RwLock::new(None),
+            latest_optimistic_update: RwLock::new(None),
+            prev_block_cache: Mutex::new(LruCache::new(PREV_BLOCK_CACHE_SIZE)),
+        }
+    }
+}
+
+impl<T: BeaconChainTypes> LightClientServerCache<T> {
+    /// Cache the data from the post-state of an imported block that is required to produce
+    /// updates signed by its descendants.
+    ///
+    /// No-op for blocks prior to Altair.
+    pub fn cache_state_data(
+        &self,
+        spec: &ChainSpec,
+        block: BeaconBlockRef<T::EthSpec>,
+        block_root: Hash256,
+        block_post_state: &mut BeaconState<T::EthSpec>,
+    ) -> Result<(), BeaconChainError> {
+        if spec
+            .altair_fork_epoch
+            .map_or(true, |altair_epoch| block.epoch() < altair_epoch)
+        {
+            return Ok(());
+        }
+
+        let cached_data = LightClientCachedData::from_state(block_post_state, block.state_root())?;
+        self.prev_block_cache.lock().put(block_root, cached_data);
+        Ok(())
+    }
+
+    /// Produce the updates signed by the sync aggregate in `event`.
+    ///
+    /// Finality and optimistic updates are cached and returned if they supersede the latest
+    /// updates produced. The update is also persisted to the database if it is the best update
+    /// seen for its sync committee period.
+    pub fn recompute_and_cache_updates(
+        &self,
+        store: &BeaconStore<T>,
+        event: &LightClientProducerEvent<T::EthSpec>,
+        spec: &ChainSpec,
+    ) -> Result<LightClientProducedUpdates<T::EthSpec>, BeaconChainError> {
+        let mut produced = LightClientProducedUpdates {
+            finality_update: None,
+            optimistic_update: None,
+        };
+
+        let sync_aggregate = &event.sync_aggregate;
+        if sync_aggregate.num_set_bits() < spec.min_sync_committee_participants as usize {
+            return Ok(produced);
+        }
+
+        let cached_data =
+            self.get_or_compute_prev_block_cache(store, &event.attested_block_root)?;
+        let attested_slot = cached_data.attested_header.slot;
+
+        {
+            let mut latest_optimistic_update = self.latest_optimistic_update.write();
+            if latest_optimistic_update
+                .as_ref()
+                .map_or(true, |latest| attested_slot > latest.attested_header.slot)
+            {
+                let optimistic_update = LightClientOptimisticUpdate {
+                    attested_header: cached_data.attested_header.clone(),
+                    sync_aggregate: sync_aggregate.clone(),
+                    signature_slot: event.signature_slot,
+                };
+                *latest_optimistic_update = Some(optimistic_update.clone());
+                produced.optimistic_update = Some(optimistic_update);
+            }
+        }
+
+        // Finality and full updates require the finalized block, which is absent prior to the first
+        // finalization.
+        let finalized_block_root = cached_data.finalized_checkpoint.root;
+        if finalized_block_root.is_zero() {
+            return Ok(produced);
+        }
+        let finalized_header = match store.get_blinded_block(&finalized_block_root)? {
+            Some(finalized_block) => finalized_block.message().block_header(),
+            None => return Ok(produced),
+        };
+        let finality_branch = cached_data.finality_branch()?;
+
+        {
+            let mut latest_finality_update = self.latest_finality_update.write();
+            if latest_finality_update.as_ref().map_or(true, |latest| {
+                is_latest_finality_update(latest, &finalized_header, sync_aggregate)
+            }) {
+                let finality_update = LightClientFinalityUpdate {
+                    attested_header: cached_data.attested_header.clone(),
+                    finalized_header: finalized_header.clone(),
+                    finality_branch: finality_branch.clone(),
+                    sync_aggregate: sync_aggregate.clone(),
+                    signature_slot: event.signature_slot,
+                };
+                *latest_finality_update = Some(finality_update.clone());
+                produced.finality_update = Some(finality_update);
+            }
+        }
+
+        // Only updates signed by the sync committee of the attested period are useful for
+        // advancing a light client to the next period.
+        let attested_period = attested_slot
+            .epoch(T::EthSpec::slots_per_epoch())
+            .sync_committee_period(spec)?;
+        let signature_period = event
+            .signature_slot
+            .epoch(T::EthSpec::slots_per_epoch())
+            .sync_committee_period(spec)?;
+        if attested_period != signature_period {
+            return Ok(produced);
+        }
+
+        let update = LightClientUpdate {
+            next_sync_committee_branch: cached_data.next_sync_committee_branch()?,
+            attested_header: cached_data.attested_header,
+            next_sync_committee: cached_data.next_sync_committee,
+            finalized_header,
+            finality_branch,
+            sync_aggregate: sync_aggregate.clone(),
+            signature_slot: event.signature_slot,
+        };
+        let is_better_update = match store.get_light_client_update(attested_period)? {
+            Some(best_update) => update.is_better_update(&best_update, spec)?,
+            None => true,
+        };
+        if is_better_update {
+            store.put_light_client_update(attested_period, &update)?;
+        }
+
+        Ok(produced)
+    }
+
+    /// Returns the latest finality update produced by this node.
+    pub fn get_latest_finality_update(&self) -> Option<LightClientFinalityUpdate<T::EthSpec>> {
+        self.latest_finality_update.read().clone()
+    }
+
+    /// Returns the latest optimistic update produced by this node.
+    pub fn get_latest_optimistic_update(&self) -> Option<LightClientOptimisticUpdate<T::EthSpec>> {
+        self.latest_optimistic_update.read().clone()
+    }
+
+    /// Retrieve the cached data for `block_root`, loading its state from the database on a miss.
+    fn get_or_compute_prev_block_cache(
+        &self,
+        store: &BeaconStore<T>,
+        block_root: &Hash256,
+    ) -> Result<LightClientCachedData<T::EthSpec>, BeaconChainError> {
+        if let Some(cached_data) = self.prev_block_cache.lock().get(block_root) {
+            return Ok(cached_data.clone());
+        }
+
+        let block = store
+            .get_blinded_block(block_root)?
+            .ok_or(BeaconChainError::MissingBeaconBlock(*block_root))?;
+        let mut state = store
+            .get_state(&block.state_root(), Some(block.slot()))?
+            .ok_or(BeaconChainError::MissingBeaconState(block.state_root()))?;
+        // States loaded from the database have no tree hash cache.
+        state.update_tree_hash_cache()?;
+        let cached_data = LightClientCachedData::from_state(&mut state, block.state_root())?;
+
+        self.prev_block_cache
+            .lock()
+            .put(*block_root, cached_data.clone());
+        Ok(cached_data)
+    }
+}
+
+/// Returns `true` if a finality update for `finalized_header` signed by `sync_aggregate` should
+/// replace the `latest` update, following the gossip validation rules for
+/// `light_client_finality_update`.
+///
+/// An update for the same finalized slot only replaces the latest update if it has supermajority
+/// participation and the latest update does not.
+fn is_latest_finality_update<T: EthSpec>(
+    latest: &LightClientFinalityUpdate<T>,
+    finalized_header: &BeaconBlockHeader,
+    sync_aggregate: &SyncAggregate<T>,
+) -> bool {
+    let has_supermajority = |sync_aggregate: &SyncAggregate<T>| {
+        sync_aggregate.num_set_bits() * 3 > T::sync_committee_size() * 2
+    };
+
+    match finalized_header.slot.cmp(&latest.finalized_header.slot) {
+        Ordering::Greater => true,
+        Ordering::Equal => {
+            has_supermajority(sync_aggregate) && !has_supermajority(&latest.sync_aggregate)
+        }
+        Ordering::Less => false,
+    }
+}
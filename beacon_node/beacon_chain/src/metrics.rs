@@ -34,6 +34,27 @@ lazy_static! {
         "beacon_block_processing_snapshot_cache_clones",
         "Count of snapshot cache clones"
     );
+    pub static ref STATE_CACHE_MEMORY_BYTES: Result<IntGauge> = try_create_int_gauge(
+        "beacon_state_cache_memory_bytes",
+        "Estimated memory used by the states in the snapshot cache"
+    );
+    pub static ref STATE_CACHE_STATES: Result<IntGauge> = try_create_int_gauge(
+        "beacon_state_cache_states",
+        "Count of states loaded from the database which are held in the snapshot cache"
+    );
+    pub static ref STATE_CACHE_HITS: Result<IntCounter> = try_create_int_counter(
+        "beacon_state_cache_hits_total",
+        "Count of state lookups by root which were served from the snapshot cache"
+    );
+    pub static ref STATE_CACHE_MISSES: Result<IntCounter> = try_create_int_counter(
+        "beacon_state_cache_misses_total",
+        "Count of state lookups by root which were loaded from the database"
+    );
+    pub static ref STATE_CACHE_EVICTIONS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "beacon_state_cache_evictions_total",
+        "Count of items evicted from the snapshot cache, by reason",
+        &["reason"]
+    );
     pub static ref BLOCK_PROCESSING_TIMES: Result<Histogram> =
         try_create_histogram("beacon_block_processing_seconds", "Full runtime of block processing");
     pub static ref BLOCK_PROCESSING_BLOCK_ROOT: Result<Histogram> = try_create_histogram(
@@ -86,6 +107,10 @@ lazy_static! {
         "block_sync_aggregate_set_bits",
         "The number of true bits in the last sync aggregate in a block"
     );
+    pub static ref PROPOSER_EQUIVOCATIONS_DETECTED: Result<IntCounter> = try_create_int_counter(
+        "beacon_block_proposer_equivocations_detected_total",
+        "Count of proposer slashings formed from conflicting blocks seen on gossip"
+    );
 
     /*
      * Block Production
@@ -274,6 +299,18 @@ lazy_static! {
     pub static ref SHUFFLING_CACHE_PROMISE_FAILS: Result<IntCounter> =
         try_create_int_counter("beacon_shuffling_cache_promise_fails_total", "Count of times shuffling cache detects a failed promise");
 
+    /*
+     * Duties cache
+     */
+    pub static ref DUTIES_CACHE_HITS: Result<IntCounter> =
+        try_create_int_counter("beacon_duties_cache_hits_total", "Count of times the duties cache fulfils a request");
+    pub static ref DUTIES_CACHE_MISSES: Result<IntCounter> =
+        try_create_int_counter("beacon_duties_cache_misses_total", "Count of times the duties cache misses a request");
+    pub static ref DUTIES_CACHE_PRIME_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_duties_cache_prime_seconds",
+        "Time taken to compute any missing duties from an advanced head state"
+    );
+
     /*
      * Early attester cache
      */
@@ -990,6 +1027,18 @@ lazy_static! {
         "light_client_optimistic_update_verification_success_total",
         "Number of light client optimistic updates verified for gossip"
     );
+
+    /*
+     * Reconstructed payload cache
+     */
+    pub static ref RECONSTRUCTED_PAYLOAD_CACHE_HITS: Result<IntCounter> = try_create_int_counter(
+        "beacon_reconstructed_payload_cache_hits_total",
+        "Number of pruned execution payloads served from the reconstructed payload cache"
+    );
+    pub static ref RECONSTRUCTED_PAYLOAD_CACHE_MISSES: Result<IntCounter> = try_create_int_counter(
+        "beacon_reconstructed_payload_cache_misses_total",
+        "Number of pruned execution payloads which had to be fetched from the execution engine"
+    );
 }
 
 /// Scrape the `beacon_chain` for metrics that are not constantly updated (e.g., the present slot,
@@ -1014,7 +1063,9 @@ pub fn scrape_for_metrics<T: BeaconChainTypes>(beacon_chain: &BeaconChain<T>) {
         set_gauge(
             &BLOCK_PROCESSING_SNAPSHOT_CACHE_SIZE,
             snapshot_cache.len() as i64,
-        )
+        );
+        set_gauge_by_usize(&STATE_CACHE_MEMORY_BYTES, snapshot_cache.memory_bytes());
+        set_gauge_by_usize(&STATE_CACHE_STATES, snapshot_cache.num_states());
     }
 
     if let Some((size, num_lookups)) = beacon_chain.pre_finalization_block_cache.metrics() {
@@ -8,11 +8,12 @@ use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::sync::{mpsc, Arc};
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use store::hot_cold_store::{migrate_database, HotColdDBError};
 use store::iter::RootsIterator;
-use store::{Error, ItemStore, StoreItem, StoreOp};
+use store::{get_key_for_col, DBColumn, Error, ItemStore, KeyValueStoreOp, StoreItem, StoreOp};
 pub use store::{HotColdDB, MemoryStore};
+use task_executor::TaskExecutor;
 use types::{
     BeaconState, BeaconStateError, BeaconStateHash, Checkpoint, Epoch, EthSpec, Hash256,
     SignedBeaconBlockHash, Slot,
@@ -24,6 +25,10 @@ const MAX_COMPACTION_PERIOD_SECONDS: u64 = 604800;
 const MIN_COMPACTION_PERIOD_SECONDS: u64 = 7200;
 /// Compact after a large finality gap, if we respect `MIN_COMPACTION_PERIOD_SECONDS`.
 const COMPACTION_FINALITY_DISTANCE: u64 = 1024;
+/// Reconstruct at least this many slots of historic states before yielding to other work.
+///
+/// Batches always end at a restore point, so they may be longer with a larger SPRP.
+const RECONSTRUCTION_BATCH_SLOTS: usize = 2048;
 
 /// The background migrator runs a thread to perform pruning and migrate state from the hot
 /// to the cold database.
@@ -148,18 +153,76 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
         if let Some(Notification::Reconstruction) =
             self.send_background_notification(Notification::Reconstruction)
         {
-            Self::run_reconstruction(self.db.clone(), &self.log);
+            Self::run_reconstruction(self.db.clone(), None, &self.log);
         }
     }
 
-    pub fn run_reconstruction(db: Arc<HotColdDB<E, Hot, Cold>>, log: &Logger) {
-        if let Err(e) = db.reconstruct_historic_states() {
+    /// Start a manual compaction, returning `false` if one is already running.
+    ///
+    /// The compaction runs on a blocking task of `executor` rather than the migration thread, so
+    /// that it doesn't hold up finalization.
+    pub fn process_manual_compaction(&self, executor: &TaskExecutor) -> Result<bool, Error> {
+        if !self.db.start_compaction()? {
+            return Ok(false);
+        }
+
+        // Run in the background unless the migrator is configured to block.
+        if self.tx_thread.is_some() {
+            let db = self.db.clone();
+            let log = self.log.clone();
+            executor.spawn_blocking(
+                move || Self::run_manual_compaction(db, &log),
+                "manual_compaction",
+            );
+        } else {
+            Self::run_manual_compaction(self.db.clone(), &self.log);
+        }
+        Ok(true)
+    }
+
+    fn run_manual_compaction(db: Arc<HotColdDB<E, Hot, Cold>>, log: &Logger) {
+        info!(log, "Starting manual database compaction");
+        let result = db.compact_all().and_then(|()| {
+            let finish_time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_else(|_| Duration::from_secs(0));
+            db.store_compaction_timestamp(finish_time)
+        });
+        match result {
+            Ok(()) => info!(log, "Manual database compaction complete"),
+            Err(e) => error!(
+                log,
+                "Manual database compaction failed";
+                "error" => ?e,
+            ),
+        }
+    }
+
+    /// Reconstruct historic states, stopping after `num_slots` slots as per
+    /// `HotColdDB::reconstruct_historic_states`.
+    ///
+    /// Return the time taken.
+    pub fn run_reconstruction(
+        db: Arc<HotColdDB<E, Hot, Cold>>,
+        num_slots: Option<usize>,
+        log: &Logger,
+    ) -> Duration {
+        let start = Instant::now();
+        if let Err(e) = db.reconstruct_historic_states(num_slots) {
             error!(
                 log,
                 "State reconstruction failed";
                 "error" => ?e,
             );
         }
+        start.elapsed()
+    }
+
+    /// Return the time to wait after a batch of reconstruction taking `elapsed`, so that
+    /// reconstruction only runs for the configured percentage of the time.
+    fn reconstruction_delay(db: &HotColdDB<E, Hot, Cold>, elapsed: Duration) -> Duration {
+        let budget = u32::from(db.get_config().reconstruction_budget_percent.clamp(1, 100));
+        elapsed * (100 - budget) / budget
     }
 
     /// If configured to run in the background, send `notif` to the background thread.
@@ -279,6 +342,11 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
             }
         };
 
+        // Delete history which has fallen outside of the retention window, if configured.
+        if let Err(e) = db.try_prune_history() {
+            warn!(log, "History pruning failed"; "error" => ?e);
+        }
+
         // Finally, compact the database so that new free space is properly reclaimed.
         if let Err(e) = Self::run_compaction(
             db,
@@ -301,30 +369,60 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
     ) -> (mpsc::Sender<Notification>, thread::JoinHandle<()>) {
         let (tx, rx) = mpsc::channel();
         let thread = thread::spawn(move || {
-            while let Ok(notif) = rx.recv() {
-                // Read the rest of the messages in the channel, preferring any reconstruction
-                // notification, or the finalization notification with the greatest finalized epoch.
-                let notif =
-                    rx.try_iter()
-                        .fold(notif, |best, other: Notification| match (&best, &other) {
-                            (Notification::Reconstruction, _)
-                            | (_, Notification::Reconstruction) => Notification::Reconstruction,
-                            (
-                                Notification::Finalization(fin1),
-                                Notification::Finalization(fin2),
-                            ) => {
-                                if fin2.finalized_checkpoint.epoch > fin1.finalized_checkpoint.epoch
-                                {
-                                    other
-                                } else {
-                                    best
-                                }
+            // The time at which the next batch of reconstruction should run, if it is unfinished.
+            let mut next_reconstruction_batch: Option<Instant> = None;
+            loop {
+                // While reconstruction is throttled, wake up in time for its next batch.
+                let notif = if let Some(next_batch) = next_reconstruction_batch {
+                    match rx.recv_timeout(next_batch.saturating_duration_since(Instant::now())) {
+                        Ok(notif) => Some(notif),
+                        Err(mpsc::RecvTimeoutError::Timeout) => None,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                } else {
+                    match rx.recv() {
+                        Ok(notif) => Some(notif),
+                        Err(mpsc::RecvError) => break,
+                    }
+                };
+
+                // Read the rest of the messages in the channel, keeping the finalization
+                // notification with the greatest finalized epoch.
+                let mut reconstruction = false;
+                let mut finalization_notif: Option<FinalizationNotification> = None;
+                for notif in notif.into_iter().chain(rx.try_iter()) {
+                    match notif {
+                        Notification::Reconstruction => reconstruction = true,
+                        Notification::Finalization(fin) => {
+                            if finalization_notif.as_ref().map_or(true, |best| {
+                                fin.finalized_checkpoint.epoch > best.finalized_checkpoint.epoch
+                            }) {
+                                finalization_notif = Some(fin);
                             }
-                        });
+                        }
+                    }
+                }
+
+                if let Some(fin) = finalization_notif {
+                    Self::run_migration(db.clone(), fin, &log);
+                }
 
-                match notif {
-                    Notification::Reconstruction => Self::run_reconstruction(db.clone(), &log),
-                    Notification::Finalization(fin) => Self::run_migration(db.clone(), fin, &log),
+                // Reconstruction runs in batches so that finalization isn't held up, sleeping
+                // between them to stay within its budget. Requests to reconstruct while a batch
+                // is already scheduled don't bypass the delay.
+                let run_batch = match next_reconstruction_batch {
+                    Some(next_batch) => Instant::now() >= next_batch,
+                    None => reconstruction,
+                };
+                if run_batch {
+                    let elapsed = Self::run_reconstruction(
+                        db.clone(),
+                        Some(RECONSTRUCTION_BATCH_SLOTS),
+                        &log,
+                    );
+                    next_reconstruction_batch = db
+                        .reconstruction_pending()
+                        .then(|| Instant::now() + Self::reconstruction_delay(&db, elapsed));
                 }
             }
         });
@@ -572,6 +670,10 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
                 [
                     StoreOp::DeleteBlock(block_root),
                     StoreOp::DeleteExecutionPayload(block_root),
+                    StoreOp::KeyValueOp(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                        DBColumn::BeaconBlockTimes.into(),
+                        block_root.as_bytes(),
+                    ))),
                 ]
             })
             .chain(
@@ -1,9 +1,14 @@
 //! Provides the `ObservedBlockProducers` struct which allows for rejecting gossip blocks from
 //! validators that have already produced a block.
+//!
+//! The header of the first block observed for each `(block.slot, block.proposer)` is retained so
+//! that a `ProposerSlashing` can be formed if a conflicting block is later observed.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap};
 use std::marker::PhantomData;
-use types::{BeaconBlockRef, Epoch, EthSpec, Slot, Unsigned};
+use types::{
+    BeaconBlockRef, Epoch, EthSpec, ProposerSlashing, SignedBeaconBlockHeader, Slot, Unsigned,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -14,7 +19,8 @@ pub enum Error {
     ValidatorIndexTooHigh(u64),
 }
 
-/// Maintains a cache of observed `(block.slot, block.proposer)`.
+/// Maintains a cache of observed `(block.slot, block.proposer)`, along with the header of the
+/// first block observed for each pair.
 ///
 /// The cache supports pruning based upon the finalized epoch. It does not automatically prune, you
 /// must call `Self::prune` manually.
@@ -27,7 +33,7 @@ pub enum Error {
 /// known_distinct_shufflings` which is much smaller.
 pub struct ObservedBlockProducers<E: EthSpec> {
     finalized_slot: Slot,
-    items: HashMap<Slot, HashSet<u64>>,
+    items: HashMap<Slot, HashMap<u64, SignedBeaconBlockHeader>>,
     _phantom: PhantomData<E>,
 }
 
@@ -43,25 +49,35 @@ impl<E: EthSpec> Default for ObservedBlockProducers<E> {
 }
 
 impl<E: EthSpec> ObservedBlockProducers<E> {
-    /// Observe that the `block` was produced by `block.proposer_index` at `block.slot`. This will
-    /// update `self` so future calls to it indicate that this block is known.
+    /// Observe that the block with `header` was produced by `header.proposer_index` at
+    /// `header.slot`. This will update `self` so future calls to it indicate that this block is
+    /// known.
     ///
-    /// The supplied `block` **MUST** be signature verified (see struct-level documentation).
+    /// Only the first header observed for each proposer and slot is retained.
+    ///
+    /// The supplied `header` **MUST** be signature verified (see struct-level documentation).
     ///
     /// ## Errors
     ///
-    /// - `block.proposer_index` is greater than `VALIDATOR_REGISTRY_LIMIT`.
-    /// - `block.slot` is equal to or less than the latest pruned `finalized_slot`.
-    pub fn observe_proposer(&mut self, block: BeaconBlockRef<'_, E>) -> Result<bool, Error> {
-        self.sanitize_block(block)?;
-
-        let did_not_exist = self
+    /// - `header.proposer_index` is greater than `VALIDATOR_REGISTRY_LIMIT`.
+    /// - `header.slot` is equal to or less than the latest pruned `finalized_slot`.
+    pub fn observe_proposer(&mut self, header: &SignedBeaconBlockHeader) -> Result<bool, Error> {
+        let slot = header.message.slot;
+        let proposer_index = header.message.proposer_index;
+        self.sanitize_block(slot, proposer_index)?;
+
+        let proposers = self
             .items
-            .entry(block.slot())
-            .or_insert_with(|| HashSet::with_capacity(E::SlotsPerEpoch::to_usize()))
-            .insert(block.proposer_index());
-
-        Ok(!did_not_exist)
+            .entry(slot)
+            .or_insert_with(|| HashMap::with_capacity(E::SlotsPerEpoch::to_usize()));
+
+        match proposers.entry(proposer_index) {
+            Entry::Occupied(_) => Ok(true),
+            Entry::Vacant(entry) => {
+                entry.insert(header.clone());
+                Ok(false)
+            }
+        }
     }
 
     /// Returns `Ok(true)` if the `block` has been observed before, `Ok(false)` if not. Does not
@@ -73,26 +89,57 @@ impl<E: EthSpec> ObservedBlockProducers<E> {
     /// - `block.proposer_index` is greater than `VALIDATOR_REGISTRY_LIMIT`.
     /// - `block.slot` is equal to or less than the latest pruned `finalized_slot`.
     pub fn proposer_has_been_observed(&self, block: BeaconBlockRef<'_, E>) -> Result<bool, Error> {
-        self.sanitize_block(block)?;
+        self.sanitize_block(block.slot(), block.proposer_index())?;
 
-        let exists = self
-            .items
-            .get(&block.slot())
-            .map_or(false, |set| set.contains(&block.proposer_index()));
+        let exists = self.items.get(&block.slot()).map_or(false, |proposers| {
+            proposers.contains_key(&block.proposer_index())
+        });
 
         Ok(exists)
     }
 
-    /// Returns `Ok(())` if the given `block` is sane.
-    fn sanitize_block(&self, block: BeaconBlockRef<'_, E>) -> Result<(), Error> {
-        if block.proposer_index() >= E::ValidatorRegistryLimit::to_u64() {
-            return Err(Error::ValidatorIndexTooHigh(block.proposer_index()));
+    /// Returns a `ProposerSlashing` if a block with a different header to `header` has already
+    /// been observed from the same proposer at the same slot.
+    ///
+    /// The slashing is formed from the first observed header and `header`. Neither signature is
+    /// checked by this function: the first header was signature verified before it was observed,
+    /// but `header` may not have been.
+    ///
+    /// ## Errors
+    ///
+    /// - `header.proposer_index` is greater than `VALIDATOR_REGISTRY_LIMIT`.
+    /// - `header.slot` is equal to or less than the latest pruned `finalized_slot`.
+    pub fn proposer_slashing(
+        &self,
+        header: &SignedBeaconBlockHeader,
+    ) -> Result<Option<ProposerSlashing>, Error> {
+        let slot = header.message.slot;
+        let proposer_index = header.message.proposer_index;
+        self.sanitize_block(slot, proposer_index)?;
+
+        let slashing = self
+            .items
+            .get(&slot)
+            .and_then(|proposers| proposers.get(&proposer_index))
+            .filter(|observed| observed.message != header.message)
+            .map(|observed| ProposerSlashing {
+                signed_header_1: observed.clone(),
+                signed_header_2: header.clone(),
+            });
+
+        Ok(slashing)
+    }
+
+    /// Returns `Ok(())` if a block with the given `slot` and `proposer_index` is sane.
+    fn sanitize_block(&self, slot: Slot, proposer_index: u64) -> Result<(), Error> {
+        if proposer_index >= E::ValidatorRegistryLimit::to_u64() {
+            return Err(Error::ValidatorIndexTooHigh(proposer_index));
         }
 
         let finalized_slot = self.finalized_slot;
-        if finalized_slot > 0 && block.slot() <= finalized_slot {
+        if finalized_slot > 0 && slot <= finalized_slot {
             return Err(Error::FinalizedBlock {
-                slot: block.slot(),
+                slot,
                 finalized_slot,
             });
         }
@@ -112,7 +159,7 @@ impl<E: EthSpec> ObservedBlockProducers<E> {
         }
 
         self.finalized_slot = finalized_slot;
-        self.items.retain(|slot, _set| *slot > finalized_slot);
+        self.items.retain(|slot, _proposers| *slot > finalized_slot);
     }
 
     /// Returns `true` if the given `validator_index` has been stored in `self` at `epoch`.
@@ -120,7 +167,7 @@ impl<E: EthSpec> ObservedBlockProducers<E> {
     /// This is useful for doppelganger detection.
     pub fn index_seen_at_epoch(&self, validator_index: u64, epoch: Epoch) -> bool {
         self.items.iter().any(|(slot, producers)| {
-            slot.epoch(E::slots_per_epoch()) == epoch && producers.contains(&validator_index)
+            slot.epoch(E::slots_per_epoch()) == epoch && producers.contains_key(&validator_index)
         })
     }
 }
@@ -128,7 +175,7 @@ impl<E: EthSpec> ObservedBlockProducers<E> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use types::{BeaconBlock, MainnetEthSpec};
+    use types::{BeaconBlock, Hash256, MainnetEthSpec, Signature};
 
     type E = MainnetEthSpec;
 
@@ -139,6 +186,13 @@ mod tests {
         block
     }
 
+    fn get_header(block: &BeaconBlock<E>) -> SignedBeaconBlockHeader {
+        SignedBeaconBlockHeader {
+            message: block.block_header(),
+            signature: Signature::empty(),
+        }
+    }
+
     #[test]
     fn pruning() {
         let mut cache = ObservedBlockProducers::default();
@@ -150,7 +204,7 @@ mod tests {
         let block_a = get_block(0, 0);
 
         assert_eq!(
-            cache.observe_proposer(block_a.to_ref()),
+            cache.observe_proposer(&get_header(&block_a)),
             Ok(false),
             "can observe proposer, indicates proposer unobserved"
         );
@@ -209,7 +263,7 @@ mod tests {
         let block_b = get_block(E::slots_per_epoch(), 0);
 
         assert_eq!(
-            cache.observe_proposer(block_b.to_ref()),
+            cache.observe_proposer(&get_header(&block_b)),
             Err(Error::FinalizedBlock {
                 slot: E::slots_per_epoch().into(),
                 finalized_slot: E::slots_per_epoch().into(),
@@ -229,7 +283,7 @@ mod tests {
         let block_b = get_block(three_epochs, 0);
 
         assert_eq!(
-            cache.observe_proposer(block_b.to_ref()),
+            cache.observe_proposer(&get_header(&block_b)),
             Ok(false),
             "can insert non-finalized block"
         );
@@ -283,7 +337,7 @@ mod tests {
             "no observation in empty cache"
         );
         assert_eq!(
-            cache.observe_proposer(block_a.to_ref()),
+            cache.observe_proposer(&get_header(&block_a)),
             Ok(false),
             "can observe proposer, indicates proposer unobserved"
         );
@@ -293,7 +347,7 @@ mod tests {
             "observed block is indicated as true"
         );
         assert_eq!(
-            cache.observe_proposer(block_a.to_ref()),
+            cache.observe_proposer(&get_header(&block_a)),
             Ok(true),
             "observing again indicates true"
         );
@@ -319,7 +373,7 @@ mod tests {
             "no observation for new slot"
         );
         assert_eq!(
-            cache.observe_proposer(block_b.to_ref()),
+            cache.observe_proposer(&get_header(&block_b)),
             Ok(false),
             "can observe proposer for new slot, indicates proposer unobserved"
         );
@@ -329,7 +383,7 @@ mod tests {
             "observed block in slot 1 is indicated as true"
         );
         assert_eq!(
-            cache.observe_proposer(block_b.to_ref()),
+            cache.observe_proposer(&get_header(&block_b)),
             Ok(true),
             "observing slot 1 again indicates true"
         );
@@ -364,7 +418,7 @@ mod tests {
             "no observation for new proposer"
         );
         assert_eq!(
-            cache.observe_proposer(block_c.to_ref()),
+            cache.observe_proposer(&get_header(&block_c)),
             Ok(false),
             "can observe new proposer, indicates proposer unobserved"
         );
@@ -374,7 +428,7 @@ mod tests {
             "observed new proposer block is indicated as true"
         );
         assert_eq!(
-            cache.observe_proposer(block_c.to_ref()),
+            cache.observe_proposer(&get_header(&block_c)),
             Ok(true),
             "observing new proposer again indicates true"
         );
@@ -400,4 +454,77 @@ mod tests {
             "only one proposer should be present in slot 1"
         );
     }
+
+    #[test]
+    fn proposer_slashing() {
+        let mut cache = ObservedBlockProducers::default();
+
+        // Slot 1, proposer 0
+        let block_a = get_block(1, 0);
+        let header_a = get_header(&block_a);
+
+        assert_eq!(
+            cache.proposer_slashing(&header_a),
+            Ok(None),
+            "no slashing in empty cache"
+        );
+        assert_eq!(
+            cache.observe_proposer(&header_a),
+            Ok(false),
+            "can observe proposer, indicates proposer unobserved"
+        );
+        assert_eq!(
+            cache.proposer_slashing(&header_a),
+            Ok(None),
+            "no slashing for an identical header"
+        );
+
+        // Slot 1, proposer 0, different contents.
+        let mut block_b = get_block(1, 0);
+        *block_b.parent_root_mut() = Hash256::repeat_byte(1);
+        let header_b = get_header(&block_b);
+
+        let slashing = cache
+            .proposer_slashing(&header_b)
+            .expect("should check for slashing")
+            .expect("should form slashing for conflicting header");
+        assert_eq!(
+            slashing.signed_header_1, header_a,
+            "first header is retained"
+        );
+        assert_eq!(
+            slashing.signed_header_2, header_b,
+            "second header is the new one"
+        );
+
+        assert_eq!(
+            cache.observe_proposer(&header_b),
+            Ok(true),
+            "observing a conflicting header indicates true"
+        );
+        assert_eq!(
+            cache
+                .proposer_slashing(&header_b)
+                .expect("should check for slashing")
+                .map(|slashing| slashing.signed_header_1),
+            Some(header_a),
+            "conflicting header does not replace the first header"
+        );
+
+        // Slot 1, proposer 1
+        let block_c = get_block(1, 1);
+        assert_eq!(
+            cache.proposer_slashing(&get_header(&block_c)),
+            Ok(None),
+            "no slashing for a different proposer"
+        );
+
+        // Slot 2, proposer 0
+        let block_d = get_block(2, 0);
+        assert_eq!(
+            cache.proposer_slashing(&get_header(&block_d)),
+            Ok(None),
+            "no slashing for a different slot"
+        );
+    }
 }
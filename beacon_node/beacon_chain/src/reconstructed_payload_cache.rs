@@ -0,0 +1,52 @@
+//! Provides the `ReconstructedPayloadCache`, which holds the execution payloads most recently
+//! fetched from the execution engine to turn pruned blinded blocks back into full blocks.
+//!
+//! Clients tend to request the same old blocks repeatedly (e.g. several peers syncing the same
+//! range), so caching the payloads avoids repeating potentially slow execution engine requests.
+use crate::metrics;
+use lru::LruCache;
+use parking_lot::Mutex;
+use types::{EthSpec, ExecutionBlockHash, ExecutionPayload};
+
+/// The number of payloads to cache. Most payloads are a few hundred kilobytes.
+pub const RECONSTRUCTED_PAYLOAD_CACHE_SIZE: usize = 64;
+
+/// An LRU cache of execution payloads, keyed by their block hash.
+///
+/// Only payloads which have been checked against the payload header of their block should be
+/// inserted. As the block hash commits to the whole payload, the payloads may then be used for
+/// any block with the same block hash without checking them again.
+pub struct ReconstructedPayloadCache<E: EthSpec> {
+    payloads: Mutex<LruCache<ExecutionBlockHash, ExecutionPayload<E>>>,
+}
+
+impl<E: EthSpec> Default for ReconstructedPayloadCache<E> {
+    fn default() -> Self {
+        Self {
+            payloads: Mutex::new(LruCache::new(RECONSTRUCTED_PAYLOAD_CACHE_SIZE)),
+        }
+    }
+}
+
+impl<E: EthSpec> ReconstructedPayloadCache<E> {
+    /// Return a copy of the payload with `block_hash`, if it is cached.
+    pub fn get(&self, block_hash: &ExecutionBlockHash) -> Option<ExecutionPayload<E>> {
+        let payload = self.payloads.lock().get(block_hash).cloned();
+        if payload.is_some() {
+            metrics::inc_counter(&metrics::RECONSTRUCTED_PAYLOAD_CACHE_HITS);
+        } else {
+            metrics::inc_counter(&metrics::RECONSTRUCTED_PAYLOAD_CACHE_MISSES);
+        }
+        payload
+    }
+
+    /// Cache a payload which has been verified against its block's payload header.
+    pub fn insert(&self, payload: ExecutionPayload<E>) {
+        self.payloads.lock().put(payload.block_hash(), payload);
+    }
+
+    /// Return `true` if the payload with `block_hash` is cached, without updating its recency.
+    pub fn contains(&self, block_hash: &ExecutionBlockHash) -> bool {
+        self.payloads.lock().contains(block_hash)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::beacon_chain::BeaconChainTypes;
+use slog::{info, Logger};
+use std::sync::Arc;
+use store::config::OnDiskStoreConfig;
+use store::hdiff::HierarchyConfig;
+use store::metadata::CONFIG_KEY;
+use store::{Error, HotColdDB, KeyValueStoreOp, StoreItem};
+
+/// Store restore point states in the freezer as hierarchical diffs, as configured.
+///
+/// The states are rewritten one at a time, directly to the freezer database. An interrupted
+/// upgrade is completed the next time it is run.
+pub fn upgrade_to_v17<T: BeaconChainTypes>(
+    db: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
+    log: Logger,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    let hierarchy = db.get_config().hierarchy_config.clone();
+    info!(
+        log,
+        "Converting restore points to hierarchical diffs";
+        "hierarchy_exponents" => %hierarchy,
+        "info" => "this may take several minutes on an archive node",
+    );
+    db.rewrite_restore_point_states(&hierarchy)?;
+
+    Ok(vec![])
+}
+
+/// Store every restore point state in the freezer as a snapshot, which previous versions expect.
+///
+/// The on-disk config is also rewritten without the hierarchy, which previous versions can't
+/// decode.
+pub fn downgrade_from_v17<T: BeaconChainTypes>(
+    db: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
+    log: Logger,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    info!(
+        log,
+        "Converting hierarchical diffs to restore point snapshots";
+        "info" => "this may take several minutes on an archive node",
+    );
+    db.rewrite_restore_point_states(&HierarchyConfig::snapshots_only())?;
+
+    let disk_config = OnDiskStoreConfig {
+        slots_per_restore_point: db.get_config().slots_per_restore_point,
+        hierarchy_config: None,
+    };
+    Ok(vec![disk_config.as_kv_store_op(CONFIG_KEY)])
+}
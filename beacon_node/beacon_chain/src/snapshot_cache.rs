@@ -1,6 +1,6 @@
-use crate::BeaconSnapshot;
+use crate::{metrics, BeaconSnapshot};
 use itertools::process_results;
-use std::cmp;
+use ssz::Encode;
 use std::sync::Arc;
 use std::time::Duration;
 use types::{
@@ -8,8 +8,16 @@ use types::{
     SignedBeaconBlock, Slot,
 };
 
-/// The default size of the cache.
-pub const DEFAULT_SNAPSHOT_CACHE_SIZE: usize = 4;
+/// The default memory budget of the cache, in megabytes.
+///
+/// A mainnet state with its caches is estimated at around 700 MB, so this holds the head and a few
+/// recent snapshots with their advanced states, with some room left for replayed states.
+pub const DEFAULT_STATE_CACHE_MEMORY_MB: usize = 4_096;
+
+/// The approximate memory used per validator by the committee and pubkey caches of a state.
+const CACHE_BYTES_PER_VALIDATOR: usize = 128;
+/// The approximate memory used per validator by the tree hash cache of a state.
+const TREE_HASH_CACHE_BYTES_PER_VALIDATOR: usize = 384;
 
 /// The minimum block delay to clone the state in the cache instead of removing it.
 /// This helps keep block processing fast during re-orgs from late blocks.
@@ -45,12 +53,21 @@ impl<T: EthSpec> From<BeaconSnapshot<T>> for PreProcessingSnapshot<T> {
 
 impl<T: EthSpec> CacheItem<T> {
     pub fn new_without_pre_state(snapshot: BeaconSnapshot<T>) -> Self {
-        Self {
+        let mut item = Self {
             beacon_block: snapshot.beacon_block,
             beacon_block_root: snapshot.beacon_block_root,
             beacon_state: snapshot.beacon_state,
             pre_state: None,
-        }
+            size: 0,
+            last_used: 0,
+        };
+        item.update_size();
+        item
+    }
+
+    fn update_size(&mut self) {
+        self.size = estimate_state_size(&self.beacon_state)
+            + self.pre_state.as_ref().map_or(0, estimate_state_size);
     }
 
     fn clone_to_snapshot_with(&self, clone_config: CloneConfig) -> BeaconSnapshot<T> {
@@ -127,6 +144,10 @@ pub struct CacheItem<T: EthSpec> {
     /// This state is equivalent to `self.beacon_state` that has had `per_slot_processing` applied
     /// to it. This state assists in optimizing block processing.
     pre_state: Option<BeaconState<T>>,
+    /// The estimated memory used by `self.beacon_state` and `self.pre_state`.
+    size: usize,
+    /// The value of the cache's clock when this item was last used.
+    last_used: u64,
 }
 
 impl<T: EthSpec> Into<BeaconSnapshot<T>> for CacheItem<T> {
@@ -139,33 +160,90 @@ impl<T: EthSpec> Into<BeaconSnapshot<T>> for CacheItem<T> {
     }
 }
 
-/// Provides a cache of `BeaconSnapshot` that is intended primarily for block processing.
+/// A state loaded from the database by its root, which would otherwise need to be replayed.
 ///
-/// ## Cache Queuing
+/// The state is shared so that it can be cloned without holding the cache lock.
+struct StateItem<T: EthSpec> {
+    state_root: Hash256,
+    state: Arc<BeaconState<T>>,
+    /// Whether the state was loaded from the hot database, and so may be deleted by pruning.
+    hot: bool,
+    size: usize,
+    last_used: u64,
+}
+
+/// The reason an item was removed from the `SnapshotCache`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EvictionReason {
+    /// The cache exceeded its memory budget.
+    MemoryBudget,
+    /// The snapshot already has more than one descendant.
+    Descendant,
+    /// The snapshot is prior to the finalized checkpoint.
+    Finalized,
+}
+
+impl EvictionReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EvictionReason::MemoryBudget => "memory_budget",
+            EvictionReason::Descendant => "descendant",
+            EvictionReason::Finalized => "finalized",
+        }
+    }
+}
+
+fn record_eviction(reason: EvictionReason) {
+    metrics::inc_counter_vec(&metrics::STATE_CACHE_EVICTIONS, &[reason.as_str()]);
+}
+
+/// Estimate the memory used by `state`, including its caches.
 ///
-/// The cache has a non-standard queue mechanism (specifically, it is not LRU).
+/// The estimate is based on the SSZ size of the state, which is cheap to compute, plus an
+/// allowance for the caches which grow with the number of validators.
+pub fn estimate_state_size<T: EthSpec>(state: &BeaconState<T>) -> usize {
+    let validator_count = state.validators().len();
+    let mut size = state
+        .ssz_bytes_len()
+        .saturating_add(validator_count.saturating_mul(CACHE_BYTES_PER_VALIDATOR));
+    if state.tree_hash_cache().is_initialized() {
+        size = size
+            .saturating_add(validator_count.saturating_mul(TREE_HASH_CACHE_BYTES_PER_VALIDATOR));
+    }
+    size
+}
+
+/// Provides a cache of `BeaconSnapshot` that is intended primarily for block processing, along
+/// with the states most recently loaded from the database by their root.
+///
+/// ## Cache Queuing
 ///
-/// The cache has a max number of elements (`max_len`). Until `max_len` is achieved, all snapshots
-/// are simply added to the queue. Once `max_len` is achieved, adding a new snapshot will cause an
-/// existing snapshot to be ejected. The ejected snapshot will:
+/// The cache is bounded by the estimated memory used by its states (`max_bytes`), rather than
+/// the number of items. Once the budget is exceeded, the least recently used items are ejected
+/// until the cache fits again. The snapshot of the `head_block_root` is never ejected.
 ///
-/// - Never be the `head_block_root`.
-/// - Be the snapshot with the lowest `state.slot` (ties broken arbitrarily).
+/// Snapshots are also removed once they have more than one descendant, or once they are prior
+/// to the finalized checkpoint.
 pub struct SnapshotCache<T: EthSpec> {
-    max_len: usize,
+    max_bytes: usize,
     head_block_root: Hash256,
     snapshots: Vec<CacheItem<T>>,
+    states: Vec<StateItem<T>>,
+    /// Incremented on each access, used to determine the least recently used item.
+    clock: u64,
 }
 
 impl<T: EthSpec> SnapshotCache<T> {
     /// Instantiate a new cache which contains the `head` snapshot.
     ///
-    /// Setting `max_len = 0` is equivalent to setting `max_len = 1`.
-    pub fn new(max_len: usize, head: BeaconSnapshot<T>) -> Self {
+    /// The `head` is retained regardless of `max_bytes`.
+    pub fn new(max_bytes: usize, head: BeaconSnapshot<T>) -> Self {
         Self {
-            max_len: cmp::max(max_len, 1),
+            max_bytes,
             head_block_root: head.beacon_block_root,
             snapshots: vec![CacheItem::new_without_pre_state(head)],
+            states: vec![],
+            clock: 0,
         }
     }
 
@@ -179,8 +257,62 @@ impl<T: EthSpec> SnapshotCache<T> {
         self.snapshots.len()
     }
 
-    /// Insert a snapshot, potentially removing an existing snapshot if `self` is at capacity (see
-    /// struct-level documentation for more info).
+    /// The number of states loaded from the database contained in `self`.
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    /// The estimated memory used by all states contained in `self`.
+    pub fn memory_bytes(&self) -> usize {
+        self.snapshots
+            .iter()
+            .map(|snapshot| snapshot.size)
+            .chain(self.states.iter().map(|state| state.size))
+            .sum()
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Eject the least recently used items until `self` fits within its memory budget.
+    fn evict_to_budget(&mut self) {
+        while self.memory_bytes() > self.max_bytes {
+            let head_block_root = self.head_block_root;
+            let lru_snapshot = self
+                .snapshots
+                .iter()
+                .enumerate()
+                .filter(|(_, snapshot)| snapshot.beacon_block_root != head_block_root)
+                .min_by_key(|(_, snapshot)| snapshot.last_used)
+                .map(|(i, snapshot)| (i, snapshot.last_used));
+            let lru_state = self
+                .states
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, state)| state.last_used)
+                .map(|(i, state)| (i, state.last_used));
+
+            match (lru_snapshot, lru_state) {
+                (Some((i, snapshot_used)), Some((_, state_used))) if snapshot_used < state_used => {
+                    self.snapshots.remove(i);
+                }
+                (Some((i, _)), None) => {
+                    self.snapshots.remove(i);
+                }
+                (_, Some((i, _))) => {
+                    self.states.remove(i);
+                }
+                // Only the head remains.
+                (None, None) => break,
+            }
+            record_eviction(EvictionReason::MemoryBudget);
+        }
+    }
+
+    /// Insert a snapshot, potentially removing existing items if `self` is over its memory budget
+    /// (see struct-level documentation for more info).
     pub fn insert(
         &mut self,
         snapshot: BeaconSnapshot<T>,
@@ -188,12 +320,15 @@ impl<T: EthSpec> SnapshotCache<T> {
         spec: &ChainSpec,
     ) {
         let parent_root = snapshot.beacon_block.message().parent_root();
-        let item = CacheItem {
+        let mut item = CacheItem {
             beacon_block: snapshot.beacon_block.clone(),
             beacon_block_root: snapshot.beacon_block_root,
             beacon_state: snapshot.beacon_state,
             pre_state,
+            size: 0,
+            last_used: self.tick(),
         };
+        item.update_size();
 
         // Remove the grandparent of the block that was just inserted.
         //
@@ -210,31 +345,16 @@ impl<T: EthSpec> SnapshotCache<T> {
             let head_block_root = self.head_block_root;
             self.snapshots.retain(|snapshot| {
                 let root = snapshot.beacon_block_root;
-                root == head_block_root || root != grandparent_root
+                let retain = root == head_block_root || root != grandparent_root;
+                if !retain {
+                    record_eviction(EvictionReason::Descendant);
+                }
+                retain
             });
         }
 
-        if self.snapshots.len() < self.max_len {
-            self.snapshots.push(item);
-        } else {
-            let insert_at = self
-                .snapshots
-                .iter()
-                .enumerate()
-                .filter_map(|(i, snapshot)| {
-                    if snapshot.beacon_block_root != self.head_block_root {
-                        Some((i, snapshot.beacon_state.slot()))
-                    } else {
-                        None
-                    }
-                })
-                .min_by_key(|(_i, slot)| *slot)
-                .map(|(i, _slot)| i);
-
-            if let Some(i) = insert_at {
-                self.snapshots[i] = item;
-            }
-        }
+        self.snapshots.push(item);
+        self.evict_to_budget();
     }
 
     /// If available, returns a `CacheItem` that should be used for importing/processing a block.
@@ -252,17 +372,19 @@ impl<T: EthSpec> SnapshotCache<T> {
         block_delay: Option<Duration>,
         spec: &ChainSpec,
     ) -> Option<(PreProcessingSnapshot<T>, bool)> {
+        let last_used = self.tick();
         self.snapshots
             .iter()
             .position(|snapshot| snapshot.beacon_block_root == block_root)
             .map(|i| {
-                if let Some(cache) = self.snapshots.get(i) {
+                if let Some(cache) = self.snapshots.get_mut(i) {
                     // Avoid cloning the block during sync (when the `block_delay` is `None`).
                     if let Some(delay) = block_delay {
                         if delay >= minimum_block_delay_for_clone(spec.seconds_per_slot)
                             && delay <= Duration::from_secs(spec.seconds_per_slot) * 4
                             || block_slot > cache.beacon_block.slot() + 1
                         {
+                            cache.last_used = last_used;
                             return (cache.clone_as_pre_state(), true);
                         }
                     }
@@ -313,6 +435,56 @@ impl<T: EthSpec> SnapshotCache<T> {
             .map(|snapshot| snapshot.clone_to_snapshot_with(clone_config))
     }
 
+    /// If there is a snapshot or a state loaded from the database with `state_root`, return the
+    /// state with only its committee caches.
+    ///
+    /// States loaded from the database are shared rather than cloned, so that they can be cloned
+    /// once the cache lock has been released.
+    pub fn get_state_by_root(&mut self, state_root: Hash256) -> Option<Arc<BeaconState<T>>> {
+        let last_used = self.tick();
+        if let Some(snapshot) = self
+            .snapshots
+            .iter_mut()
+            .find(|snapshot| snapshot.beacon_block.state_root() == state_root)
+        {
+            snapshot.last_used = last_used;
+            return Some(Arc::new(
+                snapshot
+                    .beacon_state
+                    .clone_with(CloneConfig::committee_caches_only()),
+            ));
+        }
+
+        self.states
+            .iter_mut()
+            .find(|item| item.state_root == state_root)
+            .map(|item| {
+                item.last_used = last_used;
+                item.state.clone()
+            })
+    }
+
+    /// Insert a state which was loaded from the database, potentially removing existing items if
+    /// `self` is over its memory budget.
+    ///
+    /// States loaded from the hot database (`hot`) are removed when the cache is pruned, as they
+    /// may since have been deleted.
+    pub fn insert_state(&mut self, state_root: Hash256, state: BeaconState<T>, hot: bool) {
+        if self.states.iter().any(|item| item.state_root == state_root) {
+            return;
+        }
+
+        let item = StateItem {
+            state_root,
+            size: estimate_state_size(&state),
+            state: Arc::new(state),
+            hot,
+            last_used: self.tick(),
+        };
+        self.states.push(item);
+        self.evict_to_budget();
+    }
+
     pub fn get_for_state_advance(&mut self, block_root: Hash256) -> StateAdvance<T> {
         if let Some(snapshot) = self
             .snapshots
@@ -326,8 +498,11 @@ impl<T: EthSpec> SnapshotCache<T> {
                     .beacon_state
                     .clone_with(CloneConfig::committee_caches_only());
 
+                let state = std::mem::replace(&mut snapshot.beacon_state, cloned);
+                snapshot.update_size();
+
                 StateAdvance::State {
-                    state: Box::new(std::mem::replace(&mut snapshot.beacon_state, cloned)),
+                    state: Box::new(state),
                     state_root: snapshot.beacon_block.state_root(),
                     block_slot: snapshot.beacon_block.slot(),
                 }
@@ -338,25 +513,45 @@ impl<T: EthSpec> SnapshotCache<T> {
     }
 
     pub fn update_pre_state(&mut self, block_root: Hash256, state: BeaconState<T>) -> Option<()> {
-        self.snapshots
+        let result = self
+            .snapshots
             .iter_mut()
             .find(|snapshot| snapshot.beacon_block_root == block_root)
             .map(|snapshot| {
                 snapshot.pre_state = Some(state);
-            })
+                snapshot.update_size();
+            });
+        self.evict_to_budget();
+        result
     }
 
     /// Removes all snapshots from the queue that are less than or equal to the finalized epoch.
+    ///
+    /// States loaded from the database remain valid after finalization, so are left for the
+    /// memory budget to eject.
     pub fn prune(&mut self, finalized_epoch: Epoch) {
         self.snapshots.retain(|snapshot| {
-            snapshot.beacon_state.slot() > finalized_epoch.start_slot(T::slots_per_epoch())
-        })
+            let retain =
+                snapshot.beacon_state.slot() > finalized_epoch.start_slot(T::slots_per_epoch());
+            if !retain {
+                record_eviction(EvictionReason::Finalized);
+            }
+            retain
+        });
+        // Pruning deletes abandoned forks from the hot database, so its states can't be trusted
+        // to still exist.
+        self.states.retain(|item| {
+            if item.hot {
+                record_eviction(EvictionReason::Finalized);
+            }
+            !item.hot
+        });
     }
 
     /// Inform the cache that the head of the beacon chain has changed.
     ///
     /// The snapshot that matches this `head_block_root` will never be ejected from the cache
-    /// during `Self::insert`.
+    /// when it exceeds its memory budget.
     pub fn update_head(&mut self, head_block_root: Hash256) {
         self.head_block_root = head_block_root
     }
@@ -407,7 +602,9 @@ mod test {
     #[test]
     fn insert_get_prune_update() {
         let spec = MainnetEthSpec::default_spec();
-        let mut cache = SnapshotCache::new(CACHE_SIZE, get_snapshot(0));
+        // Each snapshot has the same size, so the budget fits exactly `CACHE_SIZE` of them.
+        let snapshot_size = estimate_state_size(&get_snapshot(0).beacon_state);
+        let mut cache = SnapshotCache::new(CACHE_SIZE * snapshot_size, get_snapshot(0));
 
         // Insert a bunch of entries in the cache. It should look like this:
         //
@@ -453,7 +650,7 @@ mod test {
                     &spec
                 )
                 .is_none(),
-            "the snapshot with the lowest slot should have been removed during the insert function"
+            "the least recently used snapshot should have been removed during the insert function"
         );
         assert!(cache
             .get_cloned(Hash256::from_low_u64_be(1), CloneConfig::none())
@@ -520,4 +717,60 @@ mod test {
             "get_state_for_block_processing should get the correct snapshot"
         );
     }
+
+    #[test]
+    fn states_share_memory_budget() {
+        let spec = MainnetEthSpec::default_spec();
+        let head = get_snapshot(0);
+        let state_size = estimate_state_size(&head.beacon_state);
+        let head_state = head.beacon_state.clone();
+        let mut cache = SnapshotCache::new(3 * state_size, head);
+
+        let state_root = |i| Hash256::from_low_u64_be(1000 + i);
+
+        // Fill the cache with the head and two states.
+        cache.insert_state(state_root(1), head_state.clone(), false);
+        cache.insert_state(state_root(2), head_state.clone(), true);
+        assert_eq!(cache.num_states(), 2);
+        assert_eq!(cache.memory_bytes(), 3 * state_size);
+        assert!(cache.get_state_by_root(state_root(3)).is_none());
+
+        // Using the first state makes the second state the least recently used.
+        assert!(cache.get_state_by_root(state_root(1)).is_some());
+        cache.insert_state(state_root(3), head_state.clone(), false);
+        assert_eq!(cache.num_states(), 2);
+        assert!(cache.get_state_by_root(state_root(1)).is_some());
+        assert!(cache.get_state_by_root(state_root(2)).is_none());
+        assert!(cache.get_state_by_root(state_root(3)).is_some());
+
+        // Snapshots and states are ejected together, least recently used first.
+        cache.insert(get_snapshot(1), None, &spec);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.num_states(), 1);
+        assert!(cache.get_state_by_root(state_root(1)).is_none());
+
+        // The head is never ejected, even if the budget is exceeded by the head alone.
+        let mut cache = SnapshotCache::new(0, get_snapshot(0));
+        cache.insert_state(state_root(1), head_state.clone(), false);
+        assert_eq!(cache.num_states(), 0);
+        assert_eq!(
+            cache.beacon_block_roots(),
+            vec![Hash256::from_low_u64_be(0)]
+        );
+    }
+
+    #[test]
+    fn prune_removes_hot_states() {
+        let head = get_snapshot(0);
+        let head_state = head.beacon_state.clone();
+        let mut cache = SnapshotCache::new(usize::MAX, head);
+
+        let state_root = |i| Hash256::from_low_u64_be(1000 + i);
+        cache.insert_state(state_root(1), head_state.clone(), false);
+        cache.insert_state(state_root(2), head_state, true);
+
+        cache.prune(Epoch::new(0));
+        assert!(cache.get_state_by_root(state_root(1)).is_some());
+        assert!(cache.get_state_by_root(state_root(2)).is_none());
+    }
 }
@@ -425,6 +425,16 @@ fn advance_head<T: BeaconChainTypes>(
         .maybe_cache_state(&state, head_root, &beacon_chain.spec)
         .map_err(BeaconChainError::from)?;
 
+    // Pre-compute the duties for the current and next epochs, if they are not already known.
+    if let Err(e) = beacon_chain.prime_duties_cache(&state, head_root) {
+        error!(
+            log,
+            "Failed to prime duties cache";
+            "head_root" => ?head_root,
+            "error" => ?e
+        );
+    }
+
     let final_slot = state.slot();
 
     // Insert the advanced state back into the snapshot cache.
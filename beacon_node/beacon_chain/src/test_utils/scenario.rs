@@ -0,0 +1,542 @@
+//! A declarative format for describing chains to be built by the `BeaconChainHarness`.
+//!
+//! A `Scenario` is a list of `Step`s which are applied in order to a fresh harness. Blocks are
+//! produced on named branches, which allows forks to be described without managing states and
+//! roots by hand. All branches share a single slot cursor which only ever moves forwards, so each
+//! block in a scenario occupies a distinct slot and the slot clock is never wound backwards.
+//!
+//! Scenarios can be built in Rust:
+//!
+//! ```ignore
+//! Scenario::new(32)
+//!     .extend(MAIN_BRANCH, 16, Participation::All)
+//!     .fork("attacker", MAIN_BRANCH)
+//!     .extend("attacker", 1, Participation::None)
+//!     .extend(MAIN_BRANCH, 2, Participation::All)
+//!     .assert_head(MAIN_BRANCH)
+//! ```
+//!
+//! Or read from YAML:
+//!
+//! ```yaml
+//! validator_count: 32
+//! steps:
+//!   - extend: { blocks: 16 }
+//!   - fork: { name: attacker, from: main }
+//!   - extend: { branch: attacker, blocks: 1, participation: none }
+//!   - extend: { blocks: 2 }
+//!   - assert_head: { branch: main }
+//! ```
+//!
+//! Runs are deterministic: the harness RNG (used for block graffiti) and the selection of
+//! attesters for `Participation::Percent` are both derived from the scenario `seed`.
+use super::{test_spec, BeaconChainHarness, EphemeralHarnessType};
+use execution_layer::{PayloadStatusV1, PayloadStatusV1Status};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
+use serde_derive::{Deserialize, Serialize};
+use slot_clock::SlotClock;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use types::{
+    BeaconState, Epoch, EthSpec, ExecPayload, ExecutionBlockHash, ForkName, Hash256, Slot,
+};
+
+/// The name of the branch which starts at genesis.
+pub const MAIN_BRANCH: &str = "main";
+
+fn main_branch() -> String {
+    MAIN_BRANCH.to_string()
+}
+
+/// A sequence of steps to be applied to a fresh `BeaconChainHarness`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub validator_count: usize,
+    /// Seeds all randomness used whilst running the scenario.
+    #[serde(default)]
+    pub seed: u64,
+    /// If set, all forks up to and including this one are activated at genesis. Otherwise the
+    /// spec from `test_spec` is used.
+    #[serde(default)]
+    pub genesis_fork: Option<ForkName>,
+    pub steps: Vec<Step>,
+}
+
+/// A single action or assertion within a `Scenario`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Produce `blocks` blocks on `branch` at consecutive slots, each attested to by
+    /// `participation`.
+    Extend {
+        #[serde(default = "main_branch")]
+        branch: String,
+        blocks: u64,
+        #[serde(default)]
+        participation: Participation,
+    },
+    /// Advance the slot cursor without producing any blocks.
+    SkipSlots { count: u64 },
+    /// Create a branch called `name` whose head is the current head of `from`.
+    Fork { name: String, from: String },
+    /// Produce a block on `branch` which is imported `delay_ms` milliseconds after the start of
+    /// its slot.
+    LateBlock {
+        #[serde(default = "main_branch")]
+        branch: String,
+        delay_ms: u64,
+        #[serde(default)]
+        participation: Participation,
+    },
+    /// Produce a block on `branch` which the mock execution engine declares invalid, and check
+    /// that it is rejected. The branch is left unchanged.
+    InvalidPayload {
+        #[serde(default = "main_branch")]
+        branch: String,
+    },
+    /// Extend `branch` with full participation until `epoch` is finalized, failing if this takes
+    /// more than `epoch + 4` epochs worth of slots.
+    FinalizeEpoch {
+        #[serde(default = "main_branch")]
+        branch: String,
+        epoch: Epoch,
+    },
+    /// Check that the head of `branch` is the canonical head.
+    AssertHead { branch: String },
+    /// Check the epoch of the justified checkpoint.
+    AssertJustifiedEpoch { epoch: Epoch },
+    /// Check the epoch of the finalized checkpoint.
+    AssertFinalizedEpoch { epoch: Epoch },
+}
+
+/// The validators which attest to each block produced in a step.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Participation {
+    #[default]
+    All,
+    None,
+    /// A random subset of validators of the given size (as a percentage of all validators),
+    /// chosen once per step.
+    Percent(u64),
+    Validators(Vec<usize>),
+}
+
+impl Scenario {
+    pub fn new(validator_count: usize) -> Self {
+        Self {
+            validator_count,
+            seed: 0,
+            genesis_fork: None,
+            steps: vec![],
+        }
+    }
+
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, String> {
+        serde_yaml::from_str(yaml).map_err(|e| format!("Unable to parse scenario: {:?}", e))
+    }
+
+    pub fn from_yaml_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let yaml = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Unable to read {:?}: {:?}", path.as_ref(), e))?;
+        Self::from_yaml_str(&yaml)
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn genesis_fork(mut self, fork: ForkName) -> Self {
+        self.genesis_fork = Some(fork);
+        self
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn extend(self, branch: &str, blocks: u64, participation: Participation) -> Self {
+        self.step(Step::Extend {
+            branch: branch.to_string(),
+            blocks,
+            participation,
+        })
+    }
+
+    pub fn skip_slots(self, count: u64) -> Self {
+        self.step(Step::SkipSlots { count })
+    }
+
+    pub fn fork(self, name: &str, from: &str) -> Self {
+        self.step(Step::Fork {
+            name: name.to_string(),
+            from: from.to_string(),
+        })
+    }
+
+    pub fn late_block(self, branch: &str, delay_ms: u64, participation: Participation) -> Self {
+        self.step(Step::LateBlock {
+            branch: branch.to_string(),
+            delay_ms,
+            participation,
+        })
+    }
+
+    pub fn invalid_payload(self, branch: &str) -> Self {
+        self.step(Step::InvalidPayload {
+            branch: branch.to_string(),
+        })
+    }
+
+    pub fn finalize_epoch(self, branch: &str, epoch: u64) -> Self {
+        self.step(Step::FinalizeEpoch {
+            branch: branch.to_string(),
+            epoch: Epoch::new(epoch),
+        })
+    }
+
+    pub fn assert_head(self, branch: &str) -> Self {
+        self.step(Step::AssertHead {
+            branch: branch.to_string(),
+        })
+    }
+
+    pub fn assert_justified_epoch(self, epoch: u64) -> Self {
+        self.step(Step::AssertJustifiedEpoch {
+            epoch: Epoch::new(epoch),
+        })
+    }
+
+    pub fn assert_finalized_epoch(self, epoch: u64) -> Self {
+        self.step(Step::AssertFinalizedEpoch {
+            epoch: Epoch::new(epoch),
+        })
+    }
+
+    /// Build a harness and apply each step to it in turn.
+    ///
+    /// Returns an error describing the first step which failed.
+    pub async fn run<E: EthSpec>(&self) -> Result<ScenarioRun<E>, String> {
+        let spec = match self.genesis_fork {
+            Some(fork) => fork.make_genesis_spec(test_spec::<E>()),
+            None => test_spec::<E>(),
+        };
+        let harness = BeaconChainHarness::builder(E::default())
+            .spec(spec)
+            .deterministic_keypairs(self.validator_count)
+            .fresh_ephemeral_store()
+            .mock_execution_layer()
+            .build();
+        *harness.rng.lock() = StdRng::seed_from_u64(self.seed);
+
+        // Blocks at and after the Bellatrix fork should contain real execution payloads.
+        if harness.spec.bellatrix_fork_epoch.is_some() {
+            harness
+                .execution_block_generator()
+                .move_to_terminal_block()
+                .map_err(|e| format!("Unable to move to terminal block: {:?}", e))?;
+        }
+
+        let (state, state_root) = harness.get_current_state_and_root();
+        let mut run = ScenarioRun {
+            next_slot: state.slot() + 1,
+            branches: HashMap::from([(
+                main_branch(),
+                Branch {
+                    head_block_root: harness.head_block_root(),
+                    state,
+                    state_root,
+                },
+            )]),
+            rng: StdRng::seed_from_u64(self.seed),
+            harness,
+        };
+
+        for (i, step) in self.steps.iter().enumerate() {
+            run.apply(step)
+                .await
+                .map_err(|e| format!("step {} ({:?}) failed: {}", i, step, e))?;
+        }
+
+        Ok(run)
+    }
+}
+
+/// The tip of a chain of blocks produced by a scenario.
+#[derive(Clone)]
+pub struct Branch<E: EthSpec> {
+    pub head_block_root: Hash256,
+    /// The post-state of the head block.
+    pub state: BeaconState<E>,
+    pub state_root: Hash256,
+}
+
+/// A harness to which a `Scenario` has been applied.
+pub struct ScenarioRun<E: EthSpec> {
+    pub harness: BeaconChainHarness<EphemeralHarnessType<E>>,
+    pub branches: HashMap<String, Branch<E>>,
+    /// The slot at which the next block will be produced.
+    pub next_slot: Slot,
+    rng: StdRng,
+}
+
+impl<E: EthSpec> ScenarioRun<E> {
+    /// Returns the root of the head block of `branch`.
+    ///
+    /// Panics if the branch does not exist.
+    pub fn branch_head(&self, branch: &str) -> Hash256 {
+        self.branches
+            .get(branch)
+            .unwrap_or_else(|| panic!("unknown branch {}", branch))
+            .head_block_root
+    }
+
+    fn branch(&self, branch: &str) -> Result<&Branch<E>, String> {
+        self.branches
+            .get(branch)
+            .ok_or_else(|| format!("unknown branch {}", branch))
+    }
+
+    fn attesters(&mut self, participation: &Participation) -> Result<Vec<usize>, String> {
+        let validator_count = self.harness.validator_keypairs.len();
+        match participation {
+            Participation::All => Ok(self.harness.get_all_validators()),
+            Participation::None => Ok(vec![]),
+            Participation::Percent(percent) if *percent <= 100 => {
+                let amount = validator_count * *percent as usize / 100;
+                let mut attesters = sample(&mut self.rng, validator_count, amount).into_vec();
+                attesters.sort_unstable();
+                Ok(attesters)
+            }
+            Participation::Percent(percent) => {
+                Err(format!("participation of {}% is invalid", percent))
+            }
+            Participation::Validators(validators) => {
+                match validators.iter().find(|i| **i >= validator_count) {
+                    Some(i) => Err(format!("validator {} does not exist", i)),
+                    None => Ok(validators.clone()),
+                }
+            }
+        }
+    }
+
+    /// Take the next slot from the cursor and set the slot clock to it.
+    fn take_slot(&mut self) -> Slot {
+        let slot = self.next_slot;
+        self.next_slot += 1;
+        self.harness.set_current_slot(slot);
+        slot
+    }
+
+    /// Produce a block on `branch` at the next slot and import it `delay` after the start of the
+    /// slot, then attest to it with `attesters`.
+    async fn add_block(
+        &mut self,
+        branch_name: &str,
+        delay: Option<Duration>,
+        attesters: &[usize],
+    ) -> Result<(), String> {
+        let state = self.branch(branch_name)?.state.clone();
+        let slot = self.take_slot();
+
+        let (block, state) = self.harness.make_block(state, slot).await;
+        if let Some(delay) = delay {
+            let slot_start = self
+                .harness
+                .chain
+                .slot_clock
+                .start_of(slot)
+                .ok_or("unable to read slot clock")?;
+            self.harness
+                .chain
+                .slot_clock
+                .set_current_time(slot_start + delay);
+        }
+        let block_hash = self
+            .harness
+            .process_block_result(block.clone())
+            .await
+            .map_err(|e| format!("block at slot {} was rejected: {:?}", slot, e))?;
+
+        let state_root = block.state_root();
+        self.harness
+            .attest_block(&state, state_root, block_hash, &block, attesters);
+
+        self.branches.insert(
+            branch_name.to_string(),
+            Branch {
+                head_block_root: block_hash.into(),
+                state,
+                state_root,
+            },
+        );
+        Ok(())
+    }
+
+    async fn apply(&mut self, step: &Step) -> Result<(), String> {
+        match step {
+            Step::Extend {
+                branch,
+                blocks,
+                participation,
+            } => {
+                let attesters = self.attesters(participation)?;
+                for _ in 0..*blocks {
+                    self.add_block(branch, None, &attesters).await?;
+                }
+            }
+            Step::SkipSlots { count } => {
+                self.next_slot += *count;
+            }
+            Step::Fork { name, from } => {
+                if self.branches.contains_key(name) {
+                    return Err(format!("branch {} already exists", name));
+                }
+                let branch = self.branch(from)?.clone();
+                self.branches.insert(name.clone(), branch);
+            }
+            Step::LateBlock {
+                branch,
+                delay_ms,
+                participation,
+            } => {
+                let attesters = self.attesters(participation)?;
+                self.add_block(branch, Some(Duration::from_millis(*delay_ms)), &attesters)
+                    .await?;
+            }
+            Step::InvalidPayload { branch } => {
+                let state = self.branch(branch)?.state.clone();
+                let slot = self.take_slot();
+                let (block, _) = self.harness.make_block(state, slot).await;
+
+                let payload = block
+                    .message()
+                    .body()
+                    .execution_payload()
+                    .ok()
+                    .filter(|payload| payload.block_hash() != ExecutionBlockHash::zero())
+                    .ok_or("block has no execution payload")?;
+                self.harness
+                    .mock_execution_layer
+                    .as_ref()
+                    .ok_or("harness has no mock execution layer")?
+                    .server
+                    .set_new_payload_status(
+                        payload.block_hash(),
+                        PayloadStatusV1 {
+                            status: PayloadStatusV1Status::Invalid,
+                            latest_valid_hash: Some(payload.parent_hash()),
+                            validation_error: Some("invalid by scenario".into()),
+                        },
+                    );
+
+                if let Ok(block_root) = self.harness.process_block_result(block).await {
+                    return Err(format!(
+                        "block {:?} with an invalid payload was imported",
+                        block_root
+                    ));
+                }
+            }
+            Step::FinalizeEpoch { branch, epoch } => {
+                let attesters = self.harness.get_all_validators();
+                let max_slot = (*epoch + 4).start_slot(E::slots_per_epoch());
+                while self.harness.finalized_checkpoint().epoch < *epoch {
+                    if self.next_slot > max_slot {
+                        return Err(format!(
+                            "epoch {} was not finalized by slot {}",
+                            epoch, max_slot
+                        ));
+                    }
+                    self.add_block(branch, None, &attesters).await?;
+                }
+            }
+            Step::AssertHead { branch } => {
+                let expected = self.branch(branch)?.head_block_root;
+                self.harness.chain.recompute_head_at_current_slot().await;
+                let head = self.harness.head_block_root();
+                if head != expected {
+                    return Err(format!(
+                        "head is {:?}, expected head of {} ({:?})",
+                        head, branch, expected
+                    ));
+                }
+            }
+            Step::AssertJustifiedEpoch { epoch } => {
+                let justified = self.harness.justified_checkpoint().epoch;
+                if justified != *epoch {
+                    return Err(format!(
+                        "justified epoch is {}, expected {}",
+                        justified, epoch
+                    ));
+                }
+            }
+            Step::AssertFinalizedEpoch { epoch } => {
+                let finalized = self.harness.finalized_checkpoint().epoch;
+                if finalized != *epoch {
+                    return Err(format!(
+                        "finalized epoch is {}, expected {}",
+                        finalized, epoch
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_yaml_scenario() {
+        let yaml = r#"
+validator_count: 32
+seed: 7
+genesis_fork: bellatrix
+steps:
+  - extend: { blocks: 16 }
+  - fork: { name: attacker, from: main }
+  - extend: { branch: attacker, blocks: 1, participation: none }
+  - skip_slots: { count: 2 }
+  - late_block: { delay_ms: 5000, participation: { percent: 50 } }
+  - invalid_payload: { branch: attacker }
+  - extend: { blocks: 2, participation: { validators: [0, 1, 2] } }
+  - finalize_epoch: { epoch: 2 }
+  - assert_head: { branch: main }
+  - assert_justified_epoch: { epoch: 3 }
+  - assert_finalized_epoch: { epoch: 2 }
+"#;
+        let expected = Scenario::new(32)
+            .seed(7)
+            .genesis_fork(ForkName::Merge)
+            .extend(MAIN_BRANCH, 16, Participation::All)
+            .fork("attacker", MAIN_BRANCH)
+            .extend("attacker", 1, Participation::None)
+            .skip_slots(2)
+            .late_block(MAIN_BRANCH, 5000, Participation::Percent(50))
+            .invalid_payload("attacker")
+            .extend(MAIN_BRANCH, 2, Participation::Validators(vec![0, 1, 2]))
+            .finalize_epoch(MAIN_BRANCH, 2)
+            .assert_head(MAIN_BRANCH)
+            .assert_justified_epoch(3)
+            .assert_finalized_epoch(2);
+
+        assert_eq!(Scenario::from_yaml_str(yaml), Ok(expected));
+    }
+
+    #[test]
+    fn reject_unknown_fields() {
+        assert!(Scenario::from_yaml_str("validator_count: 1\nsteps: []\nslots: 3").is_err());
+        assert!(Scenario::from_yaml_str(
+            "validator_count: 1\nsteps:\n  - extend: { blocks: 1, slots: 3 }"
+        )
+        .is_err());
+    }
+}
@@ -1,11 +1,21 @@
 //! Provides detailed logging and metrics for a set of registered validators.
 //!
+//! Per-epoch summaries of each validator are kept in memory for `HISTORIC_EPOCHS` and persisted
+//! to the database as `PersistedEpochSummary` for longer-term analysis.
+//!
+//! Alerts are raised when a validator breaks one of the configured `AlertRules`, to be published
+//! as server-sent events (and optionally webhooks) by the caller.
+//!
 //! This component should not affect consensus.
 
 use crate::metrics;
-use parking_lot::RwLock;
-use slog::{crit, debug, info, Logger};
+use eth2::lighthouse::{ValidatorMonitorAlert, ValidatorMonitorAlertKind};
+use parking_lot::{Mutex, RwLock};
+use serde_derive::{Deserialize, Serialize};
+use slog::{crit, debug, info, warn, Logger};
 use slot_clock::SlotClock;
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
 use state_processing::per_epoch_processing::{
     errors::EpochProcessingError, EpochProcessingSummary,
 };
@@ -13,9 +23,12 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::io;
 use std::marker::PhantomData;
-use std::str::Utf8Error;
+use std::str::{FromStr, Utf8Error};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use store::AbstractExecPayload;
+use store::{
+    get_key_for_col, AbstractExecPayload, DBColumn, Error as StoreError, HotColdDB, ItemStore,
+    KeyValueStore, KeyValueStoreOp, StoreItem,
+};
 use types::{
     AttesterSlashing, BeaconBlockRef, BeaconState, ChainSpec, Epoch, EthSpec, Hash256,
     IndexedAttestation, ProposerSlashing, PublicKeyBytes, SignedAggregateAndProof,
@@ -37,6 +50,14 @@ pub const HISTORIC_EPOCHS: usize = 10;
 /// Prometheus cardinality and log volume.
 pub const DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD: usize = 64;
 
+/// The number of consecutive missed attestations which raise an alert, if the
+/// `missed-attestations` rule is enabled without a value.
+pub const DEFAULT_MISSED_ATTESTATIONS_ALERT_THRESHOLD: u64 = 3;
+
+/// The sync committee participation percentage below which an alert is raised, if the
+/// `sync-participation` rule is enabled without a value.
+pub const DEFAULT_SYNC_PARTICIPATION_ALERT_PERCENT: u64 = 80;
+
 #[derive(Debug)]
 pub enum Error {
     InvalidPubkey(String),
@@ -44,6 +65,79 @@ pub enum Error {
     InvalidUtf8(Utf8Error),
 }
 
+/// The rules which determine when the validator monitor raises alerts for monitored validators.
+///
+/// All rules are disabled by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRules {
+    /// Raise an alert when a validator fails to propose a block in its slot.
+    pub missed_proposal: bool,
+    /// Raise an alert when a validator fails to get an attestation on-chain for this many epochs
+    /// in a row.
+    pub consecutive_missed_attestations: Option<u64>,
+    /// Raise an alert when a sync committee member's messages are included in fewer than this
+    /// percentage of the slots of an epoch.
+    pub min_sync_participation_percent: Option<u64>,
+    /// Raise an alert when a validator is slashed.
+    pub slashed: bool,
+    /// Raise an alert when a validator's balance decreases from one epoch to the next.
+    pub balance_decrease: bool,
+}
+
+impl AlertRules {
+    /// Returns `true` if any of the rules is enabled.
+    pub fn is_enabled(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+impl FromStr for AlertRules {
+    type Err = String;
+
+    /// Parses a comma-separated list of rules, e.g.
+    /// `missed-proposal,missed-attestations=2,sync-participation=75,slashed,balance-decrease`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Self::default();
+        for rule in s.split(',').map(str::trim).filter(|rule| !rule.is_empty()) {
+            let (name, value) = match rule.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (rule, None),
+            };
+            let parse_value = |default: u64| {
+                value
+                    .map(|value| {
+                        value
+                            .parse::<u64>()
+                            .map_err(|e| format!("invalid value for {}: {:?}", name, e))
+                    })
+                    .unwrap_or(Ok(default))
+            };
+
+            match (name, value) {
+                ("missed-proposal", None) => rules.missed_proposal = true,
+                ("missed-attestations", _) => {
+                    let threshold = parse_value(DEFAULT_MISSED_ATTESTATIONS_ALERT_THRESHOLD)?;
+                    if threshold == 0 {
+                        return Err("missed-attestations must be greater than 0".to_string());
+                    }
+                    rules.consecutive_missed_attestations = Some(threshold);
+                }
+                ("sync-participation", _) => {
+                    let percent = parse_value(DEFAULT_SYNC_PARTICIPATION_ALERT_PERCENT)?;
+                    if percent > 100 {
+                        return Err("sync-participation must be a percentage".to_string());
+                    }
+                    rules.min_sync_participation_percent = Some(percent);
+                }
+                ("slashed", None) => rules.slashed = true,
+                ("balance-decrease", None) => rules.balance_decrease = true,
+                _ => return Err(format!("unknown validator monitor alert rule: {}", rule)),
+            }
+        }
+        Ok(rules)
+    }
+}
+
 /// Contains data pertaining to one validator for one epoch.
 #[derive(Default)]
 pub struct EpochSummary {
@@ -60,6 +154,14 @@ pub struct EpochSummary {
     pub attestation_block_inclusions: usize,
     /// The minimum observed inclusion distance for an attestation for this epoch..
     pub attestation_min_block_inclusion_distance: Option<Slot>,
+    /// Whether an attestation for this epoch matched on-chain (i.e., was "timely" in Altair).
+    ///
+    /// These values are only known once the epoch has been processed.
+    pub attestation_hit: Option<bool>,
+    /// Whether an attestation for this epoch matched the head on-chain.
+    pub attestation_head_hit: Option<bool>,
+    /// Whether an attestation for this epoch matched the target on-chain.
+    pub attestation_target_hit: Option<bool>,
     /*
      * Blocks with a slot in the current epoch.
      */
@@ -168,6 +270,24 @@ impl EpochSummary {
         );
     }
 
+    pub fn register_attestation_outcome(
+        &mut self,
+        hit: bool,
+        head_hit: bool,
+        target_hit: bool,
+        inclusion_distance: Option<u64>,
+    ) {
+        self.attestation_hit = Some(hit);
+        self.attestation_head_hit = Some(head_hit);
+        self.attestation_target_hit = Some(target_hit);
+        if let Some(inclusion_distance) = inclusion_distance {
+            Self::update_if_lt(
+                &mut self.attestation_min_block_inclusion_distance,
+                Slot::new(inclusion_distance),
+            );
+        }
+    }
+
     pub fn register_sync_signature_block_inclusions(&mut self) {
         self.sync_signature_block_inclusions += 1;
     }
@@ -191,6 +311,138 @@ impl EpochSummary {
 
 type SummaryMap = HashMap<Epoch, EpochSummary>;
 
+/// The contents of an `EpochSummary` as stored in the database, keyed by validator index and
+/// epoch (see `PersistedEpochSummary::db_key`).
+///
+/// All delays are in milliseconds.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct PersistedEpochSummary {
+    pub validator_index: u64,
+    pub epoch: Epoch,
+    pub attestations: u64,
+    pub attestation_min_delay: Option<u64>,
+    pub attestation_aggregate_inclusions: u64,
+    pub attestation_block_inclusions: u64,
+    pub attestation_min_block_inclusion_distance: Option<u64>,
+    pub attestation_hit: Option<bool>,
+    pub attestation_head_hit: Option<bool>,
+    pub attestation_target_hit: Option<bool>,
+    pub blocks: u64,
+    pub block_min_delay: Option<u64>,
+    pub aggregates: u64,
+    pub aggregate_min_delay: Option<u64>,
+    pub sync_committee_messages: u64,
+    pub sync_committee_message_min_delay: Option<u64>,
+    pub sync_signature_block_inclusions: u64,
+    pub sync_signature_contribution_inclusions: u64,
+    pub sync_contributions: u64,
+    pub sync_contribution_min_delay: Option<u64>,
+    pub exits: u64,
+    pub proposer_slashings: u64,
+    pub attester_slashings: u64,
+    pub total_balance: Option<u64>,
+}
+
+impl PersistedEpochSummary {
+    fn new(validator_index: u64, epoch: Epoch, summary: &EpochSummary) -> Self {
+        let millis = |delay: Option<Duration>| delay.map(|delay| delay.as_millis() as u64);
+        Self {
+            validator_index,
+            epoch,
+            attestations: summary.attestations as u64,
+            attestation_min_delay: millis(summary.attestation_min_delay),
+            attestation_aggregate_inclusions: summary.attestation_aggregate_inclusions as u64,
+            attestation_block_inclusions: summary.attestation_block_inclusions as u64,
+            attestation_min_block_inclusion_distance: summary
+                .attestation_min_block_inclusion_distance
+                .map(Slot::as_u64),
+            attestation_hit: summary.attestation_hit,
+            attestation_head_hit: summary.attestation_head_hit,
+            attestation_target_hit: summary.attestation_target_hit,
+            blocks: summary.blocks as u64,
+            block_min_delay: millis(summary.block_min_delay),
+            aggregates: summary.aggregates as u64,
+            aggregate_min_delay: millis(summary.aggregate_min_delay),
+            sync_committee_messages: summary.sync_committee_messages as u64,
+            sync_committee_message_min_delay: millis(summary.sync_committee_message_min_delay),
+            sync_signature_block_inclusions: summary.sync_signature_block_inclusions as u64,
+            sync_signature_contribution_inclusions: summary.sync_signature_contribution_inclusions
+                as u64,
+            sync_contributions: summary.sync_contributions as u64,
+            sync_contribution_min_delay: millis(summary.sync_contribution_min_delay),
+            exits: summary.exits as u64,
+            proposer_slashings: summary.proposer_slashings as u64,
+            attester_slashings: summary.attester_slashings as u64,
+            total_balance: summary.total_balance,
+        }
+    }
+
+    /// The database key of the summary for `validator_index` at `epoch`.
+    ///
+    /// The index and epoch are encoded big-endian so that the summaries of a validator are stored
+    /// contiguously and in epoch order.
+    pub fn db_key(validator_index: u64, epoch: Epoch) -> Hash256 {
+        let mut key = [0; 32];
+        key[0..8].copy_from_slice(&validator_index.to_be_bytes());
+        key[8..16].copy_from_slice(&epoch.as_u64().to_be_bytes());
+        Hash256::from(key)
+    }
+
+    /// The epoch of the summary stored under `key`, as created by `Self::db_key`.
+    pub fn epoch_from_db_key(key: &Hash256) -> Epoch {
+        let mut epoch = [0; 8];
+        epoch.copy_from_slice(&key.as_bytes()[8..16]);
+        Epoch::new(u64::from_be_bytes(epoch))
+    }
+}
+
+impl StoreItem for PersistedEpochSummary {
+    fn db_column() -> DBColumn {
+        DBColumn::ValidatorMonitorSummary
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// Deletes every persisted summary which falls outside of `history_epochs` as of `epoch`.
+///
+/// This visits every key in the column, so it is only run once at startup to clear the summaries
+/// of validators which are no longer monitored, or of epochs missed while the node was offline.
+/// Only the keys are read.
+pub fn prune_persisted_summaries<E, Hot, Cold>(
+    store: &HotColdDB<E, Hot, Cold>,
+    epoch: Epoch,
+    history_epochs: u64,
+) -> Result<(), StoreError>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    let column = PersistedEpochSummary::db_column();
+    let mut batch = vec![];
+    for result in store.hot_db.iter_column_keys(column) {
+        let key = result?;
+        if PersistedEpochSummary::epoch_from_db_key(&key) + history_epochs <= epoch {
+            batch.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                column.into(),
+                key.as_bytes(),
+            )));
+        }
+    }
+
+    if batch.is_empty() {
+        return Ok(());
+    }
+    store.hot_db.do_atomically(batch)
+}
+
 #[derive(Default)]
 pub struct ValidatorMetrics {
     pub attestation_hits: u64,
@@ -227,6 +479,21 @@ impl ValidatorMetrics {
     }
 }
 
+/// Tracks the state required to evaluate the `AlertRules` for a single validator.
+#[derive(Default)]
+struct AlertState {
+    /// The number of consecutive epochs without an attestation from the validator on-chain.
+    consecutive_missed_attestations: u64,
+    /// The latest epoch for which the on-chain attestation outcome has been counted.
+    last_attestation_epoch: Option<Epoch>,
+    /// The latest epoch for which sync committee participation has been checked.
+    last_sync_participation_epoch: Option<Epoch>,
+    /// The balance of the validator in the first head state seen for an epoch.
+    epoch_start_balance: Option<(Epoch, u64)>,
+    /// Set once an alert has been raised for the validator being slashed.
+    slashed: bool,
+}
+
 /// A validator that is being monitored by the `ValidatorMonitor`.
 pub struct MonitoredValidator {
     /// A human-readable identifier for the validator.
@@ -237,6 +504,8 @@ pub struct MonitoredValidator {
     pub summaries: RwLock<SummaryMap>,
     /// Validator metrics to be exposed over the HTTP API.
     pub metrics: RwLock<ValidatorMetrics>,
+    /// State used to decide when alerts should be raised.
+    alert_state: RwLock<AlertState>,
 }
 
 impl MonitoredValidator {
@@ -248,6 +517,7 @@ impl MonitoredValidator {
             index,
             summaries: <_>::default(),
             metrics: <_>::default(),
+            alert_state: <_>::default(),
         }
     }
 
@@ -316,6 +586,28 @@ impl MonitoredValidator {
             summary_opt.and_then(|summary| summary.total_balance)
         })
     }
+
+    /// Returns the summary for `epoch` in the form in which it is persisted, if the validator
+    /// index is known and the summary hasn't been pruned from memory.
+    pub fn get_persisted_summary(&self, epoch: Epoch) -> Option<PersistedEpochSummary> {
+        let index = self.index?;
+        self.get_from_epoch_summary(epoch, |summary_opt| {
+            summary_opt.map(|summary| PersistedEpochSummary::new(index, epoch, summary))
+        })
+    }
+
+    /// Returns all summaries held in memory in the form in which they are persisted.
+    fn get_persisted_summaries(&self) -> Vec<PersistedEpochSummary> {
+        match self.index {
+            Some(index) => self
+                .summaries
+                .read()
+                .iter()
+                .map(|(epoch, summary)| PersistedEpochSummary::new(index, *epoch, summary))
+                .collect(),
+            None => vec![],
+        }
+    }
 }
 
 /// Holds a collection of `MonitoredValidator` and is notified about a variety of events on the P2P
@@ -338,6 +630,17 @@ pub struct ValidatorMonitor<T> {
     /// large validator counts causing infeasibly high cardinailty for
     /// Prometheus and high log volumes.
     individual_tracking_threshold: usize,
+    /// The rules according to which alerts are raised for monitored validators.
+    alert_rules: AlertRules,
+    /// Alerts which have been raised but not yet collected by `Self::drain_alerts`.
+    pending_alerts: Mutex<Vec<ValidatorMonitorAlert>>,
+    /// The block proposers for recent epochs, used to detect missed proposals.
+    proposers: HashMap<Epoch, Vec<usize>>,
+    /// The latest slot which has been checked for a missed proposal.
+    last_proposal_check_slot: Option<Slot>,
+    /// The position of the withdrawal sweep in the first head state seen for an epoch, used to
+    /// tell withdrawals apart from losses.
+    withdrawal_sweep: Option<(Epoch, u64)>,
     log: Logger,
     _phantom: PhantomData<T>,
 }
@@ -354,6 +657,11 @@ impl<T: EthSpec> ValidatorMonitor<T> {
             indices: <_>::default(),
             auto_register,
             individual_tracking_threshold,
+            alert_rules: AlertRules::default(),
+            pending_alerts: <_>::default(),
+            proposers: <_>::default(),
+            last_proposal_check_slot: None,
+            withdrawal_sweep: None,
             log,
             _phantom: PhantomData,
         };
@@ -370,6 +678,178 @@ impl<T: EthSpec> ValidatorMonitor<T> {
         self.validators.len() <= self.individual_tracking_threshold
     }
 
+    pub fn alert_rules(&self) -> &AlertRules {
+        &self.alert_rules
+    }
+
+    pub fn set_alert_rules(&mut self, alert_rules: AlertRules) {
+        self.alert_rules = alert_rules;
+    }
+
+    /// Queues an alert to be collected by `Self::drain_alerts`.
+    fn raise_alert(&self, validator_index: u64, epoch: Epoch, kind: ValidatorMonitorAlertKind) {
+        warn!(
+            self.log,
+            "Validator monitor alert";
+            "alert" => ?kind,
+            "epoch" => epoch,
+            "validator" => validator_index,
+        );
+        self.pending_alerts.lock().push(ValidatorMonitorAlert {
+            validator_index,
+            epoch,
+            kind,
+        });
+    }
+
+    /// Removes and returns all alerts raised since the previous call.
+    pub fn drain_alerts(&self) -> Vec<ValidatorMonitorAlert> {
+        std::mem::take(&mut *self.pending_alerts.lock())
+    }
+
+    /// Checks the canonical head `state` for missed proposals, slashings and balance decreases of
+    /// monitored validators, raising alerts as per `self.alert_rules`.
+    pub fn process_head_state(&mut self, state: &BeaconState<T>, spec: &ChainSpec) {
+        if self.alert_rules.missed_proposal {
+            self.check_missed_proposals(state, spec);
+        }
+
+        if !self.alert_rules.slashed && !self.alert_rules.balance_decrease {
+            return;
+        }
+
+        let current_epoch = state.current_epoch();
+        let sweep_end = state.next_withdrawal_validator_index().ok();
+        let sweep_start = self.withdrawal_sweep;
+        if sweep_start.map_or(true, |(epoch, _)| epoch < current_epoch) {
+            self.withdrawal_sweep = sweep_end.map(|index| (current_epoch, index));
+        }
+
+        for monitored_validator in self.validators.values() {
+            let index = match monitored_validator.index {
+                Some(index) => index,
+                None => continue,
+            };
+            let (validator, balance) = match (
+                state.validators().get(index as usize),
+                state.balances().get(index as usize),
+            ) {
+                (Some(validator), Some(balance)) => (validator, *balance),
+                _ => continue,
+            };
+
+            let mut alert_state = monitored_validator.alert_state.write();
+
+            if self.alert_rules.slashed && validator.slashed && !alert_state.slashed {
+                alert_state.slashed = true;
+                self.raise_alert(index, current_epoch, ValidatorMonitorAlertKind::Slashed);
+            }
+
+            if self.alert_rules.balance_decrease {
+                match alert_state.epoch_start_balance {
+                    Some((epoch, _)) if epoch >= current_epoch => continue,
+                    Some((epoch, previous_balance)) if balance < previous_balance => {
+                        // Withdrawals sweep either the balance in excess of the maximum effective
+                        // balance or, once withdrawable, the entire balance. Neither is a loss,
+                        // but only if the sweep has reached the validator since `epoch`.
+                        let swept = may_have_been_swept::<T>(
+                            index,
+                            sweep_start
+                                .filter(|(sweep_epoch, _)| *sweep_epoch == epoch)
+                                .map(|(_, sweep_index)| sweep_index),
+                            sweep_end,
+                            current_epoch - epoch,
+                            state.validators().len() as u64,
+                            spec,
+                        );
+                        let withdrawn = validator.has_eth1_withdrawal_credential(spec)
+                            && swept
+                            && (validator.withdrawable_epoch <= current_epoch
+                                || (previous_balance > spec.max_effective_balance
+                                    && balance >= spec.max_effective_balance));
+                        if !withdrawn {
+                            self.raise_alert(
+                                index,
+                                current_epoch,
+                                ValidatorMonitorAlertKind::BalanceDecrease {
+                                    previous_balance,
+                                    balance,
+                                },
+                            );
+                        }
+                    }
+                    _ => (),
+                }
+                alert_state.epoch_start_balance = Some((current_epoch, balance));
+            }
+        }
+    }
+
+    /// Raises an alert for each monitored validator which was due to propose at a slot that has
+    /// been skipped on the chain of `state`, since the previous call.
+    fn check_missed_proposals(&mut self, state: &BeaconState<T>, spec: &ChainSpec) {
+        let slots_per_epoch = T::slots_per_epoch();
+        let current_epoch = state.current_epoch();
+
+        // Proposers can only be computed for the current epoch of `state`, so keep the previous
+        // epoch around for slots which are skipped across an epoch boundary.
+        if !self.proposers.contains_key(&current_epoch) {
+            match state.get_beacon_proposer_indices(spec) {
+                Ok(proposers) => {
+                    self.proposers.insert(current_epoch, proposers);
+                }
+                Err(e) => {
+                    debug!(
+                        self.log,
+                        "Unable to compute proposers for validator monitor";
+                        "epoch" => current_epoch,
+                        "error" => ?e,
+                    );
+                    return;
+                }
+            }
+            self.proposers
+                .retain(|epoch, _| *epoch + 1 >= current_epoch);
+        }
+
+        // The slot of a head state always has a block, so only prior slots need checking. Slots
+        // prior to the first head state are not checked.
+        let end_slot = state.slot();
+        let start_slot = self
+            .last_proposal_check_slot
+            .map_or(end_slot, |slot| slot + 1);
+
+        for slot in (start_slot.as_u64()..end_slot.as_u64()).map(Slot::new) {
+            let validator_index = match self
+                .proposers
+                .get(&slot.epoch(slots_per_epoch))
+                .and_then(|proposers| proposers.get(slot.as_usize() % slots_per_epoch as usize))
+            {
+                Some(index) => *index as u64,
+                None => continue,
+            };
+
+            if self.get_validator(validator_index).is_none() || slot == 0 {
+                continue;
+            }
+
+            let skipped = match (state.get_block_root(slot), state.get_block_root(slot - 1)) {
+                (Ok(block_root), Ok(prev_block_root)) => block_root == prev_block_root,
+                _ => false,
+            };
+            if skipped {
+                self.raise_alert(
+                    validator_index,
+                    slot.epoch(slots_per_epoch),
+                    ValidatorMonitorAlertKind::MissedProposal { slot },
+                );
+            }
+        }
+
+        self.last_proposal_check_slot =
+            Some(std::cmp::max(start_slot, end_slot).saturating_sub(1_u64));
+    }
+
     /// Add some validators to `self` for additional monitoring.
     fn add_validator_pubkey(&mut self, pubkey: PublicKeyBytes) {
         let index_opt = self
@@ -551,6 +1031,52 @@ impl<T: EthSpec> ValidatorMonitor<T> {
                     continue;
                 }
 
+                // Get the minimum value among the validator monitor observed inclusion distance
+                // and the epoch summary inclusion distance.
+                // The inclusion data is not retained in the epoch summary post Altair.
+                let min_inclusion_distance = min_opt(
+                    monitored_validator.min_inclusion_distance(&prev_epoch),
+                    summary
+                        .previous_epoch_inclusion_info(i)
+                        .map(|info| info.delay),
+                );
+
+                // Record the on-chain outcome in the epoch summary so that it is persisted.
+                monitored_validator.with_epoch_summary(prev_epoch, |epoch_summary| {
+                    epoch_summary.register_attestation_outcome(
+                        previous_epoch_matched_any,
+                        previous_epoch_matched_head,
+                        previous_epoch_matched_target,
+                        min_inclusion_distance,
+                    )
+                });
+
+                // The statuses of an epoch may be processed more than once, so each epoch only
+                // counts towards the streak of missed attestations once.
+                if let Some(threshold) = self.alert_rules.consecutive_missed_attestations {
+                    let mut alert_state = monitored_validator.alert_state.write();
+                    if alert_state
+                        .last_attestation_epoch
+                        .map_or(true, |epoch| epoch < prev_epoch)
+                    {
+                        alert_state.last_attestation_epoch = Some(prev_epoch);
+                        if previous_epoch_matched_any {
+                            alert_state.consecutive_missed_attestations = 0;
+                        } else {
+                            alert_state.consecutive_missed_attestations += 1;
+                            if alert_state.consecutive_missed_attestations == threshold {
+                                self.raise_alert(
+                                    i as u64,
+                                    prev_epoch,
+                                    ValidatorMonitorAlertKind::MissedAttestations {
+                                        consecutive: threshold,
+                                    },
+                                );
+                            }
+                        }
+                    }
+                }
+
                 // Store some metrics directly to be re-exposed on the HTTP API.
                 let mut validator_metrics = monitored_validator.metrics.write();
                 if previous_epoch_matched_any {
@@ -663,15 +1189,6 @@ impl<T: EthSpec> ValidatorMonitor<T> {
                     }
                 }
 
-                // Get the minimum value among the validator monitor observed inclusion distance
-                // and the epoch summary inclusion distance.
-                // The inclusion data is not retained in the epoch summary post Altair.
-                let min_inclusion_distance = min_opt(
-                    monitored_validator.min_inclusion_distance(&prev_epoch),
-                    summary
-                        .previous_epoch_inclusion_info(i)
-                        .map(|info| info.delay),
-                );
                 if let Some(inclusion_delay) = min_inclusion_distance {
                     if inclusion_delay > spec.min_attestation_inclusion_delay {
                         suboptimal_inclusion.push(id);
@@ -726,6 +1243,32 @@ impl<T: EthSpec> ValidatorMonitor<T> {
                                 "validator" => id,
                             );
                         }
+
+                        // Without a summary, e.g. after a restart, participation is unknown.
+                        if let (Some(min_percent), Some(summary)) = (
+                            self.alert_rules.min_sync_participation_percent,
+                            epoch_summary.get(&current_epoch),
+                        ) {
+                            let included = summary.sync_signature_block_inclusions as u64;
+                            let expected = T::slots_per_epoch();
+                            let mut alert_state = monitored_validator.alert_state.write();
+                            if alert_state
+                                .last_sync_participation_epoch
+                                .map_or(true, |epoch| epoch < current_epoch)
+                            {
+                                alert_state.last_sync_participation_epoch = Some(current_epoch);
+                                if included * 100 < expected * min_percent {
+                                    self.raise_alert(
+                                        i as u64,
+                                        current_epoch,
+                                        ValidatorMonitorAlertKind::LowSyncParticipation {
+                                            included,
+                                            expected,
+                                        },
+                                    );
+                                }
+                            }
+                        }
                     } else if self.individual_tracking() {
                         metrics::set_int_gauge(
                             &metrics::VALIDATOR_MONITOR_VALIDATOR_IN_CURRENT_SYNC_COMMITTEE,
@@ -808,6 +1351,31 @@ impl<T: EthSpec> ValidatorMonitor<T> {
         self.validators.values().map(|val| val.id.clone()).collect()
     }
 
+    /// Returns the summaries of all monitored validators for `epoch` which are still held in
+    /// memory, in the form in which they are persisted.
+    pub fn get_persisted_summaries_at_epoch(&self, epoch: Epoch) -> Vec<PersistedEpochSummary> {
+        self.validators
+            .values()
+            .filter_map(|validator| validator.get_persisted_summary(epoch))
+            .collect()
+    }
+
+    /// Returns all summaries held in memory, in the form in which they are persisted.
+    pub fn get_all_persisted_summaries(&self) -> Vec<PersistedEpochSummary> {
+        self.validators
+            .values()
+            .flat_map(MonitoredValidator::get_persisted_summaries)
+            .collect()
+    }
+
+    /// Returns the indices of all monitored validators with a known index.
+    pub fn get_monitored_validator_indices(&self) -> Vec<u64> {
+        self.validators
+            .values()
+            .filter_map(|validator| validator.index)
+            .collect()
+    }
+
     pub fn get_monitored_validator(&self, index: u64) -> Option<&MonitoredValidator> {
         if let Some(pubkey) = self.indices.get(&index) {
             self.validators.get(pubkey)
@@ -1787,3 +2355,147 @@ fn min_opt<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
         _ => None,
     }
 }
+
+/// Returns `true` if the withdrawal sweep may have passed the validator with `index` whilst
+/// advancing from `sweep_start` to `sweep_end` over `epochs` epochs.
+fn may_have_been_swept<T: EthSpec>(
+    index: u64,
+    sweep_start: Option<u64>,
+    sweep_end: Option<u64>,
+    epochs: Epoch,
+    validator_count: u64,
+    spec: &ChainSpec,
+) -> bool {
+    let (start, end) = match (sweep_start, sweep_end) {
+        (Some(start), Some(end)) => (start, end),
+        // The start is unknown, so assume the worst.
+        (None, Some(_)) => return true,
+        // Withdrawals are not enabled.
+        (_, None) => return false,
+    };
+
+    // The sweep may have wrapped around all validators.
+    let max_swept = epochs
+        .as_u64()
+        .saturating_mul(spec.max_validators_per_withdrawals_sweep)
+        .saturating_mul(T::slots_per_epoch());
+    if max_swept >= validator_count {
+        return true;
+    }
+
+    if start <= end {
+        (start..end).contains(&index)
+    } else {
+        index >= start || index < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MainnetEthSpec;
+
+    #[test]
+    fn persisted_epoch_summary_roundtrip() {
+        let mut summary = EpochSummary::default();
+        summary.register_unaggregated_attestation(Duration::from_millis(1_500));
+        summary.register_unaggregated_attestation(Duration::from_millis(800));
+        summary.register_block(Duration::from_millis(2_000));
+        summary.register_attestation_outcome(true, false, true, Some(2));
+        summary.register_attestation_outcome(true, false, true, Some(1));
+
+        let persisted = PersistedEpochSummary::new(42, Epoch::new(7), &summary);
+        assert_eq!(persisted.attestations, 2);
+        assert_eq!(persisted.attestation_min_delay, Some(800));
+        assert_eq!(persisted.attestation_min_block_inclusion_distance, Some(1));
+        assert_eq!(persisted.attestation_hit, Some(true));
+        assert_eq!(persisted.attestation_head_hit, Some(false));
+        assert_eq!(persisted.blocks, 1);
+        assert_eq!(persisted.block_min_delay, Some(2_000));
+        assert_eq!(persisted.sync_committee_message_min_delay, None);
+        assert_eq!(persisted.total_balance, None);
+
+        let decoded = PersistedEpochSummary::from_store_bytes(&persisted.as_store_bytes()).unwrap();
+        assert_eq!(decoded, persisted);
+    }
+
+    #[test]
+    fn parse_alert_rules() {
+        assert_eq!(AlertRules::from_str(""), Ok(AlertRules::default()));
+        assert!(!AlertRules::default().is_enabled());
+
+        let rules = AlertRules::from_str(
+            "missed-proposal, missed-attestations=2,sync-participation,slashed,balance-decrease",
+        )
+        .unwrap();
+        assert!(rules.is_enabled());
+        assert_eq!(
+            rules,
+            AlertRules {
+                missed_proposal: true,
+                consecutive_missed_attestations: Some(2),
+                min_sync_participation_percent: Some(DEFAULT_SYNC_PARTICIPATION_ALERT_PERCENT),
+                slashed: true,
+                balance_decrease: true,
+            }
+        );
+
+        assert!(AlertRules::from_str("missed-attestations=0").is_err());
+        assert!(AlertRules::from_str("sync-participation=101").is_err());
+        assert!(AlertRules::from_str("missed-proposal=1").is_err());
+        assert!(AlertRules::from_str("unknown").is_err());
+    }
+
+    #[test]
+    fn db_keys_are_ordered_by_index_then_epoch() {
+        let keys = [
+            PersistedEpochSummary::db_key(1, Epoch::new(0)),
+            PersistedEpochSummary::db_key(1, Epoch::new(1)),
+            PersistedEpochSummary::db_key(1, Epoch::new(256)),
+            PersistedEpochSummary::db_key(2, Epoch::new(0)),
+        ];
+        for pair in keys.windows(2) {
+            assert!(pair[0].as_bytes() < pair[1].as_bytes());
+        }
+    }
+
+    #[test]
+    fn withdrawal_sweep() {
+        let spec = MainnetEthSpec::default_spec();
+        let sweep_per_epoch =
+            spec.max_validators_per_withdrawals_sweep * MainnetEthSpec::slots_per_epoch();
+        let validator_count = sweep_per_epoch * 4;
+        let swept = |index, start, end, epochs| {
+            may_have_been_swept::<MainnetEthSpec>(
+                index,
+                start,
+                end,
+                Epoch::new(epochs),
+                validator_count,
+                &spec,
+            )
+        };
+
+        // Only the validators between the start and end of the sweep are swept.
+        assert!(swept(10, Some(5), Some(20), 1));
+        assert!(!swept(20, Some(5), Some(20), 1));
+        assert!(!swept(4, Some(5), Some(20), 1));
+        // The sweep may wrap around.
+        assert!(swept(2, Some(validator_count - 5), Some(5), 1));
+        assert!(!swept(10, Some(validator_count - 5), Some(5), 1));
+        // All validators may have been swept over enough epochs.
+        assert!(swept(4, Some(5), Some(20), 4));
+        // Without withdrawals nothing is swept, and an unknown start may sweep anything.
+        assert!(!swept(10, Some(5), None, 1));
+        assert!(swept(10, None, Some(20), 1));
+    }
+
+    #[test]
+    fn epoch_from_db_key() {
+        let key = PersistedEpochSummary::db_key(7, Epoch::new(1575));
+        assert_eq!(
+            PersistedEpochSummary::epoch_from_db_key(&key),
+            Epoch::new(1575)
+        );
+    }
+}
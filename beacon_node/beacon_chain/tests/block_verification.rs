@@ -943,6 +943,7 @@ async fn block_gossip_verification() {
             BlockError::RepeatProposal {
                 proposer,
                 slot,
+                ..
             }
             if proposer == other_proposer && slot == block.message().slot()
         ),
@@ -976,6 +977,7 @@ async fn block_gossip_verification() {
             BlockError::RepeatProposal {
                 proposer,
                 slot,
+                ..
             }
             if proposer == block.message().proposer_index() && slot == block.message().slot()
         ),
@@ -1036,6 +1038,65 @@ async fn verify_block_for_gossip_slashing_detection() {
     slasher_dir.close().unwrap();
 }
 
+#[tokio::test]
+async fn verify_block_for_gossip_proposer_equivocation() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let state = harness.get_current_state();
+    let (block1, _) = harness.make_block(state.clone(), Slot::new(1)).await;
+    let (block2, _) = harness.make_block(state, Slot::new(1)).await;
+    let proposer_index = block1.message().proposer_index();
+
+    harness
+        .chain
+        .verify_block_for_gossip(Arc::new(block1.clone()))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        harness
+            .chain
+            .import_proposer_equivocation(&block1.signed_block_header())
+            .unwrap(),
+        None,
+        "the first block is not an equivocation"
+    );
+
+    assert!(matches!(
+        unwrap_err(
+            harness
+                .chain
+                .verify_block_for_gossip(Arc::new(block2.clone()))
+                .await
+        ),
+        BlockError::RepeatProposal { proposer, .. } if proposer == proposer_index
+    ));
+
+    let proposer_slashing = harness
+        .chain
+        .import_proposer_equivocation(&block2.signed_block_header())
+        .unwrap()
+        .expect("should form a slashing from the conflicting blocks");
+    assert_eq!(
+        proposer_slashing.signed_header_1,
+        block1.signed_block_header()
+    );
+    assert_eq!(
+        proposer_slashing.signed_header_2,
+        block2.signed_block_header()
+    );
+    assert_eq!(harness.chain.op_pool.num_proposer_slashings(), 1);
+
+    assert_eq!(
+        harness
+            .chain
+            .import_proposer_equivocation(&block2.signed_block_header())
+            .unwrap(),
+        None,
+        "the slashing should only be returned once"
+    );
+}
+
 #[tokio::test]
 async fn verify_block_for_gossip_doppelganger_detection() {
     let harness = get_harness(VALIDATOR_COUNT);
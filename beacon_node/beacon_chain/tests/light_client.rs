@@ -0,0 +1,224 @@
+#![cfg(not(debug_assertions))]
+
+use beacon_chain::light_client_server_cache::{LightClientProducerEvent, LightClientServerCache};
+use beacon_chain::test_utils::{
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, EphemeralHarnessType,
+    SyncCommitteeStrategy,
+};
+use beacon_chain::{ChainConfig, WhenSlotSkipped};
+use lazy_static::lazy_static;
+use merkle_proof::verify_merkle_proof;
+use store::{DBColumn, KeyValueStore};
+use tree_hash::TreeHash;
+use types::light_client_update::{
+    FINALIZED_ROOT_INDEX, FINALIZED_ROOT_PROOF_LEN, NEXT_SYNC_COMMITTEE_INDEX,
+    NEXT_SYNC_COMMITTEE_PROOF_LEN,
+};
+use types::{BitVector, Epoch, EthSpec, Hash256, Keypair, MinimalEthSpec, Slot};
+
+type E = MinimalEthSpec;
+
+const VALIDATOR_COUNT: usize = 32;
+
+lazy_static! {
+    /// A cached set of keys.
+    static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
+}
+
+fn get_harness() -> BeaconChainHarness<EphemeralHarnessType<E>> {
+    let mut spec = E::default_spec();
+    spec.altair_fork_epoch = Some(Epoch::new(0));
+    let chain_config = ChainConfig {
+        enable_light_client_server: true,
+        ..ChainConfig::default()
+    };
+    let harness = BeaconChainHarness::builder(E::default())
+        .spec(spec)
+        .chain_config(chain_config)
+        .keypairs(KEYPAIRS.to_vec())
+        .fresh_ephemeral_store()
+        .mock_execution_layer()
+        .build();
+
+    harness.advance_slot();
+
+    harness
+}
+
+/// Build a finalized chain whose head is not at the start of an epoch, with full sync committee
+/// participation.
+async fn extend_finalized_chain(harness: &BeaconChainHarness<EphemeralHarnessType<E>>) {
+    let num_blocks = 5 * E::slots_per_epoch() as usize + 3;
+    harness
+        .extend_chain_with_sync(
+            num_blocks,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+            SyncCommitteeStrategy::AllValidators,
+        )
+        .await;
+    assert!(harness.finalized_checkpoint().epoch > 0);
+}
+
+/// The event sent for the block at `slot` when it is imported.
+fn producer_event(
+    harness: &BeaconChainHarness<EphemeralHarnessType<E>>,
+    slot: Slot,
+) -> LightClientProducerEvent<E> {
+    let block_root = harness
+        .chain
+        .block_root_at_slot(slot, WhenSlotSkipped::None)
+        .unwrap()
+        .unwrap();
+    let block = harness
+        .chain
+        .get_blinded_block(&block_root)
+        .unwrap()
+        .unwrap();
+    LightClientProducerEvent {
+        attested_block_root: block.parent_root(),
+        signature_slot: block.slot(),
+        sync_aggregate: block.message().body().sync_aggregate().unwrap().clone(),
+    }
+}
+
+#[tokio::test]
+async fn produces_updates_from_imported_blocks() {
+    let harness = get_harness();
+    extend_finalized_chain(&harness).await;
+
+    // Produce the updates for each imported block in order, as the light client server task does.
+    let head_slot = harness.chain.head_snapshot().beacon_block.slot();
+    for slot in 1..=head_slot.as_u64() {
+        harness
+            .chain
+            .recompute_and_cache_light_client_updates(producer_event(&harness, Slot::new(slot)))
+            .unwrap();
+    }
+
+    let head = harness.chain.head_snapshot();
+    let parent = harness
+        .chain
+        .get_blinded_block(&head.beacon_block.parent_root())
+        .unwrap()
+        .unwrap();
+    let parent_state = harness
+        .chain
+        .get_state(&parent.state_root(), Some(parent.slot()))
+        .unwrap()
+        .unwrap();
+
+    // The optimistic update attests to the parent of the head, signed by the head.
+    let optimistic_update = harness
+        .chain
+        .light_client_server_cache
+        .get_latest_optimistic_update()
+        .unwrap();
+    assert_eq!(
+        optimistic_update.attested_header,
+        parent.message().block_header()
+    );
+    assert_eq!(optimistic_update.signature_slot, head.beacon_block.slot());
+
+    // The finality update proves the finalized checkpoint of the parent's post-state.
+    let finality_update = harness
+        .chain
+        .light_client_server_cache
+        .get_latest_finality_update()
+        .unwrap();
+    let finalized_root = parent_state.finalized_checkpoint().root;
+    assert_eq!(
+        finality_update.finalized_header.canonical_root(),
+        finalized_root
+    );
+    assert!(verify_merkle_proof(
+        finalized_root,
+        &finality_update.finality_branch,
+        FINALIZED_ROOT_PROOF_LEN,
+        FINALIZED_ROOT_INDEX % 2usize.pow(FINALIZED_ROOT_PROOF_LEN as u32),
+        finality_update.attested_header.state_root,
+    ));
+
+    // The best update for the period is persisted, with a valid next sync committee branch.
+    let period = head_slot
+        .epoch(E::slots_per_epoch())
+        .sync_committee_period(&harness.spec)
+        .unwrap();
+    assert!(harness
+        .chain
+        .store
+        .hot_db
+        .key_exists(
+            DBColumn::LightClientUpdate.into(),
+            Hash256::from_low_u64_be(period).as_bytes()
+        )
+        .unwrap());
+    let best_update = harness
+        .chain
+        .store
+        .get_light_client_update(period)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        best_update.sync_aggregate.num_set_bits(),
+        E::sync_committee_size()
+    );
+    assert!(!best_update.finalized_header.canonical_root().is_zero());
+    assert!(verify_merkle_proof(
+        best_update.next_sync_committee.tree_hash_root(),
+        &best_update.next_sync_committee_branch,
+        NEXT_SYNC_COMMITTEE_PROOF_LEN,
+        NEXT_SYNC_COMMITTEE_INDEX % 2usize.pow(NEXT_SYNC_COMMITTEE_PROOF_LEN as u32),
+        best_update.attested_header.state_root,
+    ));
+}
+
+#[tokio::test]
+async fn finality_update_replaced_at_same_slot_only_by_supermajority() {
+    let harness = get_harness();
+    extend_finalized_chain(&harness).await;
+
+    // Use a fresh cache so that no updates have been produced yet.
+    let cache = LightClientServerCache::<EphemeralHarnessType<E>>::default();
+    // The head and its parent attest to blocks with the same finalized checkpoint.
+    let head_slot = harness.chain.head_snapshot().beacon_block.slot();
+    let head_event = producer_event(&harness, head_slot);
+    let parent_event = producer_event(&harness, head_slot - 1);
+    let minority_event = |event: &LightClientProducerEvent<E>| {
+        let mut sync_aggregate = event.sync_aggregate.clone();
+        sync_aggregate.sync_committee_bits = BitVector::new();
+        sync_aggregate.sync_committee_bits.set(0, true).unwrap();
+        LightClientProducerEvent {
+            attested_block_root: event.attested_block_root,
+            signature_slot: event.signature_slot,
+            sync_aggregate,
+        }
+    };
+    let recompute = |event: &LightClientProducerEvent<E>| {
+        cache
+            .recompute_and_cache_updates(&harness.chain.store, event, &harness.spec)
+            .unwrap()
+    };
+
+    // The first update is always accepted, even without a supermajority.
+    let produced = recompute(&minority_event(&parent_event));
+    assert!(produced.finality_update.is_some());
+    assert!(produced.optimistic_update.is_some());
+
+    // An update for the same finalized slot with a supermajority replaces it.
+    let produced = recompute(&parent_event);
+    let finality_update = produced.finality_update.unwrap();
+    assert_eq!(finality_update.sync_aggregate, parent_event.sync_aggregate);
+    assert!(produced.optimistic_update.is_none());
+
+    // Once the latest update has a supermajority it is only replaced by a later finalized slot.
+    let produced = recompute(&head_event);
+    assert!(produced.finality_update.is_none());
+    assert!(produced.optimistic_update.is_some());
+    let produced = recompute(&minority_event(&head_event));
+    assert!(produced.finality_update.is_none());
+    assert_eq!(
+        cache.get_latest_finality_update().unwrap().sync_aggregate,
+        parent_event.sync_aggregate
+    );
+}
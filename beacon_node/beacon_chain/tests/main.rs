@@ -2,10 +2,13 @@ mod attestation_production;
 mod attestation_verification;
 mod block_verification;
 mod capella;
+mod light_client;
 mod merge;
 mod op_verification;
 mod payload_invalidation;
 mod rewards;
+mod scenario;
 mod store_tests;
 mod sync_committee_verification;
 mod tests;
+mod validator_monitor;
@@ -9,6 +9,7 @@ use beacon_chain::{
     test_utils::{AttestationStrategy, BlockStrategy, RelativeSyncCommittee},
     types::{Epoch, EthSpec, Keypair, MinimalEthSpec},
 };
+use eth2::types::ValidatorId;
 use lazy_static::lazy_static;
 
 pub const VALIDATOR_COUNT: usize = 64;
@@ -119,3 +120,77 @@ async fn test_sync_committee_rewards() {
         mismatches.join(",")
     );
 }
+
+#[tokio::test]
+async fn test_attestation_rewards_range() {
+    let num_block_produced = MinimalEthSpec::slots_per_epoch() * 6;
+    let harness = get_harness::<MinimalEthSpec>();
+
+    harness
+        .extend_chain(
+            num_block_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let chain = &harness.chain;
+    let log = harness.logger().clone();
+    let validators = vec![ValidatorId::Index(0), ValidatorId::Index(5)];
+    let (start_epoch, end_epoch) = (Epoch::new(1), Epoch::new(3));
+
+    let mut range_rewards = vec![];
+    chain
+        .compute_attestation_rewards_range(
+            start_epoch,
+            end_epoch,
+            validators.clone(),
+            |epoch, rewards| {
+                range_rewards.push((epoch, rewards));
+                true
+            },
+        )
+        .unwrap();
+
+    let expected_epochs = (start_epoch.as_u64()..=end_epoch.as_u64())
+        .map(Epoch::new)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        range_rewards
+            .iter()
+            .map(|(epoch, _)| *epoch)
+            .collect::<Vec<_>>(),
+        expected_epochs
+    );
+
+    // Each epoch of the range matches the rewards for that epoch alone, which are served from
+    // the cache once computed.
+    for (epoch, rewards) in &range_rewards {
+        let single = chain
+            .compute_attestation_rewards(*epoch, validators.clone(), log.clone())
+            .unwrap();
+        assert_eq!(&single, rewards);
+        assert_eq!(single.total_rewards.len(), validators.len());
+
+        let cached = chain
+            .compute_attestation_rewards(*epoch, validators.clone(), log.clone())
+            .unwrap();
+        assert_eq!(cached, single);
+    }
+
+    // An empty list of validators returns the rewards of all validators.
+    let all = chain
+        .compute_attestation_rewards(start_epoch, vec![], log)
+        .unwrap();
+    assert_eq!(all.total_rewards.len(), VALIDATOR_COUNT);
+
+    // Computation stops as soon as the callback returns `false`.
+    let mut emitted = 0;
+    chain
+        .compute_attestation_rewards_range(start_epoch, end_epoch, validators, |_, _| {
+            emitted += 1;
+            false
+        })
+        .unwrap();
+    assert_eq!(emitted, 1);
+}
@@ -0,0 +1,116 @@
+#![cfg(not(debug_assertions))]
+
+use beacon_chain::test_utils::scenario::{Participation, Scenario, MAIN_BRANCH};
+use types::{ForkName, Hash256, MinimalEthSpec};
+
+type E = MinimalEthSpec;
+
+const VALIDATOR_COUNT: usize = 32;
+
+#[tokio::test]
+async fn minority_fork_is_not_head() {
+    let honest = Participation::Validators((8..VALIDATOR_COUNT).collect());
+    let attackers = Participation::Validators((0..8).collect());
+
+    let run = Scenario::new(VALIDATOR_COUNT)
+        .extend(MAIN_BRANCH, 8, Participation::All)
+        .fork("attacker", MAIN_BRANCH)
+        .extend("attacker", 1, attackers)
+        .extend(MAIN_BRANCH, 2, honest)
+        .assert_head(MAIN_BRANCH)
+        .run::<E>()
+        .await
+        .unwrap();
+
+    assert_ne!(run.branch_head("attacker"), run.branch_head(MAIN_BRANCH));
+}
+
+#[tokio::test]
+async fn failed_assertion_names_step() {
+    let err = Scenario::new(VALIDATOR_COUNT)
+        .extend(MAIN_BRANCH, 2, Participation::All)
+        .fork("other", MAIN_BRANCH)
+        .extend(MAIN_BRANCH, 1, Participation::All)
+        .assert_head("other")
+        .run::<E>()
+        .await
+        .err()
+        .unwrap();
+
+    assert!(err.starts_with("step 3 (AssertHead"), "{}", err);
+}
+
+#[tokio::test]
+async fn finalization_requires_participation() {
+    Scenario::new(VALIDATOR_COUNT)
+        .finalize_epoch(MAIN_BRANCH, 2)
+        .assert_finalized_epoch(2)
+        .extend(MAIN_BRANCH, 32, Participation::Percent(50))
+        .assert_finalized_epoch(2)
+        .run::<E>()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn invalid_payload_is_rejected() {
+    let run = Scenario::new(VALIDATOR_COUNT)
+        .genesis_fork(ForkName::Merge)
+        .extend(MAIN_BRANCH, 2, Participation::All)
+        .invalid_payload(MAIN_BRANCH)
+        .extend(MAIN_BRANCH, 1, Participation::All)
+        .assert_head(MAIN_BRANCH)
+        .run::<E>()
+        .await
+        .unwrap();
+
+    // The slot of the invalid block was skipped.
+    assert_eq!(run.harness.head_slot(), 4);
+}
+
+#[tokio::test]
+async fn late_block_is_not_boosted() {
+    let run = Scenario::new(VALIDATOR_COUNT)
+        .extend(MAIN_BRANCH, 1, Participation::All)
+        .late_block(MAIN_BRANCH, 4_000, Participation::None)
+        .assert_head(MAIN_BRANCH)
+        .run::<E>()
+        .await
+        .unwrap();
+
+    let fork_choice = run.harness.chain.canonical_head.fork_choice_read_lock();
+    assert_eq!(fork_choice.proposer_boost_root(), Hash256::zero());
+}
+
+#[tokio::test]
+async fn yaml_scenario_is_deterministic() {
+    let yaml = r#"
+validator_count: 32
+seed: 42
+steps:
+  - extend: { blocks: 4, participation: { percent: 75 } }
+  - fork: { name: attacker, from: main }
+  - skip_slots: { count: 1 }
+  - extend: { branch: attacker, blocks: 1, participation: none }
+  - extend: { blocks: 2, participation: { percent: 75 } }
+  - assert_head: { branch: main }
+"#;
+    let scenario = Scenario::from_yaml_str(yaml).unwrap();
+
+    let first = scenario.run::<E>().await.unwrap();
+    let second = scenario.run::<E>().await.unwrap();
+    assert_eq!(
+        first.branch_head(MAIN_BRANCH),
+        second.branch_head(MAIN_BRANCH)
+    );
+    assert_eq!(
+        first.branch_head("attacker"),
+        second.branch_head("attacker")
+    );
+
+    let reseeded = scenario.clone().seed(43).run::<E>().await.unwrap();
+    assert_ne!(
+        first.branch_head(MAIN_BRANCH),
+        reseeded.branch_head(MAIN_BRANCH)
+    );
+}
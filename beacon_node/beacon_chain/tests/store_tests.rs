@@ -2,6 +2,7 @@
 
 use beacon_chain::attestation_verification::Error as AttnError;
 use beacon_chain::builder::BeaconChainBuilder;
+use beacon_chain::era::{export_eras, import_eras, Era};
 use beacon_chain::schema_change::migrate_schema;
 use beacon_chain::test_utils::{
     test_spec, AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
@@ -21,12 +22,17 @@ use state_processing::BlockReplayer;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::TryInto;
+use std::fs;
 use std::sync::Arc;
 use std::time::Duration;
+use store::config::StoreConfigError;
+use store::hdiff::HierarchyConfig;
 use store::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION};
+use store::verify::Inconsistency;
 use store::{
     iter::{BlockRootsIterator, StateRootsIterator},
-    HotColdDB, LevelDB, StoreConfig,
+    BeaconNodeBackend, DBColumn, DatabaseBackend, Error as StoreError, HotColdDB, KeyValueStore,
+    StoreConfig, StoreOp,
 };
 use tempfile::{tempdir, TempDir};
 use tokio::time::sleep;
@@ -46,17 +52,39 @@ lazy_static! {
 type E = MinimalEthSpec;
 type TestHarness = BeaconChainHarness<DiskHarnessType<E>>;
 
-fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+/// The database backend under test, taken from the `BEACON_NODE_BACKEND` environment variable.
+fn test_backend() -> DatabaseBackend {
+    std::env::var("BEACON_NODE_BACKEND")
+        .map(|backend| {
+            backend
+                .parse()
+                .expect("BEACON_NODE_BACKEND should be valid")
+        })
+        .unwrap_or_default()
+}
+
+fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
     get_store_with_spec(db_path, test_spec::<E>())
 }
 
 fn get_store_with_spec(
     db_path: &TempDir,
     spec: ChainSpec,
-) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
+    let config = StoreConfig {
+        backend: test_backend(),
+        ..StoreConfig::default()
+    };
+    get_store_with_config(db_path, config, spec)
+}
+
+fn get_store_with_config(
+    db_path: &TempDir,
+    config: StoreConfig,
+    spec: ChainSpec,
+) -> Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>> {
     let hot_path = db_path.path().join("hot_db");
     let cold_path = db_path.path().join("cold_db");
-    let config = StoreConfig::default();
     let log = test_logger();
 
     HotColdDB::open(&hot_path, &cold_path, |_, _, _| Ok(()), config, spec, log)
@@ -64,7 +92,7 @@ fn get_store_with_spec(
 }
 
 fn get_harness(
-    store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
     validator_count: usize,
 ) -> TestHarness {
     let harness = BeaconChainHarness::builder(MinimalEthSpec)
@@ -2244,11 +2272,230 @@ async fn weak_subjectivity_sync() {
     // Anchor slot is still set to the starting slot.
     assert_eq!(store.get_anchor_slot(), Some(wss_slot));
 
-    // Reconstruct states.
-    store.clone().reconstruct_historic_states().unwrap();
+    // States between the state limits can be computed by replay without being stored.
+    let replay_start = Slot::new(E::slots_per_epoch() + 1);
+    let replay_end = Slot::new(3 * E::slots_per_epoch());
+    let mut replayed_slots = vec![];
+    store
+        .replay_historic_states(replay_start, replay_end, |state, state_root| {
+            let expected_root = harness
+                .chain
+                .state_root_at_slot(state.slot())
+                .unwrap()
+                .unwrap();
+            assert_eq!(state.canonical_root(), expected_root);
+            assert_eq!(state_root, expected_root);
+            replayed_slots.push(state.slot());
+            Ok::<_, store::Error>(true)
+        })
+        .unwrap();
+    assert_eq!(
+        replayed_slots,
+        (replay_start.as_u64()..=replay_end.as_u64())
+            .map(Slot::new)
+            .collect::<Vec<_>>()
+    );
+
+    // Reconstruct states in batches, stopping after the first restore point.
+    store.clone().reconstruct_historic_states(Some(1)).unwrap();
+    let progress = store.get_reconstruction_progress().unwrap();
+    assert!(progress.running);
+    assert!(store.reconstruction_pending());
+    assert!(progress.current_slot > progress.start_slot);
+    assert!(progress.current_slot < progress.target_slot);
+    assert_eq!(
+        store.get_anchor_info().unwrap().state_lower_limit,
+        progress.current_slot
+    );
+
+    // No states are reconstructed while paused.
+    assert!(store.pause_reconstruction());
+    store.clone().reconstruct_historic_states(None).unwrap();
+    assert!(!store.reconstruction_pending());
+    assert_eq!(
+        store.get_reconstruction_progress().unwrap().current_slot,
+        progress.current_slot
+    );
+
+    // Once resumed, reconstruction completes.
+    assert!(store.resume_reconstruction());
+    store.clone().reconstruct_historic_states(None).unwrap();
+    let progress = store.get_reconstruction_progress().unwrap();
+    assert!(!progress.running);
+    assert_eq!(progress.current_slot, progress.target_slot);
+    assert_eq!(progress.error, None);
+    assert!(!store.reconstruction_pending());
+    assert!(!store.pause_reconstruction());
     assert_eq!(store.get_anchor_slot(), None);
 }
 
+#[tokio::test]
+async fn era_export_import() {
+    let slots_per_era = E::slots_per_historical_root();
+
+    // Build a chain on a node with full history.
+    let temp1 = tempdir().unwrap();
+    let full_store = get_store(&temp1);
+    let harness = get_harness(full_store.clone(), LOW_VALIDATOR_COUNT);
+    harness
+        .extend_chain(
+            3 * slots_per_era,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let genesis_state = full_store
+        .get_state(&harness.chain.genesis_state_root, Some(Slot::new(0)))
+        .unwrap()
+        .unwrap();
+    let wss_checkpoint = harness.finalized_checkpoint();
+    let wss_block = full_store
+        .get_full_block(&wss_checkpoint.root)
+        .unwrap()
+        .unwrap();
+    let wss_state = full_store
+        .get_state(&wss_block.state_root(), None)
+        .unwrap()
+        .unwrap();
+    let wss_slot = wss_block.slot();
+
+    // Finalize the end of the era holding the checkpoint.
+    harness.advance_slot();
+    harness
+        .extend_chain(
+            2 * slots_per_era,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    // Export every finalized era, one file per era.
+    let log = test_logger();
+    let era_dir = tempdir().unwrap();
+    export_eras(&full_store, era_dir.path(), 0, None, &log).unwrap();
+    let last_era = (full_store.get_split_slot().as_u64() - 1) / slots_per_era as u64;
+    let era_paths = (0..=last_era)
+        .map(|era| {
+            let era_part = format!("-{:05}-", era);
+            fs::read_dir(era_dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .find(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .map_or(false, |name| name.contains(&era_part))
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fs::read_dir(era_dir.path()).unwrap().count(),
+        era_paths.len()
+    );
+
+    // Checkpoint sync a new node, and apply blocks until it finalizes the end of the era holding
+    // the checkpoint.
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let temp2 = tempdir().unwrap();
+    let store = get_store(&temp2);
+    let spec = test_spec::<E>();
+    let seconds_per_slot = spec.seconds_per_slot;
+    let beacon_chain = Arc::new(
+        BeaconChainBuilder::new(MinimalEthSpec)
+            .store(store.clone())
+            .custom_spec(test_spec::<E>())
+            .task_executor(harness.chain.task_executor.clone())
+            .weak_subjectivity_state(wss_state, wss_block.clone(), genesis_state)
+            .unwrap()
+            .logger(log.clone())
+            .store_migrator_config(MigratorConfig::default().blocking())
+            .dummy_eth1_backend()
+            .expect("should build dummy backend")
+            .testing_slot_clock(Duration::from_secs(seconds_per_slot))
+            .expect("should configure testing slot clock")
+            .shutdown_sender(shutdown_tx)
+            .chain_config(ChainConfig::default())
+            .event_handler(Some(ServerSentEventHandler::new_with_capacity(
+                log.clone(),
+                1,
+            )))
+            .monitor_validators(
+                true,
+                vec![],
+                DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD,
+                log.clone(),
+            )
+            .build()
+            .expect("should build"),
+    );
+
+    // Importing fails until the end of the checkpoint's era is finalized.
+    assert!(import_eras::<DiskHarnessType<E>>(store.clone(), era_dir.path(), &log).is_err());
+
+    let chain_dump = harness.chain.chain_dump().unwrap();
+    for snapshot in &chain_dump[wss_slot.as_usize() + 1..] {
+        let full_block = harness
+            .chain
+            .get_block(&snapshot.beacon_block_root)
+            .await
+            .unwrap()
+            .unwrap();
+        beacon_chain.slot_clock.set_slot(full_block.slot().as_u64());
+        beacon_chain
+            .process_block(
+                full_block.canonical_root(),
+                Arc::new(full_block),
+                CountUnrealized::True,
+                NotifyExecutionLayer::Yes,
+            )
+            .await
+            .unwrap();
+        beacon_chain.recompute_head_at_current_slot().await;
+    }
+    let checkpoint_era = (wss_slot.as_u64() - 1) / slots_per_era as u64 + 1;
+    assert!(store.get_split_slot() >= checkpoint_era * slots_per_era as u64);
+
+    // Give a block of era 2 the signature of another block. Its root is unchanged, so only the
+    // signature check catches it.
+    let tampered_dir = tempdir().unwrap();
+    for path in &era_paths {
+        fs::copy(path, tampered_dir.path().join(path.file_name().unwrap())).unwrap();
+    }
+    let tampered_path = tampered_dir.path().join(era_paths[2].file_name().unwrap());
+    let mut tampered_era = Era::<E>::read(&tampered_path, &spec).unwrap();
+    let signature = tampered_era.blocks[1].signature().clone();
+    let (block, _) = tampered_era.blocks[2].clone().deconstruct();
+    tampered_era.blocks[2] = SignedBeaconBlock::from_block(block, signature);
+    tampered_era.write(&tampered_path).unwrap();
+
+    // The eras after the tampered era are imported before the import fails.
+    assert!(import_eras::<DiskHarnessType<E>>(store.clone(), tampered_dir.path(), &log).is_err());
+    assert_eq!(store.get_oldest_block_slot(), 2 * slots_per_era as u64);
+
+    // The untampered files complete the import back to genesis.
+    import_eras::<DiskHarnessType<E>>(store.clone(), era_dir.path(), &log).unwrap();
+    assert!(store.get_anchor_info().unwrap().block_backfill_complete());
+    assert_eq!(store.get_oldest_block_slot(), 0);
+
+    let forwards = beacon_chain
+        .forwards_iter_block_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    let expected = harness
+        .chain
+        .forwards_iter_block_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(forwards, expected);
+    for (block_root, slot) in forwards {
+        let block = store.get_blinded_block(&block_root).unwrap().unwrap();
+        assert_eq!(block.slot(), slot);
+    }
+}
+
 #[tokio::test]
 async fn finalizes_after_resuming_from_db() {
     let validator_count = 16;
@@ -2532,6 +2779,477 @@ async fn revert_minority_fork_on_resume() {
     assert_eq!(heads.len(), 1);
 }
 
+#[tokio::test]
+async fn change_slots_per_restore_point() {
+    let num_blocks_produced = E::slots_per_historical_root() as u64 * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let old_sprp = store.get_config().slots_per_restore_point;
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    assert!(split_slot > 2 * old_sprp);
+
+    // Close the database to ensure everything is written to disk.
+    drop(store);
+    drop(harness);
+
+    let config_with_sprp = |slots_per_restore_point| StoreConfig {
+        slots_per_restore_point,
+        slots_per_restore_point_set_explicitly: true,
+        backend: test_backend(),
+        ..StoreConfig::default()
+    };
+
+    // Change to more frequent restore points and back again.
+    let mut current_sprp = old_sprp;
+    for new_sprp in [old_sprp / 4, old_sprp] {
+        // Re-open the store with its current value and change it.
+        let store =
+            get_store_with_config(&db_path, config_with_sprp(current_sprp), test_spec::<E>());
+        store.change_slots_per_restore_point(new_sprp).unwrap();
+        drop(store);
+        current_sprp = new_sprp;
+
+        // Re-open the store with the new value and check that every frozen state loads.
+        let store = get_store_with_config(&db_path, config_with_sprp(new_sprp), test_spec::<E>());
+        assert_eq!(store.load_restore_point_migration().unwrap(), None);
+
+        for (state_root, slot) in &frozen_state_roots {
+            let mut state = store.get_state(state_root, Some(*slot)).unwrap().unwrap();
+            assert_eq!(state.slot(), *slot);
+            assert_eq!(state.update_tree_hash_cache().unwrap(), *state_root);
+        }
+    }
+}
+
+#[tokio::test]
+async fn hierarchical_state_diffs() {
+    let num_blocks_produced = E::slots_per_historical_root() as u64 * 6;
+    let db_path = tempdir().unwrap();
+    // Diffs every 128 slots and snapshots every 256 slots, with restore points every 64 slots.
+    let config = StoreConfig {
+        hierarchy_config: HierarchyConfig {
+            exponents: vec![7, 8],
+        },
+        backend: test_backend(),
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config, test_spec::<E>());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+    let sprp = store.get_config().slots_per_restore_point;
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    let split_slot = store.get_split_slot();
+    let frozen_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    assert!(split_slot > 5 * sprp);
+
+    let is_diff = |state_root: &Hash256| {
+        store
+            .cold_db
+            .key_exists(DBColumn::BeaconStateDiff.into(), state_root.as_bytes())
+            .unwrap()
+    };
+    let check_frozen_states = || {
+        for (state_root, slot) in &frozen_state_roots {
+            let mut state = store.get_state(state_root, Some(*slot)).unwrap().unwrap();
+            assert_eq!(state.update_tree_hash_cache().unwrap(), *state_root);
+        }
+    };
+
+    // Only restore points aligned to 256 slots are stored in full.
+    let restore_points = frozen_state_roots
+        .iter()
+        .filter(|(_, slot)| *slot % sprp == 0)
+        .collect::<Vec<_>>();
+    for (state_root, slot) in &restore_points {
+        assert_eq!(is_diff(state_root), *slot % 256 != 0, "slot {}", slot);
+    }
+    check_frozen_states();
+
+    // Converting to snapshots and back again doesn't change the states.
+    store
+        .rewrite_restore_point_states(&HierarchyConfig::snapshots_only())
+        .unwrap();
+    assert!(restore_points
+        .iter()
+        .all(|(state_root, _)| !is_diff(state_root)));
+    check_frozen_states();
+
+    store
+        .rewrite_restore_point_states(&store.get_config().hierarchy_config)
+        .unwrap();
+    for (state_root, slot) in &restore_points {
+        assert_eq!(is_diff(state_root), *slot % 256 != 0, "slot {}", slot);
+    }
+    check_frozen_states();
+
+    // The database can't be re-opened with a different hierarchy, which could prune diff bases.
+    let config = store.get_config().clone();
+    drop(store);
+    drop(harness);
+    let hot_path = db_path.path().join("hot_db");
+    let cold_path = db_path.path().join("cold_db");
+    let open_with_hierarchy = |exponents: Vec<u8>| {
+        let config = StoreConfig {
+            hierarchy_config: HierarchyConfig { exponents },
+            ..config.clone()
+        };
+        HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+            &hot_path,
+            &cold_path,
+            |_, _, _| Ok(()),
+            config,
+            test_spec::<E>(),
+            test_logger(),
+        )
+        .map(drop)
+    };
+    assert!(matches!(
+        open_with_hierarchy(vec![8]),
+        Err(StoreError::ConfigError(
+            StoreConfigError::MismatchedHierarchyConfig { .. }
+        ))
+    ));
+    open_with_hierarchy(vec![7, 8]).unwrap();
+}
+
+#[tokio::test]
+async fn history_retention_prunes_old_history() {
+    let slots_per_historical_root = E::slots_per_historical_root();
+    let db_path = tempdir().unwrap();
+    // Retain 128 slots of history, with diffs every 128 slots and snapshots every 256 slots so
+    // that retained restore points are diffed from pruned ones. The split advances by 192 slots
+    // each time the chain is extended.
+    let config = StoreConfig {
+        history_retention_epochs: Some(16),
+        hierarchy_config: HierarchyConfig {
+            exponents: vec![7, 8],
+        },
+        backend: test_backend(),
+        ..StoreConfig::default()
+    };
+    let store = get_store_with_config(&db_path, config, test_spec::<E>());
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    // Nothing is pruned until the split is more than the retention window past a restore point.
+    harness
+        .extend_chain(
+            slots_per_historical_root * 3,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    assert_eq!(store.get_history_retention_slot(), Some(Slot::new(0)));
+    assert_eq!(store.get_anchor_info(), None);
+
+    let split_slot = store.get_split_slot();
+    let mut old_block_roots = harness
+        .chain
+        .forwards_iter_block_roots(Slot::new(1))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+    // Keep the slot of each block rather than the skipped slots following it.
+    old_block_roots.dedup_by_key(|(block_root, _)| *block_root);
+    let old_state_roots = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(1))
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+        .collect::<Vec<_>>();
+
+    harness
+        .extend_chain(
+            slots_per_historical_root * 3,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    store.try_prune_history().unwrap();
+
+    // The pruned range is recorded without fabricating an anchor for the genesis synced node.
+    let retention_slot = store.get_history_retention_slot().unwrap();
+    assert_eq!(retention_slot, 128);
+    assert_eq!(store.get_anchor_info(), None);
+    let pruning_info = store.get_history_pruning_info().unwrap();
+    assert_eq!(pruning_info.oldest_retained_slot, retention_slot);
+    assert!(pruning_info.deletion_complete());
+    assert_eq!(store.get_pruned_history_slot(), Some(retention_slot));
+    assert_eq!(store.get_oldest_block_slot(), retention_slot);
+
+    // Blocks and states prior to the retention slot are deleted, except for genesis.
+    for (block_root, slot) in &old_block_roots {
+        let block = store.get_blinded_block(block_root).unwrap();
+        let retained = *slot >= retention_slot || *block_root == harness.chain.genesis_block_root;
+        assert_eq!(block.is_some(), retained, "slot {}", slot);
+    }
+    for (state_root, slot) in &old_state_roots {
+        let state = store.get_state(state_root, Some(*slot)).unwrap();
+        if *slot < retention_slot {
+            assert!(state.is_none(), "slot {}", slot);
+        } else {
+            assert_eq!(
+                state.unwrap().update_tree_hash_cache().unwrap(),
+                *state_root
+            );
+        }
+    }
+    assert!(store
+        .get_state(&harness.chain.genesis_state_root, Some(Slot::new(0)))
+        .unwrap()
+        .is_some());
+
+    // Block roots are not available before the retention slot.
+    assert!(harness
+        .chain
+        .forwards_iter_block_roots(retention_slot - 1)
+        .is_err());
+
+    // States after the retention slot can still be loaded.
+    let split_slot = store.get_split_slot();
+    for (state_root, slot) in harness
+        .chain
+        .forwards_iter_state_roots(retention_slot)
+        .unwrap()
+        .map(Result::unwrap)
+        .take_while(|(_, slot)| *slot < split_slot)
+    {
+        let mut state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+        assert_eq!(state.update_tree_hash_cache().unwrap(), state_root);
+    }
+
+    // Once the retained restore point at slot 384 is diffed from the pruned snapshot at slot 256,
+    // the snapshot is kept until it's no longer needed.
+    let (snapshot_state_root, _) = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(256))
+        .unwrap()
+        .map(Result::unwrap)
+        .next()
+        .unwrap();
+    let snapshot_exists = || {
+        store
+            .cold_db
+            .key_exists(DBColumn::BeaconState.into(), snapshot_state_root.as_bytes())
+            .unwrap()
+    };
+
+    harness
+        .extend_chain(
+            slots_per_historical_root * 3,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    store.try_prune_history().unwrap();
+    assert_eq!(store.get_pruned_history_slot(), Some(Slot::new(384)));
+    assert_eq!(
+        store
+            .get_history_pruning_info()
+            .unwrap()
+            .diff_base_state_roots,
+        vec![snapshot_state_root]
+    );
+    assert!(snapshot_exists());
+    let (state_root, slot) = harness
+        .chain
+        .forwards_iter_state_roots(Slot::new(384))
+        .unwrap()
+        .map(Result::unwrap)
+        .next()
+        .unwrap();
+    let mut state = store.get_state(&state_root, Some(slot)).unwrap().unwrap();
+    assert_eq!(state.update_tree_hash_cache().unwrap(), state_root);
+
+    harness
+        .extend_chain(
+            slots_per_historical_root * 3,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    store.try_prune_history().unwrap();
+    let pruning_info = store.get_history_pruning_info().unwrap();
+    assert_eq!(pruning_info.oldest_retained_slot, 512);
+    assert!(pruning_info.diff_base_state_roots.is_empty());
+    assert!(!snapshot_exists());
+}
+
+#[tokio::test]
+async fn verify_and_repair_database() {
+    let num_blocks_produced = E::slots_per_historical_root() * 4;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+
+    assert_eq!(store.verify().unwrap(), vec![]);
+
+    let split_slot = store.get_split_slot();
+    let (missing_block_root, missing_block_slot) = harness
+        .chain
+        .forwards_iter_block_roots(split_slot / 2)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let (summary_state_root, summary_slot) = harness
+        .chain
+        .forwards_iter_state_roots(split_slot / 2 + 1)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let temporary_state_root = Hash256::repeat_byte(0x42);
+
+    // Delete a frozen block and a frozen state summary, and leave behind a temporary state.
+    store
+        .do_atomically(vec![
+            StoreOp::DeleteBlock(missing_block_root),
+            StoreOp::PutStateTemporaryFlag(temporary_state_root),
+        ])
+        .unwrap();
+    store
+        .cold_db
+        .key_delete(
+            DBColumn::BeaconStateSummary.into(),
+            summary_state_root.as_bytes(),
+        )
+        .unwrap();
+
+    let inconsistencies = store.verify().unwrap();
+    let missing_block = Inconsistency::MissingBlock {
+        slot: missing_block_slot,
+        block_root: missing_block_root,
+    };
+    assert_eq!(
+        inconsistencies,
+        vec![
+            missing_block.clone(),
+            Inconsistency::MissingColdStateSummary {
+                state_root: summary_state_root,
+                slot: summary_slot,
+                summary_slot: None,
+            },
+            Inconsistency::OrphanedTemporaryState {
+                state_root: temporary_state_root,
+            },
+        ]
+    );
+
+    // Everything but the missing block can be repaired.
+    assert_eq!(store.repair(&inconsistencies, &[]).unwrap(), 2);
+    assert_eq!(store.verify().unwrap(), vec![missing_block.clone()]);
+    assert_eq!(
+        store.load_cold_state_slot(&summary_state_root).unwrap(),
+        Some(summary_slot)
+    );
+
+    // Repairs which would delete a state that is still required are refused.
+    let unloadable = |state_root| Inconsistency::UnloadableHotState {
+        state_root,
+        slot: Slot::new(0),
+        error: None,
+    };
+    let is_unsafe_repair = |result: Result<usize, StoreError>, expected_state_root| {
+        matches!(
+            result,
+            Err(StoreError::UnsafeRepair { state_root, .. }) if state_root == expected_state_root
+        )
+    };
+    let split_state_root = store.get_split_info().state_root;
+    assert!(is_unsafe_repair(
+        store.repair(&[unloadable(split_state_root)], &[]),
+        split_state_root
+    ));
+    let head = harness.chain.head_snapshot();
+    let head_state_root = head.beacon_state_root();
+    assert!(is_unsafe_repair(
+        store.repair(&[unloadable(head_state_root)], &[head_state_root]),
+        head_state_root
+    ));
+    let boundary_slot = (head.beacon_state.slot() - 1)
+        .epoch(E::slots_per_epoch())
+        .start_slot(E::slots_per_epoch());
+    let boundary_state_root = *head.beacon_state.get_state_root(boundary_slot).unwrap();
+    assert!(is_unsafe_repair(
+        store.repair(&[unloadable(boundary_state_root)], &[]),
+        boundary_state_root
+    ));
+    assert_eq!(store.verify().unwrap(), vec![missing_block.clone()]);
+
+    // Without repairing, the database is opened read-only so that temporary states are reported
+    // rather than garbage collected.
+    store
+        .do_atomically(vec![StoreOp::PutStateTemporaryFlag(temporary_state_root)])
+        .unwrap();
+    drop(head);
+    drop(harness);
+    drop(store);
+
+    let read_only_store =
+        HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open_read_only(
+            &db_path.path().join("hot_db"),
+            &db_path.path().join("cold_db"),
+            StoreConfig {
+                backend: test_backend(),
+                ..StoreConfig::default()
+            },
+            test_spec::<E>(),
+            test_logger(),
+        )
+        .unwrap();
+    let orphaned_temporary_state = Inconsistency::OrphanedTemporaryState {
+        state_root: temporary_state_root,
+    };
+    assert_eq!(
+        read_only_store.verify().unwrap(),
+        vec![missing_block, orphaned_temporary_state.clone()]
+    );
+    assert!(matches!(
+        read_only_store.repair(&[orphaned_temporary_state], &[]),
+        Err(StoreError::ReadOnly)
+    ));
+}
+
 // This test checks whether the schema downgrade from the latest version to some minimum supported
 // version is correct. This is the easiest schema test to write without historic versions of
 // Lighthouse on-hand, but has the disadvantage that the min version needs to be adjusted manually
@@ -2691,7 +3409,10 @@ fn check_finalization(harness: &TestHarness, expected_slot: u64) {
 }
 
 /// Check that the HotColdDB's split_slot is equal to the start slot of the last finalized epoch.
-fn check_split_slot(harness: &TestHarness, store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>) {
+fn check_split_slot(
+    harness: &TestHarness,
+    store: Arc<HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>>,
+) {
     let split_slot = store.get_split_slot();
     assert_eq!(
         harness
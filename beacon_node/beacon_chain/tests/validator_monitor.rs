@@ -0,0 +1,243 @@
+#![cfg(test)]
+
+use beacon_chain::test_utils::{
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, EphemeralHarnessType,
+};
+use beacon_chain::validator_monitor::{
+    prune_persisted_summaries, AlertRules, PersistedEpochSummary,
+};
+use eth2::lighthouse::{ValidatorMonitorAlert, ValidatorMonitorAlertKind};
+use lazy_static::lazy_static;
+use types::{Epoch, EthSpec, Keypair, MinimalEthSpec, Slot};
+
+pub const VALIDATOR_COUNT: usize = 16;
+
+lazy_static! {
+    /// A cached set of keys.
+    static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
+}
+
+type E = MinimalEthSpec;
+
+/// Returns a harness which monitors all of its validators according to `alert_rules`.
+fn get_harness(alert_rules: AlertRules) -> BeaconChainHarness<EphemeralHarnessType<E>> {
+    let harness = BeaconChainHarness::builder(MinimalEthSpec)
+        .default_spec()
+        .keypairs(KEYPAIRS[..].to_vec())
+        .fresh_ephemeral_store()
+        .mock_execution_layer()
+        .build();
+
+    let mut validator_monitor = harness.chain.validator_monitor.write();
+    for validator_index in 0..VALIDATOR_COUNT {
+        validator_monitor.auto_register_local_validator(validator_index as u64);
+    }
+    validator_monitor.set_alert_rules(alert_rules);
+    drop(validator_monitor);
+
+    harness
+}
+
+/// Checks the head of `harness` against the alert rules and returns all alerts raised so far.
+fn process_head_alerts(
+    harness: &BeaconChainHarness<EphemeralHarnessType<E>>,
+) -> Vec<ValidatorMonitorAlert> {
+    let head_state = harness.chain.head_beacon_state_cloned();
+    let mut validator_monitor = harness.chain.validator_monitor.write();
+    validator_monitor.process_head_state(&head_state, &harness.chain.spec);
+    validator_monitor.drain_alerts()
+}
+
+#[tokio::test]
+async fn missed_proposal_raises_alert() {
+    let harness = get_harness(AlertRules {
+        missed_proposal: true,
+        ..AlertRules::default()
+    });
+    let validators = harness.get_all_validators();
+
+    let (state, state_root) = harness.get_current_state_and_root();
+    harness
+        .add_attested_blocks_at_slots(
+            state,
+            state_root,
+            &[Slot::new(1), Slot::new(2)],
+            &validators,
+        )
+        .await;
+    assert_eq!(process_head_alerts(&harness), vec![]);
+
+    // Skip a slot.
+    let skipped_slot = Slot::new(3);
+    let (state, state_root) = harness.get_current_state_and_root();
+    harness
+        .add_attested_blocks_at_slots(state, state_root, &[Slot::new(4)], &validators)
+        .await;
+
+    let proposer = harness
+        .chain
+        .head_beacon_state_cloned()
+        .get_beacon_proposer_index(skipped_slot, &harness.chain.spec)
+        .unwrap();
+    assert_eq!(
+        process_head_alerts(&harness),
+        vec![ValidatorMonitorAlert {
+            validator_index: proposer as u64,
+            epoch: Epoch::new(0),
+            kind: ValidatorMonitorAlertKind::MissedProposal { slot: skipped_slot },
+        }]
+    );
+
+    // The same slot should not be alerted twice.
+    assert_eq!(process_head_alerts(&harness), vec![]);
+}
+
+#[tokio::test]
+async fn consecutive_missed_attestations_raise_single_alert() {
+    let harness = get_harness(AlertRules {
+        consecutive_missed_attestations: Some(2),
+        ..AlertRules::default()
+    });
+    harness.advance_slot();
+
+    // Every validator except the first attests.
+    let offline_validator = 0;
+    let online_validators = (1..VALIDATOR_COUNT).collect::<Vec<_>>();
+    harness
+        .extend_chain(
+            E::slots_per_epoch() as usize * 5,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::SomeValidators(online_validators),
+        )
+        .await;
+
+    let alerts = harness.chain.validator_monitor.read().drain_alerts();
+    assert_eq!(alerts.len(), 1, "{:?}", alerts);
+    assert_eq!(alerts[0].validator_index, offline_validator as u64);
+    assert_eq!(
+        alerts[0].kind,
+        ValidatorMonitorAlertKind::MissedAttestations { consecutive: 2 }
+    );
+}
+
+#[tokio::test]
+async fn alert_rules_disabled_by_default() {
+    let harness = get_harness(AlertRules::default());
+    harness.advance_slot();
+
+    harness
+        .extend_chain(
+            E::slots_per_epoch() as usize * 3,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::SomeValidators(vec![]),
+        )
+        .await;
+
+    assert!(!harness
+        .chain
+        .validator_monitor
+        .read()
+        .alert_rules()
+        .is_enabled());
+    assert_eq!(process_head_alerts(&harness), vec![]);
+}
+
+fn epoch_summary(validator_index: u64, epoch: Epoch) -> PersistedEpochSummary {
+    PersistedEpochSummary {
+        validator_index,
+        epoch,
+        attestations: 0,
+        attestation_min_delay: None,
+        attestation_aggregate_inclusions: 0,
+        attestation_block_inclusions: 0,
+        attestation_min_block_inclusion_distance: None,
+        attestation_hit: None,
+        attestation_head_hit: None,
+        attestation_target_hit: None,
+        blocks: 0,
+        block_min_delay: None,
+        aggregates: 0,
+        aggregate_min_delay: None,
+        sync_committee_messages: 0,
+        sync_committee_message_min_delay: None,
+        sync_signature_block_inclusions: 0,
+        sync_signature_contribution_inclusions: 0,
+        sync_contributions: 0,
+        sync_contribution_min_delay: None,
+        exits: 0,
+        proposer_slashings: 0,
+        attester_slashings: 0,
+        total_balance: None,
+    }
+}
+
+#[tokio::test]
+async fn startup_prune_deletes_all_expired_summaries() {
+    let harness = get_harness(AlertRules::default());
+    let history_epochs = harness.chain.config.validator_monitor_history_epochs;
+    let current_epoch = Epoch::new(history_epochs + 10);
+
+    // Store summaries for a validator which is not monitored, spanning several expired epochs as
+    // though the node had been offline.
+    let validator_index = VALIDATOR_COUNT as u64 + 1;
+    for epoch in 0..=12 {
+        let summary = epoch_summary(validator_index, Epoch::new(epoch));
+        harness
+            .chain
+            .store
+            .put_item(
+                &PersistedEpochSummary::db_key(validator_index, summary.epoch),
+                &summary,
+            )
+            .unwrap();
+    }
+
+    prune_persisted_summaries(&harness.chain.store, current_epoch, history_epochs).unwrap();
+
+    for epoch in 0..=12 {
+        let key = PersistedEpochSummary::db_key(validator_index, Epoch::new(epoch));
+        let summary = harness
+            .chain
+            .store
+            .get_item::<PersistedEpochSummary>(&key)
+            .unwrap();
+        assert_eq!(summary.is_some(), epoch > 10, "epoch {}", epoch);
+    }
+}
+
+#[tokio::test]
+async fn persisting_summaries_deletes_expiring_epoch_of_monitored_validators() {
+    let harness = get_harness(AlertRules::default());
+    let history_epochs = harness.chain.config.validator_monitor_history_epochs;
+    let current_epoch = Epoch::new(history_epochs + 10);
+
+    let validator_index = 0;
+    for epoch in 9..=11 {
+        let summary = epoch_summary(validator_index, Epoch::new(epoch));
+        harness
+            .chain
+            .store
+            .put_item(
+                &PersistedEpochSummary::db_key(validator_index, summary.epoch),
+                &summary,
+            )
+            .unwrap();
+    }
+
+    harness
+        .chain
+        .persist_validator_monitor_summaries(vec![], current_epoch)
+        .unwrap();
+
+    // Only the epoch which expires at `current_epoch` is deleted, older epochs are left to the
+    // startup prune.
+    for epoch in 9..=11 {
+        let key = PersistedEpochSummary::db_key(validator_index, Epoch::new(epoch));
+        let summary = harness
+            .chain
+            .store
+            .get_item::<PersistedEpochSummary>(&key)
+            .unwrap();
+        assert_eq!(summary.is_some(), epoch != 10, "epoch {}", epoch);
+    }
+}
@@ -1,6 +1,12 @@
 use crate::address_change_broadcast::broadcast_address_changes_at_capella;
+use crate::checkpoint_bundle::verify_checkpoint_bundle;
+use crate::compute_light_client_updates::{
+    compute_light_client_updates, LIGHT_CLIENT_SERVER_CHANNEL_CAPACITY,
+};
 use crate::config::{ClientGenesis, Config as ClientConfig};
+use crate::event_webhooks::deliver_event_webhooks;
 use crate::notifier::spawn_notifier;
+use crate::validator_monitor_alerts::deliver_validator_monitor_alerts;
 use crate::Client;
 use beacon_chain::otb_verification_service::start_otb_verification_service;
 use beacon_chain::proposer_prep_service::start_proposer_prep_service;
@@ -8,15 +14,17 @@ use beacon_chain::schema_change::migrate_schema;
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
+    light_client_server_cache::LightClientProducerEvent,
     slot_clock::{SlotClock, SystemTimeSlotClock},
     state_advance_timer::spawn_state_advance_timer,
-    store::{HotColdDB, ItemStore, LevelDB, StoreConfig},
+    store::{BeaconNodeBackend, HotColdDB, ItemStore, StoreConfig},
     BeaconChain, BeaconChainTypes, Eth1ChainBackend, ServerSentEventHandler,
 };
 use environment::RuntimeContext;
 use eth1::{Config as Eth1Config, Service as Eth1Service};
 use eth2::{
-    types::{BlockId, StateId},
+    lighthouse::CheckpointBundle,
+    types::{BlockId, EventTopic, StateId},
     BeaconNodeHttpClient, Error as ApiError, Timeouts,
 };
 use execution_layer::ExecutionLayer;
@@ -24,18 +32,21 @@ use genesis::{interop_genesis_state, Eth1GenesisService, DEFAULT_ETH1_BLOCK_HASH
 use lighthouse_network::{prometheus_client::registry::Registry, NetworkGlobals};
 use monitoring_api::{MonitoringHttpClient, ProcessType};
 use network::{NetworkConfig, NetworkSenders, NetworkService};
+use sensitive_url::SensitiveUrl;
 use slasher::Slasher;
 use slasher_service::SlasherService;
 use slog::{debug, info, warn, Logger};
+use ssz::Decode;
 use std::net::TcpListener;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use timer::spawn_timer;
+use tokio::sync::mpsc::{self, Receiver};
 use tokio::sync::oneshot;
 use types::{
-    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec,
-    ExecutionBlockHash, Hash256, SignedBeaconBlock,
+    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, DepositTreeSnapshot,
+    EthSpec, ExecutionBlockHash, Hash256, SignedBeaconBlock,
 };
 
 /// Interval between polling the eth1 node for genesis information.
@@ -71,6 +82,10 @@ pub struct ClientBuilder<T: BeaconChainTypes> {
     http_api_config: http_api::Config,
     http_metrics_config: http_metrics::Config,
     slasher: Option<Arc<Slasher<T::EthSpec>>>,
+    light_client_server_rv: Option<Receiver<LightClientProducerEvent<T::EthSpec>>>,
+    validator_monitor_alert_webhooks: Vec<SensitiveUrl>,
+    event_webhooks: Vec<SensitiveUrl>,
+    event_webhook_topics: Vec<EventTopic>,
     eth_spec_instance: T::EthSpec,
 }
 
@@ -103,6 +118,10 @@ where
             http_api_config: <_>::default(),
             http_metrics_config: <_>::default(),
             slasher: None,
+            light_client_server_rv: None,
+            validator_monitor_alert_webhooks: vec![],
+            event_webhooks: vec![],
+            event_webhook_topics: vec![],
             eth_spec_instance,
         }
     }
@@ -143,7 +162,11 @@ where
             runtime_context.ok_or("beacon_chain_start_method requires a runtime context")?;
         let context = runtime_context.service_context("beacon".into());
         let spec = chain_spec.ok_or("beacon_chain_start_method requires a chain spec")?;
-        let event_handler = if self.http_api_config.enabled {
+        // Events and validator monitor alerts are delivered to webhooks via the event handler.
+        let event_handler = if self.http_api_config.enabled
+            || !config.validator_monitor_alert_webhooks.is_empty()
+            || !config.event_webhooks.is_empty()
+        {
             Some(ServerSentEventHandler::new(context.log().clone()))
         } else {
             None
@@ -179,7 +202,11 @@ where
                     .service_context("val_mon".to_string())
                     .log()
                     .clone(),
-            );
+            )
+            .validator_monitor_alert_rules(config.validator_monitor_alert_rules);
+        self.validator_monitor_alert_webhooks = config.validator_monitor_alert_webhooks.clone();
+        self.event_webhooks = config.event_webhooks.clone();
+        self.event_webhook_topics = config.event_webhook_topics.clone();
 
         let builder = if let Some(slasher) = self.slasher.clone() {
             builder.slasher(slasher)
@@ -187,6 +214,14 @@ where
             builder
         };
 
+        let builder = if config.chain.enable_light_client_server {
+            let (tx, rv) = mpsc::channel(LIGHT_CLIENT_SERVER_CHANNEL_CAPACITY);
+            self.light_client_server_rv = Some(rv);
+            builder.light_client_server_tx(tx)
+        } else {
+            builder
+        };
+
         let chain_exists = builder.store_contains_beacon_chain().unwrap_or(false);
 
         // If the client is expect to resume but there's no beacon chain in the database,
@@ -203,6 +238,7 @@ where
         } else if chain_exists {
             if matches!(client_genesis, ClientGenesis::WeakSubjSszBytes { .. })
                 || matches!(client_genesis, ClientGenesis::CheckpointSyncUrl { .. })
+                || matches!(client_genesis, ClientGenesis::CheckpointBundle { .. })
             {
                 info!(
                     context.log(),
@@ -403,29 +439,60 @@ where
                     "state_root" => ?state_root,
                 );
 
-                let service =
-                    deposit_snapshot.and_then(|snapshot| match Eth1Service::from_deposit_snapshot(
-                        config.eth1,
-                        context.log().clone(),
-                        spec,
-                        &snapshot,
-                    ) {
-                        Ok(service) => {
-                            info!(
-                                context.log(),
-                                "Loaded deposit tree snapshot";
-                                "deposits loaded" => snapshot.deposit_count,
-                            );
-                            Some(service)
-                        }
-                        Err(e) => {
-                            warn!(context.log(),
-                                "Unable to load deposit snapshot";
-                                "error" => ?e
-                            );
-                            None
-                        }
-                    });
+                let service = deposit_snapshot.and_then(|snapshot| {
+                    eth1_service_from_deposit_snapshot(config.eth1, context.log(), spec, &snapshot)
+                });
+
+                builder
+                    .weak_subjectivity_state(state, block, genesis_state)
+                    .map(|v| (v, service))?
+            }
+            ClientGenesis::CheckpointBundle {
+                genesis_state_bytes,
+                bundle_bytes,
+                checkpoint_root,
+            } => {
+                info!(context.log(), "Starting checkpoint sync from bundle");
+
+                let bundle = CheckpointBundle::from_ssz_bytes(&bundle_bytes)
+                    .map_err(|e| format!("Unable to parse checkpoint bundle SSZ: {:?}", e))?;
+                let state = bundle
+                    .state::<TEthSpec>(&spec)
+                    .map_err(|e| format!("Unable to parse checkpoint bundle state SSZ: {:?}", e))?;
+                let block = bundle
+                    .block::<TEthSpec>(&spec)
+                    .map_err(|e| format!("Unable to parse checkpoint bundle block SSZ: {:?}", e))?;
+                let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes, &spec)
+                    .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?;
+
+                verify_checkpoint_bundle(
+                    &state,
+                    &block,
+                    bundle.deposit_snapshot.as_ref(),
+                    checkpoint_root,
+                    config.chain.weak_subjectivity_checkpoint,
+                )?;
+
+                info!(
+                    context.log(),
+                    "Verified checkpoint bundle";
+                    "slot" => block.slot(),
+                    "block_root" => ?block.canonical_root(),
+                    "state_root" => ?block.state_root(),
+                );
+
+                let service = if config.sync_eth1_chain {
+                    bundle.deposit_snapshot.and_then(|snapshot| {
+                        eth1_service_from_deposit_snapshot(
+                            config.eth1,
+                            context.log(),
+                            spec,
+                            &snapshot,
+                        )
+                    })
+                } else {
+                    None
+                };
 
                 builder
                     .weak_subjectivity_state(state, block, genesis_state)
@@ -749,6 +816,57 @@ where
                 state_advance_log,
             );
 
+            // Spawn a service to produce and publish light client updates.
+            if let (Some(light_client_server_rv), Some(network_senders)) = (
+                self.light_client_server_rv.take(),
+                self.network_senders.as_ref(),
+            ) {
+                let inner_chain = beacon_chain.clone();
+                let network_send = network_senders.network_send();
+                let light_client_update_context =
+                    runtime_context.service_context("lc_update".to_string());
+                let log = light_client_update_context.log().clone();
+                light_client_update_context.executor.spawn(
+                    async move {
+                        compute_light_client_updates(
+                            &inner_chain,
+                            light_client_server_rv,
+                            network_send,
+                            &log,
+                        )
+                        .await
+                    },
+                    "lc_update",
+                );
+            }
+
+            // Spawn a service to post validator monitor alerts to webhooks.
+            if !self.validator_monitor_alert_webhooks.is_empty() {
+                let inner_chain = beacon_chain.clone();
+                let webhooks = std::mem::take(&mut self.validator_monitor_alert_webhooks);
+                let alert_context = runtime_context.service_context("val_mon_alert".to_string());
+                let executor = alert_context.executor.clone();
+                let log = alert_context.log().clone();
+                alert_context.executor.spawn(
+                    deliver_validator_monitor_alerts(inner_chain, webhooks, executor, log),
+                    "validator_monitor_alerts",
+                );
+            }
+
+            // Spawn a service to post events to webhooks.
+            if !self.event_webhooks.is_empty() {
+                let inner_chain = beacon_chain.clone();
+                let webhooks = std::mem::take(&mut self.event_webhooks);
+                let topics = std::mem::take(&mut self.event_webhook_topics);
+                let webhook_context = runtime_context.service_context("event_webhook".to_string());
+                let executor = webhook_context.executor.clone();
+                let log = webhook_context.log().clone();
+                webhook_context.executor.spawn(
+                    deliver_event_webhooks(inner_chain, webhooks, topics, executor, log),
+                    "event_webhooks",
+                );
+            }
+
             if let Some(execution_layer) = beacon_chain.execution_layer.as_ref() {
                 // Only send a head update *after* genesis.
                 if let Ok(current_slot) = beacon_chain.slot() {
@@ -869,7 +987,15 @@ where
 }
 
 impl<TSlotClock, TEth1Backend, TEthSpec>
-    ClientBuilder<Witness<TSlotClock, TEth1Backend, TEthSpec, LevelDB<TEthSpec>, LevelDB<TEthSpec>>>
+    ClientBuilder<
+        Witness<
+            TSlotClock,
+            TEth1Backend,
+            TEthSpec,
+            BeaconNodeBackend<TEthSpec>,
+            BeaconNodeBackend<TEthSpec>,
+        >,
+    >
 where
     TSlotClock: SlotClock + 'static,
     TEth1Backend: Eth1ChainBackend<TEthSpec> + 'static,
@@ -1063,3 +1189,30 @@ where
         Ok(self)
     }
 }
+
+/// Initializes an `Eth1Service` from an EIP-4881 deposit snapshot, logging and returning `None`
+/// if the snapshot cannot be loaded.
+fn eth1_service_from_deposit_snapshot(
+    config: Eth1Config,
+    log: &Logger,
+    spec: ChainSpec,
+    snapshot: &DepositTreeSnapshot,
+) -> Option<Eth1Service> {
+    match Eth1Service::from_deposit_snapshot(config, log.clone(), spec, snapshot) {
+        Ok(service) => {
+            info!(
+                log,
+                "Loaded deposit tree snapshot";
+                "deposits loaded" => snapshot.deposit_count,
+            );
+            Some(service)
+        }
+        Err(e) => {
+            warn!(log,
+                "Unable to load deposit snapshot";
+                "error" => ?e
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use types::{BeaconState, Checkpoint, DepositTreeSnapshot, EthSpec, Hash256, SignedBeaconBlock};
+
+/// Verify the contents of a checkpoint bundle against the roots supplied by the user, prior to
+/// writing anything to the database.
+///
+/// At least one of `checkpoint_root` or `wss_checkpoint` must be provided. The block must match
+/// `checkpoint_root` exactly, while the `wss_checkpoint` may either be the bundle's own checkpoint
+/// or an earlier checkpoint which is still present in the state's `block_roots`.
+///
+/// The state is linked to the block by `BeaconChainBuilder::weak_subjectivity_state`, which
+/// checks the block's `state_root` against the tree hash of the state before storing either.
+pub fn verify_checkpoint_bundle<E: EthSpec>(
+    state: &BeaconState<E>,
+    block: &SignedBeaconBlock<E>,
+    deposit_snapshot: Option<&DepositTreeSnapshot>,
+    checkpoint_root: Option<Hash256>,
+    wss_checkpoint: Option<Checkpoint>,
+) -> Result<(), String> {
+    if checkpoint_root.is_none() && wss_checkpoint.is_none() {
+        return Err(
+            "A checkpoint bundle must be verified against a trusted root, please supply \
+             --checkpoint-root or --wss-checkpoint"
+                .to_string(),
+        );
+    }
+
+    let block_root = block.canonical_root();
+    let block_epoch = block.slot().epoch(E::slots_per_epoch());
+
+    if let Some(checkpoint_root) = checkpoint_root {
+        if block_root != checkpoint_root {
+            return Err(format!(
+                "Checkpoint bundle block root {:?} does not match --checkpoint-root {:?}",
+                block_root, checkpoint_root
+            ));
+        }
+    }
+
+    if let Some(wss_checkpoint) = wss_checkpoint {
+        let expected_root = if wss_checkpoint.epoch == block_epoch {
+            block_root
+        } else if wss_checkpoint.epoch < block_epoch {
+            *state
+                .get_block_root_at_epoch(wss_checkpoint.epoch)
+                .map_err(|e| {
+                    format!(
+                        "Unable to verify weak subjectivity checkpoint at epoch {} against \
+                         checkpoint bundle at epoch {}: {:?}",
+                        wss_checkpoint.epoch, block_epoch, e
+                    )
+                })?
+        } else {
+            return Err(format!(
+                "Checkpoint bundle at epoch {} is older than the weak subjectivity checkpoint \
+                 at epoch {}",
+                block_epoch, wss_checkpoint.epoch
+            ));
+        };
+
+        if expected_root != wss_checkpoint.root {
+            return Err(format!(
+                "Checkpoint bundle conflicts with weak subjectivity checkpoint, expected root \
+                 {:?} at epoch {} but found {:?}",
+                wss_checkpoint.root, wss_checkpoint.epoch, expected_root
+            ));
+        }
+    }
+
+    if let Some(snapshot) = deposit_snapshot {
+        if !snapshot.is_valid() {
+            return Err("Checkpoint bundle contains an invalid deposit snapshot".to_string());
+        }
+
+        let eth1_data = state.eth1_data();
+        if snapshot.deposit_count > eth1_data.deposit_count {
+            return Err(format!(
+                "Checkpoint bundle deposit snapshot is newer than the state, snapshot has {} \
+                 deposits but state has {}",
+                snapshot.deposit_count, eth1_data.deposit_count
+            ));
+        }
+        if snapshot.deposit_count == eth1_data.deposit_count
+            && snapshot.deposit_root != eth1_data.deposit_root
+        {
+            return Err(format!(
+                "Checkpoint bundle deposit snapshot root {:?} does not match state deposit root \
+                 {:?}",
+                snapshot.deposit_root, eth1_data.deposit_root
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{BeaconBlock, Epoch, Eth1Data, MainnetEthSpec, Signature, Slot};
+
+    type E = MainnetEthSpec;
+
+    const SLOTS_PER_EPOCH: u64 = 32;
+
+    /// Returns a state and block at the start of `epoch`, with a distinct root recorded in the
+    /// state's `block_roots` for each prior slot.
+    fn state_and_block(epoch: u64) -> (BeaconState<E>, SignedBeaconBlock<E>) {
+        let spec = E::default_spec();
+        let slot = Slot::new(epoch * SLOTS_PER_EPOCH);
+
+        let mut state = BeaconState::new(0, Eth1Data::default(), &spec);
+        *state.slot_mut() = slot;
+        for i in 0..slot.as_u64() {
+            state
+                .set_block_root(Slot::new(i), Hash256::from_low_u64_be(i + 1))
+                .unwrap();
+        }
+
+        let mut block = BeaconBlock::empty(&spec);
+        *block.slot_mut() = slot;
+
+        (
+            state,
+            SignedBeaconBlock::from_block(block, Signature::empty()),
+        )
+    }
+
+    #[test]
+    fn requires_trusted_root() {
+        let (state, block) = state_and_block(2);
+        assert!(verify_checkpoint_bundle(&state, &block, None, None, None).is_err());
+    }
+
+    #[test]
+    fn checkpoint_root() {
+        let (state, block) = state_and_block(2);
+        let root = block.canonical_root();
+
+        assert!(verify_checkpoint_bundle(&state, &block, None, Some(root), None).is_ok());
+        assert!(
+            verify_checkpoint_bundle(&state, &block, None, Some(Hash256::zero()), None).is_err()
+        );
+    }
+
+    #[test]
+    fn wss_checkpoint() {
+        let (state, block) = state_and_block(2);
+        let checkpoint = |epoch: u64, root| {
+            Some(Checkpoint {
+                epoch: Epoch::new(epoch),
+                root,
+            })
+        };
+
+        // The bundle's own checkpoint.
+        let own = checkpoint(2, block.canonical_root());
+        assert!(verify_checkpoint_bundle(&state, &block, None, None, own).is_ok());
+
+        // An earlier checkpoint found in the state's block roots.
+        let earlier = checkpoint(1, Hash256::from_low_u64_be(SLOTS_PER_EPOCH + 1));
+        assert!(verify_checkpoint_bundle(&state, &block, None, None, earlier).is_ok());
+
+        let wrong = checkpoint(1, Hash256::zero());
+        assert!(verify_checkpoint_bundle(&state, &block, None, None, wrong).is_err());
+
+        // A checkpoint later than the bundle.
+        let later = checkpoint(3, Hash256::zero());
+        assert!(verify_checkpoint_bundle(&state, &block, None, None, later).is_err());
+    }
+
+    #[test]
+    fn deposit_snapshot() {
+        let (mut state, block) = state_and_block(2);
+        let root = Some(block.canonical_root());
+        let snapshot = DepositTreeSnapshot::default();
+
+        state.eth1_data_mut().deposit_root = snapshot.deposit_root;
+        assert!(verify_checkpoint_bundle(&state, &block, Some(&snapshot), root, None).is_ok());
+
+        state.eth1_data_mut().deposit_root = Hash256::zero();
+        assert!(verify_checkpoint_bundle(&state, &block, Some(&snapshot), root, None).is_err());
+
+        let invalid = DepositTreeSnapshot {
+            deposit_root: Hash256::repeat_byte(1),
+            ..DepositTreeSnapshot::default()
+        };
+        state.eth1_data_mut().deposit_count = 1;
+        assert!(verify_checkpoint_bundle(&state, &block, Some(&invalid), root, None).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use beacon_chain::light_client_server_cache::LightClientProducerEvent;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use lighthouse_network::PubsubMessage;
+use network::NetworkMessage;
+use slog::{debug, error, Logger};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+use tokio::time::sleep;
+
+/// The maximum number of light client update requests which may be queued before new requests are
+/// dropped.
+pub const LIGHT_CLIENT_SERVER_CHANNEL_CAPACITY: usize = 32;
+
+/// Produce light client updates for each block imported by `chain`, publishing any new finality or
+/// optimistic updates on gossip.
+///
+/// Updates are only published for blocks from the current slot, and not before one third of the
+/// way through the slot, as required by the gossip validation rules for light client updates.
+pub async fn compute_light_client_updates<T: BeaconChainTypes>(
+    chain: &Arc<BeaconChain<T>>,
+    mut light_client_server_rv: Receiver<LightClientProducerEvent<T::EthSpec>>,
+    network_send: UnboundedSender<NetworkMessage<T::EthSpec>>,
+    log: &Logger,
+) {
+    while let Some(event) = light_client_server_rv.recv().await {
+        let signature_slot = event.signature_slot;
+        let inner_chain = chain.clone();
+        let handle = match chain.task_executor.spawn_blocking_handle(
+            move || inner_chain.recompute_and_cache_light_client_updates(event),
+            "compute_light_client_updates",
+        ) {
+            Some(handle) => handle,
+            // The runtime is shutting down.
+            None => return,
+        };
+
+        let produced = match handle.await {
+            Ok(Ok(produced)) => produced,
+            Ok(Err(e)) => {
+                error!(
+                    log,
+                    "Failed to compute light client updates";
+                    "error" => ?e,
+                    "signature_slot" => signature_slot,
+                );
+                continue;
+            }
+            Err(e) => {
+                error!(
+                    log,
+                    "Light client update task failed";
+                    "error" => ?e,
+                );
+                continue;
+            }
+        };
+
+        if chain.slot().map_or(true, |slot| slot != signature_slot) {
+            continue;
+        }
+
+        let mut messages = vec![];
+        if let Some(finality_update) = produced.finality_update {
+            messages.push(PubsubMessage::LightClientFinalityUpdate(Box::new(
+                finality_update,
+            )));
+        }
+        if let Some(optimistic_update) = produced.optimistic_update {
+            messages.push(PubsubMessage::LightClientOptimisticUpdate(Box::new(
+                optimistic_update,
+            )));
+        }
+        if messages.is_empty() {
+            continue;
+        }
+
+        // Wait until one third of the way through the slot before publishing.
+        let one_third_slot = chain.slot_clock.slot_duration() / 3;
+        if let Some(delay) = chain
+            .slot_clock
+            .millis_from_current_slot_start()
+            .and_then(|elapsed| one_third_slot.checked_sub(elapsed))
+        {
+            sleep(delay).await;
+        }
+
+        debug!(
+            log,
+            "Publishing light client updates";
+            "signature_slot" => signature_slot,
+            "count" => messages.len(),
+        );
+        if let Err(e) = network_send.send(NetworkMessage::Publish { messages }) {
+            debug!(
+                log,
+                "Failed to publish light client updates";
+                "error" => ?e,
+            );
+        }
+    }
+}
@@ -1,12 +1,14 @@
-use beacon_chain::validator_monitor::DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD;
+use crate::event_webhooks::SUPPORTED_TOPICS;
+use beacon_chain::validator_monitor::{AlertRules, DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD};
 use directory::DEFAULT_ROOT_DIR;
 use environment::LoggerConfig;
+use eth2::types::EventTopic;
 use network::NetworkConfig;
 use sensitive_url::SensitiveUrl;
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use types::{Graffiti, PublicKeyBytes};
+use types::{Graffiti, Hash256, PublicKeyBytes};
 /// Default directory name for the freezer database under the top-level data dir.
 const DEFAULT_FREEZER_DB_DIR: &str = "freezer_db";
 
@@ -38,6 +40,14 @@ pub enum ClientGenesis {
         genesis_state_bytes: Vec<u8>,
         url: SensitiveUrl,
     },
+    /// Loads the anchor state, block and deposit snapshot from an SSZ-encoded
+    /// `eth2::lighthouse::CheckpointBundle`, which must match `checkpoint_root` and/or the
+    /// configured weak subjectivity checkpoint.
+    CheckpointBundle {
+        genesis_state_bytes: Vec<u8>,
+        bundle_bytes: Vec<u8>,
+        checkpoint_root: Option<Hash256>,
+    },
 }
 
 /// The core configuration of a Lighthouse beacon node.
@@ -65,6 +75,14 @@ pub struct Config {
     /// validator counts causing infeasibly high cardinailty for Prometheus and
     /// high log volumes.
     pub validator_monitor_individual_tracking_threshold: usize,
+    /// The rules according to which the validator monitor raises alerts.
+    pub validator_monitor_alert_rules: AlertRules,
+    /// Endpoints to which each validator monitor alert is posted.
+    pub validator_monitor_alert_webhooks: Vec<SensitiveUrl>,
+    /// Endpoints to which beacon chain events are posted.
+    pub event_webhooks: Vec<SensitiveUrl>,
+    /// The topics of the events posted to `event_webhooks`.
+    pub event_webhook_topics: Vec<EventTopic>,
     #[serde(skip)]
     /// The `genesis` field is not serialized or deserialized by `serde` to ensure it is defined
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
@@ -105,6 +123,10 @@ impl Default for Config {
             validator_monitor_auto: false,
             validator_monitor_pubkeys: vec![],
             validator_monitor_individual_tracking_threshold: DEFAULT_INDIVIDUAL_TRACKING_THRESHOLD,
+            validator_monitor_alert_rules: AlertRules::default(),
+            validator_monitor_alert_webhooks: vec![],
+            event_webhooks: vec![],
+            event_webhook_topics: SUPPORTED_TOPICS.to_vec(),
             logger_config: LoggerConfig::default(),
             always_prefer_builder_payload: false,
         }
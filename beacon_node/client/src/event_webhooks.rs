@@ -0,0 +1,427 @@
+//! Delivers beacon chain events to HTTP webhooks.
+//!
+//! Events are read from the journal of server-sent events, which is enabled whenever webhooks are
+//! configured. Each webhook persists a cursor as each event is acknowledged, so delivery resumes
+//! from the journal where it left off after a restart. Delivery is at-least-once: an event is
+//! delivered again if the node stops after it was acknowledged but before the cursor was
+//! persisted. Receivers may use the `sequence` of each event to discard duplicates.
+//!
+//! If a webhook falls further behind than the size of the journal, the oldest events are missed.
+use beacon_chain::event_journal::{JournalEntry, ReplayStart};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::types::EventTopic;
+use sensitive_url::SensitiveUrl;
+use serde_derive::Serialize;
+use slog::{debug, error, warn, Logger};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use store::{
+    get_key_for_col, DBColumn, Error as StoreError, HotColdDB, ItemStore, KeyValueStore,
+    KeyValueStoreOp, StoreItem,
+};
+use task_executor::TaskExecutor;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::sleep;
+use types::{EthSpec, Hash256};
+
+/// The topics which may be delivered to webhooks.
+pub const SUPPORTED_TOPICS: [EventTopic; 4] = [
+    EventTopic::Head,
+    EventTopic::Block,
+    EventTopic::FinalizedCheckpoint,
+    EventTopic::ChainReorg,
+];
+/// The size of the event journal if webhooks are configured without one, which bounds how far a
+/// webhook may fall behind without missing events.
+pub const DEFAULT_WEBHOOK_EVENT_JOURNAL_SIZE: usize = 65_536;
+/// The delay before the first retry, which doubles with each subsequent retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The maximum delay between retries. Delivery of an event is retried indefinitely.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+/// The timeout for a single delivery attempt.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The body of the request made to a webhook for each event.
+#[derive(Serialize)]
+struct WebhookEvent<'a> {
+    sequence: u64,
+    topic: &'a str,
+    data: serde_json::Value,
+}
+
+/// The sequence number of the last event acknowledged by a webhook.
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+struct WebhookCursor {
+    sequence: u64,
+}
+
+impl StoreItem for WebhookCursor {
+    fn db_column() -> DBColumn {
+        DBColumn::EventWebhookCursor
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// Parse a comma-separated list of topics, each of which must be in `SUPPORTED_TOPICS`.
+pub fn parse_topics(topics: &str) -> Result<Vec<EventTopic>, String> {
+    topics
+        .split(',')
+        .map(|topic| {
+            EventTopic::from_str(topic)
+                .ok()
+                .filter(|topic| SUPPORTED_TOPICS.contains(topic))
+                .ok_or_else(|| format!("Unsupported event webhook topic: {}", topic))
+        })
+        .collect()
+}
+
+/// Cursors are keyed by the hash of the webhook URL, so that they survive changes to the order in
+/// which webhooks are configured without storing any credentials in the URL.
+fn cursor_key(webhook: &SensitiveUrl) -> Hash256 {
+    Hash256::from_slice(&eth2_hashing::hash(webhook.full.as_str().as_bytes()))
+}
+
+/// Returns the cursors of `webhooks`, and deletes the cursors of webhooks which are no longer
+/// configured.
+fn load_cursors<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: &HotColdDB<E, Hot, Cold>,
+    webhooks: &[SensitiveUrl],
+) -> Result<HashMap<Hash256, u64>, StoreError> {
+    let keys = webhooks.iter().map(cursor_key).collect::<HashSet<_>>();
+
+    let mut cursors = HashMap::new();
+    let mut ops = vec![];
+    for result in store.hot_db.iter_column_keys(DBColumn::EventWebhookCursor) {
+        let key = result?;
+        if keys.contains(&key) {
+            if let Some(cursor) = store.get_item::<WebhookCursor>(&key)? {
+                cursors.insert(key, cursor.sequence);
+            }
+        } else {
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::EventWebhookCursor.into(),
+                key.as_bytes(),
+            )));
+        }
+    }
+    store.hot_db.do_atomically(ops)?;
+
+    Ok(cursors)
+}
+
+/// The JSON body posted to webhooks for `entry`.
+fn webhook_body(entry: &JournalEntry) -> Result<Vec<u8>, serde_json::Error> {
+    serde_json::to_vec(&WebhookEvent {
+        sequence: entry.sequence,
+        topic: entry.topic(),
+        data: serde_json::from_str(entry.data())?,
+    })
+}
+
+/// Posts each journaled event emitted by `chain` on one of `topics` to each of `webhooks` as JSON.
+pub async fn deliver_event_webhooks<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    webhooks: Vec<SensitiveUrl>,
+    topics: Vec<EventTopic>,
+    executor: TaskExecutor,
+    log: Logger,
+) {
+    if !chain
+        .event_handler
+        .as_ref()
+        .map_or(false, |event_handler| event_handler.journal_enabled())
+    {
+        error!(
+            log,
+            "Unable to deliver events to webhooks";
+            "reason" => "the event journal is disabled",
+        );
+        return;
+    }
+
+    let store = chain.store.clone();
+    let cursor_webhooks = webhooks.clone();
+    let cursors = match executor.spawn_blocking_handle(
+        move || load_cursors(&store, &cursor_webhooks),
+        "event_webhook_cursors",
+    ) {
+        Some(handle) => match handle.await {
+            Ok(Ok(cursors)) => cursors,
+            Ok(Err(e)) => {
+                error!(
+                    log,
+                    "Unable to load event webhook cursors";
+                    "error" => ?e
+                );
+                return;
+            }
+            Err(e) => {
+                error!(
+                    log,
+                    "Event webhook cursor task failed";
+                    "error" => ?e
+                );
+                return;
+            }
+        },
+        // The executor is shutting down.
+        None => return,
+    };
+
+    let client = reqwest::Client::new();
+    let topics = Arc::new(topics);
+    for webhook in webhooks {
+        // Webhooks without a cursor only receive new events.
+        let cursor = cursors.get(&cursor_key(&webhook)).copied();
+        let delivery = WebhookDelivery {
+            chain: chain.clone(),
+            client: client.clone(),
+            key: cursor_key(&webhook),
+            webhook,
+            topics: topics.clone(),
+            executor: executor.clone(),
+            log: log.clone(),
+        };
+        executor.spawn(delivery.run(cursor), "event_webhook");
+    }
+}
+
+/// Delivers journaled events to a single webhook.
+struct WebhookDelivery<T: BeaconChainTypes> {
+    chain: Arc<BeaconChain<T>>,
+    client: reqwest::Client,
+    webhook: SensitiveUrl,
+    key: Hash256,
+    topics: Arc<Vec<EventTopic>>,
+    executor: TaskExecutor,
+    log: Logger,
+}
+
+impl<T: BeaconChainTypes> WebhookDelivery<T> {
+    /// Posts each journaled event on one of `self.topics` to the webhook in order, starting after
+    /// `cursor`.
+    async fn run(self, mut cursor: Option<u64>) {
+        let event_handler = match self.chain.event_handler.as_ref() {
+            Some(event_handler) => event_handler,
+            None => return,
+        };
+
+        // Subscribe afresh whenever the subscription lags, so that the missed events are read
+        // back from the journal.
+        loop {
+            let start = cursor.map_or(ReplayStart::None, ReplayStart::AfterSequence);
+            let (journaled, mut new_entries) = match event_handler.subscribe_journal(start) {
+                Some(subscription) => subscription,
+                None => return,
+            };
+            if let (Some(cursor), Some(first_sequence)) =
+                (cursor, event_handler.journal_first_sequence())
+            {
+                if first_sequence > cursor + 1 {
+                    warn!(
+                        self.log,
+                        "Webhook may have missed events";
+                        "webhook" => %self.webhook,
+                        "last_delivered" => cursor,
+                        "oldest_journaled" => first_sequence,
+                        "reason" => "events were dropped from the journal",
+                    );
+                }
+            }
+
+            for entry in journaled {
+                self.deliver(&entry).await;
+                cursor = Some(entry.sequence);
+            }
+
+            loop {
+                match new_entries.recv().await {
+                    Ok(entry) => {
+                        // Entries may be received again after subscribing afresh.
+                        if cursor.map_or(false, |cursor| entry.sequence <= cursor) {
+                            continue;
+                        }
+                        self.deliver(&entry).await;
+                        cursor = Some(entry.sequence);
+                    }
+                    Err(RecvError::Lagged(_)) => break,
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        }
+    }
+
+    /// Posts `entry` to the webhook if it is on one of `self.topics`, and persists the cursor once
+    /// it has been acknowledged.
+    async fn deliver(&self, entry: &JournalEntry) {
+        let topic_selected =
+            EventTopic::from_str(entry.topic()).map_or(false, |topic| self.topics.contains(&topic));
+        if !topic_selected {
+            return;
+        }
+
+        let body = match webhook_body(entry) {
+            Ok(body) => body,
+            Err(e) => {
+                error!(
+                    self.log,
+                    "Unable to serialize event for webhook";
+                    "sequence" => entry.sequence,
+                    "error" => %e,
+                );
+                return;
+            }
+        };
+        post_with_retry(&self.client, &self.webhook, entry.sequence, body, &self.log).await;
+
+        let store = self.chain.store.clone();
+        let (key, sequence) = (self.key, entry.sequence);
+        let result = match self.executor.spawn_blocking_handle(
+            move || store.put_item(&key, &WebhookCursor { sequence }),
+            "event_webhook_cursor",
+        ) {
+            Some(handle) => handle
+                .await
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|result| result.map_err(|e| format!("{:?}", e))),
+            // The executor is shutting down, the event will be delivered again after a restart.
+            None => return,
+        };
+        if let Err(e) = result {
+            error!(
+                self.log,
+                "Unable to persist webhook cursor";
+                "webhook" => %self.webhook,
+                "error" => e,
+            );
+        }
+    }
+}
+
+/// Posts `body` to `webhook` until it is acknowledged with a success status, with an exponential
+/// backoff between attempts.
+async fn post_with_retry(
+    client: &reqwest::Client,
+    webhook: &SensitiveUrl,
+    sequence: u64,
+    body: Vec<u8>,
+    log: &Logger,
+) {
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    for attempt in 1.. {
+        let result = client
+            .post(webhook.full.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .timeout(WEBHOOK_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => {
+                debug!(
+                    log,
+                    "Delivered event to webhook";
+                    "webhook" => %webhook,
+                    "sequence" => sequence,
+                );
+                return;
+            }
+            Err(e) => {
+                warn!(
+                    log,
+                    "Failed to deliver event to webhook";
+                    "webhook" => %webhook,
+                    "sequence" => sequence,
+                    "attempt" => attempt,
+                    "retry_in" => ?retry_delay,
+                    "error" => %e,
+                );
+                sleep(retry_delay).await;
+                retry_delay = std::cmp::min(retry_delay * 2, MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::event_journal::EventJournal;
+    use eth2::types::{EventKind, SseFinalizedCheckpoint};
+    use logging::test_logger;
+    use store::{MemoryStore, StoreConfig};
+    use types::{Epoch, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    fn event(epoch: u64) -> EventKind<E> {
+        EventKind::FinalizedCheckpoint(SseFinalizedCheckpoint {
+            block: Hash256::repeat_byte(1),
+            state: Hash256::repeat_byte(2),
+            epoch: Epoch::new(epoch),
+            execution_optimistic: false,
+        })
+    }
+
+    #[test]
+    fn parse_webhook_topics() {
+        assert_eq!(
+            parse_topics("head,chain_reorg"),
+            Ok(vec![EventTopic::Head, EventTopic::ChainReorg])
+        );
+        assert!(parse_topics("head,attestation").is_err());
+        assert!(parse_topics("").is_err());
+    }
+
+    #[test]
+    fn webhook_body_format() {
+        let mut journal = EventJournal::new(8);
+        journal.append(&event(0)).unwrap();
+        let entry = journal.append(&event(1)).unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&webhook_body(&entry).unwrap()).unwrap();
+        assert_eq!(body["sequence"], 2);
+        assert_eq!(body["topic"], "finalized_checkpoint");
+        assert_eq!(body["data"]["epoch"], "1");
+    }
+
+    #[test]
+    fn cursors_of_removed_webhooks_are_deleted() {
+        let store: HotColdDB<E, MemoryStore<E>, MemoryStore<E>> =
+            HotColdDB::open_ephemeral(StoreConfig::default(), E::default_spec(), test_logger())
+                .unwrap();
+        let webhooks = vec![
+            SensitiveUrl::parse("http://localhost:9000/a").unwrap(),
+            SensitiveUrl::parse("http://localhost:9000/b").unwrap(),
+        ];
+        let (a, b) = (cursor_key(&webhooks[0]), cursor_key(&webhooks[1]));
+        store.put_item(&a, &WebhookCursor { sequence: 3 }).unwrap();
+        store.put_item(&b, &WebhookCursor { sequence: 5 }).unwrap();
+
+        let cursors = load_cursors(&store, &webhooks).unwrap();
+        assert_eq!(cursors.get(&a), Some(&3));
+        assert_eq!(cursors.get(&b), Some(&5));
+
+        // Removing a webhook deletes its cursor, so it starts afresh if it is added again.
+        let cursors = load_cursors(&store, &webhooks[..1]).unwrap();
+        assert_eq!(cursors.get(&b), None);
+        assert!(store.get_item::<WebhookCursor>(&b).unwrap().is_none());
+        let cursors = load_cursors(&store, &webhooks).unwrap();
+        assert_eq!(cursors.get(&a), Some(&3));
+        assert_eq!(cursors.get(&b), None);
+    }
+}
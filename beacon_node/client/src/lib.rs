@@ -1,9 +1,13 @@
 extern crate slog;
 
 mod address_change_broadcast;
+mod checkpoint_bundle;
+mod compute_light_client_updates;
 pub mod config;
+pub mod event_webhooks;
 mod metrics;
 mod notifier;
+mod validator_monitor_alerts;
 
 pub mod builder;
 pub mod error;
@@ -0,0 +1,120 @@
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::lighthouse::ValidatorMonitorAlert;
+use eth2::types::EventKind;
+use sensitive_url::SensitiveUrl;
+use slog::{debug, error, warn, Logger};
+use std::sync::Arc;
+use std::time::Duration;
+use task_executor::TaskExecutor;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::sleep;
+
+/// The maximum number of attempts made to deliver an alert to a single webhook.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+/// The delay before the first retry, which doubles with each subsequent retry.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The timeout for a single delivery attempt.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts each validator monitor alert raised by `chain` to each of `webhooks` as JSON.
+///
+/// Each delivery runs in its own task so that a slow or unavailable webhook does not hold up
+/// other alerts.
+pub async fn deliver_validator_monitor_alerts<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    webhooks: Vec<SensitiveUrl>,
+    executor: TaskExecutor,
+    log: Logger,
+) {
+    let mut alerts = match chain.event_handler.as_ref() {
+        Some(event_handler) => event_handler.subscribe_validator_monitor_alert(),
+        None => {
+            error!(
+                log,
+                "Unable to deliver validator monitor alerts";
+                "reason" => "no event handler"
+            );
+            return;
+        }
+    };
+    let client = reqwest::Client::new();
+
+    loop {
+        match alerts.recv().await {
+            Ok(EventKind::ValidatorMonitorAlert(alert)) => {
+                let alert = Arc::new(alert);
+                for webhook in &webhooks {
+                    let client = client.clone();
+                    let alert = alert.clone();
+                    let webhook = webhook.clone();
+                    let log = log.clone();
+                    executor.spawn(
+                        async move { deliver_alert(&client, &webhook, &alert, &log).await },
+                        "validator_monitor_alert_webhook",
+                    );
+                }
+            }
+            Ok(_) => (),
+            Err(RecvError::Lagged(missed)) => {
+                warn!(
+                    log,
+                    "Validator monitor alerts dropped";
+                    "count" => missed,
+                    "reason" => "webhook delivery is falling behind"
+                );
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Posts `alert` to `webhook`, retrying with an exponential backoff on failure.
+async fn deliver_alert(
+    client: &reqwest::Client,
+    webhook: &SensitiveUrl,
+    alert: &ValidatorMonitorAlert,
+    log: &Logger,
+) {
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let result = client
+            .post(webhook.full.clone())
+            .json(alert)
+            .timeout(WEBHOOK_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => {
+                debug!(
+                    log,
+                    "Delivered validator monitor alert";
+                    "webhook" => %webhook,
+                    "validator" => alert.validator_index,
+                );
+                return;
+            }
+            Err(e) if attempt < MAX_DELIVERY_ATTEMPTS => {
+                debug!(
+                    log,
+                    "Retrying validator monitor alert delivery";
+                    "webhook" => %webhook,
+                    "attempt" => attempt,
+                    "error" => %e,
+                );
+                sleep(retry_delay).await;
+                retry_delay *= 2;
+            }
+            Err(e) => {
+                error!(
+                    log,
+                    "Failed to deliver validator monitor alert";
+                    "webhook" => %webhook,
+                    "attempts" => attempt,
+                    "error" => %e,
+                );
+            }
+        }
+    }
+}
@@ -84,6 +84,13 @@ pub enum ProvenancedPayload<P> {
     Builder(P),
 }
 
+/// The origin of the payload within a `BlockProposalContents`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadSource {
+    Local,
+    Builder,
+}
+
 #[derive(Debug)]
 pub enum Error {
     NoEngine,
@@ -124,6 +131,7 @@ pub enum BlockProposalContents<T: EthSpec, Payload: AbstractExecPayload<T>> {
     Payload {
         payload: Payload,
         block_value: Uint256,
+        source: PayloadSource,
         // TODO: remove for 4844, since it appears in PayloadAndBlobs
         _phantom: PhantomData<T>,
     },
@@ -135,6 +143,7 @@ impl<T: EthSpec, Payload: AbstractExecPayload<T>> BlockProposalContents<T, Paylo
             Self::Payload {
                 payload,
                 block_value: _,
+                source: _,
                 _phantom: _,
             } => payload,
         }
@@ -144,6 +153,7 @@ impl<T: EthSpec, Payload: AbstractExecPayload<T>> BlockProposalContents<T, Paylo
             Self::Payload {
                 payload,
                 block_value: _,
+                source: _,
                 _phantom: _,
             } => payload,
         }
@@ -153,16 +163,28 @@ impl<T: EthSpec, Payload: AbstractExecPayload<T>> BlockProposalContents<T, Paylo
             Self::Payload {
                 payload: _,
                 block_value,
+                source: _,
                 _phantom: _,
             } => block_value,
         }
     }
+    pub fn source(&self) -> PayloadSource {
+        match self {
+            Self::Payload {
+                payload: _,
+                block_value: _,
+                source,
+                _phantom: _,
+            } => *source,
+        }
+    }
     pub fn default_at_fork(fork_name: ForkName) -> Result<Self, BeaconStateError> {
         Ok(match fork_name {
             ForkName::Base | ForkName::Altair | ForkName::Merge | ForkName::Capella => {
                 BlockProposalContents::Payload {
                     payload: Payload::default_at_fork(fork_name)?,
                     block_value: Uint256::zero(),
+                    source: PayloadSource::Local,
                     _phantom: PhantomData::default(),
                 }
             }
@@ -836,6 +858,7 @@ impl<T: EthSpec> ExecutionLayer<T> {
                                     BlockProposalContents::Payload {
                                         payload: relay.data.message.header,
                                         block_value: relay.data.message.value,
+                                        source: PayloadSource::Builder,
                                         _phantom: PhantomData::default(),
                                     },
                                 )),
@@ -891,6 +914,7 @@ impl<T: EthSpec> ExecutionLayer<T> {
                                     BlockProposalContents::Payload {
                                         payload: relay.data.message.header,
                                         block_value: relay.data.message.value,
+                                        source: PayloadSource::Builder,
                                         _phantom: PhantomData::default(),
                                     },
                                 )),
@@ -900,6 +924,7 @@ impl<T: EthSpec> ExecutionLayer<T> {
                                     BlockProposalContents::Payload {
                                         payload: relay.data.message.header,
                                         block_value: relay.data.message.value,
+                                        source: PayloadSource::Builder,
                                         _phantom: PhantomData::default(),
                                     },
                                 )),
@@ -1107,6 +1132,7 @@ impl<T: EthSpec> ExecutionLayer<T> {
                 Ok(BlockProposalContents::Payload {
                     payload: execution_payload.into(),
                     block_value,
+                    source: PayloadSource::Local,
                     _phantom: PhantomData::default(),
                 })
             })
@@ -0,0 +1,133 @@
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
+use eth2::lighthouse::{AttestationRewardsQuery, EpochAttestationRewards};
+use eth2::types::ValidatorId;
+use futures::{stream, StreamExt};
+use std::io;
+use std::sync::Arc;
+use store::Error as StoreError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use types::BeaconStateError;
+use warp::http::StatusCode;
+use warp::hyper::Body;
+use warp::reply::Response;
+use warp_utils::reject::{
+    beacon_chain_error, custom_bad_request, custom_not_found, custom_server_error,
+};
+
+/// The number of epochs which may be computed ahead of the client reading them.
+const STREAM_BUFFER_EPOCHS: usize = 4;
+
+/// Map an error from computing attestation rewards to an HTTP rejection.
+pub fn attestation_rewards_error(e: BeaconChainError) -> warp::Rejection {
+    match e {
+        BeaconChainError::MissingBeaconState(root) => {
+            custom_not_found(format!("missing state {root:?}"))
+        }
+        BeaconChainError::NoStateForSlot(slot) => {
+            custom_not_found(format!("missing state at slot {slot}"))
+        }
+        BeaconChainError::DBError(StoreError::MissingHistoricBlocks { oldest_block_slot }) => {
+            custom_not_found(format!(
+                "historic blocks are only available from slot {oldest_block_slot}"
+            ))
+        }
+        BeaconChainError::AttestationRewardsReplayTooLong {
+            state_slot,
+            base_slot,
+        } => custom_bad_request(format!(
+            "the state at slot {state_slot} is too far from the nearest state at slot \
+             {base_slot}, request a range of epochs or reconstruct historic states"
+        )),
+        BeaconChainError::BeaconStateError(BeaconStateError::UnknownValidator(validator_index)) => {
+            custom_bad_request(format!("validator is unknown: {validator_index}"))
+        }
+        BeaconChainError::ValidatorPubkeyUnknown(pubkey) => {
+            custom_bad_request(format!("validator pubkey is unknown: {pubkey:?}"))
+        }
+        e => custom_server_error(format!("unexpected error: {:?}", e)),
+    }
+}
+
+/// Compute the attestation rewards of `validators` for a range of epochs.
+///
+/// The rewards are streamed as newline-delimited JSON, one epoch per line, as they are computed.
+/// Errors which occur before the first epoch is computed are returned as usual, whilst later
+/// errors terminate the stream.
+pub async fn post_attestation_rewards<T: BeaconChainTypes>(
+    query: AttestationRewardsQuery,
+    validators: Vec<ValidatorId>,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<Response, warp::Rejection> {
+    let start_epoch = query.start_epoch;
+    let end_epoch = query.end_epoch;
+
+    if start_epoch > end_epoch {
+        return Err(custom_bad_request(format!(
+            "invalid start and end epochs: {}, {}",
+            start_epoch, end_epoch
+        )));
+    }
+
+    // The rewards for an epoch are computed from the state at the end of the next epoch.
+    let current_epoch = chain.epoch().map_err(beacon_chain_error)?;
+    if end_epoch + 1 >= current_epoch {
+        return Err(custom_bad_request(format!(
+            "end epoch {} must be at least two epochs prior to the current epoch {}",
+            end_epoch, current_epoch
+        )));
+    }
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER_EPOCHS);
+    let executor = chain.task_executor.clone();
+    executor.spawn_blocking(
+        move || {
+            let result = chain.compute_attestation_rewards_range(
+                start_epoch,
+                end_epoch,
+                validators,
+                |epoch, rewards| {
+                    // Stop computing once the client has gone away.
+                    tx.blocking_send(Ok(EpochAttestationRewards { epoch, rewards }))
+                        .is_ok()
+                },
+            );
+            if let Err(e) = result {
+                let _ = tx.blocking_send(Err(attestation_rewards_error(e)));
+            }
+        },
+        "attestation_rewards",
+    );
+
+    let mut rx = ReceiverStream::new(rx);
+    let first = match rx.next().await {
+        Some(Ok(rewards)) => rewards,
+        Some(Err(rejection)) => return Err(rejection),
+        None => {
+            return Err(custom_server_error(
+                "attestation rewards task ended unexpectedly".to_string(),
+            ))
+        }
+    };
+
+    let lines = stream::once(async { Ok(first) })
+        .chain(rx)
+        .map(|result| match result {
+            Ok(rewards) => serde_json::to_vec(&rewards)
+                .map(|mut line| {
+                    line.push(b'\n');
+                    line
+                })
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
+            Err(rejection) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{:?}", rejection),
+            )),
+        });
+
+    warp::http::Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/x-ndjson")
+        .body(Body::wrap_stream(lines))
+        .map_err(|e| custom_server_error(format!("failed to create response: {}", e)))
+}
@@ -1,4 +1,7 @@
-use crate::{state_id::checkpoint_slot_and_execution_optimistic, ExecutionOptimistic};
+use crate::{
+    state_id::{check_slot_not_pruned, checkpoint_slot_and_execution_optimistic},
+    ExecutionOptimistic,
+};
 use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes, WhenSlotSkipped};
 use eth2::types::BlockId as CoreBlockId;
 use std::fmt;
@@ -55,6 +58,7 @@ impl BlockId {
                 Ok((justified_checkpoint.root, execution_optimistic, false))
             }
             CoreBlockId::Slot(slot) => {
+                check_slot_not_pruned(chain, *slot, "block")?;
                 let execution_optimistic = chain
                     .is_optimistic_or_invalid_head()
                     .map_err(warp_utils::reject::beacon_chain_error)?;
@@ -186,9 +190,12 @@ impl BlockId {
     }
 
     /// Return the `SignedBeaconBlock` identified by `self`.
+    ///
+    /// Blocks with pruned payloads are reconstructed using the `BeaconBlockStreamer`, as for
+    /// blocks served to peers.
     pub async fn full_block<T: BeaconChainTypes>(
         &self,
-        chain: &BeaconChain<T>,
+        chain: &Arc<BeaconChain<T>>,
     ) -> Result<
         (
             Arc<SignedBeaconBlock<T::EthSpec>>,
@@ -212,7 +219,7 @@ impl BlockId {
             CoreBlockId::Slot(slot) => {
                 let (root, execution_optimistic, finalized) = self.root(chain)?;
                 chain
-                    .get_block(&root)
+                    .get_block_via_streamer(root)
                     .await
                     .map_err(warp_utils::reject::beacon_chain_error)
                     .and_then(|block_opt| match block_opt {
@@ -223,7 +230,7 @@ impl BlockId {
                                     slot
                                 )));
                             }
-                            Ok((Arc::new(block), execution_optimistic, finalized))
+                            Ok((block, execution_optimistic, finalized))
                         }
                         None => Err(warp_utils::reject::custom_not_found(format!(
                             "beacon block with root {}",
@@ -234,12 +241,12 @@ impl BlockId {
             _ => {
                 let (root, execution_optimistic, finalized) = self.root(chain)?;
                 chain
-                    .get_block(&root)
+                    .get_block_via_streamer(root)
                     .await
                     .map_err(warp_utils::reject::beacon_chain_error)
                     .and_then(|block_opt| {
                         block_opt
-                            .map(|block| (Arc::new(block), execution_optimistic, finalized))
+                            .map(|block| (block, execution_optimistic, finalized))
                             .ok_or_else(|| {
                                 warp_utils::reject::custom_not_found(format!(
                                     "beacon block with root {}",
@@ -0,0 +1,58 @@
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::lighthouse::{AttestationPacking, BlockSimulation, BlockSimulationQuery, PayloadSource};
+use std::sync::Arc;
+use types::{BlindedPayload, EthSpec, Uint256};
+use warp_utils::reject::{beacon_chain_error, block_production_error, custom_bad_request};
+
+/// Produce a block for the requested slot without a RANDAO reveal or signature checks, and
+/// describe how it was packed.
+///
+/// The blinded flow is used so that a configured builder is queried just as it would be during
+/// a real proposal.
+pub async fn simulate_block<T: BeaconChainTypes>(
+    query: BlockSimulationQuery,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<BlockSimulation<T::EthSpec>, warp::Rejection> {
+    let current_slot = chain.slot().map_err(beacon_chain_error)?;
+    let slot = query.slot.unwrap_or(current_slot);
+
+    if slot < current_slot {
+        return Err(custom_bad_request(format!(
+            "cannot simulate a block for past slot {}, current slot is {}",
+            slot, current_slot
+        )));
+    }
+
+    // Simulating far ahead would require advancing the head state through many empty slots.
+    let max_slot = current_slot + T::EthSpec::slots_per_epoch();
+    if slot > max_slot {
+        return Err(custom_bad_request(format!(
+            "cannot simulate a block for slot {}, the latest slot allowed is {}",
+            slot, max_slot
+        )));
+    }
+
+    let simulation = chain
+        .simulate_block::<BlindedPayload<T::EthSpec>>(slot, query.graffiti)
+        .await
+        .map_err(block_production_error)?;
+
+    let packing = simulation.attestation_packing;
+    Ok(BlockSimulation {
+        block: simulation.block,
+        attestation_packing: AttestationPacking {
+            prev_epoch_candidates: packing.prev_epoch_candidates,
+            curr_epoch_candidates: packing.curr_epoch_candidates,
+            prev_epoch_selected: packing.prev_epoch_selected,
+            curr_epoch_selected: packing.curr_epoch_selected,
+            covered_validators: packing.covered_validators,
+            reward: packing.reward,
+        },
+        payload_source: simulation.payload_source.map(|source| match source {
+            execution_layer::PayloadSource::Local => PayloadSource::Local,
+            execution_layer::PayloadSource::Builder => PayloadSource::Builder,
+        }),
+        payload_value: simulation.payload_value.unwrap_or_else(Uint256::zero),
+        consensus_reward: simulation.consensus_reward,
+    })
+}
@@ -0,0 +1,96 @@
+use beacon_chain::block_times_cache::BlockDelays;
+use beacon_chain::{BeaconChain, BeaconChainTypes, WhenSlotSkipped};
+use eth2::lighthouse::{BlockTiming, BlockTimings, BlockTimingsQuery, BlockTimingsSummary};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use std::time::Duration;
+use warp_utils::reject::{beacon_chain_error, custom_bad_request};
+
+/// The maximum number of slots which may be requested at once, to bound the number of database
+/// reads performed by a single request.
+const MAX_BLOCK_TIMINGS_SLOTS: u64 = 8192;
+
+/// Fetch the recorded timings of blocks from the canonical chain.
+pub fn get_block_timings<T: BeaconChainTypes>(
+    query: BlockTimingsQuery,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<BlockTimings, warp::Rejection> {
+    let start_slot = query.start_slot;
+    let end_slot = std::cmp::min(
+        query.end_slot,
+        chain.canonical_head.cached_head().head_slot(),
+    );
+
+    if start_slot > end_slot {
+        return Err(custom_bad_request(format!(
+            "invalid start and end: {}, {}",
+            start_slot, query.end_slot
+        )));
+    }
+
+    if query.end_slot - start_slot >= MAX_BLOCK_TIMINGS_SLOTS {
+        return Err(custom_bad_request(format!(
+            "cannot request more than {} slots at once",
+            MAX_BLOCK_TIMINGS_SLOTS
+        )));
+    }
+
+    // The block root at `start_slot` may be from a prior slot if `start_slot` was skipped.
+    let mut prev_block_root = if start_slot > 0 {
+        chain
+            .block_root_at_slot(start_slot - 1, WhenSlotSkipped::Prev)
+            .map_err(beacon_chain_error)?
+    } else {
+        None
+    };
+
+    let mut blocks = vec![];
+    for result in chain
+        .forwards_iter_block_roots_until(start_slot, end_slot)
+        .map_err(beacon_chain_error)?
+    {
+        let (block_root, slot) = result.map_err(beacon_chain_error)?;
+        if prev_block_root == Some(block_root) {
+            continue;
+        }
+        prev_block_root = Some(block_root);
+
+        let block_times = chain
+            .get_block_times(block_root)
+            .map_err(beacon_chain_error)?;
+
+        let timing = if let Some(block_times) = block_times {
+            let slot_start_time = chain
+                .slot_clock
+                .start_of(slot)
+                .unwrap_or_else(|| Duration::from_secs(0));
+            let delays = BlockDelays::new(block_times.timestamps(), slot_start_time);
+            let peer_info = block_times.peer_info();
+            let millis = |delay: Option<Duration>| delay.map(|delay| delay.as_millis() as u64);
+
+            BlockTiming {
+                slot,
+                block_root,
+                observed_delay: millis(delays.observed),
+                imported_delay: millis(delays.imported),
+                set_as_head_delay: millis(delays.set_as_head),
+                peer_id: peer_info.id,
+                peer_client: peer_info.client,
+            }
+        } else {
+            BlockTiming {
+                slot,
+                block_root,
+                observed_delay: None,
+                imported_delay: None,
+                set_as_head_delay: None,
+                peer_id: None,
+                peer_client: None,
+            }
+        };
+        blocks.push(timing);
+    }
+
+    let summary = BlockTimingsSummary::new(&blocks);
+    Ok(BlockTimings { blocks, summary })
+}
@@ -1,6 +1,7 @@
 use beacon_chain::store::{metadata::CURRENT_SCHEMA_VERSION, AnchorInfo};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
-use eth2::lighthouse::DatabaseInfo;
+use eth2::lighthouse::{DatabaseInfo, DatabaseSizes};
+use slog::error;
 use std::sync::Arc;
 use types::SignedBlindedBeaconBlock;
 
@@ -11,15 +12,85 @@ pub fn info<T: BeaconChainTypes>(
     let split = store.get_split_info();
     let config = store.get_config().clone();
     let anchor = store.get_anchor_info();
+    let compaction = store.get_compaction_progress();
+    let reconstruction = store.get_reconstruction_progress();
 
     Ok(DatabaseInfo {
         schema_version: CURRENT_SCHEMA_VERSION.as_u64(),
         config,
         split,
         anchor,
+        compaction,
+        reconstruction,
     })
 }
 
+pub fn size<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+) -> Result<DatabaseSizes, warp::Rejection> {
+    // Computing the column sizes reads the whole database, so it's done in the background and
+    // the sizes last computed are returned.
+    if chain.store.start_column_sizes_computation() {
+        let store = chain.store.clone();
+        let log = chain.log.clone();
+        chain.task_executor.spawn_blocking(
+            move || {
+                if let Err(e) = store.compute_column_sizes() {
+                    error!(log, "Failed to compute database column sizes"; "error" => ?e);
+                }
+            },
+            "compute_column_sizes",
+        );
+    }
+
+    let (hot, freezer, column_sizes) = chain
+        .store
+        .get_db_sizes()
+        .map_err(|e| warp_utils::reject::custom_server_error(format!("{:?}", e)))?;
+
+    Ok(DatabaseSizes {
+        hot,
+        freezer,
+        column_sizes,
+    })
+}
+
+pub fn compact<T: BeaconChainTypes>(chain: Arc<BeaconChain<T>>) -> Result<String, warp::Rejection> {
+    let started = chain
+        .store_migrator
+        .process_manual_compaction(&chain.task_executor)
+        .map_err(|e| warp_utils::reject::custom_server_error(format!("{:?}", e)))?;
+    if !started {
+        return Err(warp_utils::reject::custom_bad_request(
+            "compaction is already running".to_string(),
+        ));
+    }
+    Ok("success".to_string())
+}
+
+pub fn pause_reconstruction<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+) -> Result<String, warp::Rejection> {
+    if !chain.store.pause_reconstruction() {
+        return Err(warp_utils::reject::custom_bad_request(
+            "reconstruction is not running".to_string(),
+        ));
+    }
+    Ok("success".to_string())
+}
+
+pub fn resume_reconstruction<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+) -> Result<String, warp::Rejection> {
+    if !chain.store.resume_reconstruction() {
+        return Err(warp_utils::reject::custom_bad_request(
+            "reconstruction is not running".to_string(),
+        ));
+    }
+    chain.store_migrator.process_reconstruction();
+    Ok("success".to_string())
+}
+
 pub fn historical_blocks<T: BeaconChainTypes>(
     chain: Arc<BeaconChain<T>>,
     blocks: Vec<Arc<SignedBlindedBeaconBlock<T::EthSpec>>>,
@@ -6,11 +6,15 @@
 //! used for development.
 
 mod attestation_performance;
+mod attestation_rewards;
 mod attester_duties;
 mod block_id;
 mod block_packing_efficiency;
 mod block_rewards;
+mod block_simulation;
+mod block_timings;
 mod database;
+mod light_client;
 mod metrics;
 mod proposer_duties;
 mod publish_blocks;
@@ -21,12 +25,16 @@ mod sync_committees;
 pub mod test_utils;
 mod ui;
 mod validator_inclusion;
+mod validator_monitor;
 mod version;
 
 use beacon_chain::{
-    attestation_verification::VerifiedAttestation, observed_operations::ObservationOutcome,
-    validator_monitor::timestamp_now, AttestationError as AttnError, BeaconChain, BeaconChainError,
-    BeaconChainTypes, ProduceBlockVerification, WhenSlotSkipped,
+    attestation_verification::VerifiedAttestation,
+    event_journal::{is_journaled_topic, ReplayStart},
+    observed_operations::ObservationOutcome,
+    validator_monitor::timestamp_now,
+    AttestationError as AttnError, BeaconChain, BeaconChainError, BeaconChainTypes,
+    ProduceBlockVerification, WhenSlotSkipped,
 };
 pub use block_id::BlockId;
 use directory::DEFAULT_ROOT_DIR;
@@ -54,7 +62,7 @@ use std::sync::Arc;
 use sysinfo::{System, SystemExt};
 use system_health::observe_system_health_bn;
 use tokio::sync::mpsc::{Sender, UnboundedSender};
-use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 use types::{
     Attestation, AttestationData, AttestationShufflingId, AttesterSlashing, BeaconStateError,
     BlindedPayload, CommitteeCache, ConfigAndPreset, Epoch, EthSpec, ForkName, FullPayload,
@@ -1388,6 +1396,85 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    /*
+     * beacon/light_client
+     */
+
+    let beacon_light_client_path = eth_v1
+        .and(warp::path("beacon"))
+        .and(warp::path("light_client"))
+        .and(chain_filter.clone());
+
+    // GET beacon/light_client/bootstrap/{block_root}
+    let get_beacon_light_client_bootstrap = beacon_light_client_path
+        .clone()
+        .and(warp::path("bootstrap"))
+        .and(block_id_or_err)
+        .and(warp::path::end())
+        .and(warp::header::optional::<api_types::Accept>("accept"))
+        .and_then(
+            |chain: Arc<BeaconChain<T>>,
+             block_id: BlockId,
+             accept_header: Option<api_types::Accept>| {
+                blocking_response_task(move || {
+                    let block_root = match block_id.0 {
+                        api_types::BlockId::Root(block_root) => block_root,
+                        _ => {
+                            return Err(warp_utils::reject::custom_bad_request(
+                                "block_root must be a block root".to_string(),
+                            ))
+                        }
+                    };
+                    light_client::get_light_client_bootstrap(chain, block_root, accept_header)
+                })
+            },
+        );
+
+    // GET beacon/light_client/updates?start_period,count
+    let get_beacon_light_client_updates = beacon_light_client_path
+        .clone()
+        .and(warp::path("updates"))
+        .and(warp::path::end())
+        .and(warp::query::<api_types::LightClientUpdatesQuery>())
+        .and(warp::header::optional::<api_types::Accept>("accept"))
+        .and_then(
+            |chain: Arc<BeaconChain<T>>,
+             query: api_types::LightClientUpdatesQuery,
+             accept_header: Option<api_types::Accept>| {
+                blocking_response_task(move || {
+                    light_client::get_light_client_updates(chain, query, accept_header)
+                })
+            },
+        );
+
+    // GET beacon/light_client/finality_update
+    let get_beacon_light_client_finality_update = beacon_light_client_path
+        .clone()
+        .and(warp::path("finality_update"))
+        .and(warp::path::end())
+        .and(warp::header::optional::<api_types::Accept>("accept"))
+        .and_then(
+            |chain: Arc<BeaconChain<T>>, accept_header: Option<api_types::Accept>| {
+                blocking_response_task(move || {
+                    light_client::get_light_client_finality_update(chain, accept_header)
+                })
+            },
+        );
+
+    // GET beacon/light_client/optimistic_update
+    let get_beacon_light_client_optimistic_update = beacon_light_client_path
+        .clone()
+        .and(warp::path("optimistic_update"))
+        .and(warp::path::end())
+        .and(warp::header::optional::<api_types::Accept>("accept"))
+        .and_then(
+            |chain: Arc<BeaconChain<T>>, accept_header: Option<api_types::Accept>| {
+                blocking_response_task(move || {
+                    light_client::get_light_client_optimistic_update(chain, accept_header)
+                })
+            },
+        );
+
     /*
      * beacon/pool
      */
@@ -1953,32 +2040,7 @@ pub fn serve<T: BeaconChainTypes>(
                 blocking_json_task(move || {
                     let attestation_rewards = chain
                         .compute_attestation_rewards(epoch, validators, log)
-                        .map_err(|e| match e {
-                            BeaconChainError::MissingBeaconState(root) => {
-                                warp_utils::reject::custom_not_found(format!(
-                                    "missing state {root:?}",
-                                ))
-                            }
-                            BeaconChainError::NoStateForSlot(slot) => {
-                                warp_utils::reject::custom_not_found(format!(
-                                    "missing state at slot {slot}"
-                                ))
-                            }
-                            BeaconChainError::BeaconStateError(
-                                BeaconStateError::UnknownValidator(validator_index),
-                            ) => warp_utils::reject::custom_bad_request(format!(
-                                "validator is unknown: {validator_index}"
-                            )),
-                            BeaconChainError::ValidatorPubkeyUnknown(pubkey) => {
-                                warp_utils::reject::custom_bad_request(format!(
-                                    "validator pubkey is unknown: {pubkey:?}"
-                                ))
-                            }
-                            e => warp_utils::reject::custom_server_error(format!(
-                                "unexpected error: {:?}",
-                                e
-                            )),
-                        })?;
+                        .map_err(attestation_rewards::attestation_rewards_error)?;
                     let execution_optimistic =
                         chain.is_optimistic_or_invalid_head().unwrap_or_default();
 
@@ -3521,6 +3583,22 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/validator/blocks/simulate
+    let get_lighthouse_validator_blocks_simulate = warp::path("lighthouse")
+        .and(warp::path("validator"))
+        .and(warp::path("blocks"))
+        .and(warp::path("simulate"))
+        .and(warp::path::end())
+        .and(warp::query::<eth2::lighthouse::BlockSimulationQuery>())
+        .and(not_while_syncing_filter.clone())
+        .and(chain_filter.clone())
+        .and_then(|query, chain: Arc<BeaconChain<T>>| async move {
+            let simulation = block_simulation::simulate_block(query, chain).await?;
+            Ok::<_, warp::reject::Rejection>(
+                warp::reply::json(&api_types::GenericResponse::from(simulation)).into_response(),
+            )
+        });
+
     // GET lighthouse/staking
     let get_lighthouse_staking = warp::path("lighthouse")
         .and(warp::path("staking"))
@@ -3562,6 +3640,42 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // POST lighthouse/database/reconstruct/pause
+    let post_lighthouse_database_reconstruct_pause = database_path
+        .and(warp::path("reconstruct"))
+        .and(warp::path("pause"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || database::pause_reconstruction(chain))
+        });
+
+    // POST lighthouse/database/reconstruct/resume
+    let post_lighthouse_database_reconstruct_resume = database_path
+        .and(warp::path("reconstruct"))
+        .and(warp::path("resume"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || database::resume_reconstruction(chain))
+        });
+
+    // GET lighthouse/database/size
+    let get_lighthouse_database_size = database_path
+        .and(warp::path("size"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| blocking_json_task(move || database::size(chain)));
+
+    // POST lighthouse/database/compact
+    let post_lighthouse_database_compact = database_path
+        .and(warp::path("compact"))
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || database::compact(chain))
+        });
+
     // POST lighthouse/database/historical_blocks
     let post_lighthouse_database_historical_blocks = database_path
         .and(warp::path("historical_blocks"))
@@ -3595,6 +3709,17 @@ pub fn serve<T: BeaconChainTypes>(
             blocking_json_task(move || block_rewards::get_block_rewards(query, chain, log))
         });
 
+    // GET lighthouse/analysis/block_timings
+    let get_lighthouse_block_timings = warp::path("lighthouse")
+        .and(warp::path("analysis"))
+        .and(warp::path("block_timings"))
+        .and(warp::query::<eth2::lighthouse::BlockTimingsQuery>())
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|query, chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || block_timings::get_block_timings(query, chain))
+        });
+
     // POST lighthouse/analysis/block_rewards
     let post_lighthouse_block_rewards = warp::path("lighthouse")
         .and(warp::path("analysis"))
@@ -3607,6 +3732,18 @@ pub fn serve<T: BeaconChainTypes>(
             blocking_json_task(move || block_rewards::compute_block_rewards(blocks, chain, log))
         });
 
+    // POST lighthouse/analysis/attestation_rewards
+    let post_lighthouse_attestation_rewards = warp::path("lighthouse")
+        .and(warp::path("analysis"))
+        .and(warp::path("attestation_rewards"))
+        .and(warp::query::<eth2::lighthouse::AttestationRewardsQuery>())
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(chain_filter.clone())
+        .and_then(|query, validators, chain: Arc<BeaconChain<T>>| {
+            attestation_rewards::post_attestation_rewards(query, validators, chain)
+        });
+
     // GET lighthouse/analysis/attestation_performance/{index}
     let get_lighthouse_attestation_performance = warp::path("lighthouse")
         .and(warp::path("analysis"))
@@ -3634,6 +3771,19 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/validator_monitor/{index}
+    let get_lighthouse_validator_monitor = warp::path("lighthouse")
+        .and(warp::path("validator_monitor"))
+        .and(warp::path::param::<u64>())
+        .and(warp::query::<eth2::lighthouse::ValidatorMonitorQuery>())
+        .and(warp::path::end())
+        .and(chain_filter.clone())
+        .and_then(|validator_index, query, chain: Arc<BeaconChain<T>>| {
+            blocking_json_task(move || {
+                validator_monitor::get_validator_monitor_summaries(validator_index, query, chain)
+            })
+        });
+
     // GET lighthouse/merge_readiness
     let get_lighthouse_merge_readiness = warp::path("lighthouse")
         .and(warp::path("merge_readiness"))
@@ -3651,17 +3801,30 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("events"))
         .and(warp::path::end())
         .and(multi_key_query::<api_types::EventQuery>())
+        .and(warp::header::optional::<String>("last-event-id"))
         .and(chain_filter)
         .and_then(
             |topics_res: Result<api_types::EventQuery, warp::Rejection>,
+             last_event_id: Option<String>,
              chain: Arc<BeaconChain<T>>| {
                 blocking_response_task(move || {
                     let topics = topics_res?;
                     // for each topic subscribed spawn a new subscription
-                    let mut receivers = Vec::with_capacity(topics.topics.len());
+                    let mut receivers: Vec<
+                        Pin<Box<dyn Stream<Item = Result<Event, warp::Rejection>> + Send>>,
+                    > = Vec::with_capacity(topics.topics.len());
 
                     if let Some(event_handler) = chain.event_handler.as_ref() {
+                        // Journaled topics are streamed from the journal so that each event has an
+                        // id from which the client may resume.
+                        let mut journaled_topics = vec![];
+
                         for topic in topics.topics {
+                            if event_handler.journal_enabled() && is_journaled_topic(topic) {
+                                journaled_topics.push(topic);
+                                continue;
+                            }
+
                             let receiver = match topic {
                                 api_types::EventTopic::Head => event_handler.subscribe_head(),
                                 api_types::EventTopic::Block => event_handler.subscribe_block(),
@@ -3686,12 +3849,21 @@ pub fn serve<T: BeaconChainTypes>(
                                 api_types::EventTopic::LateHead => {
                                     event_handler.subscribe_late_head()
                                 }
+                                api_types::EventTopic::LightClientFinalityUpdate => {
+                                    event_handler.subscribe_light_client_finality_update()
+                                }
+                                api_types::EventTopic::LightClientOptimisticUpdate => {
+                                    event_handler.subscribe_light_client_optimistic_update()
+                                }
                                 api_types::EventTopic::BlockReward => {
                                     event_handler.subscribe_block_reward()
                                 }
+                                api_types::EventTopic::ValidatorMonitorAlert => {
+                                    event_handler.subscribe_validator_monitor_alert()
+                                }
                             };
 
-                            receivers.push(BroadcastStream::new(receiver).map(|msg| {
+                            receivers.push(Box::pin(BroadcastStream::new(receiver).map(|msg| {
                                 match msg {
                                     Ok(data) => Event::default()
                                         .event(data.topic_name())
@@ -3706,7 +3878,61 @@ pub fn serve<T: BeaconChainTypes>(
                                         format!("{:?}", e),
                                     )),
                                 }
-                            }));
+                            })));
+                        }
+
+                        if !event_handler.journal_enabled() && topics.since_slot.is_some() {
+                            return Err(warp_utils::reject::custom_bad_request(
+                                "since_slot requires the event journal to be enabled".to_string(),
+                            ));
+                        }
+
+                        if !journaled_topics.is_empty() {
+                            // A client which reconnects automatically sends `Last-Event-ID` along
+                            // with its original query, in which case it takes precedence.
+                            let start = match (last_event_id, topics.since_slot) {
+                                (Some(id), _) => {
+                                    id.parse().map(ReplayStart::AfterSequence).map_err(|e| {
+                                        warp_utils::reject::custom_bad_request(format!(
+                                            "invalid Last-Event-ID {:?}: {:?}",
+                                            id, e
+                                        ))
+                                    })?
+                                }
+                                (None, Some(slot)) => ReplayStart::Slot(slot),
+                                (None, None) => ReplayStart::None,
+                            };
+                            let (backlog, receiver) =
+                                event_handler.subscribe_journal(start).ok_or_else(|| {
+                                    warp_utils::reject::custom_server_error(
+                                        "event journal was not initialized".to_string(),
+                                    )
+                                })?;
+
+                            let topic_names = journaled_topics
+                                .into_iter()
+                                .map(|topic| topic.to_string())
+                                .collect::<Vec<_>>();
+                            let live = BroadcastStream::new(receiver).map(|msg| {
+                                msg.map_err(|e| {
+                                    warp_utils::reject::server_sent_event_error(format!("{:?}", e))
+                                })
+                            });
+                            let entries = tokio_stream::iter(backlog.into_iter().map(Ok))
+                                .chain(live)
+                                .filter(move |entry| match entry {
+                                    Ok(entry) => topic_names.iter().any(|t| t == entry.topic()),
+                                    Err(_) => true,
+                                })
+                                .map(|entry| {
+                                    entry.map(|entry| {
+                                        Event::default()
+                                            .id(entry.sequence.to_string())
+                                            .event(entry.topic())
+                                            .data(entry.data())
+                                    })
+                                });
+                            receivers.push(Box::pin(entries));
                         }
                     } else {
                         return Err(warp_utils::reject::custom_server_error(
@@ -3741,6 +3967,10 @@ pub fn serve<T: BeaconChainTypes>(
                 .uor(get_beacon_block_attestations)
                 .uor(get_beacon_blinded_block)
                 .uor(get_beacon_block_root)
+                .uor(get_beacon_light_client_bootstrap)
+                .uor(get_beacon_light_client_updates)
+                .uor(get_beacon_light_client_finality_update)
+                .uor(get_beacon_light_client_optimistic_update)
                 .uor(get_beacon_pool_attestations)
                 .uor(get_beacon_pool_attester_slashings)
                 .uor(get_beacon_pool_proposer_slashings)
@@ -3781,11 +4011,15 @@ pub fn serve<T: BeaconChainTypes>(
                 .uor(get_lighthouse_eth1_block_cache)
                 .uor(get_lighthouse_eth1_deposit_cache)
                 .uor(get_lighthouse_beacon_states_ssz)
+                .uor(get_lighthouse_validator_blocks_simulate)
                 .uor(get_lighthouse_staking)
                 .uor(get_lighthouse_database_info)
+                .uor(get_lighthouse_database_size)
                 .uor(get_lighthouse_block_rewards)
+                .uor(get_lighthouse_block_timings)
                 .uor(get_lighthouse_attestation_performance)
                 .uor(get_lighthouse_block_packing_efficiency)
+                .uor(get_lighthouse_validator_monitor)
                 .uor(get_lighthouse_merge_readiness)
                 .uor(get_events)
                 .recover(warp_utils::reject::handle_rejection),
@@ -3813,8 +4047,12 @@ pub fn serve<T: BeaconChainTypes>(
                     .uor(post_validator_register_validator)
                     .uor(post_lighthouse_liveness)
                     .uor(post_lighthouse_database_reconstruct)
+                    .uor(post_lighthouse_database_reconstruct_pause)
+                    .uor(post_lighthouse_database_reconstruct_resume)
+                    .uor(post_lighthouse_database_compact)
                     .uor(post_lighthouse_database_historical_blocks)
                     .uor(post_lighthouse_block_rewards)
+                    .uor(post_lighthouse_attestation_rewards)
                     .uor(post_lighthouse_ui_validator_metrics)
                     .uor(post_lighthouse_ui_validator_info)
                     .recover(warp_utils::reject::handle_rejection),
@@ -0,0 +1,162 @@
+use crate::version::{add_consensus_version_header, fork_versioned_response, V2};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::types::{Accept, LightClientUpdatesQuery};
+use lighthouse_network::rpc::MAX_REQUEST_LIGHT_CLIENT_UPDATES;
+use ssz::Encode;
+use std::sync::Arc;
+use types::{ChainSpec, EthSpec, ForkName, Hash256, LightClientUpdate};
+use warp::http::Response as HttpResponse;
+use warp::reply::{self, Reply, Response};
+use warp_utils::reject::{beacon_chain_error, custom_bad_request, custom_not_found};
+
+/// Reject requests to the light client endpoints unless the light client server is enabled.
+pub fn check_light_client_server<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+) -> Result<(), warp::Rejection> {
+    if chain.config.enable_light_client_server {
+        Ok(())
+    } else {
+        Err(custom_not_found(
+            "light client server is disabled, see --light-client-server".to_string(),
+        ))
+    }
+}
+
+/// Respond to `GET beacon/light_client/bootstrap/{block_root}`.
+pub fn get_light_client_bootstrap<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    block_root: Hash256,
+    accept_header: Option<Accept>,
+) -> Result<Response, warp::Rejection> {
+    check_light_client_server(&chain)?;
+
+    let (bootstrap, fork_name) = chain
+        .get_light_client_bootstrap(&block_root)
+        .map_err(beacon_chain_error)?
+        .ok_or_else(|| {
+            custom_not_found(format!(
+                "no light client bootstrap available for block {:?}",
+                block_root
+            ))
+        })?;
+
+    match accept_header {
+        Some(Accept::Ssz) => ssz_response(bootstrap.as_ssz_bytes()),
+        _ => fork_versioned_response(V2, fork_name, bootstrap)
+            .map(|res| reply::json(&res).into_response()),
+    }
+    .map(|resp| add_consensus_version_header(resp, fork_name))
+}
+
+/// Respond to `GET beacon/light_client/updates?start_period,count`.
+///
+/// The SSZ response is a sequence of chunks, each consisting of the length of the remainder of
+/// the chunk as a little-endian `uint64`, the fork digest of the update and the update itself.
+pub fn get_light_client_updates<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    query: LightClientUpdatesQuery,
+    accept_header: Option<Accept>,
+) -> Result<Response, warp::Rejection> {
+    check_light_client_server(&chain)?;
+
+    if query.count == 0 {
+        return Err(custom_bad_request("count must be non-zero".to_string()));
+    }
+    let count = std::cmp::min(query.count, MAX_REQUEST_LIGHT_CLIENT_UPDATES);
+
+    let updates = chain
+        .get_light_client_updates(query.start_period, count)
+        .map_err(beacon_chain_error)?;
+
+    match accept_header {
+        Some(Accept::Ssz) => {
+            let mut bytes = vec![];
+            for update in &updates {
+                let fork_digest = ChainSpec::compute_fork_digest(
+                    chain
+                        .spec
+                        .fork_version_for_name(update_fork_name(&chain.spec, update)),
+                    chain.genesis_validators_root,
+                );
+                let payload = update.as_ssz_bytes();
+                let chunk_len = (fork_digest.len() + payload.len()) as u64;
+
+                bytes.extend_from_slice(&chunk_len.to_le_bytes());
+                bytes.extend_from_slice(&fork_digest);
+                bytes.extend_from_slice(&payload);
+            }
+            ssz_response(bytes)
+        }
+        _ => updates
+            .into_iter()
+            .map(|update| {
+                let fork_name = update_fork_name(&chain.spec, &update);
+                fork_versioned_response(V2, fork_name, update)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|res| reply::json(&res).into_response()),
+    }
+}
+
+/// Respond to `GET beacon/light_client/finality_update`.
+pub fn get_light_client_finality_update<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    accept_header: Option<Accept>,
+) -> Result<Response, warp::Rejection> {
+    check_light_client_server(&chain)?;
+
+    let update = chain
+        .light_client_server_cache
+        .get_latest_finality_update()
+        .ok_or_else(|| custom_not_found("no light client finality update available".to_string()))?;
+    let fork_name = chain
+        .spec
+        .fork_name_at_slot::<T::EthSpec>(update.attested_header.slot);
+
+    match accept_header {
+        Some(Accept::Ssz) => ssz_response(update.as_ssz_bytes()),
+        _ => fork_versioned_response(V2, fork_name, update)
+            .map(|res| reply::json(&res).into_response()),
+    }
+    .map(|resp| add_consensus_version_header(resp, fork_name))
+}
+
+/// Respond to `GET beacon/light_client/optimistic_update`.
+pub fn get_light_client_optimistic_update<T: BeaconChainTypes>(
+    chain: Arc<BeaconChain<T>>,
+    accept_header: Option<Accept>,
+) -> Result<Response, warp::Rejection> {
+    check_light_client_server(&chain)?;
+
+    let update = chain
+        .light_client_server_cache
+        .get_latest_optimistic_update()
+        .ok_or_else(|| {
+            custom_not_found("no light client optimistic update available".to_string())
+        })?;
+    let fork_name = chain
+        .spec
+        .fork_name_at_slot::<T::EthSpec>(update.attested_header.slot);
+
+    match accept_header {
+        Some(Accept::Ssz) => ssz_response(update.as_ssz_bytes()),
+        _ => fork_versioned_response(V2, fork_name, update)
+            .map(|res| reply::json(&res).into_response()),
+    }
+    .map(|resp| add_consensus_version_header(resp, fork_name))
+}
+
+/// The fork of an update is determined by the slot of its attested header.
+fn update_fork_name<E: EthSpec>(spec: &ChainSpec, update: &LightClientUpdate<E>) -> ForkName {
+    spec.fork_name_at_slot::<E>(update.attested_header.slot)
+}
+
+fn ssz_response(bytes: Vec<u8>) -> Result<Response, warp::Rejection> {
+    HttpResponse::builder()
+        .status(200)
+        .header("Content-Type", "application/octet-stream")
+        .body(bytes.into())
+        .map_err(|e| {
+            warp_utils::reject::custom_server_error(format!("failed to create response: {}", e))
+        })
+}
@@ -3,6 +3,7 @@
 use crate::state_id::StateId;
 use beacon_chain::{
     beacon_proposer_cache::{compute_proposer_duties_from_head, ensure_state_is_in_epoch},
+    duties_cache::ProposerDuties,
     BeaconChain, BeaconChainError, BeaconChainTypes, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 use eth2::types::{self as api_types};
@@ -10,6 +11,7 @@ use safe_arith::SafeArith;
 use slog::{debug, Logger};
 use slot_clock::SlotClock;
 use std::cmp::Ordering;
+use std::sync::Arc;
 use types::{CloneConfig, Epoch, EthSpec, Hash256, Slot};
 
 /// The struct that is returned to the requesting HTTP client.
@@ -83,8 +85,8 @@ pub fn proposer_duties<T: BeaconChainTypes>(
     }
 }
 
-/// Attempt to load the proposer duties from the `chain.beacon_proposer_cache`, returning `Ok(None)`
-/// if there is a cache miss.
+/// Attempt to load the proposer duties from the `chain.duties_cache` or the
+/// `chain.beacon_proposer_cache`, returning `Ok(None)` if there is a cache miss.
 ///
 /// ## Notes
 ///
@@ -121,25 +123,35 @@ fn try_proposer_duties_from_cache<T: BeaconChainTypes>(
         }
     };
 
-    chain
-        .beacon_proposer_cache
+    let cached_duties = chain
+        .duties_cache
         .lock()
-        .get_epoch::<T::EthSpec>(dependent_root, request_epoch)
-        .cloned()
+        .get_proposer(request_epoch, dependent_root);
+    let proposers = if let Some(duties) = cached_duties {
+        Some(duties.proposers.clone())
+    } else {
+        chain
+            .beacon_proposer_cache
+            .lock()
+            .get_epoch::<T::EthSpec>(dependent_root, request_epoch)
+            .map(|indices| indices.to_vec())
+    };
+
+    proposers
         .map(|indices| {
             convert_to_api_response(
                 chain,
                 request_epoch,
                 dependent_root,
                 execution_optimistic,
-                indices.to_vec(),
+                indices,
             )
         })
         .transpose()
 }
 
-/// Compute the proposer duties using the head state, add the duties to the proposer cache and
-/// return the proposers.
+/// Compute the proposer duties using the head state, add the duties to the proposer and duties
+/// caches and return the proposers.
 ///
 /// This method does *not* attempt to read the values from the cache before computing them. See
 /// `try_proposer_duties_from_cache` to read values.
@@ -163,6 +175,11 @@ fn compute_and_cache_proposer_duties<T: BeaconChainTypes>(
         .insert(current_epoch, dependent_root, indices.clone(), fork)
         .map_err(BeaconChainError::from)
         .map_err(warp_utils::reject::beacon_chain_error)?;
+    chain.duties_cache.lock().insert_proposer(
+        current_epoch,
+        dependent_root,
+        Arc::new(ProposerDuties::new(indices.clone())),
+    );
 
     convert_to_api_response(
         chain,
@@ -104,7 +104,7 @@ pub async fn publish_block<T: BeaconChainTypes>(
             );
             Ok(())
         }
-        Err(BlockError::RepeatProposal { proposer, slot }) => {
+        Err(BlockError::RepeatProposal { proposer, slot, .. }) => {
             warn!(
                 log,
                 "Block ignored due to repeat proposal";
@@ -50,19 +50,22 @@ impl StateId {
                     checkpoint_slot_and_execution_optimistic(chain, justified_checkpoint)?;
                 (slot, execution_optimistic, false)
             }
-            CoreStateId::Slot(slot) => (
-                *slot,
-                chain
-                    .is_optimistic_or_invalid_head()
-                    .map_err(warp_utils::reject::beacon_chain_error)?,
-                *slot
-                    <= chain
-                        .canonical_head
-                        .cached_head()
-                        .finalized_checkpoint()
-                        .epoch
-                        .start_slot(T::EthSpec::slots_per_epoch()),
-            ),
+            CoreStateId::Slot(slot) => {
+                check_slot_not_pruned(chain, *slot, "state")?;
+                (
+                    *slot,
+                    chain
+                        .is_optimistic_or_invalid_head()
+                        .map_err(warp_utils::reject::beacon_chain_error)?,
+                    *slot
+                        <= chain
+                            .canonical_head
+                            .cached_head()
+                            .finalized_checkpoint()
+                            .epoch
+                            .start_slot(T::EthSpec::slots_per_epoch()),
+                )
+            }
             CoreStateId::Root(root) => {
                 if let Some(hot_summary) = chain
                     .store
@@ -263,3 +266,23 @@ pub fn checkpoint_slot_and_execution_optimistic<T: BeaconChainTypes>(
 
     Ok((slot, execution_optimistic))
 }
+
+/// Returns an error if the history at `slot` has been pruned because the node only retains a
+/// limited window of history.
+///
+/// The genesis block and state are always retained.
+pub fn check_slot_not_pruned<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    slot: Slot,
+    item: &str,
+) -> Result<(), warp::reject::Rejection> {
+    match chain.store.get_pruned_history_slot() {
+        Some(pruned_slot) if slot < pruned_slot && slot != chain.spec.genesis_slot => {
+            Err(warp_utils::reject::custom_not_found(format!(
+                "beacon {} at slot {} has been pruned, history is retained from slot {}",
+                item, slot, pruned_slot
+            )))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,118 @@
+use beacon_chain::validator_monitor::PersistedEpochSummary;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::lighthouse::{
+    AttestationSummary, SyncCommitteeSummary, ValidatorMonitorEpochSummary, ValidatorMonitorQuery,
+};
+use std::sync::Arc;
+use warp_utils::reject::{beacon_chain_error, custom_bad_request, custom_not_found};
+
+const MAX_REQUEST_RANGE_EPOCHS: u64 = 1024;
+
+/// Fetch the per-epoch summaries recorded by the validator monitor for `validator_index`.
+///
+/// Epochs for which no summary is available are omitted from the response.
+pub fn get_validator_monitor_summaries<T: BeaconChainTypes>(
+    validator_index: u64,
+    query: ValidatorMonitorQuery,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<Vec<ValidatorMonitorEpochSummary>, warp::Rejection> {
+    let start_epoch = query.start_epoch;
+    let end_epoch = query.end_epoch;
+
+    if start_epoch > end_epoch {
+        return Err(custom_bad_request(format!(
+            "invalid start and end epochs: {}, {}",
+            start_epoch, end_epoch
+        )));
+    }
+
+    if end_epoch - start_epoch >= MAX_REQUEST_RANGE_EPOCHS {
+        return Err(custom_bad_request(format!(
+            "epoch range exceeds the maximum of {} epochs",
+            MAX_REQUEST_RANGE_EPOCHS
+        )));
+    }
+
+    if chain
+        .validator_monitor
+        .read()
+        .get_monitored_validator(validator_index)
+        .is_none()
+    {
+        return Err(custom_not_found(format!(
+            "validator {} is not monitored",
+            validator_index
+        )));
+    }
+
+    // Fetch the epoch prior to `start_epoch` so the balance delta of the first epoch can be
+    // computed.
+    let summaries = chain
+        .get_validator_monitor_summaries(
+            validator_index,
+            start_epoch.saturating_sub(1_u64),
+            end_epoch,
+        )
+        .map_err(beacon_chain_error)?;
+
+    let mut prev_balance = None;
+    let mut prev_epoch = None;
+    let mut response = Vec::with_capacity(summaries.len());
+    for summary in summaries {
+        let epoch = summary.epoch;
+        let balance = summary.total_balance;
+
+        // Only compute a delta between consecutive epochs.
+        let balance_delta = match (prev_epoch, prev_balance, balance) {
+            (Some(prev_epoch), Some(prev_balance), Some(balance)) if prev_epoch + 1 == epoch => {
+                Some(balance as i64 - prev_balance as i64)
+            }
+            _ => None,
+        };
+
+        prev_epoch = Some(epoch);
+        prev_balance = balance;
+
+        if epoch >= start_epoch {
+            response.push(into_epoch_summary(summary, balance_delta));
+        }
+    }
+
+    Ok(response)
+}
+
+fn into_epoch_summary(
+    summary: PersistedEpochSummary,
+    balance_delta: Option<i64>,
+) -> ValidatorMonitorEpochSummary {
+    ValidatorMonitorEpochSummary {
+        epoch: summary.epoch,
+        attestations: AttestationSummary {
+            seen: summary.attestations,
+            min_delay: summary.attestation_min_delay,
+            aggregate_inclusions: summary.attestation_aggregate_inclusions,
+            block_inclusions: summary.attestation_block_inclusions,
+            min_inclusion_distance: summary.attestation_min_block_inclusion_distance,
+            hit: summary.attestation_hit,
+            head_hit: summary.attestation_head_hit,
+            target_hit: summary.attestation_target_hit,
+            aggregates: summary.aggregates,
+            aggregate_min_delay: summary.aggregate_min_delay,
+        },
+        sync_committee: SyncCommitteeSummary {
+            messages: summary.sync_committee_messages,
+            message_min_delay: summary.sync_committee_message_min_delay,
+            block_inclusions: summary.sync_signature_block_inclusions,
+            contribution_inclusions: summary.sync_signature_contribution_inclusions,
+            contributions: summary.sync_contributions,
+            contribution_min_delay: summary.sync_contribution_min_delay,
+        },
+        proposals: summary.blocks,
+        proposal_min_delay: summary.block_min_delay,
+        exits: summary.exits,
+        proposer_slashings: summary.proposer_slashings,
+        attester_slashings: summary.attester_slashings,
+        balance: summary.total_balance,
+        balance_delta,
+    }
+}
@@ -1,7 +1,9 @@
 //! Generic tests that make use of the (newer) `InteractiveApiTester`
 use beacon_chain::{
     chain_config::{DisallowedReOrgOffsets, ReOrgThreshold},
+    light_client_server_cache::LightClientProducerEvent,
     test_utils::{AttestationStrategy, BlockStrategy, SyncCommitteeStrategy},
+    ChainConfig,
 };
 use eth2::types::DepositContractData;
 use execution_layer::{ForkchoiceState, PayloadAttributes};
@@ -743,3 +745,104 @@ pub async fn fork_choice_before_proposal() {
     // D's parent is B.
     assert_eq!(block_d.parent_root(), block_root_b.into());
 }
+
+// Test that the light client endpoints serve the updates produced by the light client server.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+pub async fn light_client_endpoints() {
+    let validator_count = 64;
+    let num_initial = 5 * E::slots_per_epoch();
+    let mut spec = E::default_spec();
+    spec.altair_fork_epoch = Some(Epoch::new(0));
+
+    let tester = InteractiveTester::<E>::new_with_initializer_and_mutator(
+        Some(spec),
+        validator_count,
+        None,
+        Some(Box::new(|builder| {
+            builder.chain_config(ChainConfig {
+                enable_light_client_server: true,
+                ..ChainConfig::default()
+            })
+        })),
+    )
+    .await;
+    let harness = &tester.harness;
+
+    // Create a finalized chain with full sync committee participation.
+    harness.advance_slot();
+    harness
+        .extend_chain_with_sync(
+            num_initial as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+            SyncCommitteeStrategy::AllValidators,
+        )
+        .await;
+
+    // Updates are produced by a background service which is not running, so produce the updates
+    // signed by the head block manually.
+    let head = harness.chain.head_snapshot();
+    let head_block = head.beacon_block.message();
+    harness
+        .chain
+        .recompute_and_cache_light_client_updates(LightClientProducerEvent {
+            attested_block_root: head_block.parent_root(),
+            signature_slot: head_block.slot(),
+            sync_aggregate: head_block.body().sync_aggregate().unwrap().clone(),
+        })
+        .unwrap();
+
+    let finalized_root = head.beacon_state.finalized_checkpoint().root;
+    let bootstrap = tester
+        .client
+        .get_light_client_bootstrap::<E>(finalized_root)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bootstrap.version, Some(ForkName::Altair));
+    assert_eq!(bootstrap.data.header.canonical_root(), finalized_root);
+
+    let finality_update = tester
+        .client
+        .get_light_client_finality_update::<E>()
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(
+        finality_update.attested_header.canonical_root(),
+        head_block.parent_root()
+    );
+    assert_eq!(
+        Some(finality_update),
+        harness
+            .chain
+            .light_client_server_cache
+            .get_latest_finality_update()
+    );
+
+    let optimistic_update = tester
+        .client
+        .get_light_client_optimistic_update::<E>()
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(optimistic_update.signature_slot, head_block.slot());
+    assert_eq!(
+        Some(optimistic_update),
+        harness
+            .chain
+            .light_client_server_cache
+            .get_latest_optimistic_update()
+    );
+
+    let updates = tester
+        .client
+        .get_light_client_updates::<E>(0, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].data.signature_slot, head_block.slot());
+}
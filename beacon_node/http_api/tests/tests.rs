@@ -1,7 +1,7 @@
 use beacon_chain::test_utils::RelativeSyncCommittee;
 use beacon_chain::{
     test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy, EphemeralHarnessType},
-    BeaconChain, StateSkipConfig, WhenSlotSkipped, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+    BeaconChain, ChainConfig, StateSkipConfig, WhenSlotSkipped, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 use environment::null_logger;
 use eth2::{
@@ -79,6 +79,7 @@ struct ApiTester {
 
 struct ApiTesterConfig {
     spec: ChainSpec,
+    chain_config: ChainConfig,
     builder_threshold: Option<u128>,
 }
 
@@ -88,6 +89,7 @@ impl Default for ApiTesterConfig {
         spec.shard_committee_period = 2;
         Self {
             spec,
+            chain_config: ChainConfig::default(),
             builder_threshold: None,
         }
     }
@@ -120,6 +122,7 @@ impl ApiTester {
         let harness = Arc::new(
             BeaconChainHarness::builder(MainnetEthSpec)
                 .spec(spec.clone())
+                .chain_config(config.chain_config)
                 .logger(logging::test_logger())
                 .deterministic_keypairs(VALIDATOR_COUNT)
                 .fresh_ephemeral_store()
@@ -382,6 +385,7 @@ impl ApiTester {
         let mut config = ApiTesterConfig {
             builder_threshold: Some(0),
             spec: E::default_spec(),
+            chain_config: ChainConfig::default(),
         };
         config.spec.altair_fork_epoch = Some(Epoch::new(0));
         config.spec.bellatrix_fork_epoch = Some(Epoch::new(0));
@@ -2399,6 +2403,54 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_lighthouse_block_simulation(self) -> Self {
+        let current_slot = self.chain.slot().unwrap();
+        let head_root = self.chain.head_beacon_block_root();
+
+        for slot in [current_slot, current_slot + 1] {
+            let simulation = self
+                .client
+                .get_lighthouse_validator_blocks_simulate::<E>(Some(slot), None)
+                .await
+                .unwrap()
+                .data;
+
+            assert_eq!(simulation.block.slot(), slot);
+            assert!(simulation.block.body().randao_reveal().is_empty());
+            assert_eq!(
+                simulation.attestation_packing.prev_epoch_selected
+                    + simulation.attestation_packing.curr_epoch_selected,
+                simulation.block.body().attestations().len()
+            );
+            assert_eq!(
+                simulation.consensus_reward.proposer_index,
+                simulation.block.proposer_index()
+            );
+        }
+
+        // Simulating a block doesn't import it.
+        assert_eq!(self.chain.head_beacon_block_root(), head_root);
+
+        // Past slots are rejected.
+        if current_slot > 0 {
+            self.client
+                .get_lighthouse_validator_blocks_simulate::<E>(Some(current_slot - 1), None)
+                .await
+                .unwrap_err();
+        }
+
+        // Slots more than an epoch ahead are rejected.
+        self.client
+            .get_lighthouse_validator_blocks_simulate::<E>(
+                Some(current_slot + E::slots_per_epoch() + 1),
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        self
+    }
+
     pub async fn test_block_production_verify_randao_invalid(self) -> Self {
         let fork = self.chain.canonical_head.cached_head().head_fork();
         let genesis_validators_root = self.chain.genesis_validators_root;
@@ -3942,6 +3994,223 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_post_lighthouse_database_reconstruct_pause_resume(self) -> Self {
+        // The harness is synced from genesis, so there is nothing to reconstruct.
+        assert!(self
+            .client
+            .post_lighthouse_database_reconstruct_pause()
+            .await
+            .is_err());
+        assert!(self
+            .client
+            .post_lighthouse_database_reconstruct_resume()
+            .await
+            .is_err());
+        assert_eq!(
+            self.client
+                .get_lighthouse_database_info()
+                .await
+                .unwrap()
+                .reconstruction,
+            None
+        );
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_database_size(self) -> Self {
+        // The first request starts computing the column sizes in the background.
+        let sizes = self.client.get_lighthouse_database_size().await.unwrap();
+        assert!(sizes.column_sizes.computing || sizes.column_sizes.computed_at.is_some());
+
+        let mut sizes = sizes;
+        for _ in 0..100 {
+            if sizes.column_sizes.computed_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            sizes = self.client.get_lighthouse_database_size().await.unwrap();
+        }
+        assert!(!sizes.column_sizes.computing);
+        assert!(sizes.column_sizes.computed_at.is_some());
+
+        // Column sizes are cached, so they match those computed in the background.
+        let (hot, freezer, column_sizes) = self.chain.store.get_db_sizes().unwrap();
+        assert_eq!(sizes.hot.columns, hot.columns);
+        assert_eq!(sizes.freezer.columns, freezer.columns);
+        assert_eq!(sizes.column_sizes, column_sizes);
+        assert!(sizes
+            .hot
+            .columns
+            .contains_key(store::DBColumn::BeaconBlock.as_str()));
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_database_compact(self) -> Self {
+        let response = self
+            .client
+            .post_lighthouse_database_compact()
+            .await
+            .unwrap();
+        assert_eq!(response, "success");
+
+        // The migrator runs in the foreground in tests, so compaction has already completed.
+        let progress = self
+            .client
+            .get_lighthouse_database_info()
+            .await
+            .unwrap()
+            .compaction
+            .unwrap();
+        assert!(!progress.running);
+        assert_eq!(progress.columns_compacted, progress.columns_total);
+        assert!(progress.finished_at.is_some());
+        assert_eq!(progress.error, None);
+
+        // A compaction can't be started while another is running.
+        assert!(self.chain.store.start_compaction().unwrap());
+        assert!(self
+            .client
+            .post_lighthouse_database_compact()
+            .await
+            .is_err());
+        self.chain.store.compact_all().unwrap();
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_analysis_block_timings(self) -> Self {
+        let head_slot = self.chain.head_snapshot().beacon_block.slot();
+        let timings = self
+            .client
+            .get_lighthouse_analysis_block_timings(Slot::new(1), head_slot)
+            .await
+            .unwrap();
+
+        assert!(!timings.blocks.is_empty());
+        assert_eq!(timings.summary.num_blocks, timings.blocks.len());
+        assert_eq!(
+            timings.blocks.last().unwrap().block_root,
+            self.chain.head_beacon_block_root()
+        );
+        for pair in timings.blocks.windows(2) {
+            assert!(pair[0].slot < pair[1].slot);
+        }
+
+        // Requests spanning too many slots are rejected.
+        let result = self
+            .client
+            .get_lighthouse_analysis_block_timings(Slot::new(0), Slot::new(8192))
+            .await;
+        assert_eq!(result.unwrap_err().status().map(Into::into), Some(400));
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_analysis_attestation_rewards_invalid(self) -> Self {
+        // The start epoch must not be after the end epoch.
+        let result = self
+            .client
+            .post_lighthouse_analysis_attestation_rewards(Epoch::new(2), Epoch::new(1), &[])
+            .await;
+        assert_eq!(result.unwrap_err().status().map(Into::into), Some(400));
+
+        // The rewards for the current epoch are not yet known.
+        let current_epoch = self.chain.epoch().unwrap();
+        let result = self
+            .client
+            .post_lighthouse_analysis_attestation_rewards(current_epoch, current_epoch, &[])
+            .await;
+        assert_eq!(result.unwrap_err().status().map(Into::into), Some(400));
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_analysis_attestation_rewards(self) -> Self {
+        let start_epoch = Epoch::new(0);
+        let end_epoch = self.chain.epoch().unwrap() - 2;
+        let validators = vec![ValidatorId::Index(0), ValidatorId::Index(1)];
+
+        let result = self
+            .client
+            .post_lighthouse_analysis_attestation_rewards(start_epoch, end_epoch, &validators)
+            .await
+            .unwrap();
+
+        // Each epoch in the range is returned in order, with the same rewards as the standard
+        // endpoint.
+        assert_eq!(
+            result
+                .iter()
+                .map(|rewards| rewards.epoch)
+                .collect::<Vec<_>>(),
+            (start_epoch.as_u64()..=end_epoch.as_u64())
+                .map(Epoch::new)
+                .collect::<Vec<_>>()
+        );
+        for rewards in result {
+            let expected = self
+                .chain
+                .compute_attestation_rewards(
+                    rewards.epoch,
+                    validators.clone(),
+                    self.chain.log.clone(),
+                )
+                .unwrap();
+            assert_eq!(rewards.rewards, expected);
+            assert_eq!(rewards.rewards.total_rewards.len(), validators.len());
+        }
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_validator_monitor(self) -> Self {
+        let validator_index = 0;
+        let current_epoch = self.chain.epoch().unwrap();
+        self.chain
+            .validator_monitor
+            .write()
+            .auto_register_local_validator(validator_index);
+
+        let summaries = self
+            .client
+            .get_lighthouse_validator_monitor(validator_index, Epoch::new(0), current_epoch)
+            .await
+            .unwrap();
+        for summary in &summaries {
+            assert!(summary.epoch <= current_epoch);
+        }
+        for pair in summaries.windows(2) {
+            assert!(pair[0].epoch < pair[1].epoch);
+        }
+
+        assert_eq!(
+            self.client
+                .get_lighthouse_validator_monitor(validator_index, current_epoch, Epoch::new(0))
+                .await
+                .unwrap_err()
+                .status()
+                .map(Into::into),
+            Some(400),
+            "should reject an inverted epoch range"
+        );
+
+        let unmonitored_index = self.chain.head_snapshot().beacon_state.validators().len() as u64;
+        assert_eq!(
+            self.client
+                .get_lighthouse_validator_monitor(unmonitored_index, Epoch::new(0), current_epoch)
+                .await
+                .unwrap_err()
+                .status()
+                .map(Into::into),
+            Some(404),
+            "should not return summaries for an unmonitored validator"
+        );
+
+        self
+    }
+
     pub async fn test_post_lighthouse_liveness(self) -> Self {
         let epoch = self.chain.epoch().unwrap();
         let head_state = self.chain.head_beacon_state_cloned();
@@ -4150,6 +4419,44 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_events_since_slot(self) -> Self {
+        let head = self.chain.head_snapshot();
+        let head_slot = head.beacon_block.slot();
+        let mut events_future = Box::pin(
+            self.client
+                .get_events_since_slot::<E>(&[EventTopic::Head], head_slot)
+                .await
+                .unwrap(),
+        );
+
+        // The head event for the current head is replayed from the journal.
+        let replayed_events =
+            poll_events(&mut events_future, 1, Duration::from_millis(10000)).await;
+        match replayed_events.as_slice() {
+            [EventKind::Head(sse_head)] => {
+                assert_eq!(sse_head.slot, head_slot);
+                assert_eq!(sse_head.block, head.beacon_block_root);
+            }
+            other => panic!("unexpected events {:?}", other),
+        }
+
+        // New events follow the replayed ones.
+        self.client
+            .post_beacon_blocks(&self.next_block)
+            .await
+            .unwrap();
+
+        let new_events = poll_events(&mut events_future, 1, Duration::from_millis(10000)).await;
+        match new_events.as_slice() {
+            [EventKind::Head(sse_head)] => {
+                assert_eq!(sse_head.block, self.next_block.canonical_root())
+            }
+            other => panic!("unexpected events {:?}", other),
+        }
+
+        self
+    }
+
     pub async fn test_get_events_altair(self) -> Self {
         let topics = vec![EventTopic::ContributionAndProof];
         let mut events_future = self
@@ -4286,6 +4593,16 @@ async fn get_events() {
     ApiTester::new().await.test_get_events().await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_events_since_slot() {
+    let mut config = ApiTesterConfig::default();
+    config.chain_config.event_journal_size = 64;
+    ApiTester::new_from_config(config)
+        .await
+        .test_get_events_since_slot()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn get_events_altair() {
     let mut config = ApiTesterConfig::default();
@@ -4541,6 +4858,14 @@ async fn block_production_with_skip_slots() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lighthouse_block_simulation() {
+    ApiTester::new()
+        .await
+        .test_lighthouse_block_simulation()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn block_production_no_verify_randao() {
     ApiTester::new()
@@ -4836,6 +5161,7 @@ async fn builder_works_post_capella() {
     let mut config = ApiTesterConfig {
         builder_threshold: Some(0),
         spec: E::default_spec(),
+        chain_config: ChainConfig::default(),
     };
     config.spec.altair_fork_epoch = Some(Epoch::new(0));
     config.spec.bellatrix_fork_epoch = Some(Epoch::new(0));
@@ -4879,10 +5205,30 @@ async fn lighthouse_endpoints() {
         .await
         .test_post_lighthouse_database_reconstruct()
         .await
+        .test_post_lighthouse_database_reconstruct_pause_resume()
+        .await
+        .test_get_lighthouse_database_size()
+        .await
+        .test_post_lighthouse_database_compact()
+        .await
+        .test_get_lighthouse_analysis_block_timings()
+        .await
+        .test_post_lighthouse_analysis_attestation_rewards_invalid()
+        .await
+        .test_get_lighthouse_validator_monitor()
+        .await
         .test_post_lighthouse_liveness()
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lighthouse_analysis_attestation_rewards() {
+    ApiTester::new_with_hard_forks(true, false)
+        .await
+        .test_post_lighthouse_analysis_attestation_rewards()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn optimistic_responses() {
     ApiTester::new_with_hard_forks(true, true)
@@ -17,7 +17,8 @@ use std::sync::Arc;
 use tokio_util::codec::{Decoder, Encoder};
 use types::light_client_bootstrap::LightClientBootstrap;
 use types::{
-    EthSpec, ForkContext, ForkName, Hash256, SignedBeaconBlock, SignedBeaconBlockAltair,
+    EthSpec, ForkContext, ForkName, Hash256, LightClientFinalityUpdate,
+    LightClientOptimisticUpdate, LightClientUpdate, SignedBeaconBlock, SignedBeaconBlockAltair,
     SignedBeaconBlockBase, SignedBeaconBlockCapella, SignedBeaconBlockMerge,
 };
 use unsigned_varint::codec::Uvi;
@@ -72,6 +73,9 @@ impl<TSpec: EthSpec> Encoder<RPCCodedResponse<TSpec>> for SSZSnappyInboundCodec<
                 RPCResponse::BlocksByRange(res) => res.as_ssz_bytes(),
                 RPCResponse::BlocksByRoot(res) => res.as_ssz_bytes(),
                 RPCResponse::LightClientBootstrap(res) => res.as_ssz_bytes(),
+                RPCResponse::LightClientUpdatesByRange(res) => res.as_ssz_bytes(),
+                RPCResponse::LightClientFinalityUpdate(res) => res.as_ssz_bytes(),
+                RPCResponse::LightClientOptimisticUpdate(res) => res.as_ssz_bytes(),
                 RPCResponse::Pong(res) => res.data.as_ssz_bytes(),
                 RPCResponse::MetaData(res) =>
                 // Encode the correct version of the MetaData response based on the negotiated version.
@@ -139,8 +143,15 @@ impl<TSpec: EthSpec> Decoder for SSZSnappyInboundCodec<TSpec> {
     type Error = RPCError;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        if self.protocol.message_name == Protocol::MetaData {
-            return Ok(Some(InboundRequest::MetaData(PhantomData)));
+        match self.protocol.message_name {
+            Protocol::MetaData => return Ok(Some(InboundRequest::MetaData(PhantomData))),
+            Protocol::LightClientFinalityUpdate => {
+                return Ok(Some(InboundRequest::LightClientFinalityUpdate))
+            }
+            Protocol::LightClientOptimisticUpdate => {
+                return Ok(Some(InboundRequest::LightClientOptimisticUpdate))
+            }
+            _ => {}
         }
         let length = match handle_length(&mut self.inner, &mut self.len, src)? {
             Some(len) => len,
@@ -233,6 +244,10 @@ impl<TSpec: EthSpec> Encoder<OutboundRequest<TSpec>> for SSZSnappyOutboundCodec<
             OutboundRequest::Ping(req) => req.as_ssz_bytes(),
             OutboundRequest::MetaData(_) => return Ok(()), // no metadata to encode
             OutboundRequest::LightClientBootstrap(req) => req.as_ssz_bytes(),
+            OutboundRequest::LightClientUpdatesByRange(req) => req.as_ssz_bytes(),
+            // light client update requests are empty
+            OutboundRequest::LightClientFinalityUpdate
+            | OutboundRequest::LightClientOptimisticUpdate => return Ok(()),
         };
         // SSZ encoded bytes should be within `max_packet_size`
         if bytes.len() > self.max_packet_size {
@@ -313,7 +328,11 @@ impl<TSpec: EthSpec> Decoder for SSZSnappyOutboundCodec<TSpec> {
                 let _read_bytes = src.split_to(n as usize);
 
                 match self.protocol.version {
-                    Version::V1 => handle_v1_response(self.protocol.message_name, &decoded_buffer),
+                    Version::V1 => {
+                        // The context bytes of V1 protocols apply to a single response chunk.
+                        self.fork_name = None;
+                        handle_v1_response(self.protocol.message_name, &decoded_buffer)
+                    }
                     Version::V2 => handle_v2_response(
                         self.protocol.message_name,
                         &decoded_buffer,
@@ -426,6 +445,20 @@ fn context_bytes<T: EthSpec>(
                     SignedBeaconBlock::Base { .. } => Some(fork_context.genesis_context_bytes()),
                 };
             }
+            // Light client containers are sent with the digest of the fork at the slot of the
+            // block they describe, which may differ from the current fork.
+            let light_client_slot = match rpc_variant {
+                RPCResponse::LightClientBootstrap(bootstrap) => Some(bootstrap.header.slot),
+                RPCResponse::LightClientUpdatesByRange(update) => Some(update.attested_header.slot),
+                RPCResponse::LightClientFinalityUpdate(update) => Some(update.attested_header.slot),
+                RPCResponse::LightClientOptimisticUpdate(update) => {
+                    Some(update.attested_header.slot)
+                }
+                _ => None,
+            };
+            if let Some(slot) = light_client_slot {
+                return fork_context.to_context_bytes(fork_context.fork_name_at_slot::<T>(slot));
+            }
         }
     }
     None
@@ -484,6 +517,9 @@ fn handle_v1_request<T: EthSpec>(
                 root: Hash256::from_ssz_bytes(decoded_buffer)?,
             },
         ))),
+        Protocol::LightClientUpdatesByRange => Ok(Some(InboundRequest::LightClientUpdatesByRange(
+            LightClientUpdatesByRangeRequest::from_ssz_bytes(decoded_buffer)?,
+        ))),
         // MetaData requests return early from InboundUpgrade and do not reach the decoder.
         // Handle this case just for completeness.
         Protocol::MetaData => {
@@ -495,6 +531,18 @@ fn handle_v1_request<T: EthSpec>(
                 Ok(Some(InboundRequest::MetaData(PhantomData)))
             }
         }
+        // Like MetaData, these requests are empty and return early from InboundUpgrade.
+        Protocol::LightClientFinalityUpdate | Protocol::LightClientOptimisticUpdate => {
+            if !decoded_buffer.is_empty() {
+                Err(RPCError::InternalError(
+                    "Light client update requests shouldn't reach decoder",
+                ))
+            } else if protocol == Protocol::LightClientFinalityUpdate {
+                Ok(Some(InboundRequest::LightClientFinalityUpdate))
+            } else {
+                Ok(Some(InboundRequest::LightClientOptimisticUpdate))
+            }
+        }
     }
 }
 
@@ -558,6 +606,17 @@ fn handle_v1_response<T: EthSpec>(
         Protocol::LightClientBootstrap => Ok(Some(RPCResponse::LightClientBootstrap(
             LightClientBootstrap::from_ssz_bytes(decoded_buffer)?,
         ))),
+        Protocol::LightClientUpdatesByRange => Ok(Some(RPCResponse::LightClientUpdatesByRange(
+            Arc::new(LightClientUpdate::from_ssz_bytes(decoded_buffer)?),
+        ))),
+        Protocol::LightClientFinalityUpdate => Ok(Some(RPCResponse::LightClientFinalityUpdate(
+            Arc::new(LightClientFinalityUpdate::from_ssz_bytes(decoded_buffer)?),
+        ))),
+        Protocol::LightClientOptimisticUpdate => {
+            Ok(Some(RPCResponse::LightClientOptimisticUpdate(Arc::new(
+                LightClientOptimisticUpdate::from_ssz_bytes(decoded_buffer)?,
+            ))))
+        }
     }
 }
 
@@ -660,8 +719,9 @@ mod tests {
     };
     use std::sync::Arc;
     use types::{
-        BeaconBlock, BeaconBlockAltair, BeaconBlockBase, BeaconBlockMerge, EmptyBlock, Epoch,
-        ForkContext, FullPayload, Hash256, Signature, SignedBeaconBlock, Slot,
+        BeaconBlock, BeaconBlockAltair, BeaconBlockBase, BeaconBlockHeader, BeaconBlockMerge,
+        EmptyBlock, Epoch, FixedVector, ForkContext, FullPayload, Hash256, Signature,
+        SignedBeaconBlock, Slot, SyncAggregate, SyncCommittee,
     };
 
     use snap::write::FrameEncoder;
@@ -775,6 +835,46 @@ mod tests {
         })
     }
 
+    fn block_header(slot: u64) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot: Slot::new(slot),
+            proposer_index: 0,
+            parent_root: Hash256::zero(),
+            state_root: Hash256::zero(),
+            body_root: Hash256::zero(),
+        }
+    }
+
+    fn light_client_update(attested_slot: u64) -> LightClientUpdate<Spec> {
+        LightClientUpdate {
+            attested_header: block_header(attested_slot),
+            next_sync_committee: Arc::new(SyncCommittee::temporary().unwrap()),
+            next_sync_committee_branch: FixedVector::from_elem(Hash256::zero()),
+            finalized_header: block_header(0),
+            finality_branch: FixedVector::from_elem(Hash256::zero()),
+            sync_aggregate: SyncAggregate::new(),
+            signature_slot: Slot::new(attested_slot + 1),
+        }
+    }
+
+    fn light_client_finality_update() -> LightClientFinalityUpdate<Spec> {
+        LightClientFinalityUpdate {
+            attested_header: block_header(64),
+            finalized_header: block_header(32),
+            finality_branch: FixedVector::from_elem(Hash256::zero()),
+            sync_aggregate: SyncAggregate::new(),
+            signature_slot: Slot::new(65),
+        }
+    }
+
+    fn light_client_optimistic_update() -> LightClientOptimisticUpdate<Spec> {
+        LightClientOptimisticUpdate {
+            attested_header: block_header(64),
+            sync_aggregate: SyncAggregate::new(),
+            signature_slot: Slot::new(65),
+        }
+    }
+
     /// Encodes the given protocol response as bytes.
     fn encode_response(
         protocol: Protocol,
@@ -897,6 +997,15 @@ mod tests {
                 OutboundRequest::LightClientBootstrap(bootstrap) => {
                     assert_eq!(decoded, InboundRequest::LightClientBootstrap(bootstrap))
                 }
+                OutboundRequest::LightClientUpdatesByRange(req) => {
+                    assert_eq!(decoded, InboundRequest::LightClientUpdatesByRange(req))
+                }
+                OutboundRequest::LightClientFinalityUpdate => {
+                    assert_eq!(decoded, InboundRequest::LightClientFinalityUpdate)
+                }
+                OutboundRequest::LightClientOptimisticUpdate => {
+                    assert_eq!(decoded, InboundRequest::LightClientOptimisticUpdate)
+                }
             }
         }
     }
@@ -1008,6 +1117,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_then_decode_light_client() {
+        let update = Arc::new(light_client_update(64));
+        assert_eq!(
+            encode_then_decode_response(
+                Protocol::LightClientUpdatesByRange,
+                Version::V1,
+                RPCCodedResponse::Success(RPCResponse::LightClientUpdatesByRange(update.clone())),
+                ForkName::Altair,
+            ),
+            Ok(Some(RPCResponse::LightClientUpdatesByRange(update)))
+        );
+
+        let finality_update = Arc::new(light_client_finality_update());
+        assert_eq!(
+            encode_then_decode_response(
+                Protocol::LightClientFinalityUpdate,
+                Version::V1,
+                RPCCodedResponse::Success(RPCResponse::LightClientFinalityUpdate(
+                    finality_update.clone()
+                )),
+                ForkName::Capella,
+            ),
+            Ok(Some(RPCResponse::LightClientFinalityUpdate(
+                finality_update
+            )))
+        );
+
+        let optimistic_update = Arc::new(light_client_optimistic_update());
+        assert_eq!(
+            encode_then_decode_response(
+                Protocol::LightClientOptimisticUpdate,
+                Version::V1,
+                RPCCodedResponse::Success(RPCResponse::LightClientOptimisticUpdate(
+                    optimistic_update.clone()
+                )),
+                ForkName::Merge,
+            ),
+            Ok(Some(RPCResponse::LightClientOptimisticUpdate(
+                optimistic_update
+            )))
+        );
+    }
+
+    /// Light client responses carry the digest of the fork at their attested slot, rather than
+    /// the current fork.
+    #[test]
+    fn test_light_client_context_bytes_use_attested_fork() {
+        let fork_context = fork_context(ForkName::Capella);
+        for (attested_slot, fork_name) in [(64, ForkName::Merge), (8256, ForkName::Capella)] {
+            let update = Arc::new(light_client_update(attested_slot));
+            let encoded = encode_response(
+                Protocol::LightClientUpdatesByRange,
+                Version::V1,
+                RPCCodedResponse::Success(RPCResponse::LightClientUpdatesByRange(update)),
+                ForkName::Capella,
+            )
+            .unwrap();
+            assert_eq!(
+                encoded[..CONTEXT_BYTES_LEN],
+                fork_context.to_context_bytes(fork_name).unwrap()
+            );
+        }
+    }
+
+    /// Each chunk of a light client updates by range response carries its own context bytes.
+    #[test]
+    fn test_decode_multiple_light_client_updates() {
+        let updates = vec![
+            Arc::new(light_client_update(64)),
+            Arc::new(light_client_update(8256)),
+        ];
+
+        let mut buf = BytesMut::new();
+        for update in &updates {
+            let encoded = encode_response(
+                Protocol::LightClientUpdatesByRange,
+                Version::V1,
+                RPCCodedResponse::Success(RPCResponse::LightClientUpdatesByRange(update.clone())),
+                ForkName::Altair,
+            )
+            .unwrap();
+            buf.extend_from_slice(&encoded);
+        }
+
+        let snappy_protocol_id = ProtocolId::new(
+            Protocol::LightClientUpdatesByRange,
+            Version::V1,
+            Encoding::SSZSnappy,
+        );
+        let fork_context = Arc::new(fork_context(ForkName::Altair));
+        let max_packet_size = max_rpc_size(&fork_context);
+        let mut snappy_outbound_codec =
+            SSZSnappyOutboundCodec::<Spec>::new(snappy_protocol_id, max_packet_size, fork_context);
+
+        for update in updates {
+            assert_eq!(
+                snappy_outbound_codec.decode(&mut buf).unwrap(),
+                Some(RPCResponse::LightClientUpdatesByRange(update))
+            );
+        }
+        assert!(buf.is_empty());
+    }
+
     // Test RPCResponse encoding/decoding for V1 messages
     #[test]
     fn test_encode_then_decode_v2() {
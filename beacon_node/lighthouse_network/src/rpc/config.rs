@@ -140,6 +140,9 @@ impl FromStr for OutboundRateLimiterConfig {
                 Protocol::Ping => ping_quota = ping_quota.or(quota),
                 Protocol::MetaData => meta_data_quota = meta_data_quota.or(quota),
                 Protocol::LightClientBootstrap => return Err("Lighthouse does not send LightClientBootstrap requests. Quota should not be set."),
+                Protocol::LightClientUpdatesByRange => return Err("Lighthouse does not send LightClientUpdatesByRange requests. Quota should not be set."),
+                Protocol::LightClientFinalityUpdate => return Err("Lighthouse does not send LightClientFinalityUpdate requests. Quota should not be set."),
+                Protocol::LightClientOptimisticUpdate => return Err("Lighthouse does not send LightClientOptimisticUpdate requests. Quota should not be set."),
             }
         }
         Ok(OutboundRateLimiterConfig {
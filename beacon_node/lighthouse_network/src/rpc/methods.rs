@@ -13,13 +13,18 @@ use std::sync::Arc;
 use strum::IntoStaticStr;
 use superstruct::superstruct;
 use types::{
-    light_client_bootstrap::LightClientBootstrap, Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot,
+    light_client_bootstrap::LightClientBootstrap, Epoch, EthSpec, Hash256,
+    LightClientFinalityUpdate, LightClientOptimisticUpdate, LightClientUpdate, SignedBeaconBlock,
+    Slot,
 };
 
 /// Maximum number of blocks in a single request.
 pub type MaxRequestBlocks = U1024;
 pub const MAX_REQUEST_BLOCKS: u64 = 1024;
 
+/// Maximum number of light client updates in a single request.
+pub const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u64 = 128;
+
 /// Maximum length of error message.
 pub type MaxErrorLen = U256;
 pub const MAX_ERROR_LEN: u64 = 256;
@@ -248,6 +253,16 @@ pub enum RPCResponse<T: EthSpec> {
     /// A response to a get LIGHTCLIENT_BOOTSTRAP request.
     LightClientBootstrap(LightClientBootstrap<T>),
 
+    /// A response to a get LIGHT_CLIENT_UPDATES_BY_RANGE request. A None response signifies the
+    /// end of the batch.
+    LightClientUpdatesByRange(Arc<LightClientUpdate<T>>),
+
+    /// A response to a get LIGHT_CLIENT_FINALITY_UPDATE request.
+    LightClientFinalityUpdate(Arc<LightClientFinalityUpdate<T>>),
+
+    /// A response to a get LIGHT_CLIENT_OPTIMISTIC_UPDATE request.
+    LightClientOptimisticUpdate(Arc<LightClientOptimisticUpdate<T>>),
+
     /// A PONG response to a PING request.
     Pong(Ping),
 
@@ -263,6 +278,9 @@ pub enum ResponseTermination {
 
     /// Blocks by root stream termination.
     BlocksByRoot,
+
+    /// Light client updates by range stream termination.
+    LightClientUpdatesByRange,
 }
 
 /// The structured response containing a result/code indicating success or failure
@@ -284,6 +302,16 @@ pub struct LightClientBootstrapRequest {
     pub root: Hash256,
 }
 
+/// Request the best light client update for a range of sync committee periods.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct LightClientUpdatesByRangeRequest {
+    /// The first sync committee period to request an update for.
+    pub start_period: u64,
+
+    /// The number of periods from the start period.
+    pub count: u64,
+}
+
 /// The code assigned to an erroneous `RPCResponse`.
 #[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
@@ -333,6 +361,9 @@ impl<T: EthSpec> RPCCodedResponse<T> {
                 RPCResponse::Pong(_) => false,
                 RPCResponse::MetaData(_) => false,
                 RPCResponse::LightClientBootstrap(_) => false,
+                RPCResponse::LightClientUpdatesByRange(_) => true,
+                RPCResponse::LightClientFinalityUpdate(_) => false,
+                RPCResponse::LightClientOptimisticUpdate(_) => false,
             },
             RPCCodedResponse::Error(_, _) => true,
             // Stream terminations are part of responses that have chunks
@@ -368,6 +399,9 @@ impl<T: EthSpec> RPCResponse<T> {
             RPCResponse::Pong(_) => Protocol::Ping,
             RPCResponse::MetaData(_) => Protocol::MetaData,
             RPCResponse::LightClientBootstrap(_) => Protocol::LightClientBootstrap,
+            RPCResponse::LightClientUpdatesByRange(_) => Protocol::LightClientUpdatesByRange,
+            RPCResponse::LightClientFinalityUpdate(_) => Protocol::LightClientFinalityUpdate,
+            RPCResponse::LightClientOptimisticUpdate(_) => Protocol::LightClientOptimisticUpdate,
         }
     }
 }
@@ -406,6 +440,21 @@ impl<T: EthSpec> std::fmt::Display for RPCResponse<T> {
             RPCResponse::LightClientBootstrap(bootstrap) => {
                 write!(f, "LightClientBootstrap Slot: {}", bootstrap.header.slot)
             }
+            RPCResponse::LightClientUpdatesByRange(update) => write!(
+                f,
+                "LightClientUpdatesByRange: Attested slot: {}",
+                update.attested_header.slot
+            ),
+            RPCResponse::LightClientFinalityUpdate(update) => write!(
+                f,
+                "LightClientFinalityUpdate: Finalized slot: {}",
+                update.finalized_header.slot
+            ),
+            RPCResponse::LightClientOptimisticUpdate(update) => write!(
+                f,
+                "LightClientOptimisticUpdate: Attested slot: {}",
+                update.attested_header.slot
+            ),
         }
     }
 }
@@ -442,6 +491,16 @@ impl std::fmt::Display for BlocksByRangeRequest {
     }
 }
 
+impl std::fmt::Display for LightClientUpdatesByRangeRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Start Period: {}, Count: {}",
+            self.start_period, self.count
+        )
+    }
+}
+
 impl std::fmt::Display for OldBlocksByRangeRequest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -39,6 +39,9 @@ pub enum OutboundRequest<TSpec: EthSpec> {
     BlocksByRange(OldBlocksByRangeRequest),
     BlocksByRoot(BlocksByRootRequest),
     LightClientBootstrap(LightClientBootstrapRequest),
+    LightClientUpdatesByRange(LightClientUpdatesByRangeRequest),
+    LightClientFinalityUpdate,
+    LightClientOptimisticUpdate,
     Ping(Ping),
     MetaData(PhantomData<TSpec>),
 }
@@ -88,7 +91,10 @@ impl<TSpec: EthSpec> OutboundRequest<TSpec> {
             // Note: This match arm is technically unreachable as we only respond to light client requests
             // that we generate from the beacon state.
             // We do not make light client rpc requests from the beacon node
-            OutboundRequest::LightClientBootstrap(_) => vec![],
+            OutboundRequest::LightClientBootstrap(_)
+            | OutboundRequest::LightClientUpdatesByRange(_)
+            | OutboundRequest::LightClientFinalityUpdate
+            | OutboundRequest::LightClientOptimisticUpdate => vec![],
         }
     }
     /* These functions are used in the handler for stream management */
@@ -103,6 +109,13 @@ impl<TSpec: EthSpec> OutboundRequest<TSpec> {
             OutboundRequest::Ping(_) => 1,
             OutboundRequest::MetaData(_) => 1,
             OutboundRequest::LightClientBootstrap(_) => 1,
+            // Responders serve at most `MAX_REQUEST_LIGHT_CLIENT_UPDATES` updates, regardless of
+            // the requested count.
+            OutboundRequest::LightClientUpdatesByRange(req) => {
+                std::cmp::min(req.count, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+            }
+            OutboundRequest::LightClientFinalityUpdate => 1,
+            OutboundRequest::LightClientOptimisticUpdate => 1,
         }
     }
 
@@ -116,6 +129,9 @@ impl<TSpec: EthSpec> OutboundRequest<TSpec> {
             OutboundRequest::Ping(_) => Protocol::Ping,
             OutboundRequest::MetaData(_) => Protocol::MetaData,
             OutboundRequest::LightClientBootstrap(_) => Protocol::LightClientBootstrap,
+            OutboundRequest::LightClientUpdatesByRange(_) => Protocol::LightClientUpdatesByRange,
+            OutboundRequest::LightClientFinalityUpdate => Protocol::LightClientFinalityUpdate,
+            OutboundRequest::LightClientOptimisticUpdate => Protocol::LightClientOptimisticUpdate,
         }
     }
 
@@ -127,7 +143,12 @@ impl<TSpec: EthSpec> OutboundRequest<TSpec> {
             // variants that have `multiple_responses()` can have values.
             OutboundRequest::BlocksByRange(_) => ResponseTermination::BlocksByRange,
             OutboundRequest::BlocksByRoot(_) => ResponseTermination::BlocksByRoot,
+            OutboundRequest::LightClientUpdatesByRange(_) => {
+                ResponseTermination::LightClientUpdatesByRange
+            }
             OutboundRequest::LightClientBootstrap(_) => unreachable!(),
+            OutboundRequest::LightClientFinalityUpdate => unreachable!(),
+            OutboundRequest::LightClientOptimisticUpdate => unreachable!(),
             OutboundRequest::Status(_) => unreachable!(),
             OutboundRequest::Goodbye(_) => unreachable!(),
             OutboundRequest::Ping(_) => unreachable!(),
@@ -188,6 +209,15 @@ impl<TSpec: EthSpec> std::fmt::Display for OutboundRequest<TSpec> {
             OutboundRequest::LightClientBootstrap(bootstrap) => {
                 write!(f, "Lightclient Bootstrap: {}", bootstrap.root)
             }
+            OutboundRequest::LightClientUpdatesByRange(req) => {
+                write!(f, "Lightclient Updates by range: {}", req)
+            }
+            OutboundRequest::LightClientFinalityUpdate => {
+                write!(f, "Lightclient Finality Update request")
+            }
+            OutboundRequest::LightClientOptimisticUpdate => {
+                write!(f, "Lightclient Optimistic Update request")
+            }
         }
     }
 }
@@ -21,9 +21,10 @@ use tokio_util::{
     compat::{Compat, FuturesAsyncReadCompatExt},
 };
 use types::{
-    BeaconBlock, BeaconBlockAltair, BeaconBlockBase, BeaconBlockCapella, BeaconBlockMerge,
-    EmptyBlock, EthSpec, ForkContext, ForkName, Hash256, MainnetEthSpec, Signature,
-    SignedBeaconBlock,
+    light_client_bootstrap::LightClientBootstrap, BeaconBlock, BeaconBlockAltair, BeaconBlockBase,
+    BeaconBlockCapella, BeaconBlockMerge, EmptyBlock, EthSpec, ForkContext, ForkName, Hash256,
+    LightClientFinalityUpdate, LightClientOptimisticUpdate, LightClientUpdate, MainnetEthSpec,
+    Signature, SignedBeaconBlock,
 };
 
 lazy_static! {
@@ -177,6 +178,15 @@ pub enum Protocol {
     /// The `LightClientBootstrap` protocol name.
     #[strum(serialize = "light_client_bootstrap")]
     LightClientBootstrap,
+    /// The `LightClientUpdatesByRange` protocol name.
+    #[strum(serialize = "light_client_updates_by_range")]
+    LightClientUpdatesByRange,
+    /// The `LightClientFinalityUpdate` protocol name.
+    #[strum(serialize = "light_client_finality_update")]
+    LightClientFinalityUpdate,
+    /// The `LightClientOptimisticUpdate` protocol name.
+    #[strum(serialize = "light_client_optimistic_update")]
+    LightClientOptimisticUpdate,
 }
 
 /// RPC Versions
@@ -240,11 +250,28 @@ impl<TSpec: EthSpec> UpgradeInfo for RPCProtocol<TSpec> {
             ProtocolId::new(Protocol::MetaData, Version::V1, Encoding::SSZSnappy),
         ];
         if self.enable_light_client_server {
-            supported_protocols.push(ProtocolId::new(
-                Protocol::LightClientBootstrap,
-                Version::V1,
-                Encoding::SSZSnappy,
-            ));
+            supported_protocols.extend([
+                ProtocolId::new(
+                    Protocol::LightClientBootstrap,
+                    Version::V1,
+                    Encoding::SSZSnappy,
+                ),
+                ProtocolId::new(
+                    Protocol::LightClientUpdatesByRange,
+                    Version::V1,
+                    Encoding::SSZSnappy,
+                ),
+                ProtocolId::new(
+                    Protocol::LightClientFinalityUpdate,
+                    Version::V1,
+                    Encoding::SSZSnappy,
+                ),
+                ProtocolId::new(
+                    Protocol::LightClientOptimisticUpdate,
+                    Version::V1,
+                    Encoding::SSZSnappy,
+                ),
+            ]);
         }
         supported_protocols
     }
@@ -312,7 +339,13 @@ impl ProtocolId {
                 <LightClientBootstrapRequest as Encode>::ssz_fixed_len(),
                 <LightClientBootstrapRequest as Encode>::ssz_fixed_len(),
             ),
-            Protocol::MetaData => RpcLimits::new(0, 0), // Metadata requests are empty
+            Protocol::LightClientUpdatesByRange => RpcLimits::new(
+                <LightClientUpdatesByRangeRequest as Encode>::ssz_fixed_len(),
+                <LightClientUpdatesByRangeRequest as Encode>::ssz_fixed_len(),
+            ),
+            Protocol::MetaData
+            | Protocol::LightClientFinalityUpdate
+            | Protocol::LightClientOptimisticUpdate => RpcLimits::new(0, 0), // These requests are empty
         }
     }
 
@@ -335,8 +368,20 @@ impl ProtocolId {
                 <MetaDataV2<T> as Encode>::ssz_fixed_len(),
             ),
             Protocol::LightClientBootstrap => RpcLimits::new(
-                <LightClientBootstrapRequest as Encode>::ssz_fixed_len(),
-                <LightClientBootstrapRequest as Encode>::ssz_fixed_len(),
+                <LightClientBootstrap<T> as Encode>::ssz_fixed_len(),
+                <LightClientBootstrap<T> as Encode>::ssz_fixed_len(),
+            ),
+            Protocol::LightClientUpdatesByRange => RpcLimits::new(
+                <LightClientUpdate<T> as Encode>::ssz_fixed_len(),
+                <LightClientUpdate<T> as Encode>::ssz_fixed_len(),
+            ),
+            Protocol::LightClientFinalityUpdate => RpcLimits::new(
+                <LightClientFinalityUpdate<T> as Encode>::ssz_fixed_len(),
+                <LightClientFinalityUpdate<T> as Encode>::ssz_fixed_len(),
+            ),
+            Protocol::LightClientOptimisticUpdate => RpcLimits::new(
+                <LightClientOptimisticUpdate<T> as Encode>::ssz_fixed_len(),
+                <LightClientOptimisticUpdate<T> as Encode>::ssz_fixed_len(),
             ),
         }
     }
@@ -349,7 +394,10 @@ impl ProtocolId {
                 Version::V2 => true,
                 Version::V1 => false,
             },
-            Protocol::LightClientBootstrap => match self.version {
+            Protocol::LightClientBootstrap
+            | Protocol::LightClientUpdatesByRange
+            | Protocol::LightClientFinalityUpdate
+            | Protocol::LightClientOptimisticUpdate => match self.version {
                 Version::V2 | Version::V1 => true,
             },
             Protocol::Goodbye | Protocol::Ping | Protocol::Status | Protocol::MetaData => false,
@@ -418,9 +466,15 @@ where
 
             let socket = Framed::new(Box::pin(timed_socket), codec);
 
-            // MetaData requests should be empty, return the stream
+            // MetaData and light client update requests should be empty, return the stream
             match protocol_name {
                 Protocol::MetaData => Ok((InboundRequest::MetaData(PhantomData), socket)),
+                Protocol::LightClientFinalityUpdate => {
+                    Ok((InboundRequest::LightClientFinalityUpdate, socket))
+                }
+                Protocol::LightClientOptimisticUpdate => {
+                    Ok((InboundRequest::LightClientOptimisticUpdate, socket))
+                }
                 _ => {
                     match tokio::time::timeout(
                         Duration::from_secs(REQUEST_TIMEOUT),
@@ -447,6 +501,9 @@ pub enum InboundRequest<TSpec: EthSpec> {
     BlocksByRange(OldBlocksByRangeRequest),
     BlocksByRoot(BlocksByRootRequest),
     LightClientBootstrap(LightClientBootstrapRequest),
+    LightClientUpdatesByRange(LightClientUpdatesByRangeRequest),
+    LightClientFinalityUpdate,
+    LightClientOptimisticUpdate,
     Ping(Ping),
     MetaData(PhantomData<TSpec>),
 }
@@ -465,6 +522,13 @@ impl<TSpec: EthSpec> InboundRequest<TSpec> {
             InboundRequest::Ping(_) => 1,
             InboundRequest::MetaData(_) => 1,
             InboundRequest::LightClientBootstrap(_) => 1,
+            // Responders serve at most `MAX_REQUEST_LIGHT_CLIENT_UPDATES` updates, regardless of
+            // the requested count.
+            InboundRequest::LightClientUpdatesByRange(req) => {
+                std::cmp::min(req.count, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+            }
+            InboundRequest::LightClientFinalityUpdate => 1,
+            InboundRequest::LightClientOptimisticUpdate => 1,
         }
     }
 
@@ -478,6 +542,9 @@ impl<TSpec: EthSpec> InboundRequest<TSpec> {
             InboundRequest::Ping(_) => Protocol::Ping,
             InboundRequest::MetaData(_) => Protocol::MetaData,
             InboundRequest::LightClientBootstrap(_) => Protocol::LightClientBootstrap,
+            InboundRequest::LightClientUpdatesByRange(_) => Protocol::LightClientUpdatesByRange,
+            InboundRequest::LightClientFinalityUpdate => Protocol::LightClientFinalityUpdate,
+            InboundRequest::LightClientOptimisticUpdate => Protocol::LightClientOptimisticUpdate,
         }
     }
 
@@ -489,11 +556,16 @@ impl<TSpec: EthSpec> InboundRequest<TSpec> {
             // variants that have `multiple_responses()` can have values.
             InboundRequest::BlocksByRange(_) => ResponseTermination::BlocksByRange,
             InboundRequest::BlocksByRoot(_) => ResponseTermination::BlocksByRoot,
+            InboundRequest::LightClientUpdatesByRange(_) => {
+                ResponseTermination::LightClientUpdatesByRange
+            }
             InboundRequest::Status(_) => unreachable!(),
             InboundRequest::Goodbye(_) => unreachable!(),
             InboundRequest::Ping(_) => unreachable!(),
             InboundRequest::MetaData(_) => unreachable!(),
             InboundRequest::LightClientBootstrap(_) => unreachable!(),
+            InboundRequest::LightClientFinalityUpdate => unreachable!(),
+            InboundRequest::LightClientOptimisticUpdate => unreachable!(),
         }
     }
 }
@@ -600,6 +672,15 @@ impl<TSpec: EthSpec> std::fmt::Display for InboundRequest<TSpec> {
             InboundRequest::LightClientBootstrap(bootstrap) => {
                 write!(f, "LightClientBootstrap: {}", bootstrap.root)
             }
+            InboundRequest::LightClientUpdatesByRange(req) => {
+                write!(f, "LightClientUpdatesByRange: {}", req)
+            }
+            InboundRequest::LightClientFinalityUpdate => {
+                write!(f, "LightClientFinalityUpdate request")
+            }
+            InboundRequest::LightClientOptimisticUpdate => {
+                write!(f, "LightClientOptimisticUpdate request")
+            }
         }
     }
 }
@@ -69,10 +69,16 @@ impl<Id: ReqId, TSpec: EthSpec> SelfRateLimiter<Id, TSpec> {
             .set_quota(Protocol::Goodbye, goodbye_quota)
             .set_quota(Protocol::BlocksByRange, blocks_by_range_quota)
             .set_quota(Protocol::BlocksByRoot, blocks_by_root_quota)
-            // Manually set the LightClientBootstrap quota, since we use the same rate limiter for
-            // inbound and outbound requests, and the LightClientBootstrap is an only inbound
-            // protocol.
+            // Manually set the light client quotas, since we use the same rate limiter for
+            // inbound and outbound requests, and the light client protocols are only inbound
+            // protocols.
             .one_every(Protocol::LightClientBootstrap, Duration::from_secs(10))
+            .one_every(Protocol::LightClientUpdatesByRange, Duration::from_secs(10))
+            .one_every(Protocol::LightClientFinalityUpdate, Duration::from_secs(10))
+            .one_every(
+                Protocol::LightClientOptimisticUpdate,
+                Duration::from_secs(10),
+            )
             .build()?;
 
         Ok(SelfRateLimiter {
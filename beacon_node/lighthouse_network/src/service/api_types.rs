@@ -2,12 +2,16 @@ use std::sync::Arc;
 
 use libp2p::core::connection::ConnectionId;
 use types::light_client_bootstrap::LightClientBootstrap;
-use types::{EthSpec, SignedBeaconBlock};
+use types::{
+    EthSpec, LightClientFinalityUpdate, LightClientOptimisticUpdate, LightClientUpdate,
+    SignedBeaconBlock,
+};
 
 use crate::rpc::{
     methods::{
         BlocksByRangeRequest, BlocksByRootRequest, LightClientBootstrapRequest,
-        OldBlocksByRangeRequest, RPCCodedResponse, RPCResponse, ResponseTermination, StatusMessage,
+        LightClientUpdatesByRangeRequest, OldBlocksByRangeRequest, RPCCodedResponse, RPCResponse,
+        ResponseTermination, StatusMessage,
     },
     OutboundRequest, SubstreamId,
 };
@@ -37,6 +41,12 @@ pub enum Request {
     BlocksByRoot(BlocksByRootRequest),
     // light client bootstrap request
     LightClientBootstrap(LightClientBootstrapRequest),
+    // light client updates by range request
+    LightClientUpdatesByRange(LightClientUpdatesByRangeRequest),
+    // light client finality update request
+    LightClientFinalityUpdate,
+    // light client optimistic update request
+    LightClientOptimisticUpdate,
 }
 
 impl<TSpec: EthSpec> std::convert::From<Request> for OutboundRequest<TSpec> {
@@ -51,6 +61,9 @@ impl<TSpec: EthSpec> std::convert::From<Request> for OutboundRequest<TSpec> {
                 })
             }
             Request::LightClientBootstrap(b) => OutboundRequest::LightClientBootstrap(b),
+            Request::LightClientUpdatesByRange(r) => OutboundRequest::LightClientUpdatesByRange(r),
+            Request::LightClientFinalityUpdate => OutboundRequest::LightClientFinalityUpdate,
+            Request::LightClientOptimisticUpdate => OutboundRequest::LightClientOptimisticUpdate,
             Request::Status(s) => OutboundRequest::Status(s),
         }
     }
@@ -72,6 +85,13 @@ pub enum Response<TSpec: EthSpec> {
     BlocksByRoot(Option<Arc<SignedBeaconBlock<TSpec>>>),
     /// A response to a LightClientUpdate request.
     LightClientBootstrap(LightClientBootstrap<TSpec>),
+    /// A response to a LightClientUpdatesByRange request. A None response signals the end of the
+    /// batch.
+    LightClientUpdatesByRange(Option<Arc<LightClientUpdate<TSpec>>>),
+    /// A response to a LightClientFinalityUpdate request.
+    LightClientFinalityUpdate(Arc<LightClientFinalityUpdate<TSpec>>),
+    /// A response to a LightClientOptimisticUpdate request.
+    LightClientOptimisticUpdate(Arc<LightClientOptimisticUpdate<TSpec>>),
 }
 
 impl<TSpec: EthSpec> std::convert::From<Response<TSpec>> for RPCCodedResponse<TSpec> {
@@ -89,6 +109,18 @@ impl<TSpec: EthSpec> std::convert::From<Response<TSpec>> for RPCCodedResponse<TS
             Response::LightClientBootstrap(b) => {
                 RPCCodedResponse::Success(RPCResponse::LightClientBootstrap(b))
             }
+            Response::LightClientUpdatesByRange(r) => match r {
+                Some(u) => RPCCodedResponse::Success(RPCResponse::LightClientUpdatesByRange(u)),
+                None => RPCCodedResponse::StreamTermination(
+                    ResponseTermination::LightClientUpdatesByRange,
+                ),
+            },
+            Response::LightClientFinalityUpdate(u) => {
+                RPCCodedResponse::Success(RPCResponse::LightClientFinalityUpdate(u))
+            }
+            Response::LightClientOptimisticUpdate(u) => {
+                RPCCodedResponse::Success(RPCResponse::LightClientOptimisticUpdate(u))
+            }
         }
     }
 }
@@ -45,7 +45,7 @@ use beacon_chain::{BeaconChain, BeaconChainTypes, GossipVerifiedBlock, NotifyExe
 use derivative::Derivative;
 use futures::stream::{Stream, StreamExt};
 use futures::task::Poll;
-use lighthouse_network::rpc::LightClientBootstrapRequest;
+use lighthouse_network::rpc::{LightClientBootstrapRequest, LightClientUpdatesByRangeRequest};
 use lighthouse_network::{
     rpc::{BlocksByRangeRequest, BlocksByRootRequest, StatusMessage},
     Client, MessageId, NetworkGlobals, PeerId, PeerRequestId,
@@ -183,6 +183,14 @@ const MAX_BLS_TO_EXECUTION_CHANGE_QUEUE_LEN: usize = 16_384;
 /// will be stored before we start dropping them.
 const MAX_LIGHT_CLIENT_BOOTSTRAP_QUEUE_LEN: usize = 1_024;
 
+/// The maximum number of queued `LightClientUpdatesByRangeRequest` objects received from the
+/// network RPC that will be stored before we start dropping them.
+const MAX_LIGHT_CLIENT_UPDATES_BY_RANGE_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued `LightClientFinalityUpdate` and `LightClientOptimisticUpdate`
+/// requests received from the network RPC that will be stored before we start dropping them.
+const MAX_LIGHT_CLIENT_UPDATE_REQUEST_QUEUE_LEN: usize = 1_024;
+
 /// The name of the manager tokio task.
 const MANAGER_TASK_NAME: &str = "beacon_processor_manager";
 
@@ -226,6 +234,9 @@ pub const STATUS_PROCESSING: &str = "status_processing";
 pub const BLOCKS_BY_RANGE_REQUEST: &str = "blocks_by_range_request";
 pub const BLOCKS_BY_ROOTS_REQUEST: &str = "blocks_by_roots_request";
 pub const LIGHT_CLIENT_BOOTSTRAP_REQUEST: &str = "light_client_bootstrap";
+pub const LIGHT_CLIENT_UPDATES_BY_RANGE_REQUEST: &str = "light_client_updates_by_range";
+pub const LIGHT_CLIENT_FINALITY_UPDATE_REQUEST: &str = "light_client_finality_update_request";
+pub const LIGHT_CLIENT_OPTIMISTIC_UPDATE_REQUEST: &str = "light_client_optimistic_update_request";
 pub const UNKNOWN_BLOCK_ATTESTATION: &str = "unknown_block_attestation";
 pub const UNKNOWN_BLOCK_AGGREGATE: &str = "unknown_block_aggregate";
 pub const UNKNOWN_LIGHT_CLIENT_UPDATE: &str = "unknown_light_client_update";
@@ -658,6 +669,47 @@ impl<T: BeaconChainTypes> WorkEvent<T> {
         }
     }
 
+    /// Create a new work event to process `LightClientUpdatesByRange`s from the RPC network.
+    pub fn lightclient_updates_by_range_request(
+        peer_id: PeerId,
+        request_id: PeerRequestId,
+        request: LightClientUpdatesByRangeRequest,
+    ) -> Self {
+        Self {
+            drop_during_sync: true,
+            work: Work::LightClientUpdatesByRangeRequest {
+                peer_id,
+                request_id,
+                request,
+            },
+        }
+    }
+
+    /// Create a new work event to process `LightClientFinalityUpdate`s from the RPC network.
+    pub fn lightclient_finality_update_request(peer_id: PeerId, request_id: PeerRequestId) -> Self {
+        Self {
+            drop_during_sync: true,
+            work: Work::LightClientFinalityUpdateRequest {
+                peer_id,
+                request_id,
+            },
+        }
+    }
+
+    /// Create a new work event to process `LightClientOptimisticUpdate`s from the RPC network.
+    pub fn lightclient_optimistic_update_request(
+        peer_id: PeerId,
+        request_id: PeerRequestId,
+    ) -> Self {
+        Self {
+            drop_during_sync: true,
+            work: Work::LightClientOptimisticUpdateRequest {
+                peer_id,
+                request_id,
+            },
+        }
+    }
+
     /// Get a `str` representation of the type of work this `WorkEvent` contains.
     pub fn work_type(&self) -> &'static str {
         self.work.str_id()
@@ -880,6 +932,19 @@ pub enum Work<T: BeaconChainTypes> {
         request_id: PeerRequestId,
         request: LightClientBootstrapRequest,
     },
+    LightClientUpdatesByRangeRequest {
+        peer_id: PeerId,
+        request_id: PeerRequestId,
+        request: LightClientUpdatesByRangeRequest,
+    },
+    LightClientFinalityUpdateRequest {
+        peer_id: PeerId,
+        request_id: PeerRequestId,
+    },
+    LightClientOptimisticUpdateRequest {
+        peer_id: PeerId,
+        request_id: PeerRequestId,
+    },
 }
 
 impl<T: BeaconChainTypes> Work<T> {
@@ -909,6 +974,11 @@ impl<T: BeaconChainTypes> Work<T> {
             Work::BlocksByRangeRequest { .. } => BLOCKS_BY_RANGE_REQUEST,
             Work::BlocksByRootsRequest { .. } => BLOCKS_BY_ROOTS_REQUEST,
             Work::LightClientBootstrapRequest { .. } => LIGHT_CLIENT_BOOTSTRAP_REQUEST,
+            Work::LightClientUpdatesByRangeRequest { .. } => LIGHT_CLIENT_UPDATES_BY_RANGE_REQUEST,
+            Work::LightClientFinalityUpdateRequest { .. } => LIGHT_CLIENT_FINALITY_UPDATE_REQUEST,
+            Work::LightClientOptimisticUpdateRequest { .. } => {
+                LIGHT_CLIENT_OPTIMISTIC_UPDATE_REQUEST
+            }
             Work::UnknownBlockAttestation { .. } => UNKNOWN_BLOCK_ATTESTATION,
             Work::UnknownBlockAggregate { .. } => UNKNOWN_BLOCK_AGGREGATE,
             Work::GossipBlsToExecutionChange { .. } => GOSSIP_BLS_TO_EXECUTION_CHANGE,
@@ -1065,6 +1135,9 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
             FifoQueue::new(MAX_BLS_TO_EXECUTION_CHANGE_QUEUE_LEN);
 
         let mut lcbootstrap_queue = FifoQueue::new(MAX_LIGHT_CLIENT_BOOTSTRAP_QUEUE_LEN);
+        let mut lcupdates_queue = FifoQueue::new(MAX_LIGHT_CLIENT_UPDATES_BY_RANGE_QUEUE_LEN);
+        let mut lcfinality_queue = FifoQueue::new(MAX_LIGHT_CLIENT_UPDATE_REQUEST_QUEUE_LEN);
+        let mut lcoptimistic_queue = FifoQueue::new(MAX_LIGHT_CLIENT_UPDATE_REQUEST_QUEUE_LEN);
 
         let chain = match self.beacon_chain.upgrade() {
             Some(chain) => chain,
@@ -1351,6 +1424,12 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         // This statement should always be the final else statement.
                         } else if let Some(item) = lcbootstrap_queue.pop() {
                             self.spawn_worker(item, toolbox);
+                        } else if let Some(item) = lcoptimistic_queue.pop() {
+                            self.spawn_worker(item, toolbox);
+                        } else if let Some(item) = lcfinality_queue.pop() {
+                            self.spawn_worker(item, toolbox);
+                        } else if let Some(item) = lcupdates_queue.pop() {
+                            self.spawn_worker(item, toolbox);
                         } else {
                             // Let the journal know that a worker is freed and there's nothing else
                             // for it to do.
@@ -1460,6 +1539,15 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                             Work::LightClientBootstrapRequest { .. } => {
                                 lcbootstrap_queue.push(work, work_id, &self.log)
                             }
+                            Work::LightClientUpdatesByRangeRequest { .. } => {
+                                lcupdates_queue.push(work, work_id, &self.log)
+                            }
+                            Work::LightClientFinalityUpdateRequest { .. } => {
+                                lcfinality_queue.push(work, work_id, &self.log)
+                            }
+                            Work::LightClientOptimisticUpdateRequest { .. } => {
+                                lcoptimistic_queue.push(work, work_id, &self.log)
+                            }
                             Work::UnknownBlockAttestation { .. } => {
                                 unknown_block_attestation_queue.push(work)
                             }
@@ -1900,6 +1988,31 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
             } => task_spawner.spawn_blocking(move || {
                 worker.handle_light_client_bootstrap(peer_id, request_id, request)
             }),
+            /*
+             * Processing of lightclient updates by range requests from other peers.
+             */
+            Work::LightClientUpdatesByRangeRequest {
+                peer_id,
+                request_id,
+                request,
+            } => task_spawner.spawn_blocking(move || {
+                worker.handle_light_client_updates_by_range(peer_id, request_id, request)
+            }),
+            /*
+             * Processing of lightclient finality and optimistic update requests from other peers.
+             */
+            Work::LightClientFinalityUpdateRequest {
+                peer_id,
+                request_id,
+            } => task_spawner.spawn_blocking(move || {
+                worker.handle_light_client_finality_update(peer_id, request_id)
+            }),
+            Work::LightClientOptimisticUpdateRequest {
+                peer_id,
+                request_id,
+            } => task_spawner.spawn_blocking(move || {
+                worker.handle_light_client_optimistic_update(peer_id, request_id)
+            }),
             Work::UnknownBlockAttestation {
                 message_id,
                 peer_id,
@@ -11,7 +11,9 @@ use beacon_chain::{
     BeaconChainError, BeaconChainTypes, BlockError, CountUnrealized, ForkChoiceError,
     GossipVerifiedBlock, NotifyExecutionLayer,
 };
-use lighthouse_network::{Client, MessageAcceptance, MessageId, PeerAction, PeerId, ReportSource};
+use lighthouse_network::{
+    Client, MessageAcceptance, MessageId, PeerAction, PeerId, PubsubMessage, ReportSource,
+};
 use operation_pool::ReceivedPreCapella;
 use slog::{crit, debug, error, info, trace, warn};
 use slot_clock::SlotClock;
@@ -23,8 +25,8 @@ use tokio::sync::mpsc;
 use types::{
     Attestation, AttesterSlashing, EthSpec, Hash256, IndexedAttestation, LightClientFinalityUpdate,
     LightClientOptimisticUpdate, ProposerSlashing, SignedAggregateAndProof, SignedBeaconBlock,
-    SignedBlsToExecutionChange, SignedContributionAndProof, SignedVoluntaryExit, Slot, SubnetId,
-    SyncCommitteeMessage, SyncSubnetId,
+    SignedBeaconBlockHeader, SignedBlsToExecutionChange, SignedContributionAndProof,
+    SignedVoluntaryExit, Slot, SubnetId, SyncCommitteeMessage, SyncSubnetId,
 };
 
 use super::{
@@ -698,6 +700,39 @@ impl<T: BeaconChainTypes> Worker<T> {
         }
     }
 
+    /// Form a proposer slashing if `block` conflicts with a block previously observed from the
+    /// same proposer at the same slot, and publish it if configured to do so.
+    fn process_proposer_equivocation(
+        &self,
+        block_header: &SignedBeaconBlockHeader,
+        block_root: Hash256,
+    ) {
+        match self.chain.import_proposer_equivocation(block_header) {
+            Ok(Some(proposer_slashing)) => {
+                if self.chain.config.publish_proposer_equivocations {
+                    debug!(
+                        self.log,
+                        "Publishing proposer slashing for equivocation";
+                        "block_root" => ?block_root,
+                        "proposer_index" => block_header.message.proposer_index,
+                    );
+                    self.send_network_message(NetworkMessage::Publish {
+                        messages: vec![PubsubMessage::ProposerSlashing(Box::new(
+                            proposer_slashing,
+                        ))],
+                    });
+                }
+            }
+            Ok(None) => (),
+            Err(e) => debug!(
+                self.log,
+                "Unable to process proposer equivocation";
+                "error" => ?e,
+                "block_root" => ?block_root,
+            ),
+        }
+    }
+
     /// Process the beacon block received from the gossip network and
     /// if it passes gossip propagation criteria, tell the network thread to forward it.
     ///
@@ -729,10 +764,12 @@ impl<T: BeaconChainTypes> Worker<T> {
             .verify_block_for_gossip(block.clone())
             .await;
 
-        let block_root = if let Ok(verified_block) = &verification_result {
-            verified_block.block_root
-        } else {
-            block.canonical_root()
+        // A repeat proposal carries the header computed during verification, so the block root
+        // can be computed without hashing the body again.
+        let block_root = match &verification_result {
+            Ok(verified_block) => verified_block.block_root,
+            Err(BlockError::RepeatProposal { header, .. }) => header.message.canonical_root(),
+            Err(_) => block.canonical_root(),
         };
 
         // Write the time the block was observed into delay cache.
@@ -805,6 +842,9 @@ impl<T: BeaconChainTypes> Worker<T> {
             | Err(e @ BlockError::NotFinalizedDescendant { .. }) => {
                 debug!(self.log, "Could not verify block for gossip. Ignoring the block";
                             "error" => %e);
+                if let BlockError::RepeatProposal { header, .. } = &e {
+                    self.process_proposer_equivocation(header, block_root);
+                }
                 // Prevent recurring behaviour by penalizing the peer slightly.
                 self.gossip_penalize_peer(
                     peer_id,
@@ -9,6 +9,7 @@ use lighthouse_network::rpc::*;
 use lighthouse_network::{PeerId, PeerRequestId, ReportSource, Response, SyncInfo};
 use slog::{debug, error, warn};
 use slot_clock::SlotClock;
+use std::sync::Arc;
 use task_executor::TaskExecutor;
 use tokio_stream::StreamExt;
 use types::{light_client_bootstrap::LightClientBootstrap, Epoch, EthSpec, Hash256, Slot};
@@ -281,6 +282,110 @@ impl<T: BeaconChainTypes> Worker<T> {
         )
     }
 
+    /// Handle a `LightClientUpdatesByRange` request from the peer.
+    pub fn handle_light_client_updates_by_range(
+        self,
+        peer_id: PeerId,
+        request_id: PeerRequestId,
+        mut req: LightClientUpdatesByRangeRequest,
+    ) {
+        debug!(self.log, "Received LightClientUpdatesByRange Request";
+            "peer_id" => %peer_id,
+            "start_period" => req.start_period,
+            "count" => req.count,
+        );
+
+        // Should not send more than max request light client updates
+        if req.count > MAX_REQUEST_LIGHT_CLIENT_UPDATES {
+            req.count = MAX_REQUEST_LIGHT_CLIENT_UPDATES;
+        }
+
+        let updates = match self
+            .chain
+            .get_light_client_updates(req.start_period, req.count)
+        {
+            Ok(updates) => updates,
+            Err(e) => {
+                error!(self.log, "Unable to load light client updates";
+                    "peer_id" => %peer_id,
+                    "start_period" => req.start_period,
+                    "error" => ?e,
+                );
+                self.send_error_response(
+                    peer_id,
+                    RPCResponseErrorCode::ServerError,
+                    "Database error".into(),
+                    request_id,
+                );
+                return;
+            }
+        };
+
+        debug!(self.log, "LightClientUpdatesByRange Response processed";
+            "peer" => %peer_id,
+            "start_period" => req.start_period,
+            "requested" => req.count,
+            "returned" => updates.len(),
+        );
+
+        for update in updates {
+            self.send_response(
+                peer_id,
+                Response::LightClientUpdatesByRange(Some(Arc::new(update))),
+                request_id,
+            );
+        }
+
+        // send stream termination
+        self.send_response(
+            peer_id,
+            Response::LightClientUpdatesByRange(None),
+            request_id,
+        );
+    }
+
+    /// Handle a `LightClientFinalityUpdate` request from the peer.
+    pub fn handle_light_client_finality_update(self, peer_id: PeerId, request_id: PeerRequestId) {
+        match self
+            .chain
+            .light_client_server_cache
+            .get_latest_finality_update()
+        {
+            Some(update) => self.send_response(
+                peer_id,
+                Response::LightClientFinalityUpdate(Arc::new(update)),
+                request_id,
+            ),
+            None => self.send_error_response(
+                peer_id,
+                RPCResponseErrorCode::ResourceUnavailable,
+                "Latest finality update not available".into(),
+                request_id,
+            ),
+        }
+    }
+
+    /// Handle a `LightClientOptimisticUpdate` request from the peer.
+    pub fn handle_light_client_optimistic_update(self, peer_id: PeerId, request_id: PeerRequestId) {
+        match self
+            .chain
+            .light_client_server_cache
+            .get_latest_optimistic_update()
+        {
+            Some(update) => self.send_response(
+                peer_id,
+                Response::LightClientOptimisticUpdate(Arc::new(update)),
+                request_id,
+            ),
+            None => self.send_error_response(
+                peer_id,
+                RPCResponseErrorCode::ResourceUnavailable,
+                "Latest optimistic update not available".into(),
+                request_id,
+            ),
+        }
+    }
+
     /// Handle a `BlocksByRange` request from the peer.
     pub fn handle_blocks_by_range_request(
         self,
@@ -312,11 +417,19 @@ impl<T: BeaconChainTypes> Worker<T> {
                     oldest_block_slot,
                 },
             )) => {
-                debug!(self.log, "Range request failed during backfill"; "requested_slot" => slot, "oldest_known_slot" => oldest_block_slot);
+                // Blocks prior to the oldest block are either still being backfilled, or have
+                // been pruned because this node only retains a limited window of history.
+                let history_pruned = self.chain.store.get_pruned_history_slot().is_some();
+                debug!(self.log, "Range request failed for unavailable blocks"; "requested_slot" => slot, "oldest_known_slot" => oldest_block_slot, "history_pruned" => history_pruned);
+                let reason = if history_pruned {
+                    "Pruned"
+                } else {
+                    "Backfilling"
+                };
                 return self.send_error_response(
                     peer_id,
                     RPCResponseErrorCode::ResourceUnavailable,
-                    "Backfilling".into(),
+                    reason.into(),
                     request_id,
                 );
             }
@@ -199,6 +199,15 @@ impl<T: BeaconChainTypes> Router<T> {
             Request::LightClientBootstrap(request) => self.send_beacon_processor_work(
                 BeaconWorkEvent::lightclient_bootstrap_request(peer_id, request_id, request),
             ),
+            Request::LightClientUpdatesByRange(request) => self.send_beacon_processor_work(
+                BeaconWorkEvent::lightclient_updates_by_range_request(peer_id, request_id, request),
+            ),
+            Request::LightClientFinalityUpdate => self.send_beacon_processor_work(
+                BeaconWorkEvent::lightclient_finality_update_request(peer_id, request_id),
+            ),
+            Request::LightClientOptimisticUpdate => self.send_beacon_processor_work(
+                BeaconWorkEvent::lightclient_optimistic_update_request(peer_id, request_id),
+            ),
         }
     }
 
@@ -223,7 +232,10 @@ impl<T: BeaconChainTypes> Router<T> {
             Response::BlocksByRoot(beacon_block) => {
                 self.on_blocks_by_root_response(peer_id, request_id, beacon_block);
             }
-            Response::LightClientBootstrap(_) => unreachable!(),
+            Response::LightClientBootstrap(_)
+            | Response::LightClientUpdatesByRange(_)
+            | Response::LightClientFinalityUpdate(_)
+            | Response::LightClientOptimisticUpdate(_) => unreachable!(),
         }
     }
 
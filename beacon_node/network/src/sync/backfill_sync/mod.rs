@@ -1140,7 +1140,15 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
 
     /// Checks with the beacon chain if backfill sync has completed.
     fn check_completed(&mut self) -> bool {
-        if self.current_start == 0 {
+        // Backfill stops short of genesis if the node only retains a limited window of history.
+        let reached_retention_slot = self
+            .beacon_chain
+            .store
+            .get_history_retention_slot()
+            .map_or(false, |retention_slot| {
+                self.current_start.start_slot(T::EthSpec::slots_per_epoch()) <= retention_slot
+            });
+        if self.current_start == 0 || reached_retention_slot {
             // Check that the beacon chain agrees
 
             if let Some(anchor_info) = self.beacon_chain.store.get_anchor_info() {
@@ -87,6 +87,23 @@ pub struct AttestationStats {
     pub max_aggregates_per_data: usize,
 }
 
+/// Statistics about the attestations considered and selected while packing a block.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AttestationPackingStats {
+    /// Number of valid previous epoch attestations considered by max cover.
+    pub prev_epoch_candidates: usize,
+    /// Number of valid current epoch attestations considered by max cover.
+    pub curr_epoch_candidates: usize,
+    /// Number of previous epoch attestations selected for inclusion.
+    pub prev_epoch_selected: usize,
+    /// Number of current epoch attestations selected for inclusion.
+    pub curr_epoch_selected: usize,
+    /// Number of validators whose first attestation in the epoch is covered by the selection.
+    pub covered_validators: usize,
+    /// Total proposer reward for the selected attestations, in GWei.
+    pub reward: u64,
+}
+
 impl From<SyncAggregateError> for OpPoolError {
     fn from(e: SyncAggregateError) -> Self {
         OpPoolError::SyncAggregateError(e)
@@ -252,6 +269,23 @@ impl<T: EthSpec> OperationPool<T> {
         curr_epoch_validity_filter: impl for<'a> FnMut(&AttestationRef<'a, T>) -> bool + Send,
         spec: &ChainSpec,
     ) -> Result<Vec<Attestation<T>>, OpPoolError> {
+        self.get_attestations_with_stats(
+            state,
+            prev_epoch_validity_filter,
+            curr_epoch_validity_filter,
+            spec,
+        )
+        .map(|(attestations, _)| attestations)
+    }
+
+    /// Same as `get_attestations`, but also returns statistics about the packing.
+    pub fn get_attestations_with_stats(
+        &self,
+        state: &BeaconState<T>,
+        prev_epoch_validity_filter: impl for<'a> FnMut(&AttestationRef<'a, T>) -> bool + Send,
+        curr_epoch_validity_filter: impl for<'a> FnMut(&AttestationRef<'a, T>) -> bool + Send,
+        spec: &ChainSpec,
+    ) -> Result<(Vec<Attestation<T>>, AttestationPackingStats), OpPoolError> {
         // Attestations for the current fork, which may be from the current or previous epoch.
         let (prev_epoch_key, curr_epoch_key) = CheckpointKey::keys_for_state(state);
         let all_attestations = self.attestations.read();
@@ -327,11 +361,34 @@ impl<T: EthSpec> OperationPool<T> {
         metrics::set_gauge(&metrics::NUM_PREV_EPOCH_ATTESTATIONS, num_prev_valid);
         metrics::set_gauge(&metrics::NUM_CURR_EPOCH_ATTESTATIONS, num_curr_valid);
 
-        Ok(max_cover::merge_solutions(
-            curr_cover,
-            prev_cover,
-            T::MaxAttestations::to_usize(),
-        ))
+        // The merge takes the highest scoring items from both solutions, so the selected scores
+        // are the greatest `MaxAttestations` scores across both.
+        let mut selected_scores = curr_cover
+            .iter()
+            .chain(prev_cover.iter())
+            .map(|item| (item.score() as u64, item.covering_set().len()))
+            .collect::<Vec<_>>();
+        selected_scores.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        selected_scores.truncate(T::MaxAttestations::to_usize());
+
+        let attestations =
+            max_cover::merge_solutions(curr_cover, prev_cover, T::MaxAttestations::to_usize());
+
+        let current_epoch = state.current_epoch();
+        let curr_epoch_selected = attestations
+            .iter()
+            .filter(|att| att.data.target.epoch == current_epoch)
+            .count();
+        let stats = AttestationPackingStats {
+            prev_epoch_candidates: num_prev_valid as usize,
+            curr_epoch_candidates: num_curr_valid as usize,
+            prev_epoch_selected: attestations.len() - curr_epoch_selected,
+            curr_epoch_selected,
+            covered_validators: selected_scores.iter().map(|(_, covered)| covered).sum(),
+            reward: selected_scores.iter().map(|(score, _)| score).sum(),
+        };
+
+        Ok((attestations, stats))
     }
 
     /// Remove attestations which are too old to be included in a block.
@@ -1222,6 +1279,22 @@ mod release_tests {
         for att in &best_attestations {
             assert!(att.aggregation_bits.num_set_bits() >= big_step_size);
         }
+
+        let (packed, packing_stats) = op_pool
+            .get_attestations_with_stats(&state, |_| true, |_| true, spec)
+            .expect("should have best attestations");
+        assert_eq!(packed.len(), max_attestations);
+        assert_eq!(packing_stats.curr_epoch_selected, max_attestations);
+        assert_eq!(packing_stats.prev_epoch_selected, 0);
+        assert!(packing_stats.curr_epoch_candidates > max_attestations);
+        assert!(packing_stats.covered_validators > 0);
+        assert!(
+            packing_stats.covered_validators
+                <= packed
+                    .iter()
+                    .map(|att| att.aggregation_bits.num_set_bits())
+                    .sum::<usize>()
+        );
     }
 
     #[test]
@@ -394,6 +394,17 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Shufflings are dependent on validator count and setting this value to a large number can consume a large amount of memory.")
             .takes_value(true)
         )
+        .arg(
+            Arg::with_name("state-cache-memory")
+            .long("state-cache-memory")
+            .value_name("MEGABYTES")
+            .help("The estimated memory which may be used by the cache of recent states, in \
+            megabytes. The cache holds the states used for block processing and production, \
+            along with states replayed from the database for HTTP API requests. The least \
+            recently used states are dropped once the budget is exceeded, although the head \
+            state is always kept. Defaults to 4096.")
+            .takes_value(true)
+        )
 
         /*
          * Monitoring metrics
@@ -504,6 +515,18 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        [default: 8192 (mainnet) or 64 (minimal)]")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("hierarchy-exponents")
+                .long("hierarchy-exponents")
+                .value_name("EXPONENTS")
+                .help("Comma-separated, increasing powers of two giving the layers of freezer DB \
+                       restore points. Restore points on the last layer are stored in full, while \
+                       the others are stored as diffs from the layer above. None of the layers \
+                       may be more frequent than --slots-per-restore-point. An empty value stores \
+                       every restore point in full. Can't be changed once the database has been \
+                       created. [default: 16,18,21]")
+                .takes_value(true)
+        )
         .arg(
             Arg::with_name("block-cache-size")
                 .long("block-cache-size")
@@ -511,6 +534,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many blocks the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("beacon-node-backend")
+                .long("beacon-node-backend")
+                .value_name("DATABASE")
+                .help("Set the key-value store used for the hot and cold databases. An existing \
+                       database can't be opened with a different backend, use `lighthouse db \
+                       convert` to migrate it. [default: leveldb]")
+                .takes_value(true)
+                .possible_values(store::DatabaseBackend::VARIANTS)
+        )
         /*
          * Execution Layer Integration
          */
@@ -632,6 +665,18 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
                 .default_value("true")
         )
+        .arg(
+            Arg::with_name("history-retention-epochs")
+                .long("history-retention-epochs")
+                .value_name("EPOCHS")
+                .help("Only retain this many epochs of finalized blocks and states, deleting older \
+                       history as the chain finalizes. The genesis block and state are always \
+                       kept. Values below MIN_EPOCHS_FOR_BLOCK_REQUESTS (33024 epochs on mainnet) \
+                       violate the networking spec, as peers may request blocks this node no \
+                       longer has. By default all history is retained.")
+                .takes_value(true)
+                .conflicts_with("reconstruct-historic-states")
+        )
 
         /*
          * Misc.
@@ -805,6 +850,26 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
                 .conflicts_with("checkpoint-state")
         )
+        .arg(
+            Arg::with_name("checkpoint-bundle")
+                .long("checkpoint-bundle")
+                .help("Set a checkpoint bundle file to start syncing from. The bundle contains an \
+                       aligned finalized state, its block and an optional deposit snapshot. It \
+                       is verified against --checkpoint-root and/or --wss-checkpoint before the \
+                       database is initialized.")
+                .value_name("BUNDLE_SSZ")
+                .takes_value(true)
+                .conflicts_with_all(&["checkpoint-state", "checkpoint-sync-url"])
+        )
+        .arg(
+            Arg::with_name("checkpoint-root")
+                .long("checkpoint-root")
+                .help("The trusted block root of the finalized checkpoint in the \
+                       --checkpoint-bundle. The root should be 0x-prefixed.")
+                .value_name("BLOCK_ROOT")
+                .takes_value(true)
+                .requires("checkpoint-bundle")
+        )
         .arg(
             Arg::with_name("checkpoint-sync-url-timeout")
                 .long("checkpoint-sync-url-timeout")
@@ -819,6 +884,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("After a checkpoint sync, reconstruct historic states in the database.")
                 .takes_value(false)
         )
+        .arg(
+            Arg::with_name("reconstruction-budget")
+                .long("reconstruction-budget")
+                .value_name("PERCENT")
+                .help("The percentage of the time that historic state reconstruction may run for, \
+                       between 1 and 100. Reconstruction runs in batches, sleeping between them to \
+                       stay within this budget and leave resources for block import. \
+                       Defaults to 50.")
+                .takes_value(true)
+        )
         .arg(
             Arg::with_name("validator-monitor-auto")
                 .long("validator-monitor-auto")
@@ -855,6 +930,81 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("INTEGER")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("validator-monitor-history-epochs")
+                .long("validator-monitor-history-epochs")
+                .help("Number of epochs of per-validator summaries that the validator monitor \
+                    should keep in the database. These summaries are served by the \
+                    /lighthouse/validator_monitor/{index} HTTP API endpoint. Set to 0 to \
+                    disable persistence. Defaults to 1575 (approximately one week).")
+                .value_name("EPOCHS")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("block-times-history-epochs")
+                .long("block-times-history-epochs")
+                .help("Number of epochs of block observation, import and head times to keep in \
+                    the database. These times are served by the \
+                    /lighthouse/analysis/block_timings HTTP API endpoint and are pruned \
+                    whenever the chain finalizes. Set to 0 to disable persistence. Defaults \
+                    to 1575 (approximately one week).")
+                .value_name("EPOCHS")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("validator-monitor-alerts")
+                .long("validator-monitor-alerts")
+                .help("A comma-separated list of rules for which the validator monitor should \
+                    raise alerts about monitored validators. Alerts are published on the \
+                    validator_monitor_alert server-sent event topic. The available rules are \
+                    missed-proposal, missed-attestations=N (N consecutive misses, default 3), \
+                    sync-participation=X (below X% of the epoch, default 80), slashed and \
+                    balance-decrease.")
+                .value_name("RULES")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("validator-monitor-alert-webhooks")
+                .long("validator-monitor-alert-webhooks")
+                .help("A comma-separated list of URLs to which each validator monitor alert is \
+                    POSTed as JSON. Failed deliveries are retried with an exponential backoff.")
+                .value_name("URLS")
+                .requires("validator-monitor-alerts")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("event-webhooks")
+                .long("event-webhooks")
+                .help("A comma-separated list of URLs to which beacon chain events are POSTed as \
+                    JSON. Each event is retried with an exponential backoff until it is \
+                    acknowledged, and delivery resumes from the last acknowledged event after a \
+                    restart. Events may be delivered more than once and carry a sequence number \
+                    which may be used to discard duplicates. Events are delivered from the event \
+                    journal, which is enabled with a size of 65536 unless --event-journal-size is \
+                    set.")
+                .value_name("URLS")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("event-webhook-topics")
+                .long("event-webhook-topics")
+                .help("A comma-separated list of the event topics to POST to --event-webhooks. \
+                    The available topics are head, block, finalized_checkpoint and chain_reorg, \
+                    all of which are posted by default.")
+                .value_name("TOPICS")
+                .requires("event-webhooks")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("event-journal-size")
+                .long("event-journal-size")
+                .help("Number of head, block, finalized_checkpoint and chain_reorg events to keep \
+                    in the database for replay to HTTP API event stream clients which resume \
+                    with a Last-Event-ID header or a since_slot query parameter. The journal is \
+                    disabled by default, or when set to 0, unless --event-webhooks is set.")
+                .value_name("EVENTS")
+                .takes_value(true)
+        )
         .arg(
             Arg::with_name("disable-lock-timeouts")
                 .long("disable-lock-timeouts")
@@ -926,6 +1076,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        --prepare-payload-lookahead flag.")
                 .takes_value(false)
         )
+        .arg(
+            Arg::with_name("publish-proposer-equivocations")
+                .long("publish-proposer-equivocations")
+                .help("Publish proposer slashings formed from conflicting blocks seen on gossip. \
+                       Such slashings are always added to the op pool for inclusion in blocks \
+                       produced by this node, regardless of this flag.")
+                .takes_value(false)
+        )
         .arg(
             Arg::with_name("fork-choice-before-proposal-timeout")
                 .long("fork-choice-before-proposal-timeout")
@@ -4,7 +4,7 @@ use beacon_chain::chain_config::{
 };
 use clap::ArgMatches;
 use clap_utils::flags::DISABLE_MALLOC_TUNING_FLAG;
-use client::{ClientConfig, ClientGenesis};
+use client::{event_webhooks, ClientConfig, ClientGenesis};
 use directory::{DEFAULT_BEACON_NODE_DIR, DEFAULT_NETWORK_DIR, DEFAULT_ROOT_DIR};
 use environment::RuntimeContext;
 use execution_layer::DEFAULT_JWT_FILE;
@@ -152,6 +152,10 @@ pub fn get_config<E: EthSpec>(
         client_config.chain.shuffling_cache_size = cache_size;
     }
 
+    if let Some(memory_mb) = clap_utils::parse_optional(cli_args, "state-cache-memory")? {
+        client_config.chain.state_cache_memory_mb = memory_mb;
+    }
+
     /*
      * Prometheus metrics HTTP server
      */
@@ -377,12 +381,20 @@ pub fn get_config<E: EthSpec>(
     client_config.store.slots_per_restore_point = sprp;
     client_config.store.slots_per_restore_point_set_explicitly = sprp_explicit;
 
+    if let Some(hierarchy_config) = clap_utils::parse_optional(cli_args, "hierarchy-exponents")? {
+        client_config.store.hierarchy_config = hierarchy_config;
+    }
+
     if let Some(block_cache_size) = cli_args.value_of("block-cache-size") {
         client_config.store.block_cache_size = block_cache_size
             .parse()
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
+    if let Some(backend) = clap_utils::parse_optional(cli_args, "beacon-node-backend")? {
+        client_config.store.backend = backend;
+    }
+
     client_config.store.compact_on_init = cli_args.is_present("compact-db");
     if let Some(compact_on_prune) = cli_args.value_of("auto-compact-db") {
         client_config.store.compact_on_prune = compact_on_prune
@@ -394,6 +406,21 @@ pub fn get_config<E: EthSpec>(
         client_config.store.prune_payloads = prune_payloads;
     }
 
+    client_config.store.history_retention_epochs =
+        clap_utils::parse_optional(cli_args, "history-retention-epochs")?;
+    if let Some(history_retention_epochs) = client_config.store.history_retention_epochs {
+        let min_epochs_for_block_requests = spec.min_epochs_for_block_requests();
+        if history_retention_epochs < min_epochs_for_block_requests {
+            warn!(
+                log,
+                "History retention is below the networking minimum";
+                "history_retention_epochs" => history_retention_epochs,
+                "min_epochs_for_block_requests" => min_epochs_for_block_requests,
+                "info" => "peers may request blocks which this node has pruned"
+            );
+        }
+    }
+
     /*
      * Zero-ports
      *
@@ -446,64 +473,91 @@ pub fn get_config<E: EthSpec>(
     client_config.chain.checkpoint_sync_url_timeout =
         clap_utils::parse_required::<u64>(cli_args, "checkpoint-sync-url-timeout")?;
 
-    client_config.genesis = if let Some(genesis_state_bytes) =
-        eth2_network_config.genesis_state_bytes.clone()
-    {
-        // Set up weak subjectivity sync, or start from the hardcoded genesis state.
-        if let (Some(initial_state_path), Some(initial_block_path)) = (
-            cli_args.value_of("checkpoint-state"),
-            cli_args.value_of("checkpoint-block"),
-        ) {
-            let read = |path: &str| {
-                use std::fs::File;
-                use std::io::Read;
-                File::open(Path::new(path))
-                    .and_then(|mut f| {
-                        let mut buffer = vec![];
-                        f.read_to_end(&mut buffer)?;
-                        Ok(buffer)
-                    })
-                    .map_err(|e| format!("Unable to open {}: {:?}", path, e))
-            };
-
-            let anchor_state_bytes = read(initial_state_path)?;
-            let anchor_block_bytes = read(initial_block_path)?;
-
-            ClientGenesis::WeakSubjSszBytes {
-                genesis_state_bytes,
-                anchor_state_bytes,
-                anchor_block_bytes,
-            }
-        } else if let Some(remote_bn_url) = cli_args.value_of("checkpoint-sync-url") {
-            let url = SensitiveUrl::parse(remote_bn_url)
-                .map_err(|e| format!("Invalid checkpoint sync URL: {:?}", e))?;
+    client_config.genesis =
+        if let Some(genesis_state_bytes) = eth2_network_config.genesis_state_bytes.clone() {
+            // Set up weak subjectivity sync, or start from the hardcoded genesis state.
+            if let (Some(initial_state_path), Some(initial_block_path)) = (
+                cli_args.value_of("checkpoint-state"),
+                cli_args.value_of("checkpoint-block"),
+            ) {
+                let read = |path: &str| {
+                    use std::fs::File;
+                    use std::io::Read;
+                    File::open(Path::new(path))
+                        .and_then(|mut f| {
+                            let mut buffer = vec![];
+                            f.read_to_end(&mut buffer)?;
+                            Ok(buffer)
+                        })
+                        .map_err(|e| format!("Unable to open {}: {:?}", path, e))
+                };
+
+                let anchor_state_bytes = read(initial_state_path)?;
+                let anchor_block_bytes = read(initial_block_path)?;
+
+                ClientGenesis::WeakSubjSszBytes {
+                    genesis_state_bytes,
+                    anchor_state_bytes,
+                    anchor_block_bytes,
+                }
+            } else if let Some(bundle_path) = cli_args.value_of("checkpoint-bundle") {
+                let bundle_bytes = fs::read(bundle_path)
+                    .map_err(|e| format!("Unable to open {}: {:?}", bundle_path, e))?;
+                let checkpoint_root = clap_utils::parse_optional(cli_args, "checkpoint-root")?;
+
+                ClientGenesis::CheckpointBundle {
+                    genesis_state_bytes,
+                    bundle_bytes,
+                    checkpoint_root,
+                }
+            } else if let Some(remote_bn_url) = cli_args.value_of("checkpoint-sync-url") {
+                let url = SensitiveUrl::parse(remote_bn_url)
+                    .map_err(|e| format!("Invalid checkpoint sync URL: {:?}", e))?;
 
-            ClientGenesis::CheckpointSyncUrl {
-                genesis_state_bytes,
-                url,
+                ClientGenesis::CheckpointSyncUrl {
+                    genesis_state_bytes,
+                    url,
+                }
+            } else {
+                // Note: re-serializing the genesis state is not so efficient, however it avoids adding
+                // trait bounds to the `ClientGenesis` enum. This would have significant flow-on
+                // effects.
+                ClientGenesis::SszBytes {
+                    genesis_state_bytes,
+                }
             }
         } else {
-            // Note: re-serializing the genesis state is not so efficient, however it avoids adding
-            // trait bounds to the `ClientGenesis` enum. This would have significant flow-on
-            // effects.
-            ClientGenesis::SszBytes {
-                genesis_state_bytes,
+            let checkpoint_flags = [
+                "checkpoint-state",
+                "checkpoint-sync-url",
+                "checkpoint-bundle",
+            ];
+            if checkpoint_flags
+                .iter()
+                .any(|flag| cli_args.is_present(flag))
+            {
+                return Err(
+                    "Checkpoint sync is not available for this network as no genesis state is known"
+                        .to_string(),
+                );
             }
-        }
-    } else {
-        if cli_args.is_present("checkpoint-state") || cli_args.is_present("checkpoint-sync-url") {
-            return Err(
-                "Checkpoint sync is not available for this network as no genesis state is known"
-                    .to_string(),
-            );
-        }
-        ClientGenesis::DepositContract
-    };
+            ClientGenesis::DepositContract
+        };
 
     if cli_args.is_present("reconstruct-historic-states") {
         client_config.chain.reconstruct_historic_states = true;
     }
 
+    if let Some(budget) = clap_utils::parse_optional::<u8>(cli_args, "reconstruction-budget")? {
+        if budget == 0 || budget > 100 {
+            return Err(format!(
+                "--reconstruction-budget must be between 1 and 100, got {}",
+                budget
+            ));
+        }
+        client_config.store.reconstruction_budget_percent = budget;
+    }
+
     let raw_graffiti = if let Some(graffiti) = cli_args.value_of("graffiti") {
         if graffiti.len() > GRAFFITI_BYTES_LEN {
             return Err(format!(
@@ -674,6 +728,49 @@ pub fn get_config<E: EthSpec>(
         client_config.validator_monitor_individual_tracking_threshold = count;
     }
 
+    if let Some(epochs) = clap_utils::parse_optional(cli_args, "validator-monitor-history-epochs")?
+    {
+        client_config.chain.validator_monitor_history_epochs = epochs;
+    }
+
+    if let Some(epochs) = clap_utils::parse_optional(cli_args, "block-times-history-epochs")? {
+        client_config.chain.block_times_history_epochs = epochs;
+    }
+
+    if let Some(rules) = clap_utils::parse_optional(cli_args, "validator-monitor-alerts")? {
+        client_config.validator_monitor_alert_rules = rules;
+    }
+
+    if let Some(webhooks) = cli_args.value_of("validator-monitor-alert-webhooks") {
+        client_config.validator_monitor_alert_webhooks = webhooks
+            .split(',')
+            .map(SensitiveUrl::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid --validator-monitor-alert-webhooks value: {:?}", e))?;
+    }
+
+    if let Some(webhooks) = cli_args.value_of("event-webhooks") {
+        client_config.event_webhooks = webhooks
+            .split(',')
+            .map(SensitiveUrl::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid --event-webhooks value: {:?}", e))?;
+    }
+
+    if let Some(topics) = cli_args.value_of("event-webhook-topics") {
+        client_config.event_webhook_topics = event_webhooks::parse_topics(topics)?;
+    }
+
+    if let Some(size) = clap_utils::parse_optional(cli_args, "event-journal-size")? {
+        if size == 0 && !client_config.event_webhooks.is_empty() {
+            return Err("--event-webhooks requires a non-zero --event-journal-size".to_string());
+        }
+        client_config.chain.event_journal_size = size;
+    } else if !client_config.event_webhooks.is_empty() {
+        // Events are delivered to webhooks from the journal.
+        client_config.chain.event_journal_size = event_webhooks::DEFAULT_WEBHOOK_EVENT_JOURNAL_SIZE;
+    }
+
     if cli_args.is_present("disable-lock-timeouts") {
         client_config.chain.enable_lock_timeouts = false;
     }
@@ -723,6 +820,11 @@ pub fn get_config<E: EthSpec>(
 
     client_config.chain.always_prepare_payload = cli_args.is_present("always-prepare-payload");
 
+    client_config.chain.publish_proposer_equivocations =
+        cli_args.is_present("publish-proposer-equivocations");
+
+    client_config.chain.enable_light_client_server = cli_args.is_present("light-client-server");
+
     if let Some(timeout) =
         clap_utils::parse_optional(cli_args, "fork-choice-before-proposal-timeout")?
     {
@@ -0,0 +1,185 @@
+//! Runtime selection of the on-disk key-value store used by the beacon node.
+use crate::config::DatabaseBackend;
+use crate::redb_store::REDB_DATA_FILENAME;
+use crate::{
+    ColumnIter, ColumnKeyIter, ColumnSize, DBColumn, Error, ItemStore, KeyValueStore,
+    KeyValueStoreOp, LevelDB, Redb,
+};
+use parking_lot::MutexGuard;
+use std::path::{Path, PathBuf};
+use types::EthSpec;
+
+/// The file which is present in every LevelDB database directory.
+const LEVELDB_CURRENT_FILENAME: &str = "CURRENT";
+
+/// Appended to the path of the hot database to give the path of the conversion marker.
+const CONVERSION_MARKER_SUFFIX: &str = ".convert-pending";
+
+/// One of the supported on-disk key-value stores.
+pub enum BeaconNodeBackend<E: EthSpec> {
+    LevelDb(LevelDB<E>),
+    Redb(Redb<E>),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $db:ident => $e:expr) => {
+        match $self {
+            BeaconNodeBackend::LevelDb($db) => $e,
+            BeaconNodeBackend::Redb($db) => $e,
+        }
+    };
+}
+
+impl<E: EthSpec> BeaconNodeBackend<E> {
+    /// Open a database of type `backend` at `path`, creating a new database if one does not
+    /// already exist.
+    ///
+    /// Return an error if `path` already contains a database of a different type.
+    pub fn open(backend: DatabaseBackend, path: &Path) -> Result<Self, Error> {
+        if let Some(existing) = Self::detect(path) {
+            if existing != backend {
+                return Err(Error::DBError {
+                    message: format!(
+                        "database at {} uses the {} backend but {} was requested, \
+                         use `lighthouse db convert` to migrate it",
+                        path.display(),
+                        existing,
+                        backend
+                    ),
+                });
+            }
+        }
+
+        match backend {
+            DatabaseBackend::LevelDb => LevelDB::open(path).map(Self::LevelDb),
+            DatabaseBackend::Redb => Redb::open(path).map(Self::Redb),
+        }
+    }
+
+    /// Open the existing database of type `backend` at `path`, rejecting all writes with
+    /// `Error::ReadOnly`.
+    ///
+    /// Return an error if `path` doesn't contain a database of type `backend`.
+    pub fn open_read_only(backend: DatabaseBackend, path: &Path) -> Result<Self, Error> {
+        match Self::detect(path) {
+            Some(existing) if existing == backend => (),
+            Some(existing) => {
+                return Err(Error::DBError {
+                    message: format!(
+                        "database at {} uses the {} backend but {} was requested",
+                        path.display(),
+                        existing,
+                        backend
+                    ),
+                })
+            }
+            None => {
+                return Err(Error::DBError {
+                    message: format!("no database found at {}", path.display()),
+                })
+            }
+        }
+
+        match backend {
+            DatabaseBackend::LevelDb => LevelDB::open_read_only(path).map(Self::LevelDb),
+            DatabaseBackend::Redb => Redb::open_read_only(path).map(Self::Redb),
+        }
+    }
+
+    /// The path of the file which is present whilst `lighthouse db convert` is moving converted
+    /// databases into place, for the databases whose hot database is at `hot_path`.
+    ///
+    /// The hot and cold databases may use different backends whilst the file is present.
+    pub fn conversion_marker_path(hot_path: &Path) -> PathBuf {
+        let mut file_name = hot_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(CONVERSION_MARKER_SUFFIX);
+        hot_path.with_file_name(file_name)
+    }
+
+    /// Return an error if a conversion of the databases whose hot database is at `hot_path` was
+    /// interrupted whilst moving the converted databases into place.
+    pub fn check_no_interrupted_conversion(hot_path: &Path) -> Result<(), Error> {
+        let marker_path = Self::conversion_marker_path(hot_path);
+        if marker_path.exists() {
+            return Err(Error::DBError {
+                message: format!(
+                    "a database conversion was interrupted, re-run `lighthouse db convert` to \
+                     complete it (found {})",
+                    marker_path.display()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Return the type of the database at `path`, if there is one.
+    pub fn detect(path: &Path) -> Option<DatabaseBackend> {
+        if path.join(LEVELDB_CURRENT_FILENAME).exists() {
+            Some(DatabaseBackend::LevelDb)
+        } else if path.join(REDB_DATA_FILENAME).exists() {
+            Some(DatabaseBackend::Redb)
+        } else {
+            None
+        }
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for BeaconNodeBackend<E> {
+    fn get_bytes(&self, column: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        dispatch!(self, db => db.get_bytes(column, key))
+    }
+
+    fn put_bytes(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        dispatch!(self, db => db.put_bytes(column, key, value))
+    }
+
+    fn put_bytes_sync(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        dispatch!(self, db => db.put_bytes_sync(column, key, value))
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        dispatch!(self, db => db.sync())
+    }
+
+    fn key_exists(&self, column: &str, key: &[u8]) -> Result<bool, Error> {
+        dispatch!(self, db => db.key_exists(column, key))
+    }
+
+    fn key_delete(&self, column: &str, key: &[u8]) -> Result<(), Error> {
+        dispatch!(self, db => db.key_delete(column, key))
+    }
+
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        dispatch!(self, db => db.do_atomically(batch))
+    }
+
+    fn begin_rw_transaction(&self) -> MutexGuard<()> {
+        dispatch!(self, db => db.begin_rw_transaction())
+    }
+
+    fn compact(&self) -> Result<(), Error> {
+        dispatch!(self, db => db.compact())
+    }
+
+    fn compact_all(&self, on_column: &mut dyn FnMut(DBColumn)) -> Result<(), Error> {
+        dispatch!(self, db => db.compact_all(on_column))
+    }
+
+    fn column_size(&self, column: DBColumn) -> Result<ColumnSize, Error> {
+        dispatch!(self, db => db.column_size(column))
+    }
+
+    fn disk_size(&self) -> Result<u64, Error> {
+        dispatch!(self, db => db.disk_size())
+    }
+
+    fn iter_column(&self, column: DBColumn) -> ColumnIter {
+        dispatch!(self, db => db.iter_column(column))
+    }
+
+    fn iter_column_keys(&self, column: DBColumn) -> ColumnKeyIter {
+        dispatch!(self, db => db.iter_column_keys(column))
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for BeaconNodeBackend<E> {}
@@ -51,7 +51,7 @@ pub fn chunk_key(cindex: usize) -> [u8; 8] {
 }
 
 /// Return the database key for the genesis value.
-fn genesis_value_key() -> [u8; 8] {
+pub fn genesis_value_key() -> [u8; 8] {
     0u64.to_be_bytes()
 }
 
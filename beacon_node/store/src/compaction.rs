@@ -0,0 +1,243 @@
+//! Manual compaction of the whole database, and reporting of its size.
+use crate::hot_cold_store::HotColdDB;
+use crate::{DBColumn, Error, ItemStore, KeyValueStore};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use strum::IntoEnumIterator;
+use types::EthSpec;
+
+/// How long column sizes are reused before they're computed again.
+///
+/// Computing them reads every entry of both databases, which may take several minutes, so they are
+/// computed in the background.
+const COLUMN_SIZES_CACHE_DURATION: Duration = Duration::from_secs(600);
+
+/// The number of entries in a column and the total size of their keys and values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSize {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// The size of the hot or freezer database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseSize {
+    /// The size of the database's files on disk, in bytes.
+    pub disk_bytes: u64,
+    /// The size of each non-empty column, keyed by its column ID.
+    ///
+    /// Sizes are of the uncompressed keys and values, so they don't add up to `disk_bytes`.
+    pub columns: BTreeMap<String, ColumnSize>,
+}
+
+/// Whether the column sizes reported by `HotColdDB::get_db_sizes` have been computed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSizesStatus {
+    /// UNIX timestamp of when the column sizes were computed, in seconds.
+    ///
+    /// `None` if they haven't been computed yet, in which case no columns are reported.
+    pub computed_at: Option<u64>,
+    /// Whether the column sizes are being computed in the background.
+    pub computing: bool,
+}
+
+/// The column sizes of the hot and freezer databases, as last computed.
+struct ComputedColumnSizes {
+    computed_at: Instant,
+    timestamp: u64,
+    hot: BTreeMap<String, ColumnSize>,
+    freezer: BTreeMap<String, ColumnSize>,
+}
+
+#[derive(Default)]
+pub(crate) struct ColumnSizesCache {
+    latest: Option<ComputedColumnSizes>,
+    computing: bool,
+}
+
+/// The progress of the latest manual compaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionProgress {
+    /// Whether the compaction is still running.
+    pub running: bool,
+    /// The column being compacted, prefixed by `hot/` or `freezer/`.
+    ///
+    /// Backends which can only compact the whole database at once don't report columns.
+    pub current_column: Option<String>,
+    /// The number of columns of both databases that have been compacted.
+    pub columns_compacted: usize,
+    /// The total number of columns of both databases.
+    pub columns_total: usize,
+    /// The combined on-disk size of both databases when the compaction started, in bytes.
+    pub disk_bytes_before: u64,
+    /// The combined on-disk size of both databases when the compaction completed, in bytes.
+    pub disk_bytes_after: Option<u64>,
+    /// UNIX timestamp of the start of the compaction, in seconds.
+    pub started_at: u64,
+    /// UNIX timestamp of the end of the compaction, in seconds.
+    pub finished_at: Option<u64>,
+    /// The error that stopped the compaction, if any.
+    pub error: Option<String>,
+}
+
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Return the size of each non-empty column of `db`.
+fn column_sizes<E: EthSpec, S: KeyValueStore<E>>(
+    db: &S,
+) -> Result<BTreeMap<String, ColumnSize>, Error> {
+    let mut columns = BTreeMap::new();
+    for column in DBColumn::iter() {
+        let size = db.column_size(column)?;
+        if size.entries > 0 {
+            columns.insert(column.as_str().to_string(), size);
+        }
+    }
+    Ok(columns)
+}
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Return the sizes of the hot and freezer databases and their columns, along with whether
+    /// the column sizes have been computed.
+    ///
+    /// Disk sizes are always current, but column sizes are those last computed by
+    /// `compute_column_sizes`. This never reads the columns itself.
+    pub fn get_db_sizes(&self) -> Result<(DatabaseSize, DatabaseSize, ColumnSizesStatus), Error> {
+        let (hot_columns, freezer_columns, status) = {
+            let cache = self.column_sizes_cache.lock();
+            let status = ColumnSizesStatus {
+                computed_at: cache.latest.as_ref().map(|latest| latest.timestamp),
+                computing: cache.computing,
+            };
+            match &cache.latest {
+                Some(latest) => (latest.hot.clone(), latest.freezer.clone(), status),
+                None => (BTreeMap::new(), BTreeMap::new(), status),
+            }
+        };
+
+        Ok((
+            DatabaseSize {
+                disk_bytes: self.hot_db.disk_size()?,
+                columns: hot_columns,
+            },
+            DatabaseSize {
+                disk_bytes: self.cold_db.disk_size()?,
+                columns: freezer_columns,
+            },
+            status,
+        ))
+    }
+
+    /// Record the start of a computation of the column sizes, returning `false` if one is
+    /// already running or the sizes were computed within `COLUMN_SIZES_CACHE_DURATION`.
+    ///
+    /// The computation itself must then be run with `compute_column_sizes`.
+    pub fn start_column_sizes_computation(&self) -> bool {
+        let mut cache = self.column_sizes_cache.lock();
+        let fresh = cache.latest.as_ref().map_or(false, |latest| {
+            latest.computed_at.elapsed() < COLUMN_SIZES_CACHE_DURATION
+        });
+        if cache.computing || fresh {
+            return false;
+        }
+        cache.computing = true;
+        true
+    }
+
+    /// Compute the size of every column of the hot and freezer databases, to be reported by
+    /// `get_db_sizes`.
+    ///
+    /// The columns are read without holding the cache's lock, so that `get_db_sizes` continues
+    /// to return the previous sizes in the meantime.
+    pub fn compute_column_sizes(&self) -> Result<(), Error> {
+        let result =
+            column_sizes(&self.hot_db).and_then(|hot| Ok((hot, column_sizes(&self.cold_db)?)));
+
+        let mut cache = self.column_sizes_cache.lock();
+        cache.computing = false;
+        let (hot, freezer) = result?;
+        cache.latest = Some(ComputedColumnSizes {
+            computed_at: Instant::now(),
+            timestamp: unix_timestamp(),
+            hot,
+            freezer,
+        });
+        Ok(())
+    }
+
+    /// Return the progress of the latest manual compaction, if one has been run.
+    pub fn get_compaction_progress(&self) -> Option<CompactionProgress> {
+        self.compaction_progress.read().clone()
+    }
+
+    /// Record the start of a manual compaction, returning `false` if one is already running.
+    ///
+    /// The check and the update are made under the same lock, so that concurrent callers can't
+    /// both start a compaction.
+    pub fn start_compaction(&self) -> Result<bool, Error> {
+        let mut progress = self.compaction_progress.write();
+        if progress.as_ref().map_or(false, |progress| progress.running) {
+            return Ok(false);
+        }
+
+        *progress = Some(CompactionProgress {
+            running: true,
+            columns_total: 2 * DBColumn::iter().count(),
+            disk_bytes_before: self.hot_db.disk_size()? + self.cold_db.disk_size()?,
+            started_at: unix_timestamp(),
+            ..CompactionProgress::default()
+        });
+        Ok(true)
+    }
+
+    /// Compact every column of the hot and freezer databases, reporting progress through
+    /// `get_compaction_progress`.
+    ///
+    /// Unlike `compact`, which only compacts the columns most affected by pruning, this reclaims
+    /// the space freed in all columns. The compaction must first be recorded with
+    /// `start_compaction`.
+    pub fn compact_all(&self) -> Result<(), Error> {
+        let result = self
+            .hot_db
+            .compact_all(&mut |column| self.record_compaction_progress("hot", column))
+            .and_then(|()| {
+                self.cold_db
+                    .compact_all(&mut |column| self.record_compaction_progress("freezer", column))
+            })
+            .and_then(|()| Ok(self.hot_db.disk_size()? + self.cold_db.disk_size()?));
+
+        if let Some(progress) = self.compaction_progress.write().as_mut() {
+            progress.running = false;
+            progress.current_column = None;
+            progress.finished_at = Some(unix_timestamp());
+            match &result {
+                Ok(disk_bytes_after) => {
+                    progress.columns_compacted = progress.columns_total;
+                    progress.disk_bytes_after = Some(*disk_bytes_after);
+                }
+                Err(e) => progress.error = Some(format!("{:?}", e)),
+            }
+        }
+
+        result.map(|_| ())
+    }
+
+    fn record_compaction_progress(&self, database: &str, column: DBColumn) {
+        if let Some(progress) = self.compaction_progress.write().as_mut() {
+            if progress.current_column.is_some() {
+                progress.columns_compacted += 1;
+            }
+            progress.current_column = Some(format!("{}/{}", database, column.as_str()));
+        }
+    }
+}
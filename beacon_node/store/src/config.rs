@@ -1,12 +1,15 @@
+use crate::hdiff::HierarchyConfig;
 use crate::{DBColumn, Error, StoreItem};
 use serde_derive::{Deserialize, Serialize};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
+use strum::{Display, EnumString, EnumVariantNames};
 use types::{EthSpec, MinimalEthSpec};
 
 pub const PREV_DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 2048;
 pub const DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 8192;
 pub const DEFAULT_BLOCK_CACHE_SIZE: usize = 5;
+pub const DEFAULT_RECONSTRUCTION_BUDGET_PERCENT: u8 = 50;
 
 /// Database configuration parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -15,6 +18,8 @@ pub struct StoreConfig {
     pub slots_per_restore_point: u64,
     /// Flag indicating whether the `slots_per_restore_point` was set explicitly by the user.
     pub slots_per_restore_point_set_explicitly: bool,
+    /// The layers of restore points which are stored as snapshots or diffs.
+    pub hierarchy_config: HierarchyConfig,
     /// Maximum number of blocks to store in the in-memory block cache.
     pub block_cache_size: usize,
     /// Whether to compact the database on initialization.
@@ -23,17 +28,74 @@ pub struct StoreConfig {
     pub compact_on_prune: bool,
     /// Whether to prune payloads on initialization and finalization.
     pub prune_payloads: bool,
+    /// Number of epochs of finalized history to retain, or `None` to retain all history.
+    pub history_retention_epochs: Option<u64>,
+    /// Percentage of the time that historic state reconstruction may run for, between 1 and 100.
+    ///
+    /// Reconstruction sleeps between batches so that it stays within this budget.
+    pub reconstruction_budget_percent: u8,
+    /// The key-value store used for the hot and cold databases.
+    pub backend: DatabaseBackend,
+}
+
+/// The on-disk key-value stores which may be used for the hot and cold databases.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    EnumVariantNames,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum DatabaseBackend {
+    #[default]
+    LevelDb,
+    Redb,
 }
 
 /// Variant of `StoreConfig` that gets written to disk. Contains immutable configuration params.
-#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OnDiskStoreConfig {
     pub slots_per_restore_point: u64,
+    /// The layers of restore points, or `None` if the config was written before restore points
+    /// could be stored as diffs, in which case every restore point is a snapshot.
+    pub hierarchy_config: Option<HierarchyConfig>,
+}
+
+/// The encoding of an `OnDiskStoreConfig` written before restore points could be stored as diffs.
+#[derive(Encode, Decode)]
+struct OnDiskStoreConfigV1 {
+    slots_per_restore_point: u64,
+}
+
+/// The encoding of an `OnDiskStoreConfig` which records the layers of restore points.
+#[derive(Encode, Decode)]
+struct OnDiskStoreConfigV2 {
+    slots_per_restore_point: u64,
+    hierarchy_exponents: Vec<u8>,
 }
 
 #[derive(Debug, Clone)]
 pub enum StoreConfigError {
-    MismatchedSlotsPerRestorePoint { config: u64, on_disk: u64 },
+    MismatchedSlotsPerRestorePoint {
+        config: u64,
+        on_disk: u64,
+    },
+    MismatchedHierarchyConfig {
+        config: HierarchyConfig,
+        on_disk: HierarchyConfig,
+    },
+    InvalidHierarchyExponents {
+        exponents: Vec<u8>,
+        slots_per_restore_point: u64,
+    },
 }
 
 impl Default for StoreConfig {
@@ -42,10 +104,14 @@ impl Default for StoreConfig {
             // Safe default for tests, shouldn't ever be read by a CLI node.
             slots_per_restore_point: MinimalEthSpec::slots_per_historical_root() as u64,
             slots_per_restore_point_set_explicitly: false,
+            hierarchy_config: HierarchyConfig::default(),
             block_cache_size: DEFAULT_BLOCK_CACHE_SIZE,
             compact_on_init: false,
             compact_on_prune: true,
             prune_payloads: true,
+            history_retention_epochs: None,
+            reconstruction_budget_percent: DEFAULT_RECONSTRUCTION_BUDGET_PERCENT,
+            backend: DatabaseBackend::default(),
         }
     }
 }
@@ -54,6 +120,7 @@ impl StoreConfig {
     pub fn as_disk_config(&self) -> OnDiskStoreConfig {
         OnDiskStoreConfig {
             slots_per_restore_point: self.slots_per_restore_point,
+            hierarchy_config: Some(self.hierarchy_config.clone()),
         }
     }
 
@@ -67,6 +134,16 @@ impl StoreConfig {
                 on_disk: on_disk_config.slots_per_restore_point,
             });
         }
+        // Diff bases are chosen by the hierarchy, so restore points which are still needed as
+        // bases could be pruned or replaced if it changed.
+        if let Some(on_disk_hierarchy) = &on_disk_config.hierarchy_config {
+            if self.hierarchy_config != *on_disk_hierarchy {
+                return Err(StoreConfigError::MismatchedHierarchyConfig {
+                    config: self.hierarchy_config.clone(),
+                    on_disk: on_disk_hierarchy.clone(),
+                });
+            }
+        }
         Ok(())
     }
 }
@@ -77,10 +154,36 @@ impl StoreItem for OnDiskStoreConfig {
     }
 
     fn as_store_bytes(&self) -> Vec<u8> {
-        self.as_ssz_bytes()
+        match &self.hierarchy_config {
+            Some(hierarchy_config) => OnDiskStoreConfigV2 {
+                slots_per_restore_point: self.slots_per_restore_point,
+                hierarchy_exponents: hierarchy_config.exponents.clone(),
+            }
+            .as_ssz_bytes(),
+            None => OnDiskStoreConfigV1 {
+                slots_per_restore_point: self.slots_per_restore_point,
+            }
+            .as_ssz_bytes(),
+        }
     }
 
     fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
-        Ok(Self::from_ssz_bytes(bytes)?)
+        // The V1 encoding is a single fixed-length field, so is always shorter than the V2
+        // encoding, which includes an offset.
+        if bytes.len() == <u64 as Decode>::ssz_fixed_len() {
+            let config = OnDiskStoreConfigV1::from_ssz_bytes(bytes)?;
+            Ok(Self {
+                slots_per_restore_point: config.slots_per_restore_point,
+                hierarchy_config: None,
+            })
+        } else {
+            let config = OnDiskStoreConfigV2::from_ssz_bytes(bytes)?;
+            Ok(Self {
+                slots_per_restore_point: config.slots_per_restore_point,
+                hierarchy_config: Some(HierarchyConfig {
+                    exponents: config.hierarchy_exponents,
+                }),
+            })
+        }
     }
 }
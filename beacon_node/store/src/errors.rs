@@ -1,5 +1,6 @@
 use crate::chunked_vector::ChunkError;
 use crate::config::StoreConfigError;
+use crate::hdiff;
 use crate::hot_cold_store::HotColdDBError;
 use ssz::DecodeError;
 use state_processing::BlockReplayError;
@@ -22,6 +23,7 @@ pub enum Error {
     NoContinuationData,
     SplitPointModified(Slot, Slot),
     ConfigError(StoreConfigError),
+    HDiffError(hdiff::Error),
     SchemaMigrationError(String),
     /// The store's `anchor_info` was mutated concurrently, the latest modification wasn't applied.
     AnchorInfoConcurrentMutation,
@@ -45,6 +47,13 @@ pub enum Error {
     SlotClockUnavailableForMigration,
     UnableToDowngrade,
     InconsistentFork(InconsistentFork),
+    /// A write was attempted on a database which was opened read-only.
+    ReadOnly,
+    /// A database repair was refused because it would delete a state which is still required.
+    UnsafeRepair {
+        state_root: Hash256,
+        reason: String,
+    },
 }
 
 pub trait HandleUnavailable<T> {
@@ -97,6 +106,12 @@ impl From<StoreConfigError> for Error {
     }
 }
 
+impl From<hdiff::Error> for Error {
+    fn from(e: hdiff::Error) -> Error {
+        Error::HDiffError(e)
+    }
+}
+
 impl From<BlockReplayError> for Error {
     fn from(e: BlockReplayError) -> Error {
         Error::BlockReplayError(e)
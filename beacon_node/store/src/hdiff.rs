@@ -0,0 +1,372 @@
+//! Hierarchical diffs between the states of restore points in the freezer database.
+//!
+//! Restore points are arranged in layers, each with an interval which is a power of two. States
+//! at the coarsest interval are stored in full, as snapshots. Every other restore point is stored
+//! as a diff from the restore point at the start of the next coarser interval which contains it.
+//! Loading a restore point therefore requires at most one diff application per layer.
+use crate::config::StoreConfigError;
+use crate::{DBColumn, Error as StoreError, PartialBeaconState, StoreItem};
+use serde_derive::{Deserialize, Serialize};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::fmt;
+use std::str::FromStr;
+use types::{ChainSpec, EthSpec, Hash256, Slot, Validator, VariableList};
+
+/// The default layers of the hierarchy: diffs every 2^16 slots, snapshots every 2^21 slots.
+pub const DEFAULT_HIERARCHY_EXPONENTS: [u8; 3] = [16, 18, 21];
+
+/// The zstd compression level used for diffs.
+const COMPRESSION_LEVEL: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Compression(String),
+    Decompression(String),
+    /// A diff didn't decode to a whole number of `u64`s.
+    InvalidU64DiffLength(usize),
+    /// A validator absent from both the base state and the diff.
+    MissingValidator(usize),
+    /// A diff produced a list longer than the registry limit.
+    ListTooLong,
+    /// The state stored in a diff could not be decoded.
+    StateDecode(ssz::DecodeError),
+}
+
+/// The layers of restore points, as exponents of two.
+///
+/// An empty hierarchy stores every restore point as a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HierarchyConfig {
+    pub exponents: Vec<u8>,
+}
+
+/// How the state of a restore point should be stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageStrategy {
+    Snapshot,
+    DiffFrom(Slot),
+}
+
+impl Default for HierarchyConfig {
+    fn default() -> Self {
+        Self {
+            exponents: DEFAULT_HIERARCHY_EXPONENTS.to_vec(),
+        }
+    }
+}
+
+impl HierarchyConfig {
+    /// A hierarchy which stores every restore point as a snapshot.
+    pub fn snapshots_only() -> Self {
+        Self { exponents: vec![] }
+    }
+
+    /// Check that the layers are strictly increasing and no finer than the restore points.
+    pub fn validate(&self, slots_per_restore_point: u64) -> Result<(), StoreConfigError> {
+        let is_increasing = self.exponents.windows(2).all(|pair| pair[0] < pair[1]);
+        let is_in_range = self.exponents.iter().all(|exponent| *exponent < 64);
+        let is_coarse_enough = self.exponents.first().map_or(true, |exponent| {
+            1u64.checked_shl(u32::from(*exponent))
+                .map_or(false, |interval| interval >= slots_per_restore_point)
+        });
+
+        if is_increasing && is_in_range && is_coarse_enough {
+            Ok(())
+        } else {
+            Err(StoreConfigError::InvalidHierarchyExponents {
+                exponents: self.exponents.clone(),
+                slots_per_restore_point,
+            })
+        }
+    }
+
+    /// Return how the state of the restore point at `slot` should be stored.
+    ///
+    /// The state is diffed from the start of the finest layer interval which `slot` isn't aligned
+    /// to, or stored as a snapshot if it is aligned to every layer.
+    pub fn storage_strategy(&self, slot: Slot) -> StorageStrategy {
+        self.exponents
+            .iter()
+            .map(|exponent| 1u64 << exponent)
+            .find(|interval| slot % *interval != 0)
+            .map_or(StorageStrategy::Snapshot, |interval| {
+                StorageStrategy::DiffFrom(slot / interval * interval)
+            })
+    }
+}
+
+impl FromStr for HierarchyConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let exponents = s
+            .split(',')
+            .map(str::trim)
+            .filter(|exponent| !exponent.is_empty())
+            .map(|exponent| {
+                exponent
+                    .parse()
+                    .map_err(|e| format!("invalid hierarchy exponent {}: {:?}", exponent, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { exponents })
+    }
+}
+
+impl fmt::Display for HierarchyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let exponents = self
+            .exponents
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{}", exponents.join(","))
+    }
+}
+
+/// A diff from the partial state of one restore point to that of another.
+///
+/// The validators, balances and inactivity scores are diffed, while the remainder of the state is
+/// stored in full. All of it is compressed.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct HDiff {
+    /// The state root of the restore point which the diff applies to.
+    pub base_state_root: Hash256,
+    /// The target partial state without its validators, balances and inactivity scores.
+    state: Vec<u8>,
+    validators: ValidatorsDiff,
+    balances: U64Diff,
+    inactivity_scores: U64Diff,
+}
+
+/// The validators which differ from those of the base state.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+struct ValidatorsDiff {
+    len: u64,
+    indices: Vec<u64>,
+    validators: Vec<Validator>,
+}
+
+/// The wrapping differences between two lists of `u64`s, compressed.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+struct U64Diff {
+    bytes: Vec<u8>,
+}
+
+impl HDiff {
+    /// Compute the diff from `base` to `target`, where `base` has state root `base_state_root`.
+    pub fn compute<E: EthSpec>(
+        base_state_root: Hash256,
+        base: &PartialBeaconState<E>,
+        target: &PartialBeaconState<E>,
+    ) -> Result<Self, Error> {
+        let mut target = target.clone();
+        let validators = std::mem::replace(target.validators_mut(), VariableList::empty());
+        let balances = std::mem::replace(target.balances_mut(), VariableList::empty());
+        let inactivity_scores = target
+            .inactivity_scores_mut()
+            .map(|scores| std::mem::replace(scores, VariableList::empty()))
+            .unwrap_or_else(|_| VariableList::empty());
+
+        Ok(Self {
+            base_state_root,
+            state: compress(&target.as_ssz_bytes())?,
+            validators: ValidatorsDiff::compute(base.validators(), &validators),
+            balances: U64Diff::compute(base.balances(), &balances)?,
+            inactivity_scores: U64Diff::compute(inactivity_scores_of(base), &inactivity_scores)?,
+        })
+    }
+
+    /// Apply the diff to `base`, which must be the partial state with `self.base_state_root`.
+    pub fn apply<E: EthSpec>(
+        &self,
+        base: &PartialBeaconState<E>,
+        spec: &ChainSpec,
+    ) -> Result<PartialBeaconState<E>, Error> {
+        let state_bytes = decompress(&self.state)?;
+        let mut state =
+            PartialBeaconState::from_ssz_bytes(&state_bytes, spec).map_err(Error::StateDecode)?;
+
+        *state.validators_mut() = VariableList::new(self.validators.apply(base.validators())?)
+            .map_err(|_| Error::ListTooLong)?;
+        *state.balances_mut() = VariableList::new(self.balances.apply(base.balances())?)
+            .map_err(|_| Error::ListTooLong)?;
+        if let Ok(scores) = state.inactivity_scores_mut() {
+            *scores = VariableList::new(self.inactivity_scores.apply(inactivity_scores_of(base))?)
+                .map_err(|_| Error::ListTooLong)?;
+        }
+
+        Ok(state)
+    }
+}
+
+impl ValidatorsDiff {
+    fn compute(base: &[Validator], target: &[Validator]) -> Self {
+        let (indices, validators) = target
+            .iter()
+            .enumerate()
+            .filter(|(i, validator)| base.get(*i) != Some(*validator))
+            .map(|(i, validator)| (i as u64, validator.clone()))
+            .unzip();
+
+        Self {
+            len: target.len() as u64,
+            indices,
+            validators,
+        }
+    }
+
+    fn apply(&self, base: &[Validator]) -> Result<Vec<Validator>, Error> {
+        let mut changes = self.indices.iter().zip(&self.validators).peekable();
+
+        (0..self.len as usize)
+            .map(
+                |i| match changes.next_if(|(index, _)| **index == i as u64) {
+                    Some((_, validator)) => Ok(validator.clone()),
+                    None => base.get(i).cloned().ok_or(Error::MissingValidator(i)),
+                },
+            )
+            .collect()
+    }
+}
+
+impl U64Diff {
+    fn compute(base: &[u64], target: &[u64]) -> Result<Self, Error> {
+        let bytes = target
+            .iter()
+            .enumerate()
+            .flat_map(|(i, value)| {
+                let base_value = base.get(i).copied().unwrap_or(0);
+                value.wrapping_sub(base_value).to_be_bytes()
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            bytes: compress(&bytes)?,
+        })
+    }
+
+    fn apply(&self, base: &[u64]) -> Result<Vec<u64>, Error> {
+        let bytes = decompress(&self.bytes)?;
+        if bytes.len() % 8 != 0 {
+            return Err(Error::InvalidU64DiffLength(bytes.len()));
+        }
+
+        Ok(bytes
+            .chunks_exact(8)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut diff = [0; 8];
+                diff.copy_from_slice(chunk);
+                let base_value = base.get(i).copied().unwrap_or(0);
+                base_value.wrapping_add(u64::from_be_bytes(diff))
+            })
+            .collect())
+    }
+}
+
+/// The inactivity scores of `state`, which are empty prior to Altair.
+fn inactivity_scores_of<E: EthSpec>(state: &PartialBeaconState<E>) -> &[u64] {
+    match state.inactivity_scores() {
+        Ok(scores) => scores,
+        Err(()) => &[],
+    }
+}
+
+fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    zstd::encode_all(bytes, COMPRESSION_LEVEL).map_err(|e| Error::Compression(e.to_string()))
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    zstd::decode_all(bytes).map_err(|e| Error::Decompression(e.to_string()))
+}
+
+impl StoreItem for HDiff {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconStateDiff
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_strategy() {
+        let config = HierarchyConfig {
+            exponents: vec![5, 7, 9],
+        };
+
+        let strategy = |slot| config.storage_strategy(Slot::new(slot));
+        assert_eq!(strategy(0), StorageStrategy::Snapshot);
+        assert_eq!(strategy(512), StorageStrategy::Snapshot);
+        assert_eq!(strategy(32), StorageStrategy::DiffFrom(Slot::new(0)));
+        assert_eq!(strategy(160), StorageStrategy::DiffFrom(Slot::new(128)));
+        assert_eq!(strategy(640), StorageStrategy::DiffFrom(Slot::new(512)));
+        assert_eq!(strategy(700), StorageStrategy::DiffFrom(Slot::new(672)));
+
+        assert_eq!(
+            HierarchyConfig::snapshots_only().storage_strategy(Slot::new(32)),
+            StorageStrategy::Snapshot
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let config = |exponents: &[u8]| HierarchyConfig {
+            exponents: exponents.to_vec(),
+        };
+
+        assert!(config(&[5, 7, 9]).validate(32).is_ok());
+        assert!(config(&[]).validate(8192).is_ok());
+        assert!(config(&[5, 7, 9]).validate(64).is_err());
+        assert!(config(&[7, 5]).validate(32).is_err());
+        assert!(config(&[7, 7]).validate(32).is_err());
+        assert!(config(&[64]).validate(32).is_err());
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let config = "16, 18,21".parse::<HierarchyConfig>().unwrap();
+        assert_eq!(config, HierarchyConfig::default());
+        assert_eq!(config.to_string(), "16,18,21");
+        assert_eq!("".parse(), Ok(HierarchyConfig::snapshots_only()));
+        assert!("16,x".parse::<HierarchyConfig>().is_err());
+    }
+
+    #[test]
+    fn u64_diff_round_trip() {
+        let base = vec![32_000_000_000, 31_000_000_000, 5, u64::MAX];
+        let target = vec![32_000_000_001, 30_999_999_999, 5, 0, 17];
+
+        let diff = U64Diff::compute(&base, &target).unwrap();
+        assert_eq!(diff.apply(&base).unwrap(), target);
+
+        let shorter = &target[..2];
+        let diff = U64Diff::compute(&base, shorter).unwrap();
+        assert_eq!(diff.apply(&base).unwrap(), shorter);
+    }
+
+    #[test]
+    fn validators_diff_round_trip() {
+        let validator = |effective_balance| Validator {
+            effective_balance,
+            ..Validator::default()
+        };
+        let base = vec![validator(1), validator(2), validator(3)];
+        let target = vec![validator(1), validator(4), validator(3), validator(5)];
+
+        let diff = ValidatorsDiff::compute(&base, &target);
+        assert_eq!(diff.indices, vec![1, 3]);
+        assert_eq!(diff.apply(&base).unwrap(), target);
+    }
+}
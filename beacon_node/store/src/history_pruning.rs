@@ -0,0 +1,363 @@
+//! Implementation of pruning finalized history outside of the `--history-retention-epochs` window.
+use crate::chunked_vector::{chunk_key, BlockRoots, Chunk, Field, RandaoMixes, StateRoots};
+use crate::hdiff::HDiff;
+use crate::hot_cold_store::{HotColdDB, RestorePointHash};
+use crate::metadata::{HistoryPruningInfo, HISTORY_PRUNING_KEY};
+use crate::{get_key_for_col, DBColumn, Error, ItemStore, KeyValueStoreOp, StoreOp};
+use slog::{debug, info};
+use std::cmp::{max, min};
+use std::collections::HashSet;
+use types::{EthSpec, Hash256, Slot};
+
+/// The number of slots of history deleted by each write.
+///
+/// Bounds the number of block and state roots held in memory while pruning.
+const HISTORY_PRUNING_BATCH_SLOTS: u64 = 1024;
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Return the slot from which history is retained, if history retention is configured.
+    ///
+    /// The slot is `history_retention_epochs` before the split, rounded down to a restore point
+    /// so that the oldest retained states can be loaded from it.
+    pub fn get_history_retention_slot(&self) -> Option<Slot> {
+        let retention_epochs = self.config.history_retention_epochs?;
+        let retention_slots = retention_epochs.saturating_mul(E::slots_per_epoch());
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let slot = self.get_split_slot().saturating_sub(retention_slots);
+        Some(slot / slots_per_restore_point * slots_per_restore_point)
+    }
+
+    /// Return the slot before which blocks and states have been pruned, if any.
+    ///
+    /// Only the genesis block and state are retained before this slot.
+    pub fn get_pruned_history_slot(&self) -> Option<Slot> {
+        self.history_pruning_info
+            .read_recursive()
+            .as_ref()
+            .map(|info| info.oldest_retained_slot)
+            .filter(|slot| *slot > self.spec.genesis_slot)
+    }
+
+    /// Get a clone of the current history pruning info, if history has been pruned.
+    pub fn get_history_pruning_info(&self) -> Option<HistoryPruningInfo> {
+        self.history_pruning_info.read_recursive().clone()
+    }
+
+    /// Load the history pruning info from disk, but do not set `self.history_pruning_info`.
+    pub(crate) fn load_history_pruning_info(&self) -> Result<Option<HistoryPruningInfo>, Error> {
+        self.hot_db.get(&HISTORY_PRUNING_KEY)
+    }
+
+    /// Write the history pruning info to disk and update `self.history_pruning_info`.
+    ///
+    /// The write is synced, as the data it describes may be deleted from the freezer database
+    /// immediately afterwards.
+    fn store_history_pruning_info(&self, info: HistoryPruningInfo) -> Result<(), Error> {
+        self.hot_db.put_sync(&HISTORY_PRUNING_KEY, &info)?;
+        *self.history_pruning_info.write() = Some(info);
+        Ok(())
+    }
+
+    /// Delete finalized blocks and states prior to the history retention slot.
+    ///
+    /// The history pruning info is updated before anything is deleted, after which the history is
+    /// deleted in batches of `HISTORY_PRUNING_BATCH_SLOTS`. An interrupted deletion is resumed by
+    /// the next call.
+    ///
+    /// Nothing is pruned if history retention is disabled, or while backfill sync is still
+    /// downloading blocks.
+    pub fn try_prune_history(&self) -> Result<(), Error> {
+        let retention_slot = match self.get_history_retention_slot() {
+            Some(slot) => slot,
+            None => return Ok(()),
+        };
+
+        let anchor_info = self.get_anchor_info();
+        if let Some(ref anchor) = anchor_info {
+            if !anchor.block_backfill_complete() {
+                debug!(
+                    self.log,
+                    "Deferring history pruning until backfill completes";
+                    "oldest_block_slot" => anchor.oldest_block_slot,
+                );
+                return Ok(());
+            }
+        }
+
+        // Checkpoint synced nodes have no history prior to the oldest backfilled block, apart from
+        // genesis.
+        let old_info = self.get_history_pruning_info().unwrap_or_else(|| {
+            let oldest_block_slot = anchor_info
+                .as_ref()
+                .map_or(self.spec.genesis_slot, |anchor| anchor.oldest_block_slot);
+            HistoryPruningInfo {
+                oldest_retained_slot: oldest_block_slot,
+                deleted_slot: oldest_block_slot,
+                diff_base_state_roots: vec![],
+            }
+        });
+        let new_start = max(retention_slot, old_info.oldest_retained_slot);
+
+        if new_start == old_info.oldest_retained_slot && old_info.deletion_complete() {
+            return Ok(());
+        }
+
+        info!(
+            self.log,
+            "Pruning history";
+            "old_start_slot" => old_info.deleted_slot,
+            "new_start_slot" => new_start,
+        );
+
+        // Delete the diff bases kept by previous runs which no retained restore point depends on
+        // any more. They're only forgotten once deleted, so that they're never leaked.
+        let diff_bases = self.load_retained_diff_bases(new_start)?;
+        let mut cold_ops = vec![];
+        let mut diff_base_state_roots = vec![];
+        for state_root in old_info.diff_base_state_roots {
+            if diff_bases.contains(&state_root) {
+                diff_base_state_roots.push(state_root);
+            } else {
+                Self::delete_cold_state_in_batch(state_root, &mut cold_ops);
+            }
+        }
+        self.cold_db.do_atomically(cold_ops)?;
+
+        // Update the pruning info before deleting anything, so that the pruned slots are never
+        // read again.
+        let mut pruning_info = HistoryPruningInfo {
+            oldest_retained_slot: new_start,
+            deleted_slot: old_info.deleted_slot,
+            diff_base_state_roots,
+        };
+        self.store_history_pruning_info(pruning_info.clone())?;
+
+        // Skipped slots after genesis share the genesis block, and skipped slots before the oldest
+        // retained slot share its block, both of which must be kept.
+        let retained_block_roots = [
+            self.load_chunk_value::<BlockRoots>(self.spec.genesis_slot)?,
+            self.load_chunk_value::<BlockRoots>(new_start)?,
+        ];
+
+        let mut num_blocks = 0;
+        let mut num_states = 0;
+        while !pruning_info.deletion_complete() {
+            let start_slot = pruning_info.deleted_slot;
+            let end_slot = min(start_slot + HISTORY_PRUNING_BATCH_SLOTS, new_start);
+            let first_slot = max(start_slot, self.spec.genesis_slot + 1);
+
+            let mut hot_ops = vec![];
+            for block_root in self
+                .load_pruned_roots(BlockRoots, first_slot, end_slot)?
+                .into_iter()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .filter(|block_root| !retained_block_roots.contains(&Some(*block_root)))
+            {
+                hot_ops.push(StoreOp::DeleteBlock(block_root));
+                hot_ops.push(StoreOp::DeleteExecutionPayload(block_root));
+                num_blocks += 1;
+            }
+
+            let mut cold_ops = vec![];
+            for state_root in self.load_pruned_roots(StateRoots, first_slot, end_slot)? {
+                cold_ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconStateSummary.into(),
+                    state_root.as_bytes(),
+                )));
+                num_states += 1;
+            }
+
+            // Record the diff bases to keep before their restore points are deleted.
+            let kept_diff_bases =
+                self.prune_restore_points(start_slot, end_slot, &diff_bases, &mut cold_ops)?;
+            let num_diff_bases = pruning_info.diff_base_state_roots.len();
+            for state_root in kept_diff_bases {
+                if !pruning_info.diff_base_state_roots.contains(&state_root) {
+                    pruning_info.diff_base_state_roots.push(state_root);
+                }
+            }
+            if pruning_info.diff_base_state_roots.len() > num_diff_bases {
+                self.store_history_pruning_info(pruning_info.clone())?;
+            }
+
+            self.prune_chunks::<BlockRoots>(start_slot, end_slot, &mut cold_ops);
+            self.prune_chunks::<StateRoots>(start_slot, end_slot, &mut cold_ops);
+            self.prune_chunks::<RandaoMixes>(start_slot, end_slot, &mut cold_ops);
+
+            self.do_atomically(hot_ops)?;
+            self.cold_db.do_atomically(cold_ops)?;
+
+            pruning_info.deleted_slot = end_slot;
+            self.store_history_pruning_info(pruning_info.clone())?;
+        }
+
+        info!(
+            self.log,
+            "History pruning complete";
+            "oldest_retained_slot" => new_start,
+            "blocks_deleted" => num_blocks,
+            "states_deleted" => num_states,
+        );
+
+        Ok(())
+    }
+
+    /// Load the value of the per-slot `F` at `slot`, if its chunk is stored.
+    fn load_chunk_value<F: Field<E, Value = Hash256>>(
+        &self,
+        slot: Slot,
+    ) -> Result<Option<Hash256>, Error> {
+        let vindex = slot.as_usize();
+        let chunk = Chunk::<Hash256>::load(
+            &self.cold_db,
+            F::column(),
+            &chunk_key(F::chunk_index(vindex)),
+        )?;
+        Ok(chunk.and_then(|chunk| chunk.values.get(vindex % F::chunk_size()).copied()))
+    }
+
+    /// Load the values of the per-slot `field` for the slots in `start_slot..end_slot`.
+    ///
+    /// Chunks which are missing from the database are skipped.
+    fn load_pruned_roots<F: Field<E, Value = Hash256>>(
+        &self,
+        _: F,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Result<Vec<Hash256>, Error> {
+        let start_vindex = start_slot.as_usize();
+        let end_vindex = end_slot.as_usize();
+        let mut roots = vec![];
+
+        for cindex in F::chunk_index(start_vindex)..=F::chunk_index(end_vindex - 1) {
+            let chunk =
+                match Chunk::<Hash256>::load(&self.cold_db, F::column(), &chunk_key(cindex))? {
+                    Some(chunk) => chunk,
+                    None => continue,
+                };
+            let chunk_start = cindex * F::chunk_size();
+            roots.extend(
+                chunk
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| (start_vindex..end_vindex).contains(&(chunk_start + i)))
+                    .map(|(_, root)| *root),
+            );
+        }
+
+        Ok(roots)
+    }
+
+    /// Return the state roots of the restore points which retained restore points are diffed
+    /// from, directly or indirectly.
+    fn load_retained_diff_bases(&self, new_start: Slot) -> Result<HashSet<Hash256>, Error> {
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+
+        // Only restore points within the largest layer of the hierarchy after `new_start` can be
+        // diffed from a restore point prior to it.
+        let mut diff_bases = HashSet::new();
+        let max_diff_distance = self
+            .config
+            .hierarchy_config
+            .exponents
+            .last()
+            .map_or(0, |exponent| 1u64 << exponent);
+        let split_slot = self.get_split_slot();
+        let mut slot = new_start;
+        while slot < new_start + max_diff_distance && slot < split_slot {
+            let restore_point_key =
+                Self::restore_point_key(slot.as_u64() / slots_per_restore_point);
+            let mut state_root = self
+                .cold_db
+                .get(&restore_point_key)?
+                .map(|r: RestorePointHash| r.state_root);
+            while let Some(root) = state_root {
+                state_root = self
+                    .cold_db
+                    .get::<HDiff>(&root)?
+                    .map(|diff| diff.base_state_root);
+                if let Some(base_root) = state_root {
+                    diff_bases.insert(base_root);
+                }
+            }
+            slot += slots_per_restore_point;
+        }
+
+        Ok(diff_bases)
+    }
+
+    /// Delete the restore points in `start_slot..end_slot`, except for the genesis restore point.
+    ///
+    /// The states of restore points in `diff_bases` are kept, and their roots are returned so that
+    /// they can be deleted once they're no longer needed.
+    fn prune_restore_points(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+        diff_bases: &HashSet<Hash256>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<Vec<Hash256>, Error> {
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let start_index = max(start_slot.as_u64() / slots_per_restore_point, 1);
+        let end_index = end_slot.as_u64() / slots_per_restore_point;
+
+        let mut kept_diff_bases = vec![];
+        for restore_point_index in start_index..end_index {
+            let restore_point_key = Self::restore_point_key(restore_point_index);
+            let state_root = match self.cold_db.get::<RestorePointHash>(&restore_point_key)? {
+                Some(restore_point) => restore_point.state_root,
+                None => continue,
+            };
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::BeaconRestorePoint.into(),
+                restore_point_key.as_bytes(),
+            )));
+            if diff_bases.contains(&state_root) {
+                kept_diff_bases.push(state_root);
+            } else {
+                Self::delete_cold_state_in_batch(state_root, ops);
+            }
+        }
+
+        Ok(kept_diff_bases)
+    }
+
+    /// Delete the full state or diff stored for a restore point.
+    fn delete_cold_state_in_batch(state_root: Hash256, ops: &mut Vec<KeyValueStoreOp>) {
+        for column in [DBColumn::BeaconState, DBColumn::BeaconStateDiff] {
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                column.into(),
+                state_root.as_bytes(),
+            )));
+        }
+    }
+
+    /// Delete the chunks of `F` which are only required by states in `start_slot..end_slot`.
+    ///
+    /// The first chunk and the genesis value are kept so that the genesis state can be loaded.
+    fn prune_chunks<F: Field<E>>(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) {
+        let (old_start_vindex, _) = F::start_and_end_vindex(start_slot, &self.spec);
+        let (new_start_vindex, _) = F::start_and_end_vindex(end_slot, &self.spec);
+        let start_cindex = max(F::chunk_index(old_start_vindex), 1);
+        let end_cindex = F::chunk_index(new_start_vindex);
+
+        for cindex in start_cindex..end_cindex {
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                F::column().into(),
+                &chunk_key(cindex),
+            )));
+        }
+    }
+}
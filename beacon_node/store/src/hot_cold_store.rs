@@ -1,28 +1,30 @@
+use crate::backend::BeaconNodeBackend;
 use crate::chunked_vector::{
     store_updated_vector, BlockRoots, HistoricalRoots, HistoricalSummaries, RandaoMixes, StateRoots,
 };
+use crate::compaction::{ColumnSizesCache, CompactionProgress};
 use crate::config::{
     OnDiskStoreConfig, StoreConfig, DEFAULT_SLOTS_PER_RESTORE_POINT,
     PREV_DEFAULT_SLOTS_PER_RESTORE_POINT,
 };
 use crate::forwards_iter::{HybridForwardsBlockRootsIterator, HybridForwardsStateRootsIterator};
+use crate::hdiff::{HDiff, HierarchyConfig, StorageStrategy};
 use crate::impls::beacon_state::{get_full_state, store_full_state};
 use crate::iter::{BlockRootsIterator, ParentRootBlockIterator, RootsIterator};
 use crate::leveldb_store::BytesKey;
-use crate::leveldb_store::LevelDB;
 use crate::memory_store::MemoryStore;
 use crate::metadata::{
-    AnchorInfo, CompactionTimestamp, PruningCheckpoint, SchemaVersion, ANCHOR_INFO_KEY,
-    COMPACTION_TIMESTAMP_KEY, CONFIG_KEY, CURRENT_SCHEMA_VERSION, PRUNING_CHECKPOINT_KEY,
-    SCHEMA_VERSION_KEY, SPLIT_KEY,
+    AnchorInfo, CompactionTimestamp, HistoryPruningInfo, PruningCheckpoint, SchemaVersion,
+    ANCHOR_INFO_KEY, COMPACTION_TIMESTAMP_KEY, CONFIG_KEY, CURRENT_SCHEMA_VERSION,
+    PRUNING_CHECKPOINT_KEY, SCHEMA_VERSION_KEY, SPLIT_KEY,
 };
 use crate::metrics;
+use crate::reconstruct::ReconstructionProgress;
 use crate::{
-    get_key_for_col, DBColumn, DatabaseBlock, Error, ItemStore, KeyValueStoreOp,
+    get_key_for_col, DBColumn, DatabaseBlock, Error, ItemStore, KeyValueStore, KeyValueStoreOp,
     PartialBeaconState, StoreItem, StoreOp,
 };
 use itertools::process_results;
-use leveldb::iterator::LevelDBIterator;
 use lru::LruCache;
 use parking_lot::{Mutex, RwLock};
 use serde_derive::{Deserialize, Serialize};
@@ -32,7 +34,7 @@ use ssz_derive::{Decode, Encode};
 use state_processing::{
     BlockProcessingError, BlockReplayer, SlotProcessingError, StateRootStrategy,
 };
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::convert::TryInto;
 use std::marker::PhantomData;
 use std::path::Path;
@@ -52,7 +54,9 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     /// greater than or equal are in the hot DB.
     pub(crate) split: RwLock<Split>,
     /// The starting slots for the range of blocks & states stored in the database.
-    anchor_info: RwLock<Option<AnchorInfo>>,
+    pub(crate) anchor_info: RwLock<Option<AnchorInfo>>,
+    /// The range of history deleted due to `--history-retention-epochs`, if any.
+    pub(crate) history_pruning_info: RwLock<Option<HistoryPruningInfo>>,
     pub(crate) config: StoreConfig,
     /// Cold database containing compact historical data.
     pub cold_db: Cold,
@@ -62,6 +66,12 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     pub hot_db: Hot,
     /// LRU cache of deserialized blocks. Updated whenever a block is loaded.
     block_cache: Mutex<LruCache<Hash256, SignedBeaconBlock<E>>>,
+    /// Progress of the latest manual compaction.
+    pub(crate) compaction_progress: RwLock<Option<CompactionProgress>>,
+    /// The column sizes of the hot and freezer databases, as last computed.
+    pub(crate) column_sizes_cache: Mutex<ColumnSizesCache>,
+    /// Progress of historic state reconstruction, if it has been started.
+    pub(crate) reconstruction_progress: RwLock<Option<ReconstructionProgress>>,
     /// Chain spec.
     pub(crate) spec: ChainSpec,
     /// Logger.
@@ -113,6 +123,19 @@ pub enum HotColdDBError {
         request_slot: Option<Slot>,
         state_root: Hash256,
     },
+    HistoricReplayOutOfRange {
+        start_slot: Slot,
+        end_slot: Slot,
+        split_slot: Slot,
+    },
+    RestorePointMigrationInProgress {
+        from: u64,
+        to: u64,
+    },
+    RestorePointMigrationReplayIncomplete {
+        next_slot: Slot,
+        end_slot: Slot,
+    },
 }
 
 impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
@@ -122,13 +145,20 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
         log: Logger,
     ) -> Result<HotColdDB<E, MemoryStore<E>, MemoryStore<E>>, Error> {
         Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
+        config
+            .hierarchy_config
+            .validate(config.slots_per_restore_point)?;
 
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
             anchor_info: RwLock::new(None),
+            history_pruning_info: RwLock::new(None),
             cold_db: MemoryStore::open(),
             hot_db: MemoryStore::open(),
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            compaction_progress: RwLock::new(None),
+            column_sizes_cache: <_>::default(),
+            reconstruction_progress: RwLock::new(None),
             config,
             spec,
             log,
@@ -139,9 +169,11 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
     }
 }
 
-impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
+impl<E: EthSpec> HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>> {
     /// Open a new or existing database, with the given paths to the hot and cold DBs.
     ///
+    /// Both databases use the key-value store selected by `config.backend`.
+    ///
     /// The `slots_per_restore_point` parameter must be a divisor of `SLOTS_PER_HISTORICAL_ROOT`.
     ///
     /// The `migrate_schema` function is passed in so that the parent `BeaconChain` can provide
@@ -155,45 +187,54 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         log: Logger,
     ) -> Result<Arc<Self>, Error> {
         Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
+        // Opening a database which is missing mid-conversion would create an empty one in its
+        // place.
+        BeaconNodeBackend::<E>::check_no_interrupted_conversion(hot_path)?;
 
         let mut db = HotColdDB {
             split: RwLock::new(Split::default()),
             anchor_info: RwLock::new(None),
-            cold_db: LevelDB::open(cold_path)?,
-            hot_db: LevelDB::open(hot_path)?,
+            history_pruning_info: RwLock::new(None),
+            cold_db: BeaconNodeBackend::open(config.backend, cold_path)?,
+            hot_db: BeaconNodeBackend::open(config.backend, hot_path)?,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            compaction_progress: RwLock::new(None),
+            column_sizes_cache: <_>::default(),
+            reconstruction_progress: RwLock::new(None),
             config,
             spec,
             log,
             _phantom: PhantomData,
         };
 
-        // Allow the slots-per-restore-point value to stay at the previous default if the config
-        // uses the new default. Don't error on a failed read because the config itself may need
-        // migrating.
-        if let Ok(Some(disk_config)) = db.load_config() {
-            if !db.config.slots_per_restore_point_set_explicitly
-                && disk_config.slots_per_restore_point == PREV_DEFAULT_SLOTS_PER_RESTORE_POINT
-                && db.config.slots_per_restore_point == DEFAULT_SLOTS_PER_RESTORE_POINT
-            {
-                debug!(
+        // Complete any change of slots-per-restore-point which was interrupted after its new restore
+        // points were written. This must happen before the on-disk config is read.
+        if let Some(migration) = db.load_restore_point_migration()? {
+            if migration.complete {
+                db.finish_restore_point_migration(migration)?;
+            } else {
+                warn!(
                     db.log,
-                    "Ignoring slots-per-restore-point config in favour of on-disk value";
-                    "config" => db.config.slots_per_restore_point,
-                    "on_disk" => disk_config.slots_per_restore_point,
+                    "Change of slots-per-restore-point is incomplete";
+                    "from" => migration.from,
+                    "to" => migration.to,
+                    "info" => "re-run `lighthouse db set_slots_per_restore_point` to resume it",
                 );
-
-                // Mutate the in-memory config so that it's compatible.
-                db.config.slots_per_restore_point = PREV_DEFAULT_SLOTS_PER_RESTORE_POINT;
             }
         }
 
+        db.use_previous_default_slots_per_restore_point();
+        db.config
+            .hierarchy_config
+            .validate(db.config.slots_per_restore_point)?;
+
         // Load the previous split slot from the database (if any). This ensures we can
         // stop and restart correctly. This needs to occur *before* running any migrations
         // because some migrations load states and depend on the split.
         if let Some(split) = db.load_split()? {
             *db.split.write() = split;
             *db.anchor_info.write() = db.load_anchor_info()?;
+            *db.history_pruning_info.write() = db.load_history_pruning_info()?;
 
             info!(
                 db.log,
@@ -237,25 +278,92 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         Ok(db)
     }
 
+    /// Open existing databases at the given paths for reading only.
+    ///
+    /// Unlike `open`, no schema migration, garbage collection or other write is performed, so the
+    /// database is seen exactly as the beacon node left it. This is used to verify the database
+    /// offline. All writes to the returned database fail with `Error::ReadOnly`.
+    pub fn open_read_only(
+        hot_path: &Path,
+        cold_path: &Path,
+        config: StoreConfig,
+        spec: ChainSpec,
+        log: Logger,
+    ) -> Result<Self, Error> {
+        Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
+        BeaconNodeBackend::<E>::check_no_interrupted_conversion(hot_path)?;
+
+        let mut db = HotColdDB {
+            split: RwLock::new(Split::default()),
+            anchor_info: RwLock::new(None),
+            history_pruning_info: RwLock::new(None),
+            cold_db: BeaconNodeBackend::open_read_only(config.backend, cold_path)?,
+            hot_db: BeaconNodeBackend::open_read_only(config.backend, hot_path)?,
+            block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            compaction_progress: RwLock::new(None),
+            column_sizes_cache: <_>::default(),
+            reconstruction_progress: RwLock::new(None),
+            config,
+            spec,
+            log,
+            _phantom: PhantomData,
+        };
+
+        // The database can't be migrated without writing to it.
+        match db.load_schema_version()? {
+            Some(schema_version) if schema_version == CURRENT_SCHEMA_VERSION => (),
+            schema_version => {
+                return Err(Error::SchemaMigrationError(format!(
+                    "database schema version {:?} differs from {:?}, start the beacon node to \
+                     migrate it",
+                    schema_version, CURRENT_SCHEMA_VERSION
+                )))
+            }
+        }
+
+        db.use_previous_default_slots_per_restore_point();
+        db.config
+            .hierarchy_config
+            .validate(db.config.slots_per_restore_point)?;
+        if let Some(disk_config) = db.load_config()? {
+            db.config.check_compatibility(&disk_config)?;
+        }
+
+        if let Some(split) = db.load_split()? {
+            *db.split.write() = split;
+            *db.anchor_info.write() = db.load_anchor_info()?;
+            *db.history_pruning_info.write() = db.load_history_pruning_info()?;
+        }
+
+        Ok(db)
+    }
+
+    /// Allow the slots-per-restore-point value to stay at the previous default if the config
+    /// uses the new default.
+    ///
+    /// Don't error on a failed read because the config itself may need migrating.
+    fn use_previous_default_slots_per_restore_point(&mut self) {
+        if let Ok(Some(disk_config)) = self.load_config() {
+            if !self.config.slots_per_restore_point_set_explicitly
+                && disk_config.slots_per_restore_point == PREV_DEFAULT_SLOTS_PER_RESTORE_POINT
+                && self.config.slots_per_restore_point == DEFAULT_SLOTS_PER_RESTORE_POINT
+            {
+                debug!(
+                    self.log,
+                    "Ignoring slots-per-restore-point config in favour of on-disk value";
+                    "config" => self.config.slots_per_restore_point,
+                    "on_disk" => disk_config.slots_per_restore_point,
+                );
+
+                // Mutate the in-memory config so that it's compatible.
+                self.config.slots_per_restore_point = PREV_DEFAULT_SLOTS_PER_RESTORE_POINT;
+            }
+        }
+    }
+
     /// Return an iterator over the state roots of all temporary states.
     pub fn iter_temporary_state_roots(&self) -> impl Iterator<Item = Result<Hash256, Error>> + '_ {
-        let column = DBColumn::BeaconStateTemporary;
-        let start_key =
-            BytesKey::from_vec(get_key_for_col(column.into(), Hash256::zero().as_bytes()));
-
-        let keys_iter = self.hot_db.keys_iter();
-        keys_iter.seek(&start_key);
-
-        keys_iter
-            .take_while(move |key| key.matches_column(column))
-            .map(move |bytes_key| {
-                bytes_key.remove_column(column).ok_or_else(|| {
-                    HotColdDBError::IterationError {
-                        unexpected_key: bytes_key,
-                    }
-                    .into()
-                })
-            })
+        self.hot_db.iter_column_keys(DBColumn::BeaconStateTemporary)
     }
 }
 
@@ -691,6 +799,23 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         self.hot_db.exists::<I>(key)
     }
 
+    /// Fetch the best `LightClientUpdate` stored for the given sync committee period.
+    pub fn get_light_client_update(
+        &self,
+        sync_committee_period: u64,
+    ) -> Result<Option<LightClientUpdate<E>>, Error> {
+        self.get_item(&Hash256::from_low_u64_be(sync_committee_period))
+    }
+
+    /// Store `update` as the best `LightClientUpdate` for the given sync committee period.
+    pub fn put_light_client_update(
+        &self,
+        sync_committee_period: u64,
+        update: &LightClientUpdate<E>,
+    ) -> Result<(), Error> {
+        self.put_item(&Hash256::from_low_u64_be(sync_committee_period), update)
+    }
+
     /// Convert a batch of `StoreOp` to a batch of `KeyValueStoreOp`.
     pub fn convert_to_kv_batch(
         &self,
@@ -895,9 +1020,28 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             "state_root" => format!("{:?}", state_root)
         );
 
-        // 1. Convert to PartialBeaconState and store that in the DB.
+        // 1. Store the state itself.
+        self.store_restore_point_state(state_root, state, ops)?;
+
+        // 2. Store restore point.
+        let restore_point_index = state.slot().as_u64() / self.config.slots_per_restore_point;
+        self.store_restore_point_hash(restore_point_index, *state_root, ops);
+
+        Ok(())
+    }
+
+    /// Store the state of a restore point in the freezer database, without indexing it.
+    pub(crate) fn store_restore_point_state(
+        &self,
+        state_root: &Hash256,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        // 1. Convert to PartialBeaconState and store that in the DB, as a diff if possible.
         let partial_state = PartialBeaconState::from_state_forgetful(state);
-        let op = partial_state.as_kv_store_op(*state_root);
+        let diff_base =
+            self.restore_point_diff_base(state.slot(), &self.config.hierarchy_config)?;
+        let op = self.restore_point_state_op(state_root, &partial_state, diff_base)?;
         ops.push(op);
 
         // 2. Store updated vector entries.
@@ -908,10 +1052,6 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         store_updated_vector(RandaoMixes, db, state, &self.spec, ops)?;
         store_updated_vector(HistoricalSummaries, db, state, &self.spec, ops)?;
 
-        // 3. Store restore point.
-        let restore_point_index = state.slot().as_u64() / self.config.slots_per_restore_point;
-        self.store_restore_point_hash(restore_point_index, *state_root, ops);
-
         Ok(())
     }
 
@@ -947,14 +1087,128 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         }
     }
 
-    /// Load a restore point state by its `state_root`.
-    fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
-        let partial_state_bytes = self
+    /// Return the state root of the restore point which the state of the restore point at `slot`
+    /// should be diffed from, as `hierarchy` dictates.
+    ///
+    /// Return `None` if the state should be stored as a snapshot, which is also the case if the
+    /// restore point it would be diffed from isn't in the database.
+    fn restore_point_diff_base(
+        &self,
+        slot: Slot,
+        hierarchy: &HierarchyConfig,
+    ) -> Result<Option<Hash256>, Error> {
+        match hierarchy.storage_strategy(slot) {
+            StorageStrategy::Snapshot => Ok(None),
+            StorageStrategy::DiffFrom(base_slot) => {
+                let base_restore_point_index =
+                    base_slot.as_u64() / self.config.slots_per_restore_point;
+                Ok(self
+                    .cold_db
+                    .get(&Self::restore_point_key(base_restore_point_index))?
+                    .map(|r: RestorePointHash| r.state_root))
+            }
+        }
+    }
+
+    /// Return the operation storing the `partial_state` of a restore point, either as a diff from
+    /// the restore point with state root `diff_base` or as a snapshot.
+    fn restore_point_state_op(
+        &self,
+        state_root: &Hash256,
+        partial_state: &PartialBeaconState<E>,
+        diff_base: Option<Hash256>,
+    ) -> Result<KeyValueStoreOp, Error> {
+        match diff_base {
+            Some(base_state_root) => {
+                let base = self.load_restore_point_partial_state(&base_state_root)?;
+                let diff = HDiff::compute(base_state_root, &base, partial_state)?;
+                Ok(diff.as_kv_store_op(*state_root))
+            }
+            None => Ok(partial_state.as_kv_store_op(*state_root)),
+        }
+    }
+
+    /// Load the partial state of a restore point by its `state_root`, applying diffs as necessary.
+    fn load_restore_point_partial_state(
+        &self,
+        state_root: &Hash256,
+    ) -> Result<PartialBeaconState<E>, Error> {
+        if let Some(partial_state_bytes) = self
             .cold_db
             .get_bytes(DBColumn::BeaconState.into(), state_root.as_bytes())?
+        {
+            return Ok(PartialBeaconState::from_ssz_bytes(
+                &partial_state_bytes,
+                &self.spec,
+            )?);
+        }
+
+        let diff: HDiff = self
+            .cold_db
+            .get(state_root)?
             .ok_or(HotColdDBError::MissingRestorePoint(*state_root))?;
-        let mut partial_state: PartialBeaconState<E> =
-            PartialBeaconState::from_ssz_bytes(&partial_state_bytes, &self.spec)?;
+        let base = self.load_restore_point_partial_state(&diff.base_state_root)?;
+
+        metrics::inc_counter(&metrics::BEACON_STATE_DIFF_APPLY_COUNT);
+        Ok(diff.apply(&base, &self.spec)?)
+    }
+
+    /// Rewrite the state of every restore point as a snapshot or diff, as `hierarchy` dictates.
+    ///
+    /// Each restore point is rewritten atomically, and those already stored in the right form are
+    /// skipped, so this may safely be interrupted and called again.
+    pub fn rewrite_restore_point_states(&self, hierarchy: &HierarchyConfig) -> Result<(), Error> {
+        let mut rewritten = 0;
+
+        for result in self.cold_db.iter_column(DBColumn::BeaconRestorePoint) {
+            let (key, value) = result?;
+            let slot = Slot::new(key.to_low_u64_be() * self.config.slots_per_restore_point);
+            let state_root = RestorePointHash::from_store_bytes(&value)?.state_root;
+
+            let diff_base = self.restore_point_diff_base(slot, hierarchy)?;
+            let current_column = if diff_base.is_some() {
+                DBColumn::BeaconState
+            } else {
+                DBColumn::BeaconStateDiff
+            };
+            if !self
+                .cold_db
+                .key_exists(current_column.into(), state_root.as_bytes())?
+            {
+                continue;
+            }
+
+            let partial_state = self.load_restore_point_partial_state(&state_root)?;
+            self.cold_db.do_atomically(vec![
+                self.restore_point_state_op(&state_root, &partial_state, diff_base)?,
+                KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    current_column.into(),
+                    state_root.as_bytes(),
+                )),
+            ])?;
+            rewritten += 1;
+
+            debug!(
+                self.log,
+                "Rewrote restore point state";
+                "slot" => slot,
+                "diff_base" => ?diff_base,
+            );
+        }
+
+        info!(
+            self.log,
+            "Rewrote restore point states";
+            "count" => rewritten,
+            "hierarchy" => %hierarchy,
+        );
+
+        Ok(())
+    }
+
+    /// Load a restore point state by its `state_root`.
+    pub(crate) fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
+        let mut partial_state = self.load_restore_point_partial_state(state_root)?;
 
         // Fill in the fields of the partial state.
         partial_state.load_block_roots(&self.cold_db, &self.spec)?;
@@ -1076,9 +1330,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             // no blocks with slot less than the start slot.
             .take_while(|result| match result {
                 Ok(block) => block.slot() >= start_slot,
-                Err(Error::BlockNotFound(_)) => {
-                    self.get_oldest_block_slot() == self.spec.genesis_slot
-                }
+                Err(Error::BlockNotFound(_)) => self.get_oldest_block_slot() <= start_slot,
                 Err(_) => true,
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -1240,7 +1492,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load the anchor info from disk, but do not set `self.anchor_info`.
-    fn load_anchor_info(&self) -> Result<Option<AnchorInfo>, Error> {
+    pub(crate) fn load_anchor_info(&self) -> Result<Option<AnchorInfo>, Error> {
         self.hot_db.get(&ANCHOR_INFO_KEY)
     }
 
@@ -1292,21 +1544,38 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         // function will return the current split slot as the upper limit. Once slot 4096 is reached
         // a new restore point will be created at that slot, making all states from 4096 onwards
         // permanently available.
+        //
+        // If history has been pruned then only the genesis state is available prior to the oldest
+        // retained slot.
         let split_slot = self.get_split_slot();
-        self.anchor_info
+        let (lower_limit, upper_limit) = self
+            .anchor_info
             .read_recursive()
             .as_ref()
             .map_or((split_slot, self.spec.genesis_slot), |a| {
                 (a.state_lower_limit, min(a.state_upper_limit, split_slot))
-            })
+            });
+        match self.get_pruned_history_slot() {
+            Some(pruned_slot) if lower_limit < upper_limit => (
+                self.spec.genesis_slot,
+                min(max(upper_limit, pruned_slot), split_slot),
+            ),
+            Some(pruned_slot) => (self.spec.genesis_slot, min(pruned_slot, split_slot)),
+            None => (lower_limit, upper_limit),
+        }
     }
 
     /// Return the minimum slot such that blocks are available for all subsequent slots.
     pub fn get_oldest_block_slot(&self) -> Slot {
-        self.anchor_info
+        let oldest_block_slot = self
+            .anchor_info
             .read_recursive()
             .as_ref()
-            .map_or(self.spec.genesis_slot, |anchor| anchor.oldest_block_slot)
+            .map_or(self.spec.genesis_slot, |anchor| anchor.oldest_block_slot);
+        self.get_pruned_history_slot()
+            .map_or(oldest_block_slot, |pruned_slot| {
+                max(oldest_block_slot, pruned_slot)
+            })
     }
 
     /// Return the in-memory configuration used by the database.
@@ -1315,7 +1584,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load previously-stored config from disk.
-    fn load_config(&self) -> Result<Option<OnDiskStoreConfig>, Error> {
+    pub(crate) fn load_config(&self) -> Result<Option<OnDiskStoreConfig>, Error> {
         self.hot_db.get(&CONFIG_KEY)
     }
 
@@ -1335,7 +1604,10 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Load the state root of a restore point.
-    fn load_restore_point_hash(&self, restore_point_index: u64) -> Result<Hash256, Error> {
+    pub(crate) fn load_restore_point_hash(
+        &self,
+        restore_point_index: u64,
+    ) -> Result<Hash256, Error> {
         let key = Self::restore_point_key(restore_point_index);
         self.cold_db
             .get(&key)?
@@ -1356,7 +1628,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     }
 
     /// Convert a `restore_point_index` into a database key.
-    fn restore_point_key(restore_point_index: u64) -> Hash256 {
+    pub(crate) fn restore_point_key(restore_point_index: u64) -> Hash256 {
         Hash256::from_low_u64_be(restore_point_index)
     }
 
@@ -1400,7 +1672,9 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     ///
     /// (2) ensures that restore points align with hot state summaries, making it
     /// quick to migrate hot to cold.
-    fn verify_slots_per_restore_point(slots_per_restore_point: u64) -> Result<(), HotColdDBError> {
+    pub(crate) fn verify_slots_per_restore_point(
+        slots_per_restore_point: u64,
+    ) -> Result<(), HotColdDBError> {
         let slots_per_historical_root = E::SlotsPerHistoricalRoot::to_u64();
         let slots_per_epoch = E::slots_per_epoch();
         if slots_per_restore_point > 0
@@ -1713,8 +1987,8 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
 /// Struct for storing the split slot and state root in the database.
 #[derive(Debug, Clone, Copy, PartialEq, Default, Encode, Decode, Deserialize, Serialize)]
 pub struct Split {
-    pub(crate) slot: Slot,
-    pub(crate) state_root: Hash256,
+    pub slot: Slot,
+    pub state_root: Hash256,
 }
 
 impl StoreItem for Split {
@@ -1739,11 +2013,11 @@ fn no_state_root_iter() -> Option<std::iter::Empty<Result<(Hash256, Slot), Error
 /// Struct for summarising a state in the hot database.
 ///
 /// Allows full reconstruction by replaying blocks.
-#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+#[derive(Debug, Clone, Copy, Default, Encode, Decode, Serialize)]
 pub struct HotStateSummary {
     pub slot: Slot,
     pub latest_block_root: Hash256,
-    epoch_boundary_state_root: Hash256,
+    pub(crate) epoch_boundary_state_root: Hash256,
 }
 
 impl StoreItem for HotStateSummary {
@@ -1805,8 +2079,8 @@ impl StoreItem for ColdStateSummary {
 
 /// Struct for storing the state root of a restore point in the database.
 #[derive(Debug, Clone, Copy, Default, Encode, Decode)]
-struct RestorePointHash {
-    state_root: Hash256,
+pub(crate) struct RestorePointHash {
+    pub state_root: Hash256,
 }
 
 impl StoreItem for RestorePointHash {
@@ -7,37 +7,63 @@ use leveldb::database::batch::{Batch, Writebatch};
 use leveldb::database::kv::KV;
 use leveldb::database::Database;
 use leveldb::error::Error as LevelDBError;
-use leveldb::iterator::{Iterable, KeyIterator, LevelDBIterator};
+use leveldb::iterator::{Iterable, LevelDBIterator};
 use leveldb::options::{Options, ReadOptions, WriteOptions};
 use parking_lot::{Mutex, MutexGuard};
 use std::marker::PhantomData;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
 
 /// A wrapped leveldb database.
 pub struct LevelDB<E: EthSpec> {
     db: Database<BytesKey>,
+    /// The directory holding the database files.
+    path: PathBuf,
     /// A mutex to synchronise sensitive read-write transactions.
     transaction_mutex: Mutex<()>,
+    /// Whether writes are rejected.
+    read_only: bool,
     _phantom: PhantomData<E>,
 }
 
 impl<E: EthSpec> LevelDB<E> {
     /// Open a database at `path`, creating a new database if one does not already exist.
     pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with_mode(path, false)
+    }
+
+    /// Open the existing database at `path`, rejecting all writes with `Error::ReadOnly`.
+    ///
+    /// LevelDB has no read-only mode, so opening the database may still recover its log.
+    pub fn open_read_only(path: &Path) -> Result<Self, Error> {
+        Self::open_with_mode(path, true)
+    }
+
+    fn open_with_mode(path: &Path, read_only: bool) -> Result<Self, Error> {
         let mut options = Options::new();
 
-        options.create_if_missing = true;
+        options.create_if_missing = !read_only;
 
         let db = Database::open(path, options)?;
         let transaction_mutex = Mutex::new(());
 
         Ok(Self {
             db,
+            path: path.to_path_buf(),
             transaction_mutex,
+            read_only,
             _phantom: PhantomData,
         })
     }
 
+    fn check_writable(&self) -> Result<(), Error> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
     fn read_options(&self) -> ReadOptions<BytesKey> {
         ReadOptions::new()
     }
@@ -59,6 +85,7 @@ impl<E: EthSpec> LevelDB<E> {
         val: &[u8],
         opts: WriteOptions,
     ) -> Result<(), Error> {
+        self.check_writable()?;
         let column_key = get_key_for_col(col, key);
 
         metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
@@ -73,8 +100,13 @@ impl<E: EthSpec> LevelDB<E> {
             })
     }
 
-    pub fn keys_iter(&self) -> KeyIterator<BytesKey> {
-        self.db.keys_iter(self.read_options())
+    /// Iterate through all keys and values in the database, in key order.
+    ///
+    /// Keys are returned in full, including their column prefix.
+    pub fn iter_raw(&self) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.db
+            .iter(self.read_options())
+            .map(|(bytes_key, value)| (bytes_key.key, value))
     }
 }
 
@@ -125,6 +157,7 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
 
     /// Removes `key` from `column`.
     fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        self.check_writable()?;
         let column_key = get_key_for_col(col, key);
 
         metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
@@ -135,6 +168,7 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
     }
 
     fn do_atomically(&self, ops_batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        self.check_writable()?;
         let mut leveldb_batch = Writebatch::new();
         for op in ops_batch {
             match op {
@@ -157,6 +191,7 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
 
     /// Compact all values in the states and states flag columns.
     fn compact(&self) -> Result<(), Error> {
+        self.check_writable()?;
         let endpoints = |column: DBColumn| {
             (
                 BytesKey::from_vec(get_key_for_col(column.as_str(), Hash256::zero().as_bytes())),
@@ -176,6 +211,50 @@ impl<E: EthSpec> KeyValueStore<E> for LevelDB<E> {
         Ok(())
     }
 
+    fn compact_all(&self, on_column: &mut dyn FnMut(DBColumn)) -> Result<(), Error> {
+        self.check_writable()?;
+        for column in DBColumn::iter() {
+            on_column(column);
+            // Start from the bare column prefix to include keys shorter than 32 bytes.
+            let start_key = BytesKey::from_vec(column.as_bytes().to_vec());
+            let end_key = BytesKey::from_vec(get_key_for_col(
+                column.as_str(),
+                Hash256::repeat_byte(0xff).as_bytes(),
+            ));
+            self.db.compact(&start_key, &end_key);
+        }
+        Ok(())
+    }
+
+    fn column_size(&self, column: DBColumn) -> Result<ColumnSize, Error> {
+        let iter = self.db.iter(self.read_options());
+        iter.seek(&BytesKey::from_vec(column.as_bytes().to_vec()));
+
+        let mut size = ColumnSize::default();
+        for (key, value) in iter.take_while(|(key, _)| key.matches_column(column)) {
+            size.entries += 1;
+            size.bytes += (key.key.len() + value.len()) as u64;
+        }
+        Ok(size)
+    }
+
+    fn disk_size(&self) -> Result<u64, Error> {
+        let read_error = |e: std::io::Error| Error::DBError {
+            message: format!("unable to read {}: {}", self.path.display(), e),
+        };
+
+        let mut size = 0;
+        for entry in std::fs::read_dir(&self.path).map_err(read_error)? {
+            let metadata = entry
+                .and_then(|entry| entry.metadata())
+                .map_err(read_error)?;
+            if metadata.is_file() {
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
     /// Iterate through all keys and values in a particular column.
     fn iter_column(&self, column: DBColumn) -> ColumnIter {
         let start_key =
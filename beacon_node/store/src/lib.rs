@@ -2,7 +2,7 @@
 //!
 //! Provides the following stores:
 //!
-//! - `HotColdDB`: an on-disk store backed by leveldb. Used in production.
+//! - `HotColdDB`: an on-disk store backed by leveldb or redb. Used in production.
 //! - `MemoryStore`: an in-memory store backed by a hash-map. Used for testing.
 //!
 //! Provides a simple API for storing/retrieving all types that sometimes needs type-hints. See
@@ -10,13 +10,17 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod backend;
 mod chunk_writer;
 pub mod chunked_iter;
 pub mod chunked_vector;
+mod compaction;
 pub mod config;
 pub mod errors;
 mod forwards_iter;
 mod garbage_collection;
+pub mod hdiff;
+mod history_pruning;
 pub mod hot_cold_store;
 mod impls;
 mod leveldb_store;
@@ -25,22 +29,29 @@ pub mod metadata;
 pub mod metrics;
 mod partial_beacon_state;
 pub mod reconstruct;
+mod redb_store;
+mod restore_point_migration;
+pub mod verify;
 
 pub mod iter;
 
+pub use self::backend::BeaconNodeBackend;
 pub use self::chunk_writer::ChunkWriter;
-pub use self::config::StoreConfig;
+pub use self::compaction::{ColumnSize, ColumnSizesStatus, CompactionProgress, DatabaseSize};
+pub use self::config::{DatabaseBackend, StoreConfig};
 pub use self::hot_cold_store::{HotColdDB, HotStateSummary, Split};
 pub use self::leveldb_store::LevelDB;
 pub use self::memory_store::MemoryStore;
 pub use self::partial_beacon_state::PartialBeaconState;
+pub use self::reconstruct::ReconstructionProgress;
+pub use self::redb_store::Redb;
 pub use errors::Error;
 pub use impls::beacon_state::StorageContainer as BeaconStateStorageContainer;
 pub use metadata::AnchorInfo;
 pub use metrics::scrape_for_metrics;
 use parking_lot::MutexGuard;
 use std::sync::Arc;
-use strum::{EnumString, IntoStaticStr};
+use strum::{EnumIter, EnumString, IntoStaticStr};
 pub use types::*;
 
 pub type ColumnIter<'a> = Box<dyn Iterator<Item = Result<(Hash256, Vec<u8>), Error>> + 'a>;
@@ -79,15 +90,34 @@ pub trait KeyValueStore<E: EthSpec>: Sync + Send + Sized + 'static {
     /// Compact the database, freeing space used by deleted items.
     fn compact(&self) -> Result<(), Error>;
 
+    /// Compact every column of the database, calling `on_column` before each column is compacted.
+    ///
+    /// Backends which can only compact the whole database at once don't call `on_column`.
+    fn compact_all(&self, _on_column: &mut dyn FnMut(DBColumn)) -> Result<(), Error> {
+        self.compact()
+    }
+
+    /// Return the number of entries in `column` and the total size of their keys and values.
+    fn column_size(&self, _column: DBColumn) -> Result<ColumnSize, Error> {
+        // Default impl for in-memory databases
+        Ok(ColumnSize::default())
+    }
+
+    /// Return the size of the database's files on disk, in bytes.
+    fn disk_size(&self) -> Result<u64, Error> {
+        // Default impl for in-memory databases
+        Ok(0)
+    }
+
     /// Iterate through all keys and values in a particular column.
     fn iter_column(&self, _column: DBColumn) -> ColumnIter {
-        // Default impl for non LevelDB databases
+        // Default impl for in-memory databases
         Box::new(std::iter::empty())
     }
 
     /// Iterate through all keys in a particular column.
     fn iter_column_keys(&self, _column: DBColumn) -> ColumnKeyIter {
-        // Default impl for non LevelDB databases
+        // Default impl for in-memory databases
         Box::new(std::iter::empty())
     }
 }
@@ -165,7 +195,7 @@ pub enum StoreOp<'a, E: EthSpec> {
 }
 
 /// A unique column identifier.
-#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr, EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, IntoStaticStr, EnumString, EnumIter)]
 pub enum DBColumn {
     /// For data related to the database itself.
     #[strum(serialize = "bma")]
@@ -175,6 +205,9 @@ pub enum DBColumn {
     /// For full `BeaconState`s in the hot database (finalized or fork-boundary states).
     #[strum(serialize = "ste")]
     BeaconState,
+    /// For restore point states in the freezer database which are stored as diffs.
+    #[strum(serialize = "bsd")]
+    BeaconStateDiff,
     /// For the mapping from state roots to their slots or summaries.
     #[strum(serialize = "bss")]
     BeaconStateSummary,
@@ -199,6 +232,9 @@ pub enum DBColumn {
     /// For the table mapping restore point numbers to state roots.
     #[strum(serialize = "brp")]
     BeaconRestorePoint,
+    /// For restore points staged while changing the slots-per-restore-point.
+    #[strum(serialize = "brs")]
+    BeaconRestorePointStaged,
     #[strum(serialize = "bbr")]
     BeaconBlockRoots,
     #[strum(serialize = "bsr")]
@@ -214,6 +250,24 @@ pub enum DBColumn {
     OptimisticTransitionBlock,
     #[strum(serialize = "bhs")]
     BeaconHistoricalSummaries,
+    /// For the times at which blocks were observed, imported and set as head.
+    #[strum(serialize = "btm")]
+    BeaconBlockTimes,
+    /// For the best `LightClientUpdate` of each sync committee period.
+    #[strum(serialize = "lcu")]
+    LightClientUpdate,
+    /// For the per-epoch summaries of validators monitored by the validator monitor.
+    #[strum(serialize = "vms")]
+    ValidatorMonitorSummary,
+    /// For the last event in the event journal delivered to each webhook.
+    #[strum(serialize = "ewc")]
+    EventWebhookCursor,
+    /// For the journal of server-sent events available for replay.
+    #[strum(serialize = "evj")]
+    EventJournal,
+    /// For the attestation outcomes of finalized epochs, used to compute attestation rewards.
+    #[strum(serialize = "arw")]
+    AttestationRewards,
 }
 
 /// A block from the database, which might have an execution payload or not.
@@ -291,6 +345,13 @@ mod tests {
         let retrieved = store.get(&key).unwrap().unwrap();
         assert_eq!(item, retrieved);
 
+        let keys = store
+            .iter_column_keys(StorableThing::db_column())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![key]);
+        store.sync().unwrap();
+
         store.delete::<StorableThing>(&key).unwrap();
 
         assert!(!store.exists::<StorableThing>(&key).unwrap());
@@ -307,6 +368,25 @@ mod tests {
         test_impl(store);
     }
 
+    #[test]
+    fn redb() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let store = Redb::open(path).unwrap();
+
+        test_impl(store);
+    }
+
+    #[test]
+    fn backend_mismatch() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        drop(BeaconNodeBackend::<MinimalEthSpec>::open(DatabaseBackend::LevelDb, path).unwrap());
+
+        assert!(BeaconNodeBackend::<MinimalEthSpec>::open(DatabaseBackend::Redb, path).is_err());
+        assert!(BeaconNodeBackend::<MinimalEthSpec>::open(DatabaseBackend::LevelDb, path).is_ok());
+    }
+
     #[test]
     fn memorydb() {
         let store = MemoryStore::open();
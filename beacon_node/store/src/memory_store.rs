@@ -1,5 +1,5 @@
 use super::{Error, ItemStore, KeyValueStore, KeyValueStoreOp};
-use crate::{ColumnIter, DBColumn};
+use crate::{ColumnIter, ColumnKeyIter, ColumnSize, DBColumn};
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
@@ -114,6 +114,10 @@ impl<E: EthSpec> KeyValueStore<E> for MemoryStore<E> {
         }
     }
 
+    fn iter_column_keys(&self, column: DBColumn) -> ColumnKeyIter {
+        Box::new(self.iter_column(column).map(|res| res.map(|(key, _)| key)))
+    }
+
     fn begin_rw_transaction(&self) -> MutexGuard<()> {
         self.transaction_mutex.lock()
     }
@@ -121,6 +125,18 @@ impl<E: EthSpec> KeyValueStore<E> for MemoryStore<E> {
     fn compact(&self) -> Result<(), Error> {
         Ok(())
     }
+
+    fn column_size(&self, column: DBColumn) -> Result<ColumnSize, Error> {
+        Ok(self
+            .db
+            .read()
+            .iter()
+            .filter(|(key, _)| key.starts_with(column.as_bytes()))
+            .fold(ColumnSize::default(), |size, (key, value)| ColumnSize {
+                entries: size.entries + 1,
+                bytes: size.bytes + (key.len() + value.len()) as u64,
+            }))
+    }
 }
 
 impl<E: EthSpec> ItemStore<E> for MemoryStore<E> {}
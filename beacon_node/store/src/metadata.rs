@@ -4,7 +4,7 @@ use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use types::{Checkpoint, Hash256, Slot};
 
-pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(16);
+pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(17);
 
 // All the keys that get stored under the `BeaconMeta` column.
 //
@@ -15,6 +15,9 @@ pub const SPLIT_KEY: Hash256 = Hash256::repeat_byte(2);
 pub const PRUNING_CHECKPOINT_KEY: Hash256 = Hash256::repeat_byte(3);
 pub const COMPACTION_TIMESTAMP_KEY: Hash256 = Hash256::repeat_byte(4);
 pub const ANCHOR_INFO_KEY: Hash256 = Hash256::repeat_byte(5);
+/// Stored in the freezer database, unlike the other keys.
+pub const RESTORE_POINT_MIGRATION_KEY: Hash256 = Hash256::repeat_byte(6);
+pub const HISTORY_PRUNING_KEY: Hash256 = Hash256::repeat_byte(7);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SchemaVersion(pub u64);
@@ -99,8 +102,10 @@ pub struct AnchorInfo {
 
 impl AnchorInfo {
     /// Returns true if the block backfill has completed.
+    ///
+    /// Backfill completes at genesis, or at the oldest retained slot if history is pruned.
     pub fn block_backfill_complete(&self) -> bool {
-        self.oldest_block_slot == 0
+        self.oldest_block_parent == Hash256::zero()
     }
 }
 
@@ -117,3 +122,73 @@ impl StoreItem for AnchorInfo {
         Ok(Self::from_ssz_bytes(bytes)?)
     }
 }
+
+/// The range of finalized history deleted due to `--history-retention-epochs`.
+///
+/// Kept separate from the `AnchorInfo`, which describes the history the node was synced with.
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct HistoryPruningInfo {
+    /// The slot from which blocks and states are retained (>=).
+    ///
+    /// Only the genesis block and state are retained before this slot.
+    pub oldest_retained_slot: Slot,
+    /// The slot before which blocks and states have been deleted.
+    ///
+    /// Deletion of the slots up to `oldest_retained_slot` is in progress while this is lower.
+    pub deleted_slot: Slot,
+    /// State roots of pruned restore points which are kept because retained restore points are
+    /// diffed from them.
+    pub diff_base_state_roots: Vec<Hash256>,
+}
+
+impl HistoryPruningInfo {
+    /// Returns true if all history prior to `oldest_retained_slot` has been deleted.
+    pub fn deletion_complete(&self) -> bool {
+        self.deleted_slot >= self.oldest_retained_slot
+    }
+}
+
+impl StoreItem for HistoryPruningInfo {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// The progress of a change to the slots-per-restore-point of the freezer database.
+///
+/// Stored in the freezer database so that it is updated atomically with the restore points.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+pub struct RestorePointMigration {
+    /// The slots-per-restore-point being changed from.
+    pub from: u64,
+    /// The slots-per-restore-point being changed to.
+    pub to: u64,
+    /// The slot of the next restore point to be staged.
+    pub next_slot: Slot,
+    /// Whether the staged restore points have replaced the old ones.
+    ///
+    /// Once set, only the on-disk config and anchor in the hot database remain to be updated.
+    pub complete: bool,
+}
+
+impl StoreItem for RestorePointMigration {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
@@ -78,6 +78,10 @@ lazy_static! {
         "store_beacon_state_read_bytes_total",
         "Total number of beacon state bytes read from the DB"
     );
+    pub static ref BEACON_STATE_DIFF_APPLY_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_beacon_state_diff_apply_total",
+        "Total number of diffs applied to load restore point states from the freezer"
+    );
     pub static ref BEACON_STATE_WRITE_OVERHEAD_TIMES: Result<Histogram> = try_create_histogram(
         "store_beacon_state_write_overhead_seconds",
         "Overhead on writing a beacon state to the DB (e.g., encoding)"
@@ -90,6 +94,25 @@ lazy_static! {
         "store_beacon_state_write_bytes_total",
         "Total number of beacon state bytes written to the DB"
     );
+    /*
+     * Historic state reconstruction
+     */
+    pub static ref RECONSTRUCTION_CURRENT_SLOT: Result<IntGauge> = try_create_int_gauge(
+        "store_reconstruction_current_slot",
+        "Slot up to which historic states have been reconstructed"
+    );
+    pub static ref RECONSTRUCTION_TARGET_SLOT: Result<IntGauge> = try_create_int_gauge(
+        "store_reconstruction_target_slot",
+        "Slot at which historic state reconstruction will complete"
+    );
+    pub static ref RECONSTRUCTION_ETA_SECONDS: Result<IntGauge> = try_create_int_gauge(
+        "store_reconstruction_eta_seconds",
+        "Estimated time until historic state reconstruction completes"
+    );
+    pub static ref RECONSTRUCTION_PAUSED: Result<IntGauge> = try_create_int_gauge(
+        "store_reconstruction_paused",
+        "Set to 1 if historic state reconstruction is paused"
+    );
     /*
      * Beacon Block
      */
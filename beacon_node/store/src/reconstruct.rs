@@ -1,14 +1,43 @@
 //! Implementation of historic state reconstruction (given complete block history).
+use crate::compaction::unix_timestamp;
 use crate::hot_cold_store::{HotColdDB, HotColdDBError};
-use crate::{Error, ItemStore};
+use crate::{metrics, Error, ItemStore};
 use itertools::{process_results, Itertools};
+use serde_derive::{Deserialize, Serialize};
 use slog::info;
 use state_processing::{
     per_block_processing, per_slot_processing, BlockSignatureStrategy, ConsensusContext,
     VerifyBlockRoot,
 };
 use std::sync::Arc;
-use types::{EthSpec, Hash256};
+use std::time::{Duration, Instant};
+use types::{BeaconState, EthSpec, Hash256, Slot};
+
+/// The progress of historic state reconstruction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconstructionProgress {
+    /// Whether reconstruction has started and has not yet completed or failed.
+    pub running: bool,
+    /// Whether reconstruction is paused, in which case no further batches are run until resumed.
+    pub paused: bool,
+    /// The state lower limit when reconstruction was started.
+    pub start_slot: Slot,
+    /// The slot up to which states have been reconstructed.
+    pub current_slot: Slot,
+    /// The slot at which reconstruction will complete.
+    pub target_slot: Slot,
+    /// The estimated number of seconds until reconstruction completes, excluding pauses.
+    pub eta_seconds: Option<u64>,
+    /// UNIX timestamp of the start of reconstruction, in seconds.
+    pub started_at: u64,
+    /// UNIX timestamp of the end of reconstruction, in seconds.
+    pub finished_at: Option<u64>,
+    /// The error that stopped reconstruction, if any.
+    pub error: Option<String>,
+    /// Time spent reconstructing states, excluding the time spent sleeping between batches.
+    #[serde(skip)]
+    busy: Duration,
+}
 
 impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
 where
@@ -16,7 +45,74 @@ where
     Hot: ItemStore<E>,
     Cold: ItemStore<E>,
 {
-    pub fn reconstruct_historic_states(self: &Arc<Self>) -> Result<(), Error> {
+    /// Return the progress of historic state reconstruction, if it has been started.
+    pub fn get_reconstruction_progress(&self) -> Option<ReconstructionProgress> {
+        self.reconstruction_progress.read().clone()
+    }
+
+    /// Return `true` if reconstruction has been started and should continue with another batch.
+    pub fn reconstruction_pending(&self) -> bool {
+        self.reconstruction_progress
+            .read()
+            .as_ref()
+            .map_or(false, |progress| progress.running && !progress.paused)
+    }
+
+    /// Pause reconstruction after its current batch.
+    ///
+    /// Return `false` if reconstruction is not running.
+    pub fn pause_reconstruction(&self) -> bool {
+        self.set_reconstruction_paused(true)
+    }
+
+    /// Allow a paused reconstruction to continue. Further batches must be requested by the caller.
+    ///
+    /// Return `false` if reconstruction is not running.
+    pub fn resume_reconstruction(&self) -> bool {
+        self.set_reconstruction_paused(false)
+    }
+
+    fn set_reconstruction_paused(&self, paused: bool) -> bool {
+        match self.reconstruction_progress.write().as_mut() {
+            Some(progress) if progress.running => {
+                progress.paused = paused;
+                metrics::set_gauge(&metrics::RECONSTRUCTION_PAUSED, paused as i64);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reconstruct historic states, stopping at the first restore point at least `num_slots`
+    /// slots after the state lower limit, or once all states are reconstructed if `num_slots` is
+    /// `None`.
+    ///
+    /// States are committed to disk at each restore point, so reconstruction can be resumed from
+    /// the new state lower limit by calling this function again, including after a restart. Use
+    /// `reconstruction_pending` to check whether more states remain. Reconstruction also stops at
+    /// the next restore point if it is paused.
+    pub fn reconstruct_historic_states(
+        self: &Arc<Self>,
+        num_slots: Option<usize>,
+    ) -> Result<(), Error> {
+        let result = self.reconstruct_historic_states_batch(num_slots);
+
+        if let Err(e) = &result {
+            if let Some(progress) = self.reconstruction_progress.write().as_mut() {
+                progress.running = false;
+                progress.eta_seconds = None;
+                progress.finished_at = Some(unix_timestamp());
+                progress.error = Some(format!("{:?}", e));
+            }
+        }
+
+        result
+    }
+
+    fn reconstruct_historic_states_batch(
+        self: &Arc<Self>,
+        num_slots: Option<usize>,
+    ) -> Result<(), Error> {
         let mut anchor = if let Some(anchor) = self.get_anchor_info() {
             anchor
         } else {
@@ -24,19 +120,12 @@ where
             return Ok(());
         };
 
-        // Check that all historic blocks are known.
-        if anchor.oldest_block_slot != 0 {
-            return Err(Error::MissingHistoricBlocks {
-                oldest_block_slot: anchor.oldest_block_slot,
-            });
+        // Check that all historic blocks are known, and haven't been pruned.
+        let oldest_block_slot = self.get_oldest_block_slot();
+        if oldest_block_slot != 0 {
+            return Err(Error::MissingHistoricBlocks { oldest_block_slot });
         }
 
-        info!(
-            self.log,
-            "Beginning historic state reconstruction";
-            "start_slot" => anchor.state_lower_limit,
-        );
-
         let slots_per_restore_point = self.config.slots_per_restore_point;
 
         // Iterate blocks from the state lower limit to the upper limit.
@@ -48,6 +137,12 @@ where
         )?;
         let upper_limit_slot = upper_limit_state.slot();
 
+        if !self.start_reconstruction_batch(lower_limit_slot, upper_limit_slot) {
+            // Reconstruction is paused.
+            return Ok(());
+        }
+        let mut batch_start = Instant::now();
+
         // Use a dummy root, as we never read the block for the upper limit state.
         let upper_limit_block_root = Hash256::repeat_byte(0xff);
 
@@ -65,6 +160,8 @@ where
 
         state.build_all_caches(&self.spec)?;
 
+        let stop_slot = num_slots.map(|num_slots| lower_limit_slot + num_slots as u64);
+
         process_results(block_root_iter, |iter| -> Result<(), Error> {
             let mut io_batch = vec![];
 
@@ -114,13 +211,6 @@ where
 
                 // If the slot lies on an epoch boundary, commit the batch and update the anchor.
                 if slot % slots_per_restore_point == 0 || slot + 1 == upper_limit_slot {
-                    info!(
-                        self.log,
-                        "State reconstruction in progress";
-                        "slot" => slot,
-                        "remaining" => upper_limit_slot - 1 - slot
-                    );
-
                     self.cold_db.do_atomically(std::mem::take(&mut io_batch))?;
 
                     // Update anchor.
@@ -139,6 +229,7 @@ where
                         }
 
                         self.compare_and_set_anchor_info_with_write(old_anchor, None)?;
+                        self.finish_reconstruction(batch_start.elapsed());
 
                         return Ok(());
                     } else {
@@ -149,6 +240,23 @@ where
                             old_anchor,
                             Some(anchor.clone()),
                         )?;
+
+                        let eta_seconds =
+                            self.record_reconstruction_progress(slot, batch_start.elapsed());
+                        batch_start = Instant::now();
+
+                        info!(
+                            self.log,
+                            "State reconstruction in progress";
+                            "slot" => slot,
+                            "remaining" => upper_limit_slot - 1 - slot,
+                            "eta_seconds" => eta_seconds,
+                        );
+
+                        let paused = !self.reconstruction_pending();
+                        if paused || stop_slot.map_or(false, |stop_slot| slot >= stop_slot) {
+                            return Ok(());
+                        }
                     }
                 }
             }
@@ -167,4 +275,247 @@ where
 
         Ok(())
     }
+
+    /// Start recording the progress of a batch of reconstruction from `lower_limit_slot`.
+    ///
+    /// Return `false` if reconstruction is paused.
+    fn start_reconstruction_batch(&self, lower_limit_slot: Slot, upper_limit_slot: Slot) -> bool {
+        let mut progress = self.reconstruction_progress.write();
+        match progress.as_ref() {
+            Some(progress) if progress.running && progress.paused => return false,
+            Some(progress) if progress.running => {}
+            _ => {
+                info!(
+                    self.log,
+                    "Beginning historic state reconstruction";
+                    "start_slot" => lower_limit_slot,
+                );
+                *progress = Some(ReconstructionProgress {
+                    running: true,
+                    start_slot: lower_limit_slot,
+                    current_slot: lower_limit_slot,
+                    target_slot: upper_limit_slot,
+                    started_at: unix_timestamp(),
+                    ..ReconstructionProgress::default()
+                });
+                metrics::set_gauge(
+                    &metrics::RECONSTRUCTION_TARGET_SLOT,
+                    upper_limit_slot.as_u64() as i64,
+                );
+                metrics::set_gauge(&metrics::RECONSTRUCTION_PAUSED, 0);
+            }
+        }
+        metrics::set_gauge(
+            &metrics::RECONSTRUCTION_CURRENT_SLOT,
+            lower_limit_slot.as_u64() as i64,
+        );
+        true
+    }
+
+    /// Record that states have been reconstructed up to `slot`, taking `elapsed` since the
+    /// previous update, and return the updated ETA.
+    fn record_reconstruction_progress(&self, slot: Slot, elapsed: Duration) -> Option<u64> {
+        let mut progress = self.reconstruction_progress.write();
+        let progress = progress.as_mut()?;
+        progress.current_slot = slot;
+        progress.busy += elapsed;
+
+        // Extrapolate from the rate so far, accounting for the time spent sleeping between
+        // batches to stay within the budget.
+        let slots_done = slot.saturating_sub(progress.start_slot).as_u64();
+        let slots_remaining = progress.target_slot.saturating_sub(slot).as_u64();
+        let budget = u64::from(self.config.reconstruction_budget_percent.clamp(1, 100));
+        let busy_millis = progress.busy.as_millis() as u64;
+        progress.eta_seconds = (slots_done > 0)
+            .then(|| busy_millis * slots_remaining / slots_done * 100 / budget / 1000);
+
+        metrics::set_gauge(&metrics::RECONSTRUCTION_CURRENT_SLOT, slot.as_u64() as i64);
+        if let Some(eta_seconds) = progress.eta_seconds {
+            metrics::set_gauge(&metrics::RECONSTRUCTION_ETA_SECONDS, eta_seconds as i64);
+        }
+        progress.eta_seconds
+    }
+
+    fn finish_reconstruction(&self, elapsed: Duration) {
+        if let Some(progress) = self.reconstruction_progress.write().as_mut() {
+            progress.running = false;
+            progress.paused = false;
+            progress.current_slot = progress.target_slot;
+            progress.busy += elapsed;
+            progress.eta_seconds = Some(0);
+            progress.finished_at = Some(unix_timestamp());
+            metrics::set_gauge(
+                &metrics::RECONSTRUCTION_CURRENT_SLOT,
+                progress.target_slot.as_u64() as i64,
+            );
+        }
+        metrics::set_gauge(&metrics::RECONSTRUCTION_ETA_SECONDS, 0);
+        metrics::set_gauge(&metrics::RECONSTRUCTION_PAUSED, 0);
+        info!(self.log, "Historic state reconstruction complete");
+    }
+
+    /// Compute the states at the end of each slot from `start_slot` to `end_slot` (inclusive) by
+    /// replaying blocks from the nearest restore point, calling `f` with each state and its root in
+    /// turn.
+    ///
+    /// Unlike `reconstruct_historic_states` no states are written to disk, so this may be used to
+    /// access states between the state lower and upper limits of a node which has not
+    /// reconstructed them. All blocks from the restore point onwards must be known, and
+    /// `end_slot` must lie before the split slot.
+    ///
+    /// Replay stops early if `f` returns `Ok(false)`. The last state computed is returned, so that
+    /// a later replay can continue from it with `replay_historic_states_from`.
+    pub fn replay_historic_states<F, Err>(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+        f: F,
+    ) -> Result<BeaconState<E>, Err>
+    where
+        F: FnMut(&BeaconState<E>, Hash256) -> Result<bool, Err>,
+        Err: From<Error>,
+    {
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+
+        // Restore points between the state limits are missing, so start from the lower limit.
+        let (lower_limit, upper_limit) = self.get_historic_state_limits();
+        let base_slot = if start_slot > lower_limit && start_slot < upper_limit {
+            lower_limit
+        } else {
+            start_slot / slots_per_restore_point * slots_per_restore_point
+        };
+        self.check_historic_replay(base_slot, start_slot, end_slot)?;
+
+        let mut state = self
+            .load_cold_state_by_slot(base_slot)?
+            .ok_or(HotColdDBError::MissingLowerLimitState(base_slot))
+            .map_err(Error::from)?;
+        state.build_all_caches(&self.spec).map_err(Error::from)?;
+
+        self.replay_historic_states_from(state, start_slot, end_slot, f)
+    }
+
+    /// Continue a replay from `state`, a finalized state returned by an earlier replay, as per
+    /// `replay_historic_states`.
+    ///
+    /// `f` is called with `state` itself only if it is at `start_slot`.
+    pub fn replay_historic_states_from<F, Err>(
+        &self,
+        mut state: BeaconState<E>,
+        start_slot: Slot,
+        end_slot: Slot,
+        mut f: F,
+    ) -> Result<BeaconState<E>, Err>
+    where
+        F: FnMut(&BeaconState<E>, Hash256) -> Result<bool, Err>,
+        Err: From<Error>,
+    {
+        let base_slot = state.slot();
+        self.check_historic_replay(base_slot, start_slot, end_slot)?;
+
+        if base_slot == start_slot {
+            let state_root = state.update_tree_hash_cache().map_err(Error::from)?;
+            if !f(&state, state_root)? {
+                return Ok(state);
+            }
+        }
+
+        // Block roots after the latest restore point are read from the split state.
+        let split = self.get_split_info();
+        let split_state = self
+            .get_state(&split.state_root, Some(split.slot))?
+            .ok_or(HotColdDBError::MissingSplitState(
+                split.state_root,
+                split.slot,
+            ))
+            .map_err(Error::from)?;
+        let split_block_root = split_state.get_latest_block_root(split.state_root);
+        let block_root_iter = self.forwards_block_roots_iterator_until(
+            base_slot,
+            end_slot,
+            || (split_state, split_block_root),
+            &self.spec,
+        )?;
+
+        process_results(block_root_iter, |iter| -> Result<(), Err> {
+            let iter = iter.take_while(|(_, slot)| *slot <= end_slot);
+            let mut prev_state_root = None;
+            for ((prev_block_root, _), (block_root, slot)) in iter.tuple_windows() {
+                let is_skipped_slot = prev_block_root == block_root;
+
+                let block = if is_skipped_slot {
+                    None
+                } else {
+                    Some(
+                        self.get_blinded_block(&block_root)?
+                            .ok_or(Error::BlockNotFound(block_root))?,
+                    )
+                };
+
+                per_slot_processing(&mut state, prev_state_root.take(), &self.spec)
+                    .map_err(|e| Error::from(HotColdDBError::BlockReplaySlotError(e)))?;
+
+                if let Some(block) = block {
+                    let mut ctxt = ConsensusContext::new(block.slot())
+                        .set_current_block_root(block_root)
+                        .set_proposer_index(block.message().proposer_index());
+
+                    per_block_processing(
+                        &mut state,
+                        &block,
+                        BlockSignatureStrategy::NoVerification,
+                        VerifyBlockRoot::True,
+                        &mut ctxt,
+                        &self.spec,
+                    )
+                    .map_err(|e| Error::from(HotColdDBError::BlockReplayBlockError(e)))?;
+
+                    prev_state_root = Some(block.state_root());
+                }
+
+                if slot >= start_slot {
+                    // The state root is only known without hashing if a block was applied.
+                    let state_root = match prev_state_root {
+                        Some(state_root) => state_root,
+                        None => {
+                            let state_root = state.update_tree_hash_cache().map_err(Error::from)?;
+                            prev_state_root = Some(state_root);
+                            state_root
+                        }
+                    };
+                    if !f(&state, state_root)? {
+                        return Ok(());
+                    }
+                }
+            }
+            Ok(())
+        })??;
+
+        Ok(state)
+    }
+
+    /// Return an error unless a replay from the state at `base_slot` can compute the states from
+    /// `start_slot` to `end_slot`.
+    fn check_historic_replay(
+        &self,
+        base_slot: Slot,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Result<(), Error> {
+        let split_slot = self.get_split_slot();
+        if base_slot > start_slot || start_slot > end_slot || end_slot >= split_slot {
+            return Err(HotColdDBError::HistoricReplayOutOfRange {
+                start_slot,
+                end_slot,
+                split_slot,
+            }
+            .into());
+        }
+
+        let oldest_block_slot = self.get_oldest_block_slot();
+        if oldest_block_slot > base_slot {
+            return Err(Error::MissingHistoricBlocks { oldest_block_slot });
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,329 @@
+use super::*;
+use crate::hot_cold_store::HotColdDBError;
+use crate::leveldb_store::BytesKey;
+use crate::metrics;
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use redb::{Database, Durability, ReadableTable, TableDefinition, WriteTransaction};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+/// The name of the file holding the redb database within its directory.
+pub const REDB_DATA_FILENAME: &str = "database.redb";
+
+/// All columns share a single table, keyed by the column prefix followed by the key, exactly as
+/// in LevelDB. This keeps the two backends interchangeable for `lighthouse db convert`.
+const TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("lighthouse");
+
+/// A wrapped redb database.
+pub struct Redb<E: EthSpec> {
+    /// Compaction requires exclusive access to the database, everything else shares it.
+    db: RwLock<Database>,
+    /// The path of the database file.
+    path: PathBuf,
+    /// A mutex to synchronise sensitive read-write transactions.
+    transaction_mutex: Mutex<()>,
+    /// Whether writes are rejected.
+    read_only: bool,
+    _phantom: PhantomData<E>,
+}
+
+impl<E: EthSpec> Redb<E> {
+    /// Open a database in the directory at `path`, creating a new database if one does not
+    /// already exist.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        std::fs::create_dir_all(path).map_err(|e| Error::DBError {
+            message: format!("unable to create {}: {}", path.display(), e),
+        })?;
+
+        let path = path.join(REDB_DATA_FILENAME);
+        let db = Database::create(&path)?;
+
+        // Create the table up-front so that read transactions never have to handle its absence.
+        let tx = db.begin_write()?;
+        tx.open_table(TABLE)?;
+        tx.commit()?;
+
+        Ok(Self {
+            db: RwLock::new(db),
+            path,
+            transaction_mutex: Mutex::new(()),
+            read_only: false,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Open the existing database in the directory at `path`, rejecting all writes with
+    /// `Error::ReadOnly`.
+    pub fn open_read_only(path: &Path) -> Result<Self, Error> {
+        let path = path.join(REDB_DATA_FILENAME);
+        let db = Database::open(&path)?;
+
+        Ok(Self {
+            db: RwLock::new(db),
+            path,
+            transaction_mutex: Mutex::new(()),
+            read_only: true,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Begin a write transaction with the given durability, unless the database is read-only.
+    fn begin_write(&self, sync: bool) -> Result<WriteTransaction, Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        let mut tx = self.db.read().begin_write()?;
+        tx.set_durability(Self::durability(sync));
+        Ok(tx)
+    }
+
+    /// The durability of a write transaction, mirroring the `sync` write option of LevelDB.
+    ///
+    /// As with LevelDB, writes which are not synced avoid an `fsync` for each commit but may be
+    /// lost on a crash. A later synced write persists them.
+    fn durability(sync: bool) -> Durability {
+        if sync {
+            Durability::Immediate
+        } else {
+            Durability::Eventual
+        }
+    }
+
+    fn put_bytes_with_durability(
+        &self,
+        col: &str,
+        key: &[u8],
+        val: &[u8],
+        sync: bool,
+    ) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+        metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, val.len() as u64);
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        let tx = self.begin_write(sync)?;
+        {
+            let mut table = tx.open_table(TABLE)?;
+            table.insert(column_key.as_slice(), val)?;
+        }
+        tx.commit()?;
+
+        metrics::stop_timer(timer);
+        Ok(())
+    }
+
+    /// Iterate through the full keys of `column`, in key order, along with their values if
+    /// `read_values` is set.
+    fn iter_column_range(
+        &self,
+        column: DBColumn,
+        read_values: bool,
+    ) -> Result<impl Iterator<Item = Result<(BytesKey, Option<Vec<u8>>), Error>>, Error> {
+        let start_key = get_key_for_col(column.into(), Hash256::zero().as_bytes());
+
+        let tx = self.db.read().begin_read()?;
+        let table = tx.open_table(TABLE)?;
+        let range = table.range(start_key.as_slice()..)?;
+
+        Ok(range
+            .map(move |result| -> Result<_, Error> {
+                let (key, value) = result?;
+                Ok((
+                    BytesKey::from_vec(key.value().to_vec()),
+                    read_values.then(|| value.value().to_vec()),
+                ))
+            })
+            .take_while(move |result| match result {
+                Ok((key, _)) => key.matches_column(column),
+                Err(_) => true,
+            }))
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for Redb<E> {
+    /// Store some `value` in `column`, indexed with `key`.
+    fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        self.put_bytes_with_durability(col, key, val, false)
+    }
+
+    fn put_bytes_sync(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        self.put_bytes_with_durability(col, key, val, true)
+    }
+
+    /// Persist all previous writes with an empty durable commit.
+    fn sync(&self) -> Result<(), Error> {
+        let tx = self.begin_write(true)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Retrieve some bytes in `column` with `key`.
+    fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_READ_COUNT);
+        let timer = metrics::start_timer(&metrics::DISK_DB_READ_TIMES);
+
+        let tx = self.db.read().begin_read()?;
+        let table = tx.open_table(TABLE)?;
+        let result = table.get(column_key.as_slice())?;
+
+        Ok(result.map(|value| {
+            let bytes = value.value().to_vec();
+            metrics::inc_counter_by(&metrics::DISK_DB_READ_BYTES, bytes.len() as u64);
+            metrics::stop_timer(timer);
+            bytes
+        }))
+    }
+
+    /// Return `true` if `key` exists in `column`.
+    fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_EXISTS_COUNT);
+
+        let tx = self.db.read().begin_read()?;
+        let table = tx.open_table(TABLE)?;
+        let exists = table.get(column_key.as_slice())?.is_some();
+        Ok(exists)
+    }
+
+    /// Removes `key` from `column`.
+    fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+
+        let tx = self.begin_write(false)?;
+        {
+            let mut table = tx.open_table(TABLE)?;
+            table.remove(column_key.as_slice())?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn do_atomically(&self, ops_batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        let tx = self.begin_write(false)?;
+        {
+            let mut table = tx.open_table(TABLE)?;
+            for op in ops_batch {
+                match op {
+                    KeyValueStoreOp::PutKeyValue(key, value) => {
+                        table.insert(key.as_slice(), value.as_slice())?;
+                    }
+
+                    KeyValueStoreOp::DeleteKey(key) => {
+                        table.remove(key.as_slice())?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn begin_rw_transaction(&self) -> MutexGuard<()> {
+        self.transaction_mutex.lock()
+    }
+
+    /// Compact the whole database file.
+    ///
+    /// Unlike LevelDB, redb can't compact a range of keys, and compaction fails if any read
+    /// transaction (such as a column iterator) is still alive.
+    fn compact(&self) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        self.db.write().compact()?;
+        Ok(())
+    }
+
+    fn column_size(&self, column: DBColumn) -> Result<ColumnSize, Error> {
+        let tx = self.db.read().begin_read()?;
+        let table = tx.open_table(TABLE)?;
+
+        let mut size = ColumnSize::default();
+        for result in table.range(column.as_bytes()..)? {
+            let (key, value) = result?;
+            if !key.value().starts_with(column.as_bytes()) {
+                break;
+            }
+            size.entries += 1;
+            size.bytes += (key.value().len() + value.value().len()) as u64;
+        }
+        Ok(size)
+    }
+
+    fn disk_size(&self) -> Result<u64, Error> {
+        std::fs::metadata(&self.path)
+            .map(|metadata| metadata.len())
+            .map_err(|e| Error::DBError {
+                message: format!("unable to read {}: {}", self.path.display(), e),
+            })
+    }
+
+    /// Iterate through all keys and values in a particular column.
+    fn iter_column(&self, column: DBColumn) -> ColumnIter {
+        let iter = match self.iter_column_range(column, true) {
+            Ok(iter) => iter,
+            Err(e) => return Box::new(std::iter::once(Err(e))),
+        };
+
+        Box::new(iter.map(move |result| -> Result<_, Error> {
+            let (bytes_key, value) = result?;
+            let value = value.unwrap_or_default();
+            let key = bytes_key
+                .remove_column(column)
+                .ok_or(HotColdDBError::IterationError {
+                    unexpected_key: bytes_key,
+                })?;
+            Ok((key, value))
+        }))
+    }
+
+    /// Iterate through all keys in a particular column, without reading their values.
+    fn iter_column_keys(&self, column: DBColumn) -> ColumnKeyIter {
+        let iter = match self.iter_column_range(column, false) {
+            Ok(iter) => iter,
+            Err(e) => return Box::new(std::iter::once(Err(e))),
+        };
+
+        Box::new(iter.map(move |result| -> Result<_, Error> {
+            let (bytes_key, _) = result?;
+            let key = bytes_key
+                .remove_column(column)
+                .ok_or(HotColdDBError::IterationError {
+                    unexpected_key: bytes_key,
+                })?;
+            Ok(key)
+        }))
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for Redb<E> {}
+
+macro_rules! impl_from_redb_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for Error {
+                fn from(e: $error) -> Error {
+                    Error::DBError {
+                        message: format!("{:?}", e),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_redb_error!(
+    redb::Error,
+    redb::DatabaseError,
+    redb::TransactionError,
+    redb::TableError,
+    redb::StorageError,
+    redb::CommitError,
+    redb::CompactionError
+);
@@ -0,0 +1,285 @@
+//! Implementation of changing the slots-per-restore-point of an existing freezer database.
+use crate::config::OnDiskStoreConfig;
+use crate::hot_cold_store::{HotColdDB, HotColdDBError, RestorePointHash};
+use crate::metadata::{
+    RestorePointMigration, ANCHOR_INFO_KEY, CONFIG_KEY, RESTORE_POINT_MIGRATION_KEY,
+};
+use crate::{get_key_for_col, DBColumn, Error, ItemStore, KeyValueStoreOp, StoreItem};
+use slog::info;
+use std::cmp::min;
+use types::{BeaconState, EthSpec, Hash256, Slot, Unsigned};
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Change the slots-per-restore-point of the freezer database to `slots_per_restore_point`.
+    ///
+    /// Restore points at the new spacing are staged alongside the existing ones, replaying blocks
+    /// from the existing restore points where necessary. Once all of them are staged they replace
+    /// the existing restore points in a single write, after which the on-disk config is updated.
+    /// Progress is recorded with every staged restore point, so an interrupted change is resumed
+    /// by calling this function again.
+    ///
+    /// The database must be opened with its current slots-per-restore-point and must not be in
+    /// use by a beacon node. The in-memory config is not updated, so the database should be
+    /// re-opened with the new value afterwards.
+    pub fn change_slots_per_restore_point(
+        &self,
+        slots_per_restore_point: u64,
+    ) -> Result<(), Error> {
+        Self::verify_slots_per_restore_point(slots_per_restore_point)?;
+        self.config
+            .hierarchy_config
+            .validate(slots_per_restore_point)?;
+
+        let from = self.config.slots_per_restore_point;
+        let to = slots_per_restore_point;
+
+        let mut migration = match self.load_restore_point_migration()? {
+            Some(migration) if migration.from == from && migration.to == to => {
+                info!(
+                    self.log,
+                    "Resuming change of slots-per-restore-point";
+                    "from" => from,
+                    "to" => to,
+                    "next_slot" => migration.next_slot,
+                );
+                migration
+            }
+            Some(migration) => {
+                return Err(HotColdDBError::RestorePointMigrationInProgress {
+                    from: migration.from,
+                    to: migration.to,
+                }
+                .into())
+            }
+            None if from == to => return Ok(()),
+            None => {
+                info!(
+                    self.log,
+                    "Changing slots-per-restore-point";
+                    "from" => from,
+                    "to" => to,
+                );
+                RestorePointMigration {
+                    from,
+                    to,
+                    next_slot: self.spec.genesis_slot,
+                    complete: false,
+                }
+            }
+        };
+
+        if !migration.complete {
+            self.stage_restore_points(&mut migration)?;
+            self.replace_restore_points(&mut migration)?;
+        }
+        self.finish_restore_point_migration(migration)
+    }
+
+    /// Stage a restore point for every slot at the new spacing, starting from
+    /// `migration.next_slot`.
+    fn stage_restore_points(&self, migration: &mut RestorePointMigration) -> Result<(), Error> {
+        let from = migration.from;
+        let to = migration.to;
+        let split_slot = self.get_split_slot();
+        let (lower_limit, upper_limit) = self.get_historic_state_limits();
+
+        while migration.next_slot < split_slot {
+            let slot = migration.next_slot;
+
+            if slot > lower_limit && slot < upper_limit {
+                // Restore points between the state limits are missing, skip to the upper limit.
+                migration.next_slot = (upper_limit + to - 1) / to * to;
+            } else if slot % from == 0 {
+                // An existing restore point, which only needs to be re-indexed.
+                let state_root = self.load_restore_point_hash(slot.as_u64() / from)?;
+                migration.next_slot = slot + to;
+                self.stage_restore_point(slot, state_root, None, migration)?;
+            } else {
+                // A new restore point. Create it, and any others before the next existing restore
+                // point, with a single replay from the previous existing restore point.
+                let mut end_slot = min((slot / from + 1) * from - to, (split_slot - 1) / to * to);
+                if slot <= lower_limit {
+                    end_slot = min(end_slot, lower_limit / to * to);
+                }
+
+                self.replay_historic_states(slot, end_slot, |state, state_root| {
+                    if state.slot() % to == 0 {
+                        migration.next_slot = state.slot() + to;
+                        self.stage_restore_point(state.slot(), state_root, Some(state), migration)?;
+                    }
+                    Ok::<_, Error>(true)
+                })?;
+
+                // Guard against looping forever if the replay stopped short.
+                if migration.next_slot <= end_slot {
+                    return Err(HotColdDBError::RestorePointMigrationReplayIncomplete {
+                        next_slot: migration.next_slot,
+                        end_slot,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stage the restore point at `slot`, storing its `state` if it isn't an existing restore
+    /// point, and record the progress of `migration` in the same write.
+    fn stage_restore_point(
+        &self,
+        slot: Slot,
+        state_root: Hash256,
+        state: Option<&BeaconState<E>>,
+        migration: &RestorePointMigration,
+    ) -> Result<(), Error> {
+        let mut ops = vec![];
+
+        if let Some(state) = state {
+            self.store_restore_point_state(&state_root, state, &mut ops)?;
+        }
+
+        let restore_point_key = Self::restore_point_key(slot.as_u64() / migration.to);
+        ops.push(KeyValueStoreOp::PutKeyValue(
+            get_key_for_col(
+                DBColumn::BeaconRestorePointStaged.into(),
+                restore_point_key.as_bytes(),
+            ),
+            RestorePointHash { state_root }.as_store_bytes(),
+        ));
+        ops.push(migration.as_kv_store_op(RESTORE_POINT_MIGRATION_KEY));
+
+        self.cold_db.do_atomically(ops)?;
+
+        if slot % E::SlotsPerHistoricalRoot::to_u64() == 0 {
+            info!(
+                self.log,
+                "Staging restore points";
+                "slot" => slot,
+                "split_slot" => self.get_split_slot(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Replace the existing restore points with the staged ones, deleting the states of existing
+    /// restore points which don't lie at the new spacing.
+    fn replace_restore_points(&self, migration: &mut RestorePointMigration) -> Result<(), Error> {
+        let mut ops = vec![];
+        let mut states_deleted = 0;
+
+        for result in self.cold_db.iter_column(DBColumn::BeaconRestorePoint) {
+            let (key, value) = result?;
+
+            if (key.to_low_u64_be() * migration.from) % migration.to != 0 {
+                // The state is stored either as a snapshot or as a diff. Neither can be the base
+                // of a diff, which are only taken from restore points at both spacings.
+                let state_root = RestorePointHash::from_store_bytes(&value)?.state_root;
+                for column in [DBColumn::BeaconState, DBColumn::BeaconStateDiff] {
+                    ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                        column.into(),
+                        state_root.as_bytes(),
+                    )));
+                }
+                states_deleted += 1;
+            }
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::BeaconRestorePoint.into(),
+                key.as_bytes(),
+            )));
+        }
+
+        let mut restore_points = 0;
+        for result in self.cold_db.iter_column(DBColumn::BeaconRestorePointStaged) {
+            let (key, value) = result?;
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::BeaconRestorePointStaged.into(),
+                key.as_bytes(),
+            )));
+            ops.push(KeyValueStoreOp::PutKeyValue(
+                get_key_for_col(DBColumn::BeaconRestorePoint.into(), key.as_bytes()),
+                value,
+            ));
+            restore_points += 1;
+        }
+
+        migration.complete = true;
+        ops.push(migration.as_kv_store_op(RESTORE_POINT_MIGRATION_KEY));
+
+        self.cold_db.do_atomically(ops)?;
+
+        info!(
+            self.log,
+            "Replaced restore points";
+            "restore_points" => restore_points,
+            "states_deleted" => states_deleted,
+        );
+
+        Ok(())
+    }
+
+    /// Update the on-disk config and anchor for a change of slots-per-restore-point whose
+    /// restore points have been replaced, then forget the change.
+    pub(crate) fn finish_restore_point_migration(
+        &self,
+        migration: RestorePointMigration,
+    ) -> Result<(), Error> {
+        let to = migration.to;
+
+        let disk_config = self.load_config()?;
+        let config_updated = disk_config
+            .as_ref()
+            .map_or(false, |config| config.slots_per_restore_point == to);
+
+        if !config_updated {
+            // The hierarchy is unchanged, as the restore points were staged with the database
+            // opened with its on-disk hierarchy.
+            let hierarchy_config = disk_config
+                .and_then(|config| config.hierarchy_config)
+                .unwrap_or_else(|| self.config.hierarchy_config.clone());
+            let disk_config = OnDiskStoreConfig {
+                slots_per_restore_point: to,
+                hierarchy_config: Some(hierarchy_config),
+            };
+            let mut ops = vec![disk_config.as_kv_store_op(CONFIG_KEY)];
+
+            // The state limits must lie on restore points at the new spacing.
+            let anchor_info = self.load_anchor_info()?.map(|mut anchor_info| {
+                anchor_info.state_lower_limit = anchor_info.state_lower_limit / to * to;
+                anchor_info.state_upper_limit = (anchor_info.state_upper_limit + to - 1) / to * to;
+                anchor_info
+            });
+            if let Some(ref anchor_info) = anchor_info {
+                ops.push(anchor_info.as_kv_store_op(ANCHOR_INFO_KEY));
+            }
+
+            self.hot_db.do_atomically(ops)?;
+            *self.anchor_info.write() = anchor_info;
+        }
+
+        self.cold_db.key_delete(
+            DBColumn::BeaconMeta.into(),
+            RESTORE_POINT_MIGRATION_KEY.as_bytes(),
+        )?;
+
+        info!(
+            self.log,
+            "Changed slots-per-restore-point";
+            "from" => migration.from,
+            "to" => to,
+        );
+
+        Ok(())
+    }
+
+    /// Load the progress of an unfinished change of slots-per-restore-point, if any.
+    pub fn load_restore_point_migration(&self) -> Result<Option<RestorePointMigration>, Error> {
+        self.cold_db.get(&RESTORE_POINT_MIGRATION_KEY)
+    }
+}
@@ -0,0 +1,480 @@
+//! Integrity checks for the hot and cold databases.
+use crate::chunked_vector::{chunk_key, BlockRoots, Chunk, Field, StateRoots};
+use crate::hot_cold_store::{ColdStateSummary, HotColdDB, HotStateSummary, RestorePointHash};
+use crate::{DBColumn, Error, ItemStore, StoreItem, StoreOp};
+use slog::{debug, info};
+use state_processing::StateRootStrategy;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use types::{EthSpec, Hash256, Slot};
+
+/// An inconsistency found by `HotColdDB::verify`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+    /// The split state is missing from the hot database.
+    MissingSplitState { slot: Slot, state_root: Hash256 },
+    /// A chunk of a chunked vector is missing from the freezer database.
+    MissingChunk {
+        column: DBColumn,
+        chunk_index: usize,
+    },
+    /// A block referenced by the freezer's block roots is missing.
+    MissingBlock { slot: Slot, block_root: Hash256 },
+    /// A block is stored at a different slot to the one given by the freezer's block roots.
+    MismatchedBlockSlot {
+        slot: Slot,
+        block_root: Hash256,
+        block_slot: Slot,
+    },
+    /// A block's parent doesn't match the previous block in the freezer's block roots.
+    BrokenBlockChain {
+        slot: Slot,
+        block_root: Hash256,
+        parent_root: Hash256,
+        expected_parent_root: Hash256,
+    },
+    /// A hot state summary which doesn't lead to a loadable state.
+    UnloadableHotState {
+        state_root: Hash256,
+        slot: Slot,
+        error: Option<String>,
+    },
+    /// A frozen state whose summary is missing or has the wrong slot.
+    MissingColdStateSummary {
+        state_root: Hash256,
+        slot: Slot,
+        summary_slot: Option<Slot>,
+    },
+    /// A restore point whose state root differs from the freezer's state roots.
+    MismatchedRestorePoint {
+        restore_point_index: u64,
+        state_root: Hash256,
+        expected_state_root: Hash256,
+    },
+    /// A restore point which can't be loaded, or which loads with the wrong state root.
+    UnloadableRestorePoint {
+        restore_point_index: u64,
+        state_root: Hash256,
+        error: String,
+    },
+    /// A temporary state left behind by a failed block import.
+    OrphanedTemporaryState { state_root: Hash256 },
+}
+
+impl Inconsistency {
+    /// Return true if `HotColdDB::repair` can fix this inconsistency.
+    ///
+    /// Missing blocks and restore points can only be restored by re-syncing.
+    pub fn is_repairable(&self) -> bool {
+        matches!(
+            self,
+            Inconsistency::UnloadableHotState { .. }
+                | Inconsistency::MissingColdStateSummary { .. }
+                | Inconsistency::OrphanedTemporaryState { .. }
+        )
+    }
+}
+
+/// Lazily loads the values of a chunked vector for increasing slots.
+struct ChunkReader<F> {
+    chunk_index: Option<usize>,
+    chunk: Option<Chunk<Hash256>>,
+    _phantom: PhantomData<F>,
+}
+
+impl<F> ChunkReader<F> {
+    fn new() -> Self {
+        Self {
+            chunk_index: None,
+            chunk: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Return the value at `slot`, or `None` if its chunk is missing.
+    ///
+    /// A missing chunk is recorded in `inconsistencies` the first time it is encountered.
+    fn get<E, S>(
+        &mut self,
+        store: &S,
+        slot: Slot,
+        inconsistencies: &mut Vec<Inconsistency>,
+    ) -> Result<Option<Hash256>, Error>
+    where
+        E: EthSpec,
+        S: ItemStore<E>,
+        F: Field<E, Value = Hash256>,
+    {
+        let vindex = slot.as_usize();
+        let chunk_index = F::chunk_index(vindex);
+        if self.chunk_index != Some(chunk_index) {
+            self.chunk = Chunk::load(store, F::column(), &chunk_key(chunk_index))?;
+            self.chunk_index = Some(chunk_index);
+            if self.chunk.is_none() {
+                inconsistencies.push(Inconsistency::MissingChunk {
+                    column: F::column(),
+                    chunk_index,
+                });
+            }
+        }
+        Ok(self.chunk.as_ref().and_then(|chunk| {
+            chunk
+                .values
+                .get(vindex - chunk_index * F::chunk_size())
+                .copied()
+        }))
+    }
+}
+
+impl<E, Hot, Cold> HotColdDB<E, Hot, Cold>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    /// Check the consistency of the hot and cold databases, returning every inconsistency found.
+    ///
+    /// This loads every restore point and unfinalized state, so may take a long time on large
+    /// databases.
+    pub fn verify(&self) -> Result<Vec<Inconsistency>, Error> {
+        let mut inconsistencies = vec![];
+
+        self.verify_split_state(&mut inconsistencies)?;
+        self.verify_frozen_block_roots(&mut inconsistencies)?;
+        self.verify_hot_state_summaries(&mut inconsistencies)?;
+        self.verify_frozen_state_roots(&mut inconsistencies)?;
+        self.verify_restore_points(&mut inconsistencies)?;
+        self.verify_temporary_states(&mut inconsistencies)?;
+
+        Ok(inconsistencies)
+    }
+
+    /// Fix the repairable `inconsistencies`, returning the number which were repaired.
+    ///
+    /// Unloadable hot states and orphaned temporary states are deleted, and cold state summaries
+    /// are rewritten from the freezer's state roots.
+    ///
+    /// Nothing is repaired if any of the states to delete is still required, i.e. if it's the
+    /// split state, one of the `head_state_roots`, or the epoch boundary state of a hot state
+    /// which is kept.
+    pub fn repair(
+        &self,
+        inconsistencies: &[Inconsistency],
+        head_state_roots: &[Hash256],
+    ) -> Result<usize, Error> {
+        let states_to_delete = inconsistencies
+            .iter()
+            .filter_map(|inconsistency| match inconsistency {
+                Inconsistency::UnloadableHotState { state_root, .. }
+                | Inconsistency::OrphanedTemporaryState { state_root } => Some(*state_root),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        self.check_states_deletable(&states_to_delete, head_state_roots)?;
+
+        let mut hot_ops = vec![];
+        let mut cold_ops = vec![];
+
+        for inconsistency in inconsistencies {
+            match inconsistency {
+                Inconsistency::UnloadableHotState { state_root, .. } => {
+                    hot_ops.push(StoreOp::DeleteState(*state_root, None));
+                }
+                Inconsistency::MissingColdStateSummary {
+                    state_root, slot, ..
+                } => {
+                    cold_ops.push(ColdStateSummary { slot: *slot }.as_kv_store_op(*state_root));
+                }
+                Inconsistency::OrphanedTemporaryState { state_root } => {
+                    hot_ops.push(StoreOp::DeleteState(*state_root, None));
+                    hot_ops.push(StoreOp::DeleteStateTemporaryFlag(*state_root));
+                }
+                _ => continue,
+            }
+        }
+
+        let repaired = inconsistencies
+            .iter()
+            .filter(|inconsistency| inconsistency.is_repairable())
+            .count();
+        self.do_atomically(hot_ops)?;
+        self.cold_db.do_atomically(cold_ops)?;
+
+        info!(self.log, "Database repair complete"; "repaired" => repaired);
+
+        Ok(repaired)
+    }
+
+    /// Return an error if any of `state_roots` is still required by the database or the chain.
+    fn check_states_deletable(
+        &self,
+        state_roots: &HashSet<Hash256>,
+        head_state_roots: &[Hash256],
+    ) -> Result<(), Error> {
+        if state_roots.is_empty() {
+            return Ok(());
+        }
+
+        let split = self.get_split_info();
+        if state_roots.contains(&split.state_root) {
+            return Err(Error::UnsafeRepair {
+                state_root: split.state_root,
+                reason: "it is the split state".into(),
+            });
+        }
+        if let Some(state_root) = head_state_roots
+            .iter()
+            .find(|state_root| state_roots.contains(state_root))
+        {
+            return Err(Error::UnsafeRepair {
+                state_root: *state_root,
+                reason: "it is the state of a head block".into(),
+            });
+        }
+
+        // Hot states are replayed from their epoch boundary state, so it must outlive them.
+        for res in self.hot_db.iter_column(DBColumn::BeaconStateSummary) {
+            let (state_root, value) = res?;
+            if state_roots.contains(&state_root) {
+                continue;
+            }
+            let summary = HotStateSummary::from_store_bytes(&value)?;
+            if state_roots.contains(&summary.epoch_boundary_state_root) {
+                return Err(Error::UnsafeRepair {
+                    state_root: summary.epoch_boundary_state_root,
+                    reason: format!("it is the epoch boundary state of {:?}", state_root),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the split state can be loaded from the hot database.
+    fn verify_split_state(&self, inconsistencies: &mut Vec<Inconsistency>) -> Result<(), Error> {
+        let split = self.get_split_info();
+        // The split is unset until the first finalization.
+        if split.state_root.is_zero() {
+            return Ok(());
+        }
+        if self.load_hot_state_summary(&split.state_root)?.is_none() {
+            inconsistencies.push(Inconsistency::MissingSplitState {
+                slot: split.slot,
+                state_root: split.state_root,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that the freezer's block roots form a chain of blocks that are in the database.
+    fn verify_frozen_block_roots(
+        &self,
+        inconsistencies: &mut Vec<Inconsistency>,
+    ) -> Result<(), Error> {
+        let start_slot = self.get_oldest_block_slot();
+        let split_slot = self.get_split_slot();
+        info!(
+            self.log,
+            "Verifying frozen blocks";
+            "start_slot" => start_slot,
+            "end_slot" => split_slot,
+        );
+
+        let mut reader = ChunkReader::<BlockRoots>::new();
+        let mut prev_block_root = None;
+
+        for slot in (start_slot.as_u64()..split_slot.as_u64()).map(Slot::new) {
+            let block_root = match reader.get(&self.cold_db, slot, inconsistencies)? {
+                Some(block_root) => block_root,
+                None => {
+                    prev_block_root = None;
+                    continue;
+                }
+            };
+            if prev_block_root == Some(block_root) {
+                continue;
+            }
+
+            let block = match self.get_blinded_block(&block_root)? {
+                Some(block) => block,
+                // The block before the oldest block slot is expected to be missing if it was
+                // pruned or never backfilled.
+                None if slot == start_slot && slot != self.spec.genesis_slot => {
+                    prev_block_root = Some(block_root);
+                    continue;
+                }
+                None => {
+                    inconsistencies.push(Inconsistency::MissingBlock { slot, block_root });
+                    prev_block_root = Some(block_root);
+                    continue;
+                }
+            };
+
+            if block.slot() != slot && slot != start_slot {
+                inconsistencies.push(Inconsistency::MismatchedBlockSlot {
+                    slot,
+                    block_root,
+                    block_slot: block.slot(),
+                });
+            }
+            if let Some(expected_parent_root) = prev_block_root {
+                if block.parent_root() != expected_parent_root {
+                    inconsistencies.push(Inconsistency::BrokenBlockChain {
+                        slot,
+                        block_root,
+                        parent_root: block.parent_root(),
+                        expected_parent_root,
+                    });
+                }
+            }
+            prev_block_root = Some(block_root);
+        }
+
+        Ok(())
+    }
+
+    /// Check that every hot state summary leads to a state which can be loaded.
+    fn verify_hot_state_summaries(
+        &self,
+        inconsistencies: &mut Vec<Inconsistency>,
+    ) -> Result<(), Error> {
+        info!(self.log, "Verifying hot states");
+
+        for res in self.hot_db.iter_column(DBColumn::BeaconStateSummary) {
+            let (state_root, value) = res?;
+            let summary = HotStateSummary::from_store_bytes(&value)?;
+
+            // Temporary states are checked separately.
+            if self.load_state_temporary_flag(&state_root)?.is_some() {
+                continue;
+            }
+
+            let error = match self.load_hot_state(&state_root, StateRootStrategy::Inconsistent) {
+                Ok(Some(_)) => continue,
+                Ok(None) => None,
+                Err(e) => Some(format!("{:?}", e)),
+            };
+            inconsistencies.push(Inconsistency::UnloadableHotState {
+                state_root,
+                slot: summary.slot,
+                error,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check that every available frozen state has a summary with the correct slot.
+    fn verify_frozen_state_roots(
+        &self,
+        inconsistencies: &mut Vec<Inconsistency>,
+    ) -> Result<(), Error> {
+        let (lower_limit, upper_limit) = self.get_historic_state_limits();
+        let split_slot = self.get_split_slot();
+        info!(
+            self.log,
+            "Verifying frozen states";
+            "state_lower_limit" => lower_limit,
+            "state_upper_limit" => upper_limit,
+        );
+
+        let mut reader = ChunkReader::<StateRoots>::new();
+        let available_slots = (0..split_slot.as_u64())
+            .map(Slot::new)
+            .filter(|slot| *slot <= lower_limit || *slot >= upper_limit);
+
+        for slot in available_slots {
+            // The genesis state root is only stored as a restore point.
+            if slot == self.spec.genesis_slot {
+                continue;
+            }
+            let state_root = match reader.get(&self.cold_db, slot, inconsistencies)? {
+                Some(state_root) => state_root,
+                None => continue,
+            };
+            let summary_slot = self.load_cold_state_slot(&state_root)?;
+            if summary_slot != Some(slot) {
+                inconsistencies.push(Inconsistency::MissingColdStateSummary {
+                    state_root,
+                    slot,
+                    summary_slot,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that every restore point matches the freezer's state roots and can be loaded.
+    fn verify_restore_points(&self, inconsistencies: &mut Vec<Inconsistency>) -> Result<(), Error> {
+        info!(self.log, "Verifying restore points");
+
+        let mut reader = ChunkReader::<StateRoots>::new();
+        let mut restore_points = self
+            .cold_db
+            .iter_column(DBColumn::BeaconRestorePoint)
+            .map(|res| {
+                let (key, value) = res?;
+                let state_root = RestorePointHash::from_store_bytes(&value)?.state_root;
+                Ok((key.to_low_u64_be(), state_root))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        restore_points.sort_unstable();
+
+        for (restore_point_index, state_root) in restore_points {
+            let slot = Slot::new(restore_point_index * self.config.slots_per_restore_point);
+            debug!(
+                self.log,
+                "Verifying restore point";
+                "slot" => slot,
+                "state_root" => ?state_root,
+            );
+
+            if slot != self.spec.genesis_slot {
+                if let Some(expected_state_root) =
+                    reader.get(&self.cold_db, slot, inconsistencies)?
+                {
+                    if expected_state_root != state_root {
+                        inconsistencies.push(Inconsistency::MismatchedRestorePoint {
+                            restore_point_index,
+                            state_root,
+                            expected_state_root,
+                        });
+                    }
+                }
+            }
+
+            let error = match self.load_restore_point(&state_root) {
+                Ok(state) if state.slot() != slot => {
+                    format!("restore point has slot {}", state.slot())
+                }
+                Ok(mut state) => match state.update_tree_hash_cache() {
+                    Ok(root) if root == state_root => continue,
+                    Ok(root) => format!("restore point has state root {:?}", root),
+                    Err(e) => format!("{:?}", e),
+                },
+                Err(e) => format!("{:?}", e),
+            };
+            inconsistencies.push(Inconsistency::UnloadableRestorePoint {
+                restore_point_index,
+                state_root,
+                error,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check for temporary states, which are only expected while a block is being imported.
+    fn verify_temporary_states(
+        &self,
+        inconsistencies: &mut Vec<Inconsistency>,
+    ) -> Result<(), Error> {
+        for state_root in self.hot_db.iter_column_keys(DBColumn::BeaconStateTemporary) {
+            inconsistencies.push(Inconsistency::OrphanedTemporaryState {
+                state_root: state_root?,
+            });
+        }
+        Ok(())
+    }
+}
@@ -799,6 +799,82 @@ impl BeaconNodeHttpClient {
         self.get_opt(path).await
     }
 
+    /// `GET beacon/light_client/bootstrap/{block_root}`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_light_client_bootstrap<T: EthSpec>(
+        &self,
+        block_root: Hash256,
+    ) -> Result<Option<ForkVersionedResponse<LightClientBootstrap<T>>>, Error> {
+        let mut path = self.eth_path(V1)?;
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("beacon")
+            .push("light_client")
+            .push("bootstrap")
+            .push(&format!("{:?}", block_root));
+
+        self.get_opt(path).await
+    }
+
+    /// `GET beacon/light_client/updates?start_period,count`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_light_client_updates<T: EthSpec>(
+        &self,
+        start_period: u64,
+        count: u64,
+    ) -> Result<Option<Vec<ForkVersionedResponse<LightClientUpdate<T>>>>, Error> {
+        let mut path = self.eth_path(V1)?;
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("beacon")
+            .push("light_client")
+            .push("updates");
+
+        path.query_pairs_mut()
+            .append_pair("start_period", &start_period.to_string())
+            .append_pair("count", &count.to_string());
+
+        self.get_opt(path).await
+    }
+
+    /// `GET beacon/light_client/finality_update`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_light_client_finality_update<T: EthSpec>(
+        &self,
+    ) -> Result<Option<ForkVersionedResponse<LightClientFinalityUpdate<T>>>, Error> {
+        let mut path = self.eth_path(V1)?;
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("beacon")
+            .push("light_client")
+            .push("finality_update");
+
+        self.get_opt(path).await
+    }
+
+    /// `GET beacon/light_client/optimistic_update`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_light_client_optimistic_update<T: EthSpec>(
+        &self,
+    ) -> Result<Option<ForkVersionedResponse<LightClientOptimisticUpdate<T>>>, Error> {
+        let mut path = self.eth_path(V1)?;
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("beacon")
+            .push("light_client")
+            .push("optimistic_update");
+
+        self.get_opt(path).await
+    }
+
     /// `POST beacon/pool/attestations`
     pub async fn post_beacon_pool_attestations<T: EthSpec>(
         &self,
@@ -1647,6 +1723,53 @@ impl BeaconNodeHttpClient {
             }))
     }
 
+    /// `GET events?topics,since_slot`
+    ///
+    /// Journaled events emitted at or after `since_slot` are returned before new events.
+    pub async fn get_events_since_slot<T: EthSpec>(
+        &self,
+        topic: &[EventTopic],
+        since_slot: Slot,
+    ) -> Result<impl Stream<Item = Result<EventKind<T>, Error>>, Error> {
+        let mut path = self.eth_path(V1)?;
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("events");
+
+        let topic_string = topic
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        path.query_pairs_mut()
+            .append_pair("topics", &topic_string)
+            .append_pair("since_slot", &since_slot.to_string());
+
+        let response = ok_or_error(self.client.get(path).send().await?).await?;
+
+        // Replayed events are sent in quick succession, so a single chunk may contain several
+        // events. Buffer the stream and split it on the blank line which ends each event.
+        Ok(futures::stream::unfold(
+            (response.bytes_stream(), Vec::new()),
+            |(mut stream, mut buffer)| async move {
+                loop {
+                    if let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                        let event = buffer.drain(..end + 2).collect::<Vec<_>>();
+                        // Skip keep-alive comments.
+                        if event.starts_with(b":") {
+                            continue;
+                        }
+                        return Some((EventKind::from_sse_bytes(&event), (stream, buffer)));
+                    }
+                    match stream.next().await? {
+                        Ok(bytes) => buffer.extend_from_slice(&bytes),
+                        Err(e) => return Some((Err(Error::Reqwest(e)), (stream, buffer))),
+                    }
+                }
+            },
+        ))
+    }
+
     /// `POST validator/duties/sync/{epoch}`
     pub async fn post_validator_duties_sync(
         &self,
@@ -4,14 +4,18 @@ mod attestation_performance;
 pub mod attestation_rewards;
 mod block_packing_efficiency;
 mod block_rewards;
+mod block_simulation;
+mod block_timings;
+mod checkpoint_bundle;
 mod standard_block_rewards;
 mod sync_committee_rewards;
+mod validator_monitor;
 
 use crate::{
     ok_or_error,
     types::{
         BeaconState, ChainSpec, DepositTreeSnapshot, Epoch, EthSpec, FinalizedExecutionBlock,
-        GenericResponse, ValidatorId,
+        GenericResponse, Graffiti, ValidatorId,
     },
     BeaconNodeHttpClient, DepositData, Error, Eth1Data, Hash256, Slot, StateId, StatusCode,
 };
@@ -20,19 +24,35 @@ use reqwest::IntoUrl;
 use serde::{Deserialize, Serialize};
 use ssz::four_byte_option_impl;
 use ssz_derive::{Decode, Encode};
-use store::{AnchorInfo, Split, StoreConfig};
+use store::{
+    AnchorInfo, ColumnSizesStatus, CompactionProgress, DatabaseSize, ReconstructionProgress, Split,
+    StoreConfig,
+};
 
 pub use attestation_performance::{
     AttestationPerformance, AttestationPerformanceQuery, AttestationPerformanceStatistics,
 };
-pub use attestation_rewards::StandardAttestationRewards;
+pub use attestation_rewards::{
+    AttestationRewardsQuery, EpochAttestationRewards, StandardAttestationRewards,
+};
 pub use block_packing_efficiency::{
     BlockPackingEfficiency, BlockPackingEfficiencyQuery, ProposerInfo, UniqueAttestation,
 };
 pub use block_rewards::{AttestationRewards, BlockReward, BlockRewardMeta, BlockRewardsQuery};
+pub use block_simulation::{
+    AttestationPacking, BlockSimulation, BlockSimulationQuery, PayloadSource,
+};
+pub use block_timings::{
+    BlockTiming, BlockTimings, BlockTimingsQuery, BlockTimingsSummary, DelayPercentiles,
+};
+pub use checkpoint_bundle::CheckpointBundle;
 pub use lighthouse_network::{types::SyncState, PeerInfo};
 pub use standard_block_rewards::StandardBlockReward;
 pub use sync_committee_rewards::SyncCommitteeReward;
+pub use validator_monitor::{
+    AttestationSummary, SyncCommitteeSummary, ValidatorMonitorAlert, ValidatorMonitorAlertKind,
+    ValidatorMonitorEpochSummary, ValidatorMonitorQuery,
+};
 
 // Define "legacy" implementations of `Option<T>` which use four bytes for encoding the union
 // selector.
@@ -359,6 +379,15 @@ pub struct DatabaseInfo {
     pub config: StoreConfig,
     pub split: Split,
     pub anchor: Option<AnchorInfo>,
+    pub compaction: Option<CompactionProgress>,
+    pub reconstruction: Option<ReconstructionProgress>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseSizes {
+    pub hot: DatabaseSize,
+    pub freezer: DatabaseSize,
+    pub column_sizes: ColumnSizesStatus,
 }
 
 impl BeaconNodeHttpClient {
@@ -567,6 +596,88 @@ impl BeaconNodeHttpClient {
         self.post_with_response(path, &()).await
     }
 
+    /// `POST lighthouse/database/reconstruct/pause`
+    pub async fn post_lighthouse_database_reconstruct_pause(&self) -> Result<String, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("reconstruct")
+            .push("pause");
+
+        self.post_with_response(path, &()).await
+    }
+
+    /// `POST lighthouse/database/reconstruct/resume`
+    pub async fn post_lighthouse_database_reconstruct_resume(&self) -> Result<String, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("reconstruct")
+            .push("resume");
+
+        self.post_with_response(path, &()).await
+    }
+
+    /// `GET lighthouse/database/size`
+    pub async fn get_lighthouse_database_size(&self) -> Result<DatabaseSizes, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("size");
+
+        self.get(path).await
+    }
+
+    /// `POST lighthouse/database/compact`
+    pub async fn post_lighthouse_database_compact(&self) -> Result<String, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("database")
+            .push("compact");
+
+        self.post_with_response(path, &()).await
+    }
+
+    /// `GET lighthouse/validator/blocks/simulate?slot,graffiti`
+    pub async fn get_lighthouse_validator_blocks_simulate<T: EthSpec>(
+        &self,
+        slot: Option<Slot>,
+        graffiti: Option<&Graffiti>,
+    ) -> Result<GenericResponse<BlockSimulation<T>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("validator")
+            .push("blocks")
+            .push("simulate");
+
+        if let Some(slot) = slot {
+            path.query_pairs_mut()
+                .append_pair("slot", &slot.to_string());
+        }
+
+        if let Some(graffiti) = graffiti {
+            path.query_pairs_mut()
+                .append_pair("graffiti", &graffiti.to_string());
+        }
+
+        self.get(path).await
+    }
+
     ///
     /// Analysis endpoints.
     ///
@@ -592,6 +703,27 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET` lighthouse/analysis/block_timings?start_slot,end_slot
+    pub async fn get_lighthouse_analysis_block_timings(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Result<BlockTimings, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("analysis")
+            .push("block_timings");
+
+        path.query_pairs_mut()
+            .append_pair("start_slot", &start_slot.to_string())
+            .append_pair("end_slot", &end_slot.to_string());
+
+        self.get(path).await
+    }
+
     /// `GET` lighthouse/analysis/block_packing?start_epoch,end_epoch
     pub async fn get_lighthouse_analysis_block_packing(
         &self,
@@ -635,4 +767,60 @@ impl BeaconNodeHttpClient {
 
         self.get(path).await
     }
+
+    /// `POST` lighthouse/analysis/attestation_rewards?start_epoch,end_epoch
+    ///
+    /// The response is streamed as newline-delimited JSON with one line per epoch, which is
+    /// collected before returning.
+    pub async fn post_lighthouse_analysis_attestation_rewards(
+        &self,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+        validators: &[ValidatorId],
+    ) -> Result<Vec<EpochAttestationRewards>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("analysis")
+            .push("attestation_rewards");
+
+        path.query_pairs_mut()
+            .append_pair("start_epoch", &start_epoch.to_string())
+            .append_pair("end_epoch", &end_epoch.to_string());
+
+        let body = self
+            .post_generic(path, &validators, None)
+            .await?
+            .bytes()
+            .await?;
+
+        body.split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).map_err(Error::InvalidJson))
+            .collect()
+    }
+
+    /// `GET` lighthouse/validator_monitor/{index}?start_epoch,end_epoch
+    pub async fn get_lighthouse_validator_monitor(
+        &self,
+        validator_index: u64,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+    ) -> Result<Vec<ValidatorMonitorEpochSummary>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("validator_monitor")
+            .push(&validator_index.to_string());
+
+        path.query_pairs_mut()
+            .append_pair("start_epoch", &start_epoch.to_string())
+            .append_pair("end_epoch", &end_epoch.to_string());
+
+        self.get(path).await
+    }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use types::Epoch;
 
 // Details about the rewards paid for attestations
 // All rewards in GWei
@@ -42,3 +43,18 @@ pub struct StandardAttestationRewards {
     pub ideal_rewards: Vec<IdealAttestationRewards>,
     pub total_rewards: Vec<TotalAttestationRewards>,
 }
+
+/// Query parameters for the bulk attestation rewards endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationRewardsQuery {
+    pub start_epoch: Epoch,
+    pub end_epoch: Epoch,
+}
+
+/// The attestation rewards for a single epoch, as streamed by the bulk endpoint.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct EpochAttestationRewards {
+    pub epoch: Epoch,
+    #[serde(flatten)]
+    pub rewards: StandardAttestationRewards,
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use types::{AbstractExecPayload, BeaconBlock, BlindedPayload, EthSpec, Graffiti, Slot, Uint256};
+
+use super::StandardBlockReward;
+
+/// Query parameters for the `/lighthouse/validator/blocks/simulate` endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockSimulationQuery {
+    /// The slot to simulate a block for, defaults to the current slot.
+    pub slot: Option<Slot>,
+    pub graffiti: Option<Graffiti>,
+}
+
+/// Where the execution payload of a simulated block was sourced from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadSource {
+    Local,
+    Builder,
+}
+
+/// Statistics about the attestations chosen by the op pool's max cover packing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttestationPacking {
+    /// Number of valid previous epoch attestations available for inclusion.
+    pub prev_epoch_candidates: usize,
+    /// Number of valid current epoch attestations available for inclusion.
+    pub curr_epoch_candidates: usize,
+    /// Number of previous epoch attestations included in the block.
+    pub prev_epoch_selected: usize,
+    /// Number of current epoch attestations included in the block.
+    pub curr_epoch_selected: usize,
+    /// Number of validators whose first attestation for the epoch is included.
+    pub covered_validators: usize,
+    /// Proposer reward for the included attestations as estimated by the op pool, in GWei.
+    pub reward: u64,
+}
+
+/// The result of a block production dry-run.
+///
+/// The block is unsigned and has an empty RANDAO reveal, so it must not be published.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: EthSpec, Payload: AbstractExecPayload<T>")]
+pub struct BlockSimulation<T: EthSpec, Payload: AbstractExecPayload<T> = BlindedPayload<T>> {
+    pub block: BeaconBlock<T, Payload>,
+    pub attestation_packing: AttestationPacking,
+    /// Where the execution payload came from, absent prior to the merge.
+    pub payload_source: Option<PayloadSource>,
+    /// Value of the execution payload to the proposer in Wei, zero prior to the merge.
+    #[serde(with = "eth2_serde_utils::quoted_u256")]
+    pub payload_value: Uint256,
+    /// Consensus rewards the proposer would receive for the block, in GWei.
+    pub consensus_reward: StandardBlockReward,
+}
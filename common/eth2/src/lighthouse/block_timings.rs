@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use types::{Hash256, Slot};
+
+/// Query parameters for the `/lighthouse/analysis/block_timings` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlockTimingsQuery {
+    /// Lower slot limit for block timings returned (inclusive).
+    pub start_slot: Slot,
+    /// Upper slot limit for block timings returned (inclusive).
+    pub end_slot: Slot,
+}
+
+/// When a canonical block was observed, imported and set as head by this node.
+///
+/// All delays are in milliseconds and are absent if the event wasn't recorded, e.g. because the
+/// block was imported before the node started recording timings.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlockTiming {
+    pub slot: Slot,
+    pub block_root: Hash256,
+    /// Delay between the start of the slot and the block being observed.
+    pub observed_delay: Option<u64>,
+    /// Delay between the block being observed and imported.
+    pub imported_delay: Option<u64>,
+    /// Delay between the block being imported and set as head.
+    pub set_as_head_delay: Option<u64>,
+    /// The peer which first sent us the block over gossip.
+    pub peer_id: Option<String>,
+    /// The client of the peer which first sent us the block over gossip.
+    pub peer_client: Option<String>,
+}
+
+/// Percentiles of a set of delays, in milliseconds.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct DelayPercentiles {
+    /// Number of delays the percentiles were computed from.
+    pub count: usize,
+    pub p50: Option<u64>,
+    pub p90: Option<u64>,
+    pub p95: Option<u64>,
+    pub p99: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl DelayPercentiles {
+    /// Compute percentiles using the nearest-rank method.
+    pub fn new(delays: impl IntoIterator<Item = u64>) -> Self {
+        let mut delays = delays.into_iter().collect::<Vec<_>>();
+        delays.sort_unstable();
+
+        let count = delays.len();
+        let percentile = |p: usize| {
+            let rank = (p * count + 99) / 100;
+            delays.get(rank.saturating_sub(1)).copied()
+        };
+
+        Self {
+            count,
+            p50: percentile(50),
+            p90: percentile(90),
+            p95: percentile(95),
+            p99: percentile(99),
+            max: delays.last().copied(),
+        }
+    }
+}
+
+/// A summary of block timings for a range of slots, for tracking network health over time.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlockTimingsSummary {
+    /// Number of canonical blocks in the range.
+    pub num_blocks: usize,
+    pub observed_delay: DelayPercentiles,
+    pub imported_delay: DelayPercentiles,
+    pub set_as_head_delay: DelayPercentiles,
+    /// Number of blocks first received from each client type.
+    pub peer_clients: BTreeMap<String, usize>,
+}
+
+impl BlockTimingsSummary {
+    pub fn new(blocks: &[BlockTiming]) -> Self {
+        let mut peer_clients = BTreeMap::new();
+        for client in blocks.iter().filter_map(|block| block.peer_client.as_ref()) {
+            *peer_clients.entry(client.clone()).or_default() += 1;
+        }
+
+        Self {
+            num_blocks: blocks.len(),
+            observed_delay: DelayPercentiles::new(
+                blocks.iter().filter_map(|block| block.observed_delay),
+            ),
+            imported_delay: DelayPercentiles::new(
+                blocks.iter().filter_map(|block| block.imported_delay),
+            ),
+            set_as_head_delay: DelayPercentiles::new(
+                blocks.iter().filter_map(|block| block.set_as_head_delay),
+            ),
+            peer_clients,
+        }
+    }
+}
+
+/// Response for the `/lighthouse/analysis/block_timings` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlockTimings {
+    pub blocks: Vec<BlockTiming>,
+    pub summary: BlockTimingsSummary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        assert_eq!(DelayPercentiles::new(vec![]), DelayPercentiles::default());
+
+        let percentiles = DelayPercentiles::new((1..=100).rev());
+        assert_eq!(percentiles.count, 100);
+        assert_eq!(percentiles.p50, Some(50));
+        assert_eq!(percentiles.p90, Some(90));
+        assert_eq!(percentiles.p95, Some(95));
+        assert_eq!(percentiles.p99, Some(99));
+        assert_eq!(percentiles.max, Some(100));
+
+        let single = DelayPercentiles::new(vec![7]);
+        assert_eq!(single.p50, Some(7));
+        assert_eq!(single.p99, Some(7));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::types::{BeaconState, ChainSpec, DepositTreeSnapshot, EthSpec, SignedBeaconBlock};
+use ssz::{Decode, DecodeError, Encode};
+use ssz_derive::{Decode, Encode};
+
+/// A single file containing everything required to checkpoint sync a beacon node without access
+/// to a remote beacon node.
+///
+/// The state and block are kept as raw SSZ bytes since decoding them requires a `ChainSpec`.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct CheckpointBundle {
+    /// SSZ bytes of the finalized `BeaconState`.
+    pub state_bytes: Vec<u8>,
+    /// SSZ bytes of the `SignedBeaconBlock` whose post-state is `state_bytes`.
+    pub block_bytes: Vec<u8>,
+    /// Optional EIP-4881 deposit snapshot, used for fast deposit sync.
+    pub deposit_snapshot: Option<DepositTreeSnapshot>,
+}
+
+impl CheckpointBundle {
+    pub fn new<E: EthSpec>(
+        state: &BeaconState<E>,
+        block: &SignedBeaconBlock<E>,
+        deposit_snapshot: Option<DepositTreeSnapshot>,
+    ) -> Self {
+        Self {
+            state_bytes: state.as_ssz_bytes(),
+            block_bytes: block.as_ssz_bytes(),
+            deposit_snapshot,
+        }
+    }
+
+    pub fn state<E: EthSpec>(&self, spec: &ChainSpec) -> Result<BeaconState<E>, DecodeError> {
+        BeaconState::from_ssz_bytes(&self.state_bytes, spec)
+    }
+
+    pub fn block<E: EthSpec>(&self, spec: &ChainSpec) -> Result<SignedBeaconBlock<E>, DecodeError> {
+        SignedBeaconBlock::from_ssz_bytes(&self.block_bytes, spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BeaconBlock, Eth1Data, Hash256, MainnetEthSpec, Signature};
+
+    type E = MainnetEthSpec;
+
+    #[test]
+    fn ssz_round_trip() {
+        let spec = E::default_spec();
+        let state = BeaconState::<E>::new(0, Eth1Data::default(), &spec);
+        let block = SignedBeaconBlock::from_block(BeaconBlock::empty(&spec), Signature::empty());
+        let snapshot = DepositTreeSnapshot {
+            finalized: vec![Hash256::repeat_byte(1)],
+            ..DepositTreeSnapshot::default()
+        };
+
+        for deposit_snapshot in [None, Some(snapshot)] {
+            let bundle = CheckpointBundle::new(&state, &block, deposit_snapshot);
+            let decoded = CheckpointBundle::from_ssz_bytes(&bundle.as_ssz_bytes()).unwrap();
+            assert_eq!(decoded, bundle);
+            assert_eq!(decoded.block::<E>(&spec).unwrap(), block);
+            assert_eq!(
+                decoded.state::<E>(&spec).unwrap().as_ssz_bytes(),
+                state.as_ssz_bytes()
+            );
+        }
+    }
+}
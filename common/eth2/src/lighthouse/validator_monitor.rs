@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use types::{Epoch, Slot};
+
+/// Query parameters for the `/lighthouse/validator_monitor/{index}` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ValidatorMonitorQuery {
+    /// Lower epoch limit for summaries returned (inclusive).
+    pub start_epoch: Epoch,
+    /// Upper epoch limit for summaries returned (inclusive).
+    pub end_epoch: Epoch,
+}
+
+/// A summary of the activity of a monitored validator during a single epoch.
+///
+/// All delays are in milliseconds and are measured from the time at which the message should have
+/// been produced until this node first observed it.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ValidatorMonitorEpochSummary {
+    pub epoch: Epoch,
+    /// Attestations with a target in this epoch.
+    pub attestations: AttestationSummary,
+    /// Sync committee messages and contributions for slots in this epoch.
+    pub sync_committee: SyncCommitteeSummary,
+    /// The number of blocks proposed by the validator in this epoch.
+    pub proposals: u64,
+    pub proposal_min_delay: Option<u64>,
+    pub exits: u64,
+    pub proposer_slashings: u64,
+    pub attester_slashings: u64,
+    /// The balance of the validator in this epoch, in Gwei.
+    pub balance: Option<u64>,
+    /// The change in balance since the previous epoch, in Gwei.
+    ///
+    /// Only present if the balance is known for both epochs.
+    pub balance_delta: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AttestationSummary {
+    /// The number of unaggregated attestations observed.
+    pub seen: u64,
+    pub min_delay: Option<u64>,
+    /// The number of times the attestation was observed in an aggregate.
+    pub aggregate_inclusions: u64,
+    /// The number of times the attestation was observed in a block.
+    pub block_inclusions: u64,
+    pub min_inclusion_distance: Option<u64>,
+    /// Whether an attestation matched on-chain, and whether it matched the head and target.
+    ///
+    /// These are only known once the epoch has been processed, and are absent otherwise.
+    pub hit: Option<bool>,
+    pub head_hit: Option<bool>,
+    pub target_hit: Option<bool>,
+    /// The number of aggregates produced by the validator.
+    pub aggregates: u64,
+    pub aggregate_min_delay: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SyncCommitteeSummary {
+    /// The number of sync committee messages observed.
+    pub messages: u64,
+    pub message_min_delay: Option<u64>,
+    /// The number of times a sync committee message was included in a block.
+    pub block_inclusions: u64,
+    /// The number of times a sync committee message was included in a contribution.
+    pub contribution_inclusions: u64,
+    /// The number of sync committee contributions produced by the validator.
+    pub contributions: u64,
+    pub contribution_min_delay: Option<u64>,
+}
+
+/// An alert raised by the validator monitor when a monitored validator breaks one of the
+/// configured alert rules.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ValidatorMonitorAlert {
+    pub validator_index: u64,
+    /// The epoch to which the alert pertains.
+    pub epoch: Epoch,
+    #[serde(flatten)]
+    pub kind: ValidatorMonitorAlertKind,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "alert", rename_all = "snake_case")]
+pub enum ValidatorMonitorAlertKind {
+    /// The validator was due to propose a block at `slot`, but the slot was skipped.
+    MissedProposal { slot: Slot },
+    /// The validator failed to get an attestation on-chain for `consecutive` epochs in a row.
+    MissedAttestations { consecutive: u64 },
+    /// The validator's sync committee messages were included in fewer blocks than required.
+    LowSyncParticipation { included: u64, expected: u64 },
+    /// The validator has been slashed.
+    Slashed,
+    /// The validator's balance decreased between the previous epoch and `epoch`.
+    BalanceDecrease { previous_balance: u64, balance: u64 },
+}
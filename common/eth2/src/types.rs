@@ -13,7 +13,7 @@ use std::time::Duration;
 pub use types::*;
 
 #[cfg(feature = "lighthouse")]
-use crate::lighthouse::BlockReward;
+use crate::lighthouse::{BlockReward, ValidatorMonitorAlert};
 
 /// An API error serializable to JSON.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -474,6 +474,12 @@ pub struct RandaoQuery {
     pub epoch: Option<Epoch>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct LightClientUpdatesQuery {
+    pub start_period: u64,
+    pub count: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct AttestationPoolQuery {
     pub slot: Option<Slot>,
@@ -609,6 +615,18 @@ where
     Ok(Some(Vec::from(QueryVec::from(vec))))
 }
 
+fn option_query_single<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: FromStr,
+{
+    match option_query_vec(deserializer)? {
+        None => Ok(None),
+        Some(mut values) if values.len() == 1 => Ok(values.pop()),
+        Some(_) => Err(serde::de::Error::custom("expected a single value")),
+    }
+}
+
 impl<T: FromStr> From<Vec<QueryVec<T>>> for QueryVec<T> {
     fn from(vecs: Vec<QueryVec<T>>) -> Self {
         Self {
@@ -1003,7 +1021,11 @@ pub enum EventKind<T: EthSpec> {
     LateHead(SseLateHead),
     #[cfg(feature = "lighthouse")]
     BlockReward(BlockReward),
+    #[cfg(feature = "lighthouse")]
+    ValidatorMonitorAlert(ValidatorMonitorAlert),
     PayloadAttributes(VersionedSsePayloadAttributes),
+    LightClientFinalityUpdate(Box<LightClientFinalityUpdate<T>>),
+    LightClientOptimisticUpdate(Box<LightClientOptimisticUpdate<T>>),
 }
 
 impl<T: EthSpec> EventKind<T> {
@@ -1018,8 +1040,12 @@ impl<T: EthSpec> EventKind<T> {
             EventKind::ContributionAndProof(_) => "contribution_and_proof",
             EventKind::PayloadAttributes(_) => "payload_attributes",
             EventKind::LateHead(_) => "late_head",
+            EventKind::LightClientFinalityUpdate(_) => "light_client_finality_update",
+            EventKind::LightClientOptimisticUpdate(_) => "light_client_optimistic_update",
             #[cfg(feature = "lighthouse")]
             EventKind::BlockReward(_) => "block_reward",
+            #[cfg(feature = "lighthouse")]
+            EventKind::ValidatorMonitorAlert(_) => "validator_monitor_alert",
         }
     }
 
@@ -1077,10 +1103,32 @@ impl<T: EthSpec> EventKind<T> {
                     ServerError::InvalidServerSentEvent(format!("Payload Attributes: {:?}", e))
                 })?,
             )),
+            "light_client_finality_update" => Ok(EventKind::LightClientFinalityUpdate(Box::new(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!(
+                        "Light Client Finality Update: {:?}",
+                        e
+                    ))
+                })?,
+            ))),
+            "light_client_optimistic_update" => Ok(EventKind::LightClientOptimisticUpdate(
+                Box::new(serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!(
+                        "Light Client Optimistic Update: {:?}",
+                        e
+                    ))
+                })?),
+            )),
             #[cfg(feature = "lighthouse")]
             "block_reward" => Ok(EventKind::BlockReward(serde_json::from_str(data).map_err(
                 |e| ServerError::InvalidServerSentEvent(format!("Block Reward: {:?}", e)),
             )?)),
+            #[cfg(feature = "lighthouse")]
+            "validator_monitor_alert" => Ok(EventKind::ValidatorMonitorAlert(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!("Validator Monitor Alert: {:?}", e))
+                })?,
+            )),
             _ => Err(ServerError::InvalidServerSentEvent(
                 "Could not parse event tag".to_string(),
             )),
@@ -1093,9 +1141,12 @@ impl<T: EthSpec> EventKind<T> {
 pub struct EventQuery {
     #[serde(deserialize_with = "query_vec")]
     pub topics: Vec<EventTopic>,
+    /// Replay journaled events from this slot before streaming new events.
+    #[serde(default, deserialize_with = "option_query_single")]
+    pub since_slot: Option<Slot>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventTopic {
     Head,
@@ -1107,8 +1158,12 @@ pub enum EventTopic {
     ContributionAndProof,
     LateHead,
     PayloadAttributes,
+    LightClientFinalityUpdate,
+    LightClientOptimisticUpdate,
     #[cfg(feature = "lighthouse")]
     BlockReward,
+    #[cfg(feature = "lighthouse")]
+    ValidatorMonitorAlert,
 }
 
 impl FromStr for EventTopic {
@@ -1125,8 +1180,12 @@ impl FromStr for EventTopic {
             "contribution_and_proof" => Ok(EventTopic::ContributionAndProof),
             "payload_attributes" => Ok(EventTopic::PayloadAttributes),
             "late_head" => Ok(EventTopic::LateHead),
+            "light_client_finality_update" => Ok(EventTopic::LightClientFinalityUpdate),
+            "light_client_optimistic_update" => Ok(EventTopic::LightClientOptimisticUpdate),
             #[cfg(feature = "lighthouse")]
             "block_reward" => Ok(EventTopic::BlockReward),
+            #[cfg(feature = "lighthouse")]
+            "validator_monitor_alert" => Ok(EventTopic::ValidatorMonitorAlert),
             _ => Err("event topic cannot be parsed.".to_string()),
         }
     }
@@ -1144,8 +1203,14 @@ impl fmt::Display for EventTopic {
             EventTopic::ContributionAndProof => write!(f, "contribution_and_proof"),
             EventTopic::PayloadAttributes => write!(f, "payload_attributes"),
             EventTopic::LateHead => write!(f, "late_head"),
+            EventTopic::LightClientFinalityUpdate => write!(f, "light_client_finality_update"),
+            EventTopic::LightClientOptimisticUpdate => {
+                write!(f, "light_client_optimistic_update")
+            }
             #[cfg(feature = "lighthouse")]
             EventTopic::BlockReward => write!(f, "block_reward"),
+            #[cfg(feature = "lighthouse")]
+            EventTopic::ValidatorMonitorAlert => write!(f, "validator_monitor_alert"),
         }
     }
 }
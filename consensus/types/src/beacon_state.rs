@@ -1730,6 +1730,39 @@ impl<T: EthSpec> BeaconState<T> {
     pub fn compute_merkle_proof(
         &mut self,
         generalized_index: usize,
+    ) -> Result<Vec<Hash256>, Error> {
+        let leaves = self.tree_hash_leaves()?;
+        Self::compute_merkle_proof_from_leaves(
+            &leaves,
+            generalized_index,
+            self.finalized_checkpoint().epoch,
+        )
+    }
+
+    /// Returns the tree hash roots of each of the fields of the `BeaconState`, updating the tree
+    /// hash cache.
+    ///
+    /// The leaves may be passed to `Self::compute_merkle_proof_from_leaves` to compute proofs
+    /// without access to the state.
+    pub fn tree_hash_leaves(&mut self) -> Result<Vec<Hash256>, Error> {
+        let mut cache = self
+            .tree_hash_cache_mut()
+            .take()
+            .ok_or(Error::TreeHashCacheNotInitialized)?;
+        let leaves = cache.recalculate_tree_hash_leaves(self);
+        self.tree_hash_cache_mut().restore(cache);
+        leaves
+    }
+
+    /// Compute a Merkle proof of the field at `generalized_index` from the `leaves` of a state,
+    /// as returned by `Self::tree_hash_leaves`.
+    ///
+    /// `finalized_epoch` must be the epoch of the state's finalized checkpoint, which is required
+    /// to prove the finalized root.
+    pub fn compute_merkle_proof_from_leaves(
+        leaves: &[Hash256],
+        generalized_index: usize,
+        finalized_epoch: Epoch,
     ) -> Result<Vec<Hash256>, Error> {
         // 1. Convert generalized index to field index.
         let field_index = match generalized_index {
@@ -1756,23 +1789,15 @@ impl<T: EthSpec> BeaconState<T> {
             _ => return Err(Error::IndexNotSupported(generalized_index)),
         };
 
-        // 2. Get all `BeaconState` leaves.
-        let mut cache = self
-            .tree_hash_cache_mut()
-            .take()
-            .ok_or(Error::TreeHashCacheNotInitialized)?;
-        let leaves = cache.recalculate_tree_hash_leaves(self)?;
-        self.tree_hash_cache_mut().restore(cache);
-
-        // 3. Make deposit tree.
+        // 2. Make deposit tree.
         // Use the depth of the `BeaconState` fields (i.e. `log2(32) = 5`).
         let depth = light_client_update::CURRENT_SYNC_COMMITTEE_PROOF_LEN;
-        let tree = merkle_proof::MerkleTree::create(&leaves, depth);
+        let tree = merkle_proof::MerkleTree::create(leaves, depth);
         let (_, mut proof) = tree.generate_proof(field_index, depth)?;
 
-        // 4. If we're proving the finalized root, patch in the finalized epoch to complete the proof.
+        // 3. If we're proving the finalized root, patch in the finalized epoch to complete the proof.
         if generalized_index == light_client_update::FINALIZED_ROOT_INDEX {
-            proof.insert(0, self.finalized_checkpoint().epoch.tree_hash_root());
+            proof.insert(0, finalized_epoch.tree_hash_root());
         }
 
         Ok(proof)
@@ -315,6 +315,14 @@ impl ChainSpec {
         }
     }
 
+    /// Returns the `MIN_EPOCHS_FOR_BLOCK_REQUESTS` networking constant, which is derived from the
+    /// withdrawability delay and churn limit quotient.
+    pub fn min_epochs_for_block_requests(&self) -> u64 {
+        self.min_validator_withdrawability_delay
+            .as_u64()
+            .saturating_add(self.churn_limit_quotient / 2)
+    }
+
     /// Returns a full `Fork` struct for a given epoch.
     pub fn fork_at_epoch(&self, epoch: Epoch) -> Fork {
         let current_fork_name = self.fork_name_at_epoch(epoch);
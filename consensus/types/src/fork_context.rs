@@ -9,6 +9,7 @@ pub struct ForkContext {
     current_fork: RwLock<ForkName>,
     fork_to_digest: HashMap<ForkName, [u8; 4]>,
     digest_to_fork: HashMap<[u8; 4], ForkName>,
+    spec: ChainSpec,
 }
 
 impl ForkContext {
@@ -66,9 +67,15 @@ impl ForkContext {
             current_fork: RwLock::new(spec.fork_name_at_slot::<T>(current_slot)),
             fork_to_digest,
             digest_to_fork,
+            spec: spec.clone(),
         }
     }
 
+    /// Returns the fork active at `slot`.
+    pub fn fork_name_at_slot<T: EthSpec>(&self, slot: Slot) -> ForkName {
+        self.spec.fork_name_at_slot::<T>(slot)
+    }
+
     /// Returns `true` if the provided `fork_name` exists in the `ForkContext` object.
     pub fn fork_exists(&self, fork_name: ForkName) -> bool {
         self.fork_to_digest.contains_key(&fork_name)
@@ -1,5 +1,8 @@
 use super::{BeaconBlockHeader, EthSpec, FixedVector, Hash256, Slot, SyncAggregate, SyncCommittee};
-use crate::{beacon_state, test_utils::TestRandom, BeaconBlock, BeaconState, ChainSpec};
+use crate::{
+    beacon_state, test_utils::TestRandom, BeaconBlock, BeaconState, ChainSpec, ForkName,
+    ForkVersionDeserialize,
+};
 use safe_arith::ArithError;
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
@@ -171,16 +174,152 @@ impl<T: EthSpec> LightClientUpdate<T> {
             signature_slot: block.slot(),
         })
     }
+
+    /// Returns `true` if `self` proves a finalized header, i.e. its finality branch is non-empty.
+    pub fn is_finality_update(&self) -> bool {
+        self.finality_branch.iter().any(|node| !node.is_zero())
+    }
+
+    /// Returns `true` if `self` is a better update than `old` for the same sync committee period,
+    /// following `is_better_update` from the light client sync protocol.
+    pub fn is_better_update(&self, old: &Self, chain_spec: &ChainSpec) -> Result<bool, Error> {
+        let period = |slot: Slot| {
+            slot.epoch(T::slots_per_epoch())
+                .sync_committee_period(chain_spec)
+        };
+
+        // Compare supermajority (> 2/3) sync committee participation.
+        let max_active_participants = self.sync_aggregate.sync_committee_bits.len();
+        let new_num_active_participants = self.sync_aggregate.num_set_bits();
+        let old_num_active_participants = old.sync_aggregate.num_set_bits();
+        let new_has_supermajority = new_num_active_participants * 3 >= max_active_participants * 2;
+        let old_has_supermajority = old_num_active_participants * 3 >= max_active_participants * 2;
+        if new_has_supermajority != old_has_supermajority {
+            return Ok(new_has_supermajority);
+        }
+        if !new_has_supermajority && new_num_active_participants != old_num_active_participants {
+            return Ok(new_num_active_participants > old_num_active_participants);
+        }
+
+        // Compare presence of relevant sync committee.
+        let new_has_relevant_sync_committee =
+            period(self.attested_header.slot)? == period(self.signature_slot)?;
+        let old_has_relevant_sync_committee =
+            period(old.attested_header.slot)? == period(old.signature_slot)?;
+        if new_has_relevant_sync_committee != old_has_relevant_sync_committee {
+            return Ok(new_has_relevant_sync_committee);
+        }
+
+        // Compare indication of any finality.
+        let new_has_finality = self.is_finality_update();
+        let old_has_finality = old.is_finality_update();
+        if new_has_finality != old_has_finality {
+            return Ok(new_has_finality);
+        }
+
+        // Compare sync committee finality.
+        if new_has_finality {
+            let new_has_sync_committee_finality =
+                period(self.finalized_header.slot)? == period(self.attested_header.slot)?;
+            let old_has_sync_committee_finality =
+                period(old.finalized_header.slot)? == period(old.attested_header.slot)?;
+            if new_has_sync_committee_finality != old_has_sync_committee_finality {
+                return Ok(new_has_sync_committee_finality);
+            }
+        }
+
+        // Tiebreaker 1: sync committee participation beyond supermajority.
+        if new_num_active_participants != old_num_active_participants {
+            return Ok(new_num_active_participants > old_num_active_participants);
+        }
+
+        // Tiebreaker 2: prefer older data (fewer changes to best).
+        if self.attested_header.slot != old.attested_header.slot {
+            return Ok(self.attested_header.slot < old.attested_header.slot);
+        }
+        Ok(self.signature_slot < old.signature_slot)
+    }
+}
+
+impl<T: EthSpec> ForkVersionDeserialize for LightClientUpdate<T> {
+    fn deserialize_by_fork<'de, D: serde::Deserializer<'de>>(
+        value: serde_json::value::Value,
+        fork_name: ForkName,
+    ) -> Result<Self, D::Error> {
+        match fork_name {
+            ForkName::Base => Err(serde::de::Error::custom(format!(
+                "LightClientUpdate failed to deserialize: unsupported fork '{}'",
+                fork_name
+            ))),
+            _ => serde_json::from_value(value).map_err(|e| {
+                serde::de::Error::custom(format!(
+                    "LightClientUpdate failed to deserialize: {:?}",
+                    e
+                ))
+            }),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::MainnetEthSpec;
+    use crate::test_utils::{SeedableRng, XorShiftRng};
+    use crate::{BitVector, MainnetEthSpec};
     use ssz_types::typenum::Unsigned;
 
     ssz_tests!(LightClientUpdate<MainnetEthSpec>);
 
+    fn update_with_participants(
+        num_participants: usize,
+        attested_slot: u64,
+    ) -> LightClientUpdate<MainnetEthSpec> {
+        let rng = &mut XorShiftRng::from_seed([42; 16]);
+        let mut update = LightClientUpdate::<MainnetEthSpec>::random_for_test(rng);
+        update.attested_header.slot = Slot::new(attested_slot);
+        update.signature_slot = Slot::new(attested_slot + 1);
+        update.finalized_header.slot = Slot::new(0);
+        update.finality_branch = FixedVector::from_elem(Hash256::repeat_byte(1));
+        update.sync_aggregate.sync_committee_bits = BitVector::new();
+        for i in 0..num_participants {
+            update
+                .sync_aggregate
+                .sync_committee_bits
+                .set(i, true)
+                .unwrap();
+        }
+        update
+    }
+
+    #[test]
+    fn is_better_update() {
+        let spec = MainnetEthSpec::default_spec();
+        let committee_size = <MainnetEthSpec as EthSpec>::SyncCommitteeSize::to_usize();
+        let supermajority = committee_size * 2 / 3 + 1;
+
+        let small = update_with_participants(10, 1);
+        let large = update_with_participants(20, 1);
+        assert!(large.is_better_update(&small, &spec).unwrap());
+        assert!(!small.is_better_update(&large, &spec).unwrap());
+
+        let supermajority_later = update_with_participants(supermajority, 2);
+        assert!(supermajority_later.is_better_update(&large, &spec).unwrap());
+
+        let mut no_finality = update_with_participants(committee_size, 1);
+        no_finality.finality_branch = FixedVector::from_elem(Hash256::zero());
+        assert!(supermajority_later
+            .is_better_update(&no_finality, &spec)
+            .unwrap());
+
+        let supermajority_earlier = update_with_participants(supermajority, 1);
+        assert!(supermajority_earlier
+            .is_better_update(&supermajority_later, &spec)
+            .unwrap());
+        assert!(!supermajority_earlier
+            .is_better_update(&supermajority_earlier, &spec)
+            .unwrap());
+    }
+
     #[test]
     fn finalized_root_params() {
         assert!(2usize.pow(FINALIZED_ROOT_PROOF_LEN as u32) <= FINALIZED_ROOT_INDEX);
@@ -0,0 +1,517 @@
+//! Decoding of database keys and values for the `inspect` subcommand.
+//!
+//! Keys are decoded according to their column as block or state roots, restore point indices or
+//! chunk indices, along with the range of slots that the entry relates to where it is known.
+//! Values are decoded to JSON for the columns with a known type, and are otherwise shown as hex.
+use beacon_chain::eth1_chain::SszEth1;
+use beacon_chain::{PersistedBeaconChain, PersistedForkChoice};
+use eth1::SszEth1Cache;
+use operation_pool::PersistedOperationPoolV15;
+use proto_array::ProtoArrayForkChoice;
+use serde::Serialize;
+use serde_json::{json, Value};
+use ssz::Decode;
+use std::convert::TryInto;
+use store::chunked_vector::{
+    chunk_key, genesis_value_key, BlockRoots, Chunk, Field, HistoricalRoots, HistoricalSummaries,
+    RandaoMixes, StateRoots, UpdatePattern,
+};
+use store::config::OnDiskStoreConfig;
+use store::hdiff::HDiff;
+use store::metadata::{
+    CompactionTimestamp, PruningCheckpoint, RestorePointMigration, SchemaVersion, ANCHOR_INFO_KEY,
+    COMPACTION_TIMESTAMP_KEY, CONFIG_KEY, PRUNING_CHECKPOINT_KEY, RESTORE_POINT_MIGRATION_KEY,
+    SCHEMA_VERSION_KEY, SPLIT_KEY,
+};
+use store::{
+    errors::Error, AnchorInfo, DBColumn, HotColdDB, HotStateSummary, ItemStore, KeyValueStore,
+    Split, StoreItem,
+};
+use types::{ChainSpec, Epoch, EthSpec, ExecutionPayload, Hash256, SignedBlindedBeaconBlock, Slot};
+
+/// An iterator over the raw keys and values of a column, with the column prefix removed.
+pub type EntryIter<'a> = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>> + 'a>;
+
+/// A database entry with its key decoded, and optionally its value.
+pub struct DecodedEntry {
+    /// Human-readable description of the key.
+    pub key: String,
+    /// The first and last slot that the entry relates to, if known.
+    pub slots: Option<(Slot, Slot)>,
+    /// The value as JSON, if requested.
+    pub value: Option<Value>,
+}
+
+impl DecodedEntry {
+    /// Create an entry for a `key` and `value` which couldn't be decoded.
+    pub fn raw(key: &[u8], value: &[u8], with_value: bool) -> Self {
+        Self {
+            key: to_hex(key),
+            slots: None,
+            value: with_value.then(|| Value::String(to_hex(value))),
+        }
+    }
+
+    /// Return `true` if the entry relates to a slot in `start_slot..=end_slot`.
+    ///
+    /// Entries which don't relate to any slot never match.
+    pub fn overlaps(&self, start_slot: Slot, end_slot: Slot) -> bool {
+        self.slots.map_or(false, |(first, last)| {
+            first <= end_slot && last >= start_slot
+        })
+    }
+
+    pub fn to_json(&self, size: usize) -> Value {
+        json!({
+            "key": self.key,
+            "first_slot": self.slots.map(|(first, _)| first),
+            "last_slot": self.slots.map(|(_, last)| last),
+            "size": size,
+            "value": self.value,
+        })
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Value, Error> {
+    serde_json::to_value(value).map_err(|e| Error::DBError {
+        message: format!("Unable to serialize value: {:?}", e),
+    })
+}
+
+fn root_from_key(key: &[u8]) -> Result<Hash256, Error> {
+    if key.len() == Hash256::len_bytes() {
+        Ok(Hash256::from_slice(key))
+    } else {
+        Err(Error::DBError {
+            message: format!("Invalid root key: {}", to_hex(key)),
+        })
+    }
+}
+
+/// Format an undecoded `key`, as a root if it has the length of one and as hex otherwise.
+pub fn format_raw_key(key: &[u8]) -> String {
+    match root_from_key(key) {
+        Ok(root) => format!("{:?}", root),
+        Err(_) => to_hex(key),
+    }
+}
+
+/// Iterate through the entries of `column` in the hot database, or the freezer database if
+/// `freezer` is set.
+///
+/// The chunked vector columns are keyed by chunk index rather than by root, so their chunks are
+/// looked up individually up to the split slot.
+pub fn iter_entries<E, Hot, Cold>(
+    db: &HotColdDB<E, Hot, Cold>,
+    column: DBColumn,
+    freezer: bool,
+) -> EntryIter<'_>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    match column {
+        DBColumn::BeaconBlockRoots => iter_chunks::<BlockRoots, _, _, _>(db, freezer),
+        DBColumn::BeaconStateRoots => iter_chunks::<StateRoots, _, _, _>(db, freezer),
+        DBColumn::BeaconHistoricalRoots => iter_chunks::<HistoricalRoots, _, _, _>(db, freezer),
+        DBColumn::BeaconRandaoMixes => iter_chunks::<RandaoMixes, _, _, _>(db, freezer),
+        DBColumn::BeaconHistoricalSummaries => {
+            iter_chunks::<HistoricalSummaries, _, _, _>(db, freezer)
+        }
+        _ => {
+            let iter = if freezer {
+                db.cold_db.iter_column(column)
+            } else {
+                db.hot_db.iter_column(column)
+            };
+            Box::new(iter.map(|res| res.map(|(key, value)| (key.as_bytes().to_vec(), value))))
+        }
+    }
+}
+
+fn iter_chunks<F, E, Hot, Cold>(db: &HotColdDB<E, Hot, Cold>, freezer: bool) -> EntryIter<'_>
+where
+    F: Field<E>,
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    let (_, end_vindex) = F::start_and_end_vindex(db.get_split_slot(), db.get_chain_spec());
+    let keys = std::iter::once(genesis_value_key())
+        .chain((0..=F::chunk_index(end_vindex)).map(chunk_key))
+        .collect::<Vec<_>>();
+
+    Box::new(keys.into_iter().filter_map(move |key| {
+        let value = if freezer {
+            db.cold_db.get_bytes(F::column().into(), &key)
+        } else {
+            db.hot_db.get_bytes(F::column().into(), &key)
+        };
+        value
+            .transpose()
+            .map(|res| res.map(|value| (key.to_vec(), value)))
+    }))
+}
+
+/// Decode the `key` of an entry in `column`, along with its `value` if `with_value` is set.
+pub fn decode_entry<E, Hot, Cold>(
+    db: &HotColdDB<E, Hot, Cold>,
+    column: DBColumn,
+    freezer: bool,
+    key: &[u8],
+    value: &[u8],
+    with_value: bool,
+) -> Result<DecodedEntry, Error>
+where
+    E: EthSpec,
+    Hot: ItemStore<E>,
+    Cold: ItemStore<E>,
+{
+    let spec = db.get_chain_spec();
+    match column {
+        DBColumn::BeaconMeta => decode_meta_entry(root_from_key(key)?, value),
+        DBColumn::BeaconBlock => {
+            let block = SignedBlindedBeaconBlock::<E>::from_ssz_bytes(value, spec)?;
+            Ok(DecodedEntry {
+                key: format!("{:?}", root_from_key(key)?),
+                slots: Some((block.slot(), block.slot())),
+                value: with_value.then(|| to_json(&block)).transpose()?,
+            })
+        }
+        DBColumn::ExecPayload => {
+            let block_root = root_from_key(key)?;
+            let slot = db.get_blinded_block(&block_root)?.map(|block| block.slot());
+            let value = if with_value {
+                Some(to_json(&ExecutionPayload::<E>::from_store_bytes(value)?)?)
+            } else {
+                None
+            };
+            Ok(DecodedEntry {
+                key: format!("{:?}", block_root),
+                slots: slot.map(|slot| (slot, slot)),
+                value,
+            })
+        }
+        DBColumn::BeaconState | DBColumn::BeaconStateDiff | DBColumn::BeaconStateTemporary => {
+            let state_root = root_from_key(key)?;
+            let slot = if freezer {
+                db.load_cold_state_slot(&state_root)?
+            } else {
+                db.load_hot_state_summary(&state_root)?
+                    .map(|summary| summary.slot)
+            };
+            // States are too large to be useful as JSON, so only the base of a diff is shown.
+            let value = match column {
+                DBColumn::BeaconStateDiff if with_value => {
+                    let diff = HDiff::from_store_bytes(value)?;
+                    Some(json!({ "base_state_root": diff.base_state_root }))
+                }
+                _ => None,
+            };
+            Ok(DecodedEntry {
+                key: format!("{:?}", state_root),
+                slots: slot.map(|slot| (slot, slot)),
+                value,
+            })
+        }
+        DBColumn::BeaconStateSummary => {
+            let (slot, value) = if freezer {
+                let slot = Slot::from_ssz_bytes(value)?;
+                (slot, json!({ "slot": slot }))
+            } else {
+                let summary = HotStateSummary::from_store_bytes(value)?;
+                (summary.slot, to_json(&summary)?)
+            };
+            Ok(DecodedEntry {
+                key: format!("{:?}", root_from_key(key)?),
+                slots: Some((slot, slot)),
+                value: with_value.then_some(value),
+            })
+        }
+        DBColumn::BeaconRestorePoint | DBColumn::BeaconRestorePointStaged => {
+            let restore_point_index = root_from_key(key)?.to_low_u64_be();
+            let slots_per_restore_point = if column == DBColumn::BeaconRestorePoint {
+                Some(db.get_config().slots_per_restore_point)
+            } else {
+                db.load_restore_point_migration()?
+                    .map(|migration| migration.to)
+            };
+            let slot = slots_per_restore_point.map(|slots_per_restore_point| {
+                Slot::new(restore_point_index * slots_per_restore_point)
+            });
+            // A `RestorePointHash` is encoded identically to its state root.
+            Ok(DecodedEntry {
+                key: format!("restore point {}", restore_point_index),
+                slots: slot.map(|slot| (slot, slot)),
+                value: with_value
+                    .then(|| Hash256::from_ssz_bytes(value))
+                    .transpose()?
+                    .map(|state_root| json!({ "state_root": state_root })),
+            })
+        }
+        DBColumn::BeaconBlockRoots => decode_chunk::<BlockRoots, E>(key, value, with_value, spec),
+        DBColumn::BeaconStateRoots => decode_chunk::<StateRoots, E>(key, value, with_value, spec),
+        DBColumn::BeaconHistoricalRoots => {
+            decode_chunk::<HistoricalRoots, E>(key, value, with_value, spec)
+        }
+        DBColumn::BeaconRandaoMixes => decode_chunk::<RandaoMixes, E>(key, value, with_value, spec),
+        DBColumn::BeaconHistoricalSummaries => {
+            decode_chunk::<HistoricalSummaries, E>(key, value, with_value, spec)
+        }
+        DBColumn::BeaconChain => {
+            let persisted_chain = PersistedBeaconChain::from_store_bytes(value)?;
+            let heads = persisted_chain
+                .ssz_head_tracker
+                .heads()
+                .map(|(block_root, slot)| json!({ "block_root": block_root, "slot": slot }))
+                .collect::<Vec<_>>();
+            Ok(DecodedEntry {
+                key: "beacon chain".into(),
+                slots: None,
+                value: with_value.then(|| {
+                    json!({
+                        "genesis_block_root": persisted_chain.genesis_block_root,
+                        "heads": heads,
+                    })
+                }),
+            })
+        }
+        DBColumn::OpPool => {
+            let value = if with_value {
+                Some(decode_op_pool::<E>(value)?)
+            } else {
+                None
+            };
+            Ok(DecodedEntry {
+                key: "operation pool".into(),
+                slots: None,
+                value,
+            })
+        }
+        DBColumn::ForkChoice => {
+            let value = if with_value {
+                Some(decode_fork_choice(value)?)
+            } else {
+                None
+            };
+            Ok(DecodedEntry {
+                key: "fork choice".into(),
+                slots: None,
+                value,
+            })
+        }
+        DBColumn::Eth1Cache => {
+            let value = if with_value {
+                Some(decode_eth1_cache(value)?)
+            } else {
+                None
+            };
+            Ok(DecodedEntry {
+                key: "eth1 cache".into(),
+                slots: None,
+                value,
+            })
+        }
+        DBColumn::PubkeyCache => Ok(DecodedEntry {
+            key: format!("validator {}", root_from_key(key)?.to_low_u64_be()),
+            slots: None,
+            value: with_value.then(|| Value::String(to_hex(value))),
+        }),
+        _ => Ok(DecodedEntry::raw(key, value, with_value)),
+    }
+}
+
+/// Decode one of the metadata items stored under a fixed key.
+fn decode_meta_entry(key: Hash256, value: &[u8]) -> Result<DecodedEntry, Error> {
+    let (name, value) = if key == SCHEMA_VERSION_KEY {
+        (
+            "schema version",
+            json!(SchemaVersion::from_store_bytes(value)?.as_u64()),
+        )
+    } else if key == CONFIG_KEY {
+        let config = OnDiskStoreConfig::from_store_bytes(value)?;
+        (
+            "config",
+            json!({
+                "slots_per_restore_point": config.slots_per_restore_point,
+                "hierarchy_exponents": config
+                    .hierarchy_config
+                    .map(|hierarchy_config| hierarchy_config.to_string()),
+            }),
+        )
+    } else if key == SPLIT_KEY {
+        ("split", to_json(&Split::from_store_bytes(value)?)?)
+    } else if key == PRUNING_CHECKPOINT_KEY {
+        let pruning_checkpoint = PruningCheckpoint::from_store_bytes(value)?;
+        (
+            "pruning checkpoint",
+            to_json(&pruning_checkpoint.checkpoint)?,
+        )
+    } else if key == COMPACTION_TIMESTAMP_KEY {
+        (
+            "compaction timestamp",
+            json!(CompactionTimestamp::from_store_bytes(value)?.0),
+        )
+    } else if key == ANCHOR_INFO_KEY {
+        ("anchor", to_json(&AnchorInfo::from_store_bytes(value)?)?)
+    } else if key == RESTORE_POINT_MIGRATION_KEY {
+        let migration = RestorePointMigration::from_store_bytes(value)?;
+        (
+            "restore point migration",
+            json!({
+                "from": migration.from,
+                "to": migration.to,
+                "next_slot": migration.next_slot,
+                "complete": migration.complete,
+            }),
+        )
+    } else {
+        return Ok(DecodedEntry::raw(key.as_bytes(), value, true));
+    };
+
+    Ok(DecodedEntry {
+        key: name.into(),
+        slots: None,
+        value: Some(value),
+    })
+}
+
+/// Return the first slot at which the value at `vindex` of `F` is set.
+fn vindex_slot<F: Field<E>, E: EthSpec>(vindex: usize, spec: &ChainSpec) -> Option<Slot> {
+    match F::update_pattern(spec) {
+        UpdatePattern::OncePerNSlots {
+            n, activation_slot, ..
+        } => activation_slot.map(|slot| slot + vindex as u64 * n),
+        UpdatePattern::OncePerEpoch { .. } => {
+            Some(Epoch::new(vindex as u64).start_slot(E::slots_per_epoch()))
+        }
+    }
+}
+
+/// Decode a chunk of a chunked vector, keyed by its chunk index.
+fn decode_chunk<F, E>(
+    key: &[u8],
+    value: &[u8],
+    with_value: bool,
+    spec: &ChainSpec,
+) -> Result<DecodedEntry, Error>
+where
+    F: Field<E>,
+    F::Value: Serialize,
+    E: EthSpec,
+{
+    let (key, slots) = if key == &genesis_value_key()[..] {
+        (
+            "genesis value".to_string(),
+            Some((spec.genesis_slot, spec.genesis_slot)),
+        )
+    } else {
+        let chunk_index = u64::from_be_bytes(key.try_into().map_err(|_| Error::DBError {
+            message: format!("Invalid chunk key: {}", to_hex(key)),
+        })?)
+        .saturating_sub(1) as usize;
+        let start_vindex = chunk_index * F::chunk_size();
+        let end_vindex = start_vindex + F::chunk_size();
+        let slots = vindex_slot::<F, E>(start_vindex, spec)
+            .zip(vindex_slot::<F, E>(end_vindex, spec))
+            .map(|(first, end)| (first, end - 1));
+        (format!("chunk {}", chunk_index), slots)
+    };
+
+    let value = if with_value {
+        Some(to_json(&Chunk::<F::Value>::decode(value)?.values)?)
+    } else {
+        None
+    };
+
+    Ok(DecodedEntry { key, slots, value })
+}
+
+fn decode_op_pool<E: EthSpec>(value: &[u8]) -> Result<Value, Error> {
+    let op_pool = PersistedOperationPoolV15::<E>::from_store_bytes(value)?;
+    let attestations = op_pool
+        .attestations
+        .iter()
+        .map(|(attestation, attesting_indices)| {
+            json!({ "attestation": attestation, "attesting_indices": attesting_indices })
+        })
+        .collect::<Vec<_>>();
+    let sync_contributions = op_pool
+        .sync_contributions
+        .iter()
+        .flat_map(|(_, contributions)| contributions)
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "attestations": attestations,
+        "sync_contributions": sync_contributions,
+        "attester_slashings": op_pool
+            .attester_slashings
+            .iter()
+            .map(|op| op.as_inner())
+            .collect::<Vec<_>>(),
+        "proposer_slashings": op_pool
+            .proposer_slashings
+            .iter()
+            .map(|op| op.as_inner())
+            .collect::<Vec<_>>(),
+        "voluntary_exits": op_pool
+            .voluntary_exits
+            .iter()
+            .map(|op| op.as_inner())
+            .collect::<Vec<_>>(),
+        "bls_to_execution_changes": op_pool
+            .bls_to_execution_changes
+            .iter()
+            .map(|op| op.as_inner())
+            .collect::<Vec<_>>(),
+        "capella_bls_change_broadcast_indices": op_pool.capella_bls_change_broadcast_indices,
+    }))
+}
+
+fn decode_fork_choice(value: &[u8]) -> Result<Value, Error> {
+    let persisted_fork_choice = PersistedForkChoice::from_store_bytes(value)?;
+    let proto_array =
+        ProtoArrayForkChoice::from_bytes(&persisted_fork_choice.fork_choice.proto_array_bytes)
+            .map_err(|message| Error::DBError { message })?;
+    let store = &persisted_fork_choice.fork_choice_store;
+
+    // The justified balances are omitted as they hold one entry per validator.
+    Ok(json!({
+        "time": store.time,
+        "finalized_checkpoint": store.finalized_checkpoint,
+        "justified_checkpoint": store.justified_checkpoint,
+        "best_justified_checkpoint": store.best_justified_checkpoint,
+        "unrealized_justified_checkpoint": store.unrealized_justified_checkpoint,
+        "unrealized_finalized_checkpoint": store.unrealized_finalized_checkpoint,
+        "proposer_boost_root": store.proposer_boost_root,
+        "equivocating_indices": store.equivocating_indices,
+        "proto_array": to_json(proto_array.core_proto_array())?,
+    }))
+}
+
+fn decode_eth1_cache(value: &[u8]) -> Result<Value, Error> {
+    let ssz_eth1 = SszEth1::from_store_bytes(value)?;
+    if ssz_eth1.use_dummy_backend {
+        return Ok(json!({ "use_dummy_backend": true }));
+    }
+
+    let cache = SszEth1Cache::from_ssz_bytes(&ssz_eth1.backend_bytes)?;
+    let deposit_cache = &cache.deposit_cache;
+
+    // Deposit logs and leaves are summarised by their count.
+    Ok(json!({
+        "use_dummy_backend": false,
+        "blocks": cache.block_cache.iter().collect::<Vec<_>>(),
+        "deposit_count": deposit_cache.logs.len(),
+        "deposit_contract_deploy_block": deposit_cache.deposit_contract_deploy_block,
+        "finalized_deposit_count": deposit_cache.finalized_deposit_count,
+        "finalized_block_height": deposit_cache.finalized_block_height,
+        "last_processed_block": cache.last_processed_block,
+    }))
+}
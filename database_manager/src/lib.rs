@@ -1,18 +1,26 @@
 use beacon_chain::{
-    builder::Witness, eth1_chain::CachingEth1Backend, schema_change::migrate_schema,
-    slot_clock::SystemTimeSlotClock,
+    builder::Witness, era, eth1_chain::CachingEth1Backend, schema_change::migrate_schema,
+    slot_clock::SystemTimeSlotClock, PersistedBeaconChain, BEACON_CHAIN_DB_KEY,
 };
 use beacon_node::{get_data_dir, get_slots_per_restore_point, ClientConfig};
 use clap::{App, Arg, ArgMatches};
 use environment::{Environment, RuntimeContext};
-use slog::{info, Logger};
+use slog::{info, warn, Logger};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use store::{
     errors::Error,
     metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION},
-    DBColumn, HotColdDB, KeyValueStore, LevelDB,
+    verify::Inconsistency,
+    BeaconNodeBackend, DBColumn, DatabaseBackend, HotColdDB, KeyValueStore, KeyValueStoreOp,
+    LevelDB,
 };
 use strum::{EnumString, EnumVariantNames, VariantNames};
-use types::EthSpec;
+use types::{EthSpec, Slot};
+
+mod inspect;
 
 pub const CMD: &str = "database_manager";
 
@@ -57,6 +65,32 @@ pub fn inspect_cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("sizes")
                 .possible_values(InspectTarget::VARIANTS),
         )
+        .arg(
+            Arg::with_name("freezer")
+                .long("freezer")
+                .help("Inspect the freezer database rather than the hot database")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("start-slot")
+                .long("start-slot")
+                .value_name("SLOT")
+                .help(
+                    "Only show entries relating to this slot or later. Entries which don't \
+                     relate to a slot are skipped when a slot range is given.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("end-slot")
+                .long("end-slot")
+                .value_name("SLOT")
+                .help(
+                    "Only show entries relating to this slot or earlier. Entries which don't \
+                     relate to a slot are skipped when a slot range is given.",
+                )
+                .takes_value(true),
+        )
 }
 
 pub fn prune_payloads_app<'a, 'b>() -> App<'a, 'b> {
@@ -65,6 +99,110 @@ pub fn prune_payloads_app<'a, 'b>() -> App<'a, 'b> {
         .about("Prune finalized execution payloads")
 }
 
+pub fn convert_cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("convert")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Convert the LevelDB hot and freezer databases to another backend. The beacon node \
+             must not be running. The original databases are kept alongside the converted ones \
+             with a `.leveldb` suffix and may be deleted once the node is running correctly.",
+        )
+        .arg(
+            Arg::with_name("to")
+                .long("to")
+                .value_name("DATABASE")
+                .help("The backend to convert the databases to")
+                .takes_value(true)
+                .required(true)
+                .possible_values(DatabaseBackend::VARIANTS),
+        )
+}
+
+pub fn set_slots_per_restore_point_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("set_slots_per_restore_point")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Change the slots-per-restore-point of the freezer database without re-syncing. \
+             The database must be opened with its current value, which may need to be provided \
+             via --slots-per-restore-point. An interrupted change is resumed by running the \
+             command again.",
+        )
+        .arg(
+            Arg::with_name("to")
+                .long("to")
+                .value_name("SLOT_COUNT")
+                .help("The new number of slots between restore points")
+                .takes_value(true)
+                .required(true),
+        )
+}
+
+pub fn verify_cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("verify")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Check the integrity of the database. The beacon node must be stopped while this \
+             runs.",
+        )
+        .arg(
+            Arg::with_name("repair")
+                .long("repair")
+                .help(
+                    "Repair the issues found where possible, by deleting unloadable hot states \
+                     and rewriting frozen state summaries. Without this flag the database is \
+                     opened read-only",
+                )
+                .takes_value(false),
+        )
+}
+
+pub fn export_era_cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("export-era")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about("Export finalized blocks and states as era files")
+        .arg(
+            Arg::with_name("output-dir")
+                .long("output-dir")
+                .value_name("DIR")
+                .help("The directory to write the era files to")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("start-era")
+                .long("start-era")
+                .value_name("ERA")
+                .help("The first era to export")
+                .takes_value(true)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("end-era")
+                .long("end-era")
+                .value_name("ERA")
+                .help("The last era to export [default: the last finalized era]")
+                .takes_value(true),
+        )
+}
+
+pub fn import_era_cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("import-era")
+        .setting(clap::AppSettings::ColoredHelp)
+        .about(
+            "Import the blocks prior to a checkpoint sync from era files, instead of backfilling \
+             them from the network. A new node must be checkpoint synced, and must finalize the \
+             end of the era holding its checkpoint, before importing era files.",
+        )
+        .arg(
+            Arg::with_name("input-dir")
+                .long("input-dir")
+                .value_name("DIR")
+                .help("The directory containing the era files")
+                .takes_value(true)
+                .required(true),
+        )
+}
+
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new(CMD)
         .visible_aliases(&["db"])
@@ -81,6 +219,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("hierarchy-exponents")
+                .long("hierarchy-exponents")
+                .value_name("EXPONENTS")
+                .help(
+                    "Comma-separated powers of two giving the layers of freezer DB restore \
+                       points, used when restore point states are written. [default: 16,18,21]",
+                )
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("freezer-dir")
                 .long("freezer-dir")
@@ -88,10 +236,23 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Data directory for the freezer database.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("beacon-node-backend")
+                .long("beacon-node-backend")
+                .value_name("DATABASE")
+                .help("Set the key-value store used for the hot and cold databases.")
+                .takes_value(true)
+                .possible_values(DatabaseBackend::VARIANTS),
+        )
         .subcommand(migrate_cli_app())
         .subcommand(version_cli_app())
         .subcommand(inspect_cli_app())
         .subcommand(prune_payloads_app())
+        .subcommand(convert_cli_app())
+        .subcommand(set_slots_per_restore_point_app())
+        .subcommand(verify_cli_app())
+        .subcommand(export_era_cli_app())
+        .subcommand(import_era_cli_app())
 }
 
 fn parse_client_config<E: EthSpec>(
@@ -110,6 +271,14 @@ fn parse_client_config<E: EthSpec>(
     client_config.store.slots_per_restore_point = sprp;
     client_config.store.slots_per_restore_point_set_explicitly = sprp_explicit;
 
+    if let Some(hierarchy_config) = clap_utils::parse_optional(cli_args, "hierarchy-exponents")? {
+        client_config.store.hierarchy_config = hierarchy_config;
+    }
+
+    if let Some(backend) = clap_utils::parse_optional(cli_args, "beacon-node-backend")? {
+        client_config.store.backend = backend;
+    }
+
     Ok(client_config)
 }
 
@@ -123,7 +292,7 @@ pub fn display_db_version<E: EthSpec>(
     let cold_path = client_config.get_freezer_db_path();
 
     let mut version = CURRENT_SCHEMA_VERSION;
-    HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, from, _| {
@@ -154,18 +323,34 @@ pub enum InspectTarget {
     ValueSizes,
     #[strum(serialize = "total")]
     ValueTotal,
+    #[strum(serialize = "values")]
+    Values,
+    #[strum(serialize = "histogram")]
+    SizeHistogram,
 }
 
 pub struct InspectConfig {
     column: DBColumn,
     target: InspectTarget,
+    freezer: bool,
+    start_slot: Option<Slot>,
+    end_slot: Option<Slot>,
 }
 
 fn parse_inspect_config(cli_args: &ArgMatches) -> Result<InspectConfig, String> {
     let column = clap_utils::parse_required(cli_args, "column")?;
     let target = clap_utils::parse_required(cli_args, "output")?;
+    let freezer = cli_args.is_present("freezer");
+    let start_slot = clap_utils::parse_optional(cli_args, "start-slot")?;
+    let end_slot = clap_utils::parse_optional(cli_args, "end-slot")?;
 
-    Ok(InspectConfig { column, target })
+    Ok(InspectConfig {
+        column,
+        target,
+        freezer,
+        start_slot,
+        end_slot,
+    })
 }
 
 pub fn inspect_db<E: EthSpec>(
@@ -178,27 +363,75 @@ pub fn inspect_db<E: EthSpec>(
     let hot_path = client_config.get_db_path();
     let cold_path = client_config.get_freezer_db_path();
 
-    let db = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, _, _| Ok(()),
         client_config.store,
         spec,
-        log,
+        log.clone(),
     )?;
 
+    let column = inspect_config.column;
+    let freezer = inspect_config.freezer;
+    let slot_range = match (inspect_config.start_slot, inspect_config.end_slot) {
+        (None, None) => None,
+        (start_slot, end_slot) => Some((
+            start_slot.unwrap_or_else(|| Slot::new(0)),
+            end_slot.unwrap_or_else(|| Slot::new(u64::MAX)),
+        )),
+    };
+    let with_value = matches!(inspect_config.target, InspectTarget::Values);
+
     let mut total = 0;
+    let mut histogram = BTreeMap::<u32, usize>::new();
 
-    for res in db.hot_db.iter_column(inspect_config.column) {
+    for res in inspect::iter_entries(&db, column, freezer) {
         let (key, value) = res?;
 
+        // Entries are only decoded when filtering by slot or showing values.
+        let entry = if slot_range.is_some() || with_value {
+            let entry = match inspect::decode_entry(&db, column, freezer, &key, &value, with_value)
+            {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!(
+                        log,
+                        "Unable to decode entry";
+                        "column" => ?column,
+                        "key" => ?key,
+                        "error" => ?e,
+                    );
+                    inspect::DecodedEntry::raw(&key, &value, with_value)
+                }
+            };
+
+            if let Some((start_slot, end_slot)) = slot_range {
+                if !entry.overlaps(start_slot, end_slot) {
+                    continue;
+                }
+            }
+            Some(entry)
+        } else {
+            None
+        };
+
+        total += value.len();
+
         match inspect_config.target {
             InspectTarget::ValueSizes => {
-                println!("{:?}: {} bytes", key, value.len());
-                total += value.len();
+                println!("{}: {} bytes", inspect::format_raw_key(&key), value.len());
+            }
+            InspectTarget::ValueTotal => (),
+            InspectTarget::Values => {
+                if let Some(entry) = entry {
+                    println!("{}", entry.to_json(value.len()));
+                }
             }
-            InspectTarget::ValueTotal => {
-                total += value.len();
+            InspectTarget::SizeHistogram => {
+                // Bucket `n` holds the values with sizes in `2^(n - 1)..2^n`.
+                let bucket = usize::BITS - value.len().leading_zeros();
+                *histogram.entry(bucket).or_default() += 1;
             }
         }
     }
@@ -207,6 +440,15 @@ pub fn inspect_db<E: EthSpec>(
         InspectTarget::ValueSizes | InspectTarget::ValueTotal => {
             println!("Total: {} bytes", total);
         }
+        InspectTarget::Values => (),
+        InspectTarget::SizeHistogram => {
+            for (bucket, count) in histogram {
+                let min_size = (1u64 << bucket) >> 1;
+                let max_size = (1u64 << bucket).saturating_sub(1);
+                println!("{}-{} bytes: {}", min_size, max_size, count);
+            }
+            println!("Total: {} bytes", total);
+        }
     }
 
     Ok(())
@@ -234,7 +476,7 @@ pub fn migrate_db<E: EthSpec>(
 
     let mut from = CURRENT_SCHEMA_VERSION;
     let to = migrate_config.to;
-    let db = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, db_initial_version, _| {
@@ -272,7 +514,7 @@ pub fn prune_payloads<E: EthSpec>(
     let hot_path = client_config.get_db_path();
     let cold_path = client_config.get_freezer_db_path();
 
-    let db = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
         &hot_path,
         &cold_path,
         |_, _, _| Ok(()),
@@ -287,6 +529,463 @@ pub fn prune_payloads<E: EthSpec>(
     db.try_prune_execution_payloads(force)
 }
 
+pub struct SetSlotsPerRestorePointConfig {
+    to: u64,
+}
+
+fn parse_set_slots_per_restore_point_config(
+    cli_args: &ArgMatches,
+) -> Result<SetSlotsPerRestorePointConfig, String> {
+    let to = clap_utils::parse_required(cli_args, "to")?;
+
+    Ok(SetSlotsPerRestorePointConfig { to })
+}
+
+pub fn set_slots_per_restore_point<E: EthSpec>(
+    set_sprp_config: SetSlotsPerRestorePointConfig,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), Error> {
+    let spec = &runtime_context.eth2_config.spec;
+    let hot_path = client_config.get_db_path();
+    let cold_path = client_config.get_freezer_db_path();
+
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+        &hot_path,
+        &cold_path,
+        |_, _, _| Ok(()),
+        client_config.store,
+        spec.clone(),
+        log.clone(),
+    )?;
+
+    db.change_slots_per_restore_point(set_sprp_config.to)?;
+
+    info!(
+        log,
+        "Start the beacon node with the new value";
+        "slots_per_restore_point" => set_sprp_config.to,
+    );
+
+    Ok(())
+}
+
+pub struct VerifyConfig {
+    repair: bool,
+}
+
+fn parse_verify_config(cli_args: &ArgMatches) -> Result<VerifyConfig, String> {
+    let repair = cli_args.is_present("repair");
+
+    Ok(VerifyConfig { repair })
+}
+
+pub fn verify_db<E: EthSpec>(
+    verify_config: VerifyConfig,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), Error> {
+    let spec = &runtime_context.eth2_config.spec;
+    let hot_path = client_config.get_db_path();
+    let cold_path = client_config.get_freezer_db_path();
+
+    // Opening the database normally runs a garbage collection pass which would hide some issues,
+    // so only do so when repairing.
+    if !verify_config.repair {
+        let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open_read_only(
+            &hot_path,
+            &cold_path,
+            client_config.store,
+            spec.clone(),
+            log.clone(),
+        )?;
+        let inconsistencies = report_inconsistencies(&db, &log)?;
+        let num_repairable = inconsistencies
+            .iter()
+            .filter(|inconsistency| inconsistency.is_repairable())
+            .count();
+
+        if !inconsistencies.is_empty() {
+            return Err(Error::DBError {
+                message: format!(
+                    "{} database issues found{}",
+                    inconsistencies.len(),
+                    if num_repairable == 0 {
+                        ", re-sync to resolve them"
+                    } else {
+                        ", run with --repair to fix the repairable ones"
+                    }
+                ),
+            });
+        }
+        return Ok(());
+    }
+
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+        &hot_path,
+        &cold_path,
+        |_, _, _| Ok(()),
+        client_config.store,
+        spec.clone(),
+        log.clone(),
+    )?;
+    let inconsistencies = report_inconsistencies(&db, &log)?;
+
+    // The states of the head blocks are needed to restart the beacon node.
+    let head_state_roots = match db.get_item::<PersistedBeaconChain>(&BEACON_CHAIN_DB_KEY)? {
+        Some(persisted_chain) => persisted_chain
+            .ssz_head_tracker
+            .heads()
+            .filter_map(|(block_root, _)| match db.get_blinded_block(&block_root) {
+                Ok(Some(block)) => Some(Ok(block.state_root())),
+                Ok(None) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+    let num_repaired = db.repair(&inconsistencies, &head_state_roots)?;
+
+    let num_remaining = inconsistencies.len() - num_repaired;
+    if num_remaining > 0 {
+        return Err(Error::DBError {
+            message: format!(
+                "{} database issues remain, re-sync to resolve them",
+                num_remaining
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Verify `db` and log every inconsistency found.
+fn report_inconsistencies<E: EthSpec>(
+    db: &HotColdDB<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>,
+    log: &Logger,
+) -> Result<Vec<Inconsistency>, Error> {
+    let inconsistencies = db.verify()?;
+    for inconsistency in &inconsistencies {
+        warn!(
+            log,
+            "Database inconsistency";
+            "issue" => ?inconsistency,
+            "repairable" => inconsistency.is_repairable(),
+        );
+    }
+
+    info!(
+        log,
+        "Database verification complete";
+        "issues" => inconsistencies.len(),
+        "repairable" => inconsistencies
+            .iter()
+            .filter(|inconsistency| inconsistency.is_repairable())
+            .count(),
+    );
+
+    Ok(inconsistencies)
+}
+
+pub struct ExportEraConfig {
+    output_dir: PathBuf,
+    start_era: u64,
+    end_era: Option<u64>,
+}
+
+fn parse_export_era_config(cli_args: &ArgMatches) -> Result<ExportEraConfig, String> {
+    let output_dir = clap_utils::parse_required(cli_args, "output-dir")?;
+    let start_era = clap_utils::parse_required(cli_args, "start-era")?;
+    let end_era = clap_utils::parse_optional(cli_args, "end-era")?;
+
+    Ok(ExportEraConfig {
+        output_dir,
+        start_era,
+        end_era,
+    })
+}
+
+pub fn export_era<E: EthSpec>(
+    export_era_config: ExportEraConfig,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), Error> {
+    let spec = &runtime_context.eth2_config.spec;
+    let hot_path = client_config.get_db_path();
+    let cold_path = client_config.get_freezer_db_path();
+
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+        &hot_path,
+        &cold_path,
+        |_, _, _| Ok(()),
+        client_config.store,
+        spec.clone(),
+        log.clone(),
+    )?;
+
+    era::export_eras(
+        &db,
+        &export_era_config.output_dir,
+        export_era_config.start_era,
+        export_era_config.end_era,
+        &log,
+    )
+}
+
+pub struct ImportEraConfig {
+    input_dir: PathBuf,
+}
+
+fn parse_import_era_config(cli_args: &ArgMatches) -> Result<ImportEraConfig, String> {
+    let input_dir = clap_utils::parse_required(cli_args, "input-dir")?;
+
+    Ok(ImportEraConfig { input_dir })
+}
+
+pub fn import_era<E: EthSpec>(
+    import_era_config: ImportEraConfig,
+    client_config: ClientConfig,
+    runtime_context: &RuntimeContext<E>,
+    log: Logger,
+) -> Result<(), Error> {
+    let spec = &runtime_context.eth2_config.spec;
+    let hot_path = client_config.get_db_path();
+    let cold_path = client_config.get_freezer_db_path();
+
+    let db = HotColdDB::<E, BeaconNodeBackend<E>, BeaconNodeBackend<E>>::open(
+        &hot_path,
+        &cold_path,
+        |_, _, _| Ok(()),
+        client_config.store,
+        spec.clone(),
+        log.clone(),
+    )?;
+
+    era::import_eras::<Witness<SystemTimeSlotClock, CachingEth1Backend<E>, _, _, _>>(
+        db,
+        &import_era_config.input_dir,
+        &log,
+    )
+}
+
+/// The maximum number of bytes of values written to the new database in a single transaction.
+const CONVERT_BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+pub struct ConvertConfig {
+    to: DatabaseBackend,
+}
+
+fn parse_convert_config(cli_args: &ArgMatches) -> Result<ConvertConfig, String> {
+    let to = clap_utils::parse_required(cli_args, "to")?;
+
+    Ok(ConvertConfig { to })
+}
+
+/// Return `path` with `suffix` appended to its final component.
+fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> Error {
+    Error::DBError {
+        message: format!("unable to {} {}: {}", action, path.display(), e),
+    }
+}
+
+/// Write `contents` to a new file at `path`, and wait for it to reach the disk.
+fn write_synced(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Copy every key and value of the LevelDB database at `source` into a new `backend` database at
+/// `destination`, returning the number of keys copied.
+fn convert_db_at_path<E: EthSpec>(
+    source: &Path,
+    destination: &Path,
+    backend: DatabaseBackend,
+    log: &Logger,
+) -> Result<usize, Error> {
+    let source_db = LevelDB::<E>::open(source)?;
+    let destination_db = BeaconNodeBackend::<E>::open(backend, destination)?;
+
+    let mut batch = vec![];
+    let mut batch_bytes = 0;
+    let mut count = 0;
+
+    for (key, value) in source_db.iter_raw() {
+        batch_bytes += key.len() + value.len();
+        batch.push(KeyValueStoreOp::PutKeyValue(key, value));
+        count += 1;
+
+        if batch_bytes >= CONVERT_BATCH_BYTES {
+            destination_db.do_atomically(std::mem::take(&mut batch))?;
+            batch_bytes = 0;
+            info!(log, "Converting database"; "path" => ?source, "keys_copied" => count);
+        }
+    }
+    destination_db.do_atomically(batch)?;
+    destination_db.sync()?;
+
+    Ok(count)
+}
+
+/// Convert the LevelDB hot and cold databases to the backend given by `convert_config`.
+///
+/// Each database is copied to a temporary directory, so an interrupted copy leaves the LevelDB
+/// databases untouched. Once both copies have completed a marker file is written and the copies
+/// are moved into place. The beacon node refuses to start whilst the marker is present, and
+/// running the conversion again resumes moving the copies into place.
+pub fn convert_db<E: EthSpec>(
+    convert_config: ConvertConfig,
+    client_config: ClientConfig,
+    log: Logger,
+) -> Result<(), Error> {
+    let to = convert_config.to;
+    let paths = [
+        client_config.get_db_path(),
+        client_config.get_freezer_db_path(),
+    ];
+    let marker_path = BeaconNodeBackend::<E>::conversion_marker_path(&paths[0]);
+
+    if marker_path.exists() {
+        let marker_to =
+            fs::read_to_string(&marker_path).map_err(|e| io_error("read", &marker_path, e))?;
+        if marker_to.trim() != to.to_string() {
+            return Err(Error::DBError {
+                message: format!(
+                    "an interrupted conversion to {} must be completed first",
+                    marker_to.trim()
+                ),
+            });
+        }
+        info!(
+            log,
+            "Resuming interrupted conversion";
+            "to" => %to,
+        );
+    } else {
+        copy_databases::<E>(&paths, to, &log)?;
+
+        // Record that the databases are about to be moved, so that a crash part way through is
+        // detected rather than leaving the hot and cold databases with different backends.
+        write_synced(&marker_path, to.to_string().as_bytes())
+            .map_err(|e| io_error("write", &marker_path, e))?;
+    }
+
+    for path in &paths {
+        let temp_path = path_with_suffix(path, ".convert");
+        let backup_path = path_with_suffix(path, ".leveldb");
+
+        // Each step is skipped if it completed before an interruption.
+        if !temp_path.exists() {
+            continue;
+        }
+        if path.exists() {
+            fs::rename(path, &backup_path).map_err(|e| io_error("move", path, e))?;
+        }
+        fs::rename(&temp_path, path).map_err(|e| io_error("move", &temp_path, e))?;
+        info!(
+            log,
+            "Database converted";
+            "path" => ?path,
+            "backup" => ?backup_path,
+        );
+    }
+
+    for path in &paths {
+        if BeaconNodeBackend::<E>::detect(path) != Some(to) {
+            return Err(Error::DBError {
+                message: format!("expected a {} database at {}", to, path.display()),
+            });
+        }
+    }
+
+    fs::remove_file(&marker_path).map_err(|e| io_error("remove", &marker_path, e))?;
+
+    info!(
+        log,
+        "Conversion complete, start the beacon node with --beacon-node-backend";
+        "backend" => %to,
+    );
+
+    Ok(())
+}
+
+/// Copy the LevelDB databases at `paths` to new `to` databases alongside them.
+fn copy_databases<E: EthSpec>(
+    paths: &[PathBuf],
+    to: DatabaseBackend,
+    log: &Logger,
+) -> Result<(), Error> {
+    for path in paths {
+        match BeaconNodeBackend::<E>::detect(path) {
+            Some(DatabaseBackend::LevelDb) => (),
+            Some(backend) => {
+                return Err(Error::DBError {
+                    message: format!(
+                        "database at {} uses the {} backend, only LevelDB databases can be \
+                         converted",
+                        path.display(),
+                        backend
+                    ),
+                })
+            }
+            None => {
+                return Err(Error::DBError {
+                    message: format!("no database found at {}", path.display()),
+                })
+            }
+        }
+
+        let backup_path = path_with_suffix(path, ".leveldb");
+        if backup_path.exists() {
+            return Err(Error::DBError {
+                message: format!(
+                    "{} already exists, remove it before converting",
+                    backup_path.display()
+                ),
+            });
+        }
+    }
+
+    if to == DatabaseBackend::LevelDb {
+        return Err(Error::DBError {
+            message: "databases already use the leveldb backend".to_string(),
+        });
+    }
+
+    for path in paths {
+        let temp_path = path_with_suffix(path, ".convert");
+        if temp_path.exists() {
+            // Left over from an interrupted copy.
+            fs::remove_dir_all(&temp_path).map_err(|e| io_error("remove", &temp_path, e))?;
+        }
+
+        info!(
+            log,
+            "Converting database";
+            "path" => ?path,
+            "to" => %to,
+        );
+        let count = convert_db_at_path::<E>(path, &temp_path, to, log)?;
+        info!(
+            log,
+            "Database copied";
+            "path" => ?path,
+            "keys_copied" => count,
+        );
+    }
+
+    Ok(())
+}
+
 /// Run the database manager, returning an error string if the operation did not succeed.
 pub fn run<T: EthSpec>(cli_args: &ArgMatches<'_>, env: Environment<T>) -> Result<(), String> {
     let client_config = parse_client_config(cli_args, &env)?;
@@ -304,6 +1003,26 @@ pub fn run<T: EthSpec>(cli_args: &ArgMatches<'_>, env: Environment<T>) -> Result
             inspect_db(inspect_config, client_config, &context, log)
         }
         ("prune_payloads", Some(_)) => prune_payloads(client_config, &context, log),
+        ("set_slots_per_restore_point", Some(cli_args)) => {
+            let set_sprp_config = parse_set_slots_per_restore_point_config(cli_args)?;
+            set_slots_per_restore_point(set_sprp_config, client_config, &context, log)
+        }
+        ("verify", Some(cli_args)) => {
+            let verify_config = parse_verify_config(cli_args)?;
+            verify_db(verify_config, client_config, &context, log)
+        }
+        ("export-era", Some(cli_args)) => {
+            let export_era_config = parse_export_era_config(cli_args)?;
+            export_era(export_era_config, client_config, &context, log)
+        }
+        ("import-era", Some(cli_args)) => {
+            let import_era_config = parse_import_era_config(cli_args)?;
+            import_era(import_era_config, client_config, &context, log)
+        }
+        ("convert", Some(cli_args)) => {
+            let convert_config = parse_convert_config(cli_args)?;
+            convert_db::<T>(convert_config, client_config, log)
+        }
         _ => {
             return Err("Unknown subcommand, for help `lighthouse database_manager --help`".into())
         }
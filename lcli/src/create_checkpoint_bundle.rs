@@ -0,0 +1,95 @@
+//! # Create Checkpoint Bundle
+//!
+//! Use this tool to combine a finalized state, its block and an optional deposit snapshot into a
+//! single file suitable for `lighthouse bn --checkpoint-bundle`.
+//!
+//! The state and block can be downloaded from any beacon node via the standard beacon-API (e.g.
+//! `/eth/v2/debug/beacon/states/{state_id}` and `/eth/v2/beacon/blocks/{block_id}` with an
+//! `Accept: application/octet-stream` header) and then transferred to an air-gapped machine.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! lcli create-checkpoint-bundle \
+//!     --state /tmp/state.ssz \
+//!     --block /tmp/block.ssz \
+//!     --output /tmp/bundle.ssz
+//! ```
+use crate::transition_blocks::load_from_ssz_with;
+use clap::ArgMatches;
+use clap_utils::{parse_optional, parse_required};
+use eth2::lighthouse::CheckpointBundle;
+use ssz::{Decode, Encode};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use types::{BeaconState, DepositTreeSnapshot, EthSpec, SignedBeaconBlock};
+
+pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
+    let spec = &T::default_spec();
+
+    let state_path: PathBuf = parse_required(matches, "state")?;
+    let block_path: PathBuf = parse_required(matches, "block")?;
+    let deposit_snapshot_path: Option<PathBuf> = parse_optional(matches, "deposit-snapshot")?;
+    let output_path: PathBuf = parse_required(matches, "output")?;
+
+    let state: BeaconState<T> = load_from_ssz_with(&state_path, spec, BeaconState::from_ssz_bytes)?;
+    let block: SignedBeaconBlock<T> =
+        load_from_ssz_with(&block_path, spec, SignedBeaconBlock::from_ssz_bytes)?;
+
+    if block.slot() % T::slots_per_epoch() != 0 {
+        return Err(format!(
+            "Block at slot {} is not aligned to an epoch boundary",
+            block.slot()
+        ));
+    }
+    if state.slot() != block.slot() {
+        return Err(format!(
+            "Slot of block ({}) does not match state ({})",
+            block.slot(),
+            state.slot()
+        ));
+    }
+    let state_root = state.canonical_root();
+    if state_root != block.state_root() {
+        return Err(format!(
+            "State root does not match block, expected: {:?}, got: {:?}",
+            block.state_root(),
+            state_root
+        ));
+    }
+
+    let deposit_snapshot = deposit_snapshot_path
+        .map(|path| {
+            let mut bytes = vec![];
+            File::open(&path)
+                .and_then(|mut f| f.read_to_end(&mut bytes))
+                .map_err(|e| format!("Unable to read {:?}: {:?}", path, e))?;
+            DepositTreeSnapshot::from_ssz_bytes(&bytes)
+                .map_err(|e| format!("Invalid deposit snapshot: {:?}", e))
+        })
+        .transpose()?;
+
+    if let Some(snapshot) = &deposit_snapshot {
+        if !snapshot.is_valid() {
+            return Err("Deposit snapshot is invalid".to_string());
+        }
+    }
+
+    let bundle = CheckpointBundle::new(&state, &block, deposit_snapshot);
+
+    let mut output = File::create(&output_path)
+        .map_err(|e| format!("Unable to create {:?}: {:?}", output_path, e))?;
+    output
+        .write_all(&bundle.as_ssz_bytes())
+        .map_err(|e| format!("Unable to write to {:?}: {:?}", output_path, e))?;
+
+    info!(
+        "Wrote checkpoint bundle for slot {} with block root {:?} to {:?}",
+        block.slot(),
+        block.canonical_root(),
+        output_path
+    );
+
+    Ok(())
+}
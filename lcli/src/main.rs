@@ -3,6 +3,7 @@ extern crate log;
 mod block_root;
 mod change_genesis_time;
 mod check_deposit_data;
+mod create_checkpoint_bundle;
 mod create_payload_header;
 mod deploy_deposit_contract;
 mod eth1_genesis;
@@ -767,6 +768,42 @@ fn main() {
                         .help("Number of repeat runs, useful for benchmarking."),
                 )
         )
+        .subcommand(
+            SubCommand::with_name("create-checkpoint-bundle")
+                .about("Combines a finalized state, block and deposit snapshot into a single \
+                        file for use with `lighthouse bn --checkpoint-bundle`.")
+                .arg(
+                    Arg::with_name("state")
+                        .long("state")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to an epoch-aligned BeaconState as SSZ."),
+                )
+                .arg(
+                    Arg::with_name("block")
+                        .long("block")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to the SignedBeaconBlock matching --state as SSZ."),
+                )
+                .arg(
+                    Arg::with_name("deposit-snapshot")
+                        .long("deposit-snapshot")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Path to an EIP-4881 DepositTreeSnapshot as SSZ."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to write the checkpoint bundle to."),
+                )
+        )
         .get_matches();
 
     let result = matches
@@ -857,6 +894,8 @@ fn run<T: EthSpec>(
             .map_err(|e| format!("Failed to run indexed-attestations command: {}", e)),
         ("block-root", Some(matches)) => block_root::run::<T>(env, matches)
             .map_err(|e| format!("Failed to run block-root command: {}", e)),
+        ("create-checkpoint-bundle", Some(matches)) => create_checkpoint_bundle::run::<T>(matches)
+            .map_err(|e| format!("Failed to run create-checkpoint-bundle command: {}", e)),
         (other, _) => Err(format!("Unknown subcommand {}. See --help.", other)),
     }
 }
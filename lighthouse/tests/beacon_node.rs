@@ -5,6 +5,8 @@ use beacon_node::beacon_chain::chain_config::{
     DisallowedReOrgOffsets, DEFAULT_RE_ORG_CUTOFF_DENOMINATOR,
     DEFAULT_RE_ORG_MAX_EPOCHS_SINCE_FINALIZATION, DEFAULT_RE_ORG_THRESHOLD,
 };
+use beacon_node::beacon_chain::store::hdiff::HierarchyConfig;
+use beacon_node::beacon_chain::store::DatabaseBackend;
 use eth1::Eth1Endpoint;
 use lighthouse_network::PeerId;
 use std::fs::File;
@@ -139,6 +141,28 @@ fn shuffling_cache_set() {
         .with_config(|config| assert_eq!(config.chain.shuffling_cache_size, 500));
 }
 
+#[test]
+fn state_cache_memory_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.chain.state_cache_memory_mb,
+                // If this value changes make sure to update the help text for
+                // the CLI command.
+                2048
+            )
+        });
+}
+
+#[test]
+fn state_cache_memory_set() {
+    CommandLineTest::new()
+        .flag("state-cache-memory", Some("512"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.state_cache_memory_mb, 512));
+}
+
 #[test]
 fn fork_choice_before_proposal_timeout_default() {
     CommandLineTest::new()
@@ -178,6 +202,64 @@ fn checkpoint_sync_url_timeout_default() {
         });
 }
 
+// The node is started by these tests, so a bundle which doesn't decode is rejected.
+#[test]
+#[should_panic]
+fn checkpoint_bundle_flag_invalid_bundle() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let path = dir.path().join("bundle.ssz");
+    File::create(&path)
+        .and_then(|mut file| file.write_all(&[42; 32]))
+        .expect("Unable to write bundle");
+    CommandLineTest::new()
+        .flag("checkpoint-bundle", path.to_str())
+        .run_with_zero_port();
+}
+
+#[test]
+#[should_panic]
+fn checkpoint_bundle_flag_missing_file() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    CommandLineTest::new()
+        .flag("checkpoint-bundle", dir.path().join("bundle.ssz").to_str())
+        .run_with_zero_port();
+}
+
+#[test]
+#[should_panic]
+fn checkpoint_root_flag_invalid_root() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let path = dir.path().join("bundle.ssz");
+    File::create(&path).expect("Unable to create bundle");
+    CommandLineTest::new()
+        .flag("checkpoint-bundle", path.to_str())
+        .flag("checkpoint-root", Some("0x42"))
+        .run_with_zero_port();
+}
+
+#[test]
+#[should_panic]
+fn checkpoint_root_flag_without_bundle() {
+    CommandLineTest::new()
+        .flag(
+            "checkpoint-root",
+            Some("0x4242424242424242424242424242424242424242424242424242424242424242"),
+        )
+        .run_with_zero_port();
+}
+
+#[test]
+#[should_panic]
+fn checkpoint_bundle_flag_conflicts_with_checkpoint_sync_url() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let path = dir.path().join("bundle.ssz");
+    File::create(&path).expect("Unable to create bundle");
+    CommandLineTest::new()
+        .flag("checkpoint-bundle", path.to_str())
+        .flag("checkpoint-sync-url", Some("http://localhost:5052"))
+        .run_with_zero_port();
+}
+
 #[test]
 fn prepare_payload_lookahead_default() {
     CommandLineTest::new()
@@ -218,6 +300,21 @@ fn always_prepare_payload_override() {
         .with_config(|config| assert!(config.chain.always_prepare_payload));
 }
 
+#[test]
+fn publish_proposer_equivocations_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert!(!config.chain.publish_proposer_equivocations));
+}
+
+#[test]
+fn publish_proposer_equivocations_on() {
+    CommandLineTest::new()
+        .flag("publish-proposer-equivocations", None)
+        .run_with_zero_port()
+        .with_config(|config| assert!(config.chain.publish_proposer_equivocations));
+}
+
 #[test]
 fn paranoid_block_proposal_default() {
     CommandLineTest::new()
@@ -1618,6 +1715,173 @@ fn validator_monitor_metrics_threshold_custom() {
             assert_eq!(config.validator_monitor_individual_tracking_threshold, 42)
         });
 }
+#[test]
+fn validator_monitor_history_epochs_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.chain.validator_monitor_history_epochs,
+                // If this value changes make sure to update the help text for
+                // the CLI command.
+                1575
+            )
+        });
+}
+#[test]
+fn validator_monitor_history_epochs_custom() {
+    CommandLineTest::new()
+        .flag("validator-monitor-history-epochs", Some("64"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.validator_monitor_history_epochs, 64));
+}
+#[test]
+fn block_times_history_epochs_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.chain.block_times_history_epochs,
+                // If this value changes make sure to update the help text for
+                // the CLI command.
+                1575
+            )
+        });
+}
+#[test]
+fn block_times_history_epochs_custom() {
+    CommandLineTest::new()
+        .flag("block-times-history-epochs", Some("0"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.block_times_history_epochs, 0));
+}
+#[test]
+fn validator_monitor_alerts_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert!(!config.validator_monitor_alert_rules.is_enabled());
+            assert!(config.validator_monitor_alert_webhooks.is_empty());
+        });
+}
+#[test]
+fn validator_monitor_alerts_flag() {
+    CommandLineTest::new()
+        .flag(
+            "validator-monitor-alerts",
+            Some("missed-proposal,missed-attestations=2,slashed"),
+        )
+        .flag(
+            "validator-monitor-alert-webhooks",
+            Some("http://localhost:9000/alerts,http://localhost:9001/alerts"),
+        )
+        .run_with_zero_port()
+        .with_config(|config| {
+            let rules = config.validator_monitor_alert_rules;
+            assert!(rules.missed_proposal);
+            assert_eq!(rules.consecutive_missed_attestations, Some(2));
+            assert_eq!(rules.min_sync_participation_percent, None);
+            assert!(rules.slashed);
+            assert!(!rules.balance_decrease);
+            assert_eq!(
+                config
+                    .validator_monitor_alert_webhooks
+                    .iter()
+                    .map(|url| url.full.to_string())
+                    .collect::<Vec<_>>(),
+                vec![
+                    "http://localhost:9000/alerts",
+                    "http://localhost:9001/alerts"
+                ]
+            );
+        });
+}
+#[test]
+#[should_panic]
+fn validator_monitor_alerts_invalid_rule() {
+    CommandLineTest::new()
+        .flag("validator-monitor-alerts", Some("missed-everything"))
+        .run_with_zero_port();
+}
+#[test]
+fn event_webhooks_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert!(config.event_webhooks.is_empty());
+            assert_eq!(
+                format!("{:?}", config.event_webhook_topics),
+                "[Head, Block, FinalizedCheckpoint, ChainReorg]"
+            );
+        });
+}
+#[test]
+fn event_webhooks_flag() {
+    CommandLineTest::new()
+        .flag("event-webhooks", Some("http://localhost:9000/events"))
+        .flag("event-webhook-topics", Some("head,chain_reorg"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config
+                    .event_webhooks
+                    .iter()
+                    .map(|url| url.full.to_string())
+                    .collect::<Vec<_>>(),
+                vec!["http://localhost:9000/events"]
+            );
+            assert_eq!(
+                format!("{:?}", config.event_webhook_topics),
+                "[Head, ChainReorg]"
+            );
+            // Webhooks enable the event journal.
+            assert_eq!(config.chain.event_journal_size, 65_536);
+        });
+}
+#[test]
+fn event_webhooks_event_journal_size() {
+    CommandLineTest::new()
+        .flag("event-webhooks", Some("http://localhost:9000/events"))
+        .flag("event-journal-size", Some("1024"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.event_journal_size, 1024));
+}
+#[test]
+#[should_panic]
+fn event_webhooks_disabled_event_journal() {
+    CommandLineTest::new()
+        .flag("event-webhooks", Some("http://localhost:9000/events"))
+        .flag("event-journal-size", Some("0"))
+        .run_with_zero_port();
+}
+#[test]
+#[should_panic]
+fn event_webhook_topics_unsupported() {
+    CommandLineTest::new()
+        .flag("event-webhooks", Some("http://localhost:9000/events"))
+        .flag("event-webhook-topics", Some("head,attestation"))
+        .run_with_zero_port();
+}
+#[test]
+fn event_journal_size_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.chain.event_journal_size,
+                // If this value changes make sure to update the help text for
+                // the CLI command.
+                0
+            )
+        });
+}
+#[test]
+fn event_journal_size_custom() {
+    CommandLineTest::new()
+        .flag("event-journal-size", Some("1024"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.event_journal_size, 1024));
+}
 
 // Tests for Store flags.
 #[test]
@@ -1669,6 +1933,36 @@ fn block_cache_size_flag() {
         .with_config(|config| assert_eq!(config.store.block_cache_size, 4_usize));
 }
 #[test]
+fn beacon_node_backend_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.store.backend, DatabaseBackend::LevelDb));
+}
+#[test]
+fn beacon_node_backend_redb() {
+    CommandLineTest::new()
+        .flag("beacon-node-backend", Some("redb"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.store.backend, DatabaseBackend::Redb));
+}
+#[test]
+fn hierarchy_exponents_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.store.hierarchy_config, HierarchyConfig::default())
+        });
+}
+#[test]
+fn hierarchy_exponents_flag() {
+    CommandLineTest::new()
+        .flag("hierarchy-exponents", Some("13,16,20"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.store.hierarchy_config.exponents, vec![13, 16, 20])
+        });
+}
+#[test]
 fn auto_compact_db_flag() {
     CommandLineTest::new()
         .flag("auto-compact-db", Some("false"))
@@ -1697,6 +1991,19 @@ fn prune_payloads_on_startup_false() {
         .with_config(|config| assert!(!config.store.prune_payloads));
 }
 #[test]
+fn history_retention_epochs_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.store.history_retention_epochs, None));
+}
+#[test]
+fn history_retention_epochs_flag() {
+    CommandLineTest::new()
+        .flag("history-retention-epochs", Some("33024"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.store.history_retention_epochs, Some(33024)));
+}
+#[test]
 fn reconstruct_historic_states_flag() {
     CommandLineTest::new()
         .flag("reconstruct-historic-states", None)
@@ -1709,6 +2016,26 @@ fn no_reconstruct_historic_states_flag() {
         .run_with_zero_port()
         .with_config(|config| assert!(!config.chain.reconstruct_historic_states));
 }
+#[test]
+fn reconstruction_budget_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.store.reconstruction_budget_percent, 50));
+}
+#[test]
+fn reconstruction_budget_flag() {
+    CommandLineTest::new()
+        .flag("reconstruction-budget", Some("20"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.store.reconstruction_budget_percent, 20));
+}
+#[test]
+#[should_panic]
+fn reconstruction_budget_zero() {
+    CommandLineTest::new()
+        .flag("reconstruction-budget", Some("0"))
+        .run_with_zero_port();
+}
 
 // Tests for Slasher flags.
 #[test]
@@ -2123,7 +2450,10 @@ fn sync_eth1_chain_disable_deposit_contract_sync_flag() {
 fn light_client_server_default() {
     CommandLineTest::new()
         .run_with_zero_port()
-        .with_config(|config| assert_eq!(config.network.enable_light_client_server, false));
+        .with_config(|config| {
+            assert_eq!(config.network.enable_light_client_server, false);
+            assert_eq!(config.chain.enable_light_client_server, false);
+        });
 }
 
 #[test]
@@ -2131,7 +2461,10 @@ fn light_client_server_enabled() {
     CommandLineTest::new()
         .flag("light-client-server", None)
         .run_with_zero_port()
-        .with_config(|config| assert_eq!(config.network.enable_light_client_server, true));
+        .with_config(|config| {
+            assert_eq!(config.network.enable_light_client_server, true);
+            assert_eq!(config.chain.enable_light_client_server, true);
+        });
 }
 
 #[test]